@@ -19,7 +19,11 @@ pub fn render_config_ui(
                 TileLens::Biome,
                 TileLens::Elevation,
                 TileLens::Humidity,
+                TileLens::Fertility,
+                TileLens::Watershed,
                 TileLens::Runoff,
+                TileLens::Terrain,
+                TileLens::CoastDistance,
             ]
             .into_iter(),
         ));
@@ -8,7 +8,7 @@ use bevy_egui::{
     EguiContext,
 };
 use std::ops::{Deref, RangeInclusive};
-use terra::{Meter3, NoiseFnType, WorldConfig};
+use terra::{Celsius, Meter, Meter3, NoiseFnType, WorldConfig};
 
 /// Standard slider range for normal (0-1) fields
 const NORMAL_RANGE: RangeInclusive<f64> = 0.0..=1.0;
@@ -131,6 +131,11 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
                 .step_by(10.0)
                 .text("World Radius"),
         );
+        ui.checkbox(&mut world_config.wrap, "Wrap Around Edges?");
+        ui.checkbox(
+            &mut world_config.capture_layers,
+            "Capture Intermediate Layers?",
+        );
     }));
 
     // ===== Edge Buffer =====
@@ -152,6 +157,14 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
             .step_by(EXPONENT_STEP)
             .text("Exponent"),
         );
+        ui.checkbox(
+            &mut world_config.elevation.force_ocean_border,
+            "Force Ocean Border?",
+        );
+        ui.checkbox(
+            &mut world_config.elevation.guarantee_center_land,
+            "Guarantee Center Land?",
+        );
     }));
 
     // ===== Elevation =====
@@ -187,7 +200,7 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
         ui.add(
             Slider::new(
                 &mut world_config.elevation.noise_fn.lacunarity,
-                0.5..=10.0,
+                1.0..=10.0,
             )
             .step_by(0.5)
             .text("Lacunarity"),
@@ -196,7 +209,7 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
         ui.add(
             Slider::new(
                 &mut world_config.elevation.noise_fn.persistence,
-                0.0..=2.0,
+                NORMAL_RANGE,
             )
             .step_by(0.1)
             .text("Persistence"),
@@ -210,6 +223,35 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
             .step_by(EXPONENT_STEP)
             .text("Exponent"),
         );
+
+        ui.add(
+            Slider::new(&mut world_config.elevation.smoothing_passes, 0..=10)
+                .step_by(1.0)
+                .text("Smoothing Passes"),
+        );
+
+        ui.add(
+            Slider::new(&mut world_config.elevation.ridge_strength, 0.0..=1.0)
+                .step_by(0.05)
+                .text("Ridge Strength"),
+        );
+
+        ui.add(
+            Slider::new(
+                &mut world_config.elevation.elevation_offset.0,
+                -50.0..=50.0,
+            )
+            .step_by(1.0)
+            .custom_formatter(format_meter)
+            .text("Elevation Offset"),
+        );
+
+        ui.add(
+            Slider::new(&mut world_config.elevation.coast_depth.0, 0.0..=20.0)
+                .step_by(0.5)
+                .custom_formatter(format_meter)
+                .text("Coast Depth"),
+        );
     }));
 
     // ===== Rainfall =====
@@ -237,6 +279,15 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
                 .text("Land Evaporation Scale"),
             );
 
+            ui.add(
+                Slider::new(
+                    &mut world_config.rainfall.coastal_evaporation_bonus,
+                    0.0..=2.0,
+                )
+                .step_by(NORMAL_STEP)
+                .text("Coastal Evaporation Bonus"),
+            );
+
             ui.add(
                 Slider::new(
                     &mut world_config.rainfall.evaporation_spread_distance,
@@ -263,9 +314,78 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
                 .step_by(0.05)
                 .text("Rainfall Fraction Limit"),
             );
+
+            ui.add(
+                Slider::new(
+                    &mut world_config.rainfall.min_rainfall.0,
+                    0.0..=1.0,
+                )
+                .step_by(0.05)
+                .custom_formatter(format_meter3)
+                .text("Min Rainfall"),
+            );
+
+            ui.add(
+                Slider::new(
+                    &mut world_config.rainfall.runoff_concentration,
+                    EXPONENT_RANGE,
+                )
+                .step_by(EXPONENT_STEP)
+                .text("Runoff Concentration"),
+            );
         });
     }));
 
+    // ===== Temperature =====
+    ui.scope(section("Temperature", |ui| {
+        ui.add(
+            Slider::new(
+                &mut world_config.temperature.equator_temperature.0,
+                -50.0..=50.0,
+            )
+            .step_by(1.0)
+            .custom_formatter(format_celsius)
+            .text("Equator Temperature"),
+        );
+
+        ui.add(
+            Slider::new(
+                &mut world_config.temperature.pole_temperature.0,
+                -50.0..=50.0,
+            )
+            .step_by(1.0)
+            .custom_formatter(format_celsius)
+            .text("Pole Temperature"),
+        );
+
+        ui.add(
+            Slider::new(
+                &mut world_config.temperature.elevation_lapse_rate,
+                0.0..=1.0,
+            )
+            .step_by(0.05)
+            .text("Elevation Lapse Rate"),
+        );
+
+        ui.add(
+            Slider::new(
+                &mut world_config.temperature.hemisphere_offset,
+                -1.0..=1.0,
+            )
+            .step_by(0.05)
+            .text("Hemisphere Offset"),
+        );
+
+        ui.add(
+            Slider::new(
+                &mut world_config.temperature.current_strength,
+                0.0..=1.0,
+            )
+            .step_by(0.05)
+            .text("Ocean Current Strength"),
+        );
+    }));
+
     // ===== Geographic Features =====
     ui.scope(section("Geographic Features", |ui| {
         ui.add(
@@ -280,10 +400,48 @@ fn controls_ui(ui: &mut Ui, world_config: &mut WorldConfig) {
             .custom_formatter(format_meter3)
             .text("River Runoff-Traversed Threshold"),
         );
+
+        ui.add(
+            Slider::new(
+                &mut world_config.geo_feature.inland_sea_size_threshold,
+                1..=1000,
+            )
+            .text("Inland Sea Size Threshold"),
+        );
+
+        ui.add(
+            Slider::new(
+                &mut world_config.geo_feature.meander_strength,
+                0.0..=1.0,
+            )
+            .step_by(0.05)
+            .text("Meander Strength"),
+        );
     }));
+
+    // ===== Biomes =====
+    ui.scope(section("Biomes", |ui| {
+        ui.add(
+            Slider::new(&mut world_config.biome.ice_cap_latitude, NORMAL_RANGE)
+                .step_by(NORMAL_STEP)
+                .text("Ice Cap Latitude"),
+        );
+
+        ui.checkbox(&mut world_config.biome.smoothing, "Smoothing?");
+    }));
+}
+
+/// Format a Meter as a string
+fn format_meter(value: f64, _: RangeInclusive<usize>) -> String {
+    Meter(value).to_string()
 }
 
 /// Format a Meter3 (cubic meter) as a string
 fn format_meter3(value: f64, _: RangeInclusive<usize>) -> String {
     Meter3(value).to_string()
 }
+
+/// Format a temperature as a string
+fn format_celsius(value: f64, _: RangeInclusive<usize>) -> String {
+    Celsius(value).to_string()
+}
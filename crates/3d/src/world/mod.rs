@@ -118,8 +118,8 @@ fn render_world(
 ) {
     for _ in render_world_events.iter() {
         debug!("Rendering tiles");
-        let renderer =
-            WorldRenderer::new(*render_config).expect("Invalid render config");
+        let renderer = WorldRenderer::new(render_config.clone())
+            .expect("Invalid render config");
 
         // We're duping these meshes on every render, but the old meshes should
         // just get thrown away so it's fine I guess
@@ -231,8 +231,14 @@ fn unrender_world(
 }
 
 fn water_material() -> StandardMaterial {
+    let color = WorldRenderer::WATER_COLOR;
     StandardMaterial {
-        base_color: Color::rgba(0.078, 0.302, 0.639, 0.5),
+        base_color: Color::rgba(
+            color.red,
+            color.green,
+            color.blue,
+            color.alpha,
+        ),
         alpha_mode: AlphaMode::Blend,
         metallic: 0.0,
         reflectance: 0.0,
@@ -39,10 +39,16 @@ struct Opt {
     /// cfg - The full config object used for the world, in TOML format
     ///
     /// json - JSON representation. Similar to the binary format, but slower
-    ///   and much less compact
+    ///   and much less compact. See `--pretty`
     ///
     /// svg - 2D rendering of the world
     ///
+    /// ppm - 2D raster rendering of the world, with no compression
+    ///
+    /// layers - 2D raster rendering of the world, split into separate PNG
+    ///   layers (`world.<layer>.png`) that can be composited in an image
+    ///   editor. Layers: biome, elevation, water, rivers
+    ///
     /// stl - 3D rendering of the world
     // TODO include link to docs about binary format after core crate is pub
     #[structopt(short = "f", long)]
@@ -59,6 +65,40 @@ struct Opt {
     #[structopt(long)]
     hide_features: bool,
 
+    /// Render only the top surface of the world as a height mesh, instead of
+    /// a solid prism per tile. The mesh won't be watertight. Only relevant
+    /// for 3D output formats, such as STL.
+    #[structopt(long)]
+    stl_surface_only: bool,
+
+    /// Also write a JSON sidecar next to the STL (`world.stl.meta.json`),
+    /// which maps ranges of triangles to the biome and features of the tile
+    /// they came from. STL can't carry colors, so this lets other tools
+    /// texture the model. Only relevant for the STL output format.
+    #[structopt(long)]
+    stl_metadata: bool,
+
+    /// Indent JSON output across multiple lines, so it's easier to read and
+    /// diff. This makes the output even bigger. Only relevant for the JSON
+    /// output format.
+    #[structopt(long)]
+    pretty: bool,
+
+    /// The width of each tile, in pixels. Only relevant for raster output
+    /// formats, such as PPM and layers.
+    #[structopt(long, default_value = "8")]
+    pixels_per_tile: u32,
+
+    /// Supersampling factor for raster output. Each pixel is rendered as an
+    /// NxN block of sub-pixels and averaged, which smooths out jagged edges.
+    /// Only relevant for raster output formats, such as PPM.
+    #[structopt(long, default_value = "1")]
+    png_supersample: u8,
+
+    /// Scale factor for tile geometry. Only relevant for SVG and STL output.
+    #[structopt(long, default_value = "1.0")]
+    tile_scale: f64,
+
     /// The logging level to use during world generation. See
     /// https://docs.rs/log/0.4.11/log/enum.LevelFilter.html for options
     #[structopt(long, default_value = "info")]
@@ -82,6 +122,10 @@ enum OutputFormat {
     Json,
     /// Render the world as a 2D SVG
     Svg,
+    /// Render the world as a 2D PPM raster
+    Ppm,
+    /// Render the world as a set of 2D PNG rasters, one per layer
+    Layers,
     /// Render the world as a 3D STL
     Stl,
     /* If you change this, make sure to update the help text for
@@ -95,6 +139,9 @@ impl OutputFormat {
             Self::Cfg => "toml",
             Self::Json => "json",
             Self::Svg => "svg",
+            Self::Ppm => "ppm",
+            // Each layer gets its own file, so this is just for show
+            Self::Layers => "*.png",
             Self::Stl => "stl",
         }
     }
@@ -118,68 +165,116 @@ fn gen_output(
     output_format: OutputFormat,
     world: &World,
     renderer: &WorldRenderer,
+    pixels_per_tile: u32,
+    stl_metadata: bool,
+    pretty_json: bool,
 ) -> anyhow::Result<()> {
-    fn generate_bytes(
+    /// Generate every file for the given format, as pairs of file name and
+    /// contents. Most formats are a single file, but some need more.
+    fn generate_files(
         output_format: OutputFormat,
         world: &World,
         renderer: &WorldRenderer,
-    ) -> Vec<u8> {
-        match output_format {
+        pixels_per_tile: u32,
+        stl_metadata: bool,
+        pretty_json: bool,
+    ) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+        let file_name = format!("world.{}", output_format.file_ext());
+        let single = |bytes: Vec<u8>| vec![(file_name.clone(), bytes)];
+        Ok(match output_format {
             OutputFormat::Bin => {
                 // Serialize the entire world via msgpack
-                world.to_bin()
+                single(world.to_bin())
             }
             OutputFormat::Cfg => {
                 // Serialize just the world config via toml
-                toml::to_string_pretty(world.config())
-                    // Panics only if config format isn't serializable (a bug)
-                    .expect("error serializing config")
-                    .into_bytes()
+                single(
+                    toml::to_string_pretty(world.config())
+                        // Panics only if config isn't serializable (a bug)
+                        .expect("error serializing config")
+                        .into_bytes(),
+                )
+            }
+            OutputFormat::Json if pretty_json => {
+                // Serialize the entire world via JSON, with indentation
+                single(world.to_json_pretty().into())
             }
             OutputFormat::Json => {
                 // Serialize the entire world via JSON
-                world.to_json().into()
+                single(world.to_json().into())
             }
             OutputFormat::Svg => {
                 // Render the world in 2D
-                renderer.render_as_svg(world).into_bytes()
+                single(renderer.render_as_svg(world).into_bytes())
+            }
+            OutputFormat::Ppm => {
+                // Rasterize the world in 2D
+                single(renderer.render_as_ppm(world, pixels_per_tile)?)
+            }
+            OutputFormat::Layers => {
+                // Each layer goes in its own file
+                renderer
+                    .render_layers(world, pixels_per_tile)?
+                    .into_iter()
+                    .map(|(name, png)| (format!("world.{name}.png"), png))
+                    .collect()
+            }
+            OutputFormat::Stl if stl_metadata => {
+                // The STL metadata has to come from the same mesh as the
+                // model, so render them together
+                let (stl, metadata) =
+                    renderer.render_as_stl_with_metadata(world);
+                vec![
+                    (file_name, stl),
+                    ("world.stl.meta.json".to_owned(), metadata.into_bytes()),
+                ]
             }
             OutputFormat::Stl => {
                 // Render the world in 3D
-                renderer.render_as_stl(world)
+                single(renderer.render_as_stl(world))
             }
-        }
+        })
     }
 
-    let output_file_path = output_dir
-        .join("world")
-        .with_extension(output_format.file_ext());
-
     timed!(
         format!(
             "Generating {} output and writing to {:?}",
-            output_format, &output_file_path
+            output_format,
+            output_dir
+                .join("world")
+                .with_extension(output_format.file_ext())
         ),
         log::Level::Info,
         {
-            let bytes = generate_bytes(output_format, world, renderer);
-            let mut file = OpenOptions::new()
-                .write(true)
-                .create(true)
-                .truncate(true)
-                .open(&output_file_path)
-                .with_context(|| {
-                    format!("error opening output file {:?}", &output_file_path)
-                })?;
-            file.write_all(&bytes).with_context(|| {
-                format!("error writing to file {:?}", &output_file_path)
-            })?;
+            let files = generate_files(
+                output_format,
+                world,
+                renderer,
+                pixels_per_tile,
+                stl_metadata,
+                pretty_json,
+            )?;
+            for (file_name, bytes) in files {
+                write_file(&output_dir.join(file_name), &bytes)?;
+            }
         }
     );
 
     Ok(())
 }
 
+/// Write bytes to a file, replacing any existing content
+fn write_file(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+        .with_context(|| format!("error opening output file {path:?}"))?;
+    file.write_all(bytes)
+        .with_context(|| format!("error writing to file {path:?}"))
+}
+
 /// Run the CLI with some options
 fn run(opt: Opt) -> anyhow::Result<()> {
     SimpleLogger::new().with_level(opt.log_level).init()?;
@@ -221,16 +316,30 @@ fn run(opt: Opt) -> anyhow::Result<()> {
         if opt.output_formats.is_empty() {
             bail!("output dir was specified, but no output formats were given")
         }
+        if opt.pixels_per_tile == 0 {
+            bail!("pixels per tile must be at least 1")
+        }
         fs::create_dir_all(&output_dir)?;
 
         let renderer = WorldRenderer::new(RenderConfig {
             tile_lens: opt.lens,
             show_features: !opt.hide_features,
+            stl_surface_only: opt.stl_surface_only,
+            png_supersample: opt.png_supersample,
+            tile_scale: opt.tile_scale,
             ..Default::default()
         })
         .context("invalid render config")?;
         for output_format in opt.output_formats {
-            gen_output(&output_dir, output_format, &world, &renderer)?;
+            gen_output(
+                &output_dir,
+                output_format,
+                &world,
+                &renderer,
+                opt.pixels_per_tile,
+                opt.stl_metadata,
+                opt.pretty,
+            )?;
         }
     }
 
@@ -1,12 +1,16 @@
 mod seed;
 
-use crate::{Meter, Meter3};
+use crate::{
+    util,
+    world::{self, hex::HexCoordinateValue},
+    Celsius, Meter, Meter3, TilePoint, TilePointMap,
+};
 use derive_more::Display;
 use fnv::FnvHasher;
 pub use seed::Seed;
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 // TODO there's a bunch of fields in here that can't easily be validated
 // because they are Meter or Meter3. Maybe a PR to validator that allows
@@ -26,6 +30,51 @@ pub struct WorldConfig {
     #[validate(range(min = 0, max = 10000))]
     pub radius: u16,
 
+    /// If enabled, the world wraps around on itself like a torus: each edge
+    /// of the hexagon is connected to the opposite edge, so tiles on the edge
+    /// have neighbors on the far side of the world. This affects every
+    /// neighbor lookup during generation (clustering, runoff, smoothing,
+    /// etc.). Since there is no edge to push down, the elevation edge buffer
+    /// is ignored for wrapping worlds.
+    pub wrap: bool,
+
+    /// The logical center of the world, in a larger coordinate space. Tiles
+    /// are always positioned around the origin, but the noise functions are
+    /// sampled as if each tile were offset by this much. This allows a huge
+    /// map to be generated in chunks: give each chunk the same seed, and set
+    /// each one's center to where it sits in the larger map, and the chunks
+    /// will share one continuous noise field. The edge buffer, ocean border,
+    /// and guaranteed center land are measured from this logical center as
+    /// well (see [Self::center_distance]), so chunks agree on them wherever
+    /// they overlap. They still use this config's `radius` though, so any
+    /// tile more than `radius` away from the logical center is treated as
+    /// past the edge of the world. To get a map bigger than one chunk,
+    /// disable the edge buffer (see [ElevationConfig::edge_buffer_fraction])
+    /// and ocean border (see [ElevationConfig::force_ocean_border]).
+    ///
+    /// A few steps look at each tile's neighbors, which get cut off at the
+    /// edge of a chunk, so they **break continuity** along the seams between
+    /// chunks. These are elevation smoothing (see
+    /// [ElevationConfig::smoothing_passes]) and everything downstream of
+    /// rainfall (runoff, lakes, rivers, etc.).
+    pub center: TilePoint,
+
+    /// If enabled, generation saves snapshots of some intermediate data that
+    /// normally gets overwritten by later steps, so it can be inspected on
+    /// the finished world via [World::layer](crate::World::layer). This is
+    /// meant for research and debugging, and costs an extra map per layer
+    /// so it's disabled by default. Captured layers:
+    /// - `raw_elevation` - Elevation straight from the noise function (after
+    ///   ridges and smoothing), before the edge buffer, ocean border,
+    ///   rounding, and ocean floor are applied
+    /// - `raw_rainfall` - Rainfall straight from the cloud simulation, before
+    ///   [RainfallConfig::min_rainfall] is applied and before any of it runs
+    ///   off. Only captured if rainfall is enabled.
+    ///
+    /// This has no effect on the generated world, so it's left out of
+    /// [Self::config_hash].
+    pub capture_layers: bool,
+
     /// Config for the noise function used to generate elevation values
     #[validate]
     pub elevation: ElevationConfig,
@@ -34,9 +83,35 @@ pub struct WorldConfig {
     #[validate]
     pub rainfall: RainfallConfig,
 
+    /// Config for fields related to temperature
+    #[validate]
+    pub temperature: TemperatureConfig,
+
     /// Config for fields related to geographic feature generation
     #[validate]
     pub geo_feature: GeoFeatureConfig,
+
+    /// Config for fields related to biome generation
+    #[validate]
+    pub biome: BiomeConfig,
+}
+
+/// A rough estimate of the cost of generating a world, from
+/// [WorldConfig::estimate]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenerationEstimate {
+    /// Number of tiles the world will have. This one's exact.
+    pub tile_count: usize,
+
+    /// Approximate peak memory usage during generation, in bytes. This only
+    /// accounts for the tiles themselves, so actual usage will be somewhat
+    /// higher.
+    pub peak_memory_bytes: usize,
+
+    /// Whether rainfall and runoff will be simulated. Runoff simulation is
+    /// by far the slowest part of generation, so this is the best indicator
+    /// of how long generation will take. See [RainfallConfig::enabled].
+    pub runoff_enabled: bool,
 }
 
 /// Configuration for elevation map generation. This controls the elevation of
@@ -46,6 +121,7 @@ pub struct WorldConfig {
 #[serde(default)]
 pub struct ElevationConfig {
     /// Configuration for the noise function used to generate elevation values
+    #[validate]
     pub noise_fn: NoiseFnConfig,
 
     /// If defined, each elevation value will be rounded to the nearest
@@ -56,6 +132,31 @@ pub struct ElevationConfig {
     // TODO validate >0
     pub rounding_interval: Option<Meter>,
 
+    /// Number of smoothing passes to apply to the raw noise values. Each pass
+    /// replaces every tile's value with the average of itself and its
+    /// neighbors, which softens sharp peaks and valleys. Smoothing runs before
+    /// the edge buffer and before rounding (see `rounding_interval`). 0
+    /// disables smoothing. Tiles on the edge of the world have fewer
+    /// neighbors to average with, so smoothing breaks continuity between
+    /// chunks of a larger map (see [WorldConfig::center]).
+    pub smoothing_passes: u16,
+
+    /// How strongly to raise up mountain ranges. Ranges follow the ridges of
+    /// a secondary (ridged multifractal) noise function, which form long,
+    /// thin chains rather than the round blobs of the main noise. This is
+    /// the fraction of the full elevation range that gets added at the crest
+    /// of a ridge, falling off to nothing away from it. Ridges are added
+    /// before smoothing. 0 disables ranges entirely.
+    #[validate(range(min = 0.0))]
+    pub ridge_strength: f64,
+
+    /// Flat amount added to every tile's elevation, to raise or lower the
+    /// whole world without touching the noise. Positive values mean more land
+    /// and less ocean, negative values the opposite. This is applied right
+    /// after the noise (and mountain ranges), before smoothing and the edge
+    /// buffer. The result is still clamped to the elevation range.
+    pub elevation_offset: Meter,
+
     /// The fraction of the world's radius that is buffer space. Tiles in the
     /// buffer space will be pushed down, to ensure that the very edge of the
     /// world is all ocean. The closer to the edge a tile is, the more it will
@@ -70,13 +171,55 @@ pub struct ElevationConfig {
     /// steeper towards the edge. Super-1.0 exponents will do the opposite
     /// (steep at first, then gradual out at the edge).
     pub edge_buffer_exponent: f64,
+
+    /// Guarantee that the world is surrounded by ocean. If enabled, the
+    /// outermost ring of tiles is forced below sea level (after the edge
+    /// buffer), and any body of water touching the edge of the world becomes
+    /// ocean, no matter how small it is. Without this, land can run right up
+    /// to the edge of the world, e.g. if there's no edge buffer. Has no effect
+    /// on wrapping worlds, since they have no edge.
+    pub force_ocean_border: bool,
+
+    /// Guarantee a landmass in the middle of the world, e.g. so a game always
+    /// has somewhere to spawn. If enabled, tiles near the origin are raised
+    /// up to a low cone that peaks a few meters above sea level at the
+    /// origin, and falls off to sea level a short distance away (scaled to
+    /// the world radius). Anything already higher than the cone is left
+    /// alone, so this fills in valleys and shallow seas without flattening
+    /// or building mountains. The landmass is only guaranteed to be land,
+    /// not dry, so lakes can still form on it. This is applied after
+    /// rounding, but before
+    /// [force_ocean_border](Self::force_ocean_border), so the border still
+    /// wins on tiny worlds.
+    pub guarantee_center_land: bool,
+
+    /// If defined, no tile will be any deeper than this. Any tile that ends
+    /// up below this elevation will be raised up to it, which flattens out
+    /// deep trenches in the ocean. Only tiles below sea level are affected,
+    /// so this must be at or below [sea level](crate::World::SEA_LEVEL). The
+    /// floor is applied last, after the edge buffer and rounding.
+    #[validate(custom = "validate_ocean_floor")]
+    pub ocean_floor: Option<Meter>,
+
+    /// Depth of the continental shelf. Ocean tiles at most this far below sea
+    /// level become [Coast](crate::Biome::Coast), and anything deeper becomes
+    /// [Ocean](crate::Biome::Ocean). This is purely based on depth, so it
+    /// doesn't matter how close the water is to land. Should be positive; 0
+    /// means only tiles exactly at sea level are coast. This doesn't affect
+    /// which bodies of water become ocean in the first place, so it has no
+    /// effect on lakes.
+    pub coast_depth: Meter,
 }
 
 /// Configuration related to rainfall and evaporation simulation. These params
 /// control how rainfall is generated for the world, which in turn has a major
 /// impact on runoff and feature generation.
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Validate)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Validate)]
 #[serde(default)]
+#[validate(schema(
+    function = "validate_extra_evaporation",
+    skip_on_field_errors = false
+))]
 pub struct RainfallConfig {
     /// Should rainfall and runoff be simulated? Rainfall and runoff simulation
     /// are fairly slow, so disable this if you need quick generation and don't
@@ -96,6 +239,15 @@ pub struct RainfallConfig {
     #[validate(range(min = 0.0))]
     pub evaporation_land_scale: f64,
 
+    /// Extra evaporation for water tiles that are adjacent to land, as a
+    /// fraction of the default evaporation. E.g. with a bonus of 0.5, each
+    /// coastal water tile will produce 1.5x the default evaporation. This
+    /// controls how much moisture the clouds carry onto land, and therefore
+    /// how far inland rain penetrates. 0 means coastal water is treated like
+    /// any other water.
+    #[validate(range(min = 0.0))]
+    pub coastal_evaporation_bonus: f64,
+
     /// The distance (in tiles) that evaporation spreads, perpendicular to the
     /// wind. E.g. if we consider the wind direction to be *forward*, then
     /// this is the distance to the left and right that a particular tile's
@@ -118,6 +270,93 @@ pub struct RainfallConfig {
     /// pretty small, to allow water spreading over large chunks of land.
     #[validate(range(min = 0.0, max = 1.0))]
     pub rainfall_fraction_limit: f64,
+
+    /// The minimum amount of rainfall that every tile gets. After the
+    /// simulation runs, any tile that got less than this is bumped up to it.
+    /// This prevents big rain shadows from leaving huge regions bone dry.
+    /// Has no effect if rainfall simulation is disabled.
+    pub min_rainfall: Meter3,
+
+    /// Additional evaporation sources, on top of the normal evaporation for
+    /// each tile. Each tile in this map will produce this much extra
+    /// evaporation when the clouds pass over it, regardless of biome. Use
+    /// this to inject moisture in unusual spots, e.g. an oasis in the
+    /// desert. Positions that aren't in the world are ignored. Every value
+    /// must be non-negative. This is serialized as a list of
+    /// `(position, evaporation)` pairs.
+    #[serde(with = "crate::util::serde_tile_point_map_to_pairs")]
+    pub extra_evaporation: TilePointMap<Meter3>,
+
+    /// If defined, the water level of a lake/basin can never rise above this
+    /// elevation. Any runoff that would pile up above the cap spills out as
+    /// overflow to the ocean, i.e. it's removed from the world. This is
+    /// **not** physically accurate; it's a safeguard for rendering and
+    /// realism, to prevent towering lakes high up on plateaus. Tiles whose
+    /// ground is already above the cap can't hold any runoff.
+    pub max_runoff_elevation: Option<Meter>,
+
+    /// Exponent applied to the elevation difference between a tile and each
+    /// of its lower neighbors, when deciding how much of the tile's runoff
+    /// goes to each neighbor. At 1.0, runoff is split proportionally to the
+    /// elevation difference. Higher values concentrate more of it into the
+    /// steepest neighbor, which makes for fewer but bigger rivers. Lower
+    /// values spread it out more evenly, and 0 splits it evenly between all
+    /// lower neighbors.
+    #[validate(range(min = 0.0))]
+    pub runoff_concentration: f64,
+
+    /// Safety valve for runoff simulation. After runoff flows downhill, it
+    /// backs up into basins, which can overflow into each other over and
+    /// over until everything settles. This is the maximum number of basin
+    /// updates allowed for each continent. If a continent hits it, a warning
+    /// is logged and its basins are finalized as they are, so some lakes may
+    /// not have finished filling. This always converges eventually, so the
+    /// default is high enough that normal worlds never get near it; it's only
+    /// here to put a predictable bound on generation time for pathological
+    /// terrain.
+    #[validate(range(min = 1))]
+    pub max_backflow_iterations: u32,
+}
+
+/// Configuration related to temperature generation. Temperature is determined
+/// by latitude (distance from the equator) and elevation. The equator runs
+/// horizontally through the center of the world, and the top and bottom edges
+/// are the poles.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Validate)]
+#[serde(default)]
+pub struct TemperatureConfig {
+    /// Temperature at sea level on the equator. This is the warmest that any
+    /// tile can get.
+    pub equator_temperature: Celsius,
+
+    /// Temperature at sea level at the poles. Temperature scales linearly
+    /// between the equator and the poles.
+    pub pole_temperature: Celsius,
+
+    /// Degrees of cooling per meter of elevation above sea level. Tiles at or
+    /// below sea level are unaffected.
+    #[validate(range(min = 0.0))]
+    pub elevation_lapse_rate: f64,
+
+    /// Latitude of the warmest line of the world, in `[-1, 1]` (see
+    /// [World::latitude](crate::World::latitude)). At 0, the warmest line is
+    /// the equator and the two hemispheres are mirror images. Shifting it
+    /// north or south makes one hemisphere larger than the other. The larger
+    /// hemisphere cools more gradually on the way to its pole, so it ends up
+    /// warmer overall. Both poles are still at
+    /// [pole_temperature](Self::pole_temperature). This only affects
+    /// temperature, so e.g. ice caps are unaffected.
+    #[validate(range(min = -1.0, max = 1.0))]
+    pub hemisphere_offset: f64,
+
+    /// How strongly ocean currents pull the temperature of coastal land
+    /// toward the temperature of the sea, in `[0, 1]`. Currents are carried
+    /// by the prevailing wind, so only land tiles with ocean directly upwind
+    /// are affected. Each affected tile moves this fraction of the way toward
+    /// the average temperature of its upwind ocean tiles, so e.g. a coast
+    /// facing a wind off a warmer sea ends up milder. 0 disables currents.
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub current_strength: f64,
 }
 
 /// Configuration surrounding how geographic features are generated. See
@@ -132,6 +371,63 @@ pub struct GeoFeatureConfig {
     /// passed over a tile (from/towards a particular direction) as opposed to
     /// the runoff that ended up on the tile after runoff simulation finished.
     pub river_runoff_traversed_threshold: Meter3,
+
+    /// The minimum number of tiles that a single contiguous lake must cover
+    /// to be considered an inland sea. Every tile in a lake that meets this
+    /// threshold will get the [InlandSea](crate::GeoFeature::InlandSea)
+    /// feature.
+    #[validate(range(min = 1))]
+    pub inland_sea_size_threshold: usize,
+
+    /// How much rivers should meander, in `[0, 1]`. Runoff always follows
+    /// the steepest descent, which makes for straight, angular rivers. With
+    /// meandering, each river segment with a gentle enough slope has this
+    /// chance of being rerouted through a neighboring tile, to create a bend.
+    /// Rivers are only ever rerouted through tiles that keep the flow
+    /// downhill. 0 disables meandering.
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub meander_strength: f64,
+}
+
+/// Configuration related to biome generation. See [Biome](crate::Biome) for
+/// more info.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Validate)]
+#[serde(default)]
+pub struct BiomeConfig {
+    /// The absolute latitude at which polar ice caps can start to form, where
+    /// 0 is the equator and 1 is either pole. Any tile at or beyond this
+    /// latitude that's at or below freezing gets the
+    /// [Ice](crate::GeoFeature::Ice) feature, and land tiles with ice become
+    /// [Snow](crate::Biome::Snow). 1.0 limits ice to the very edge of the
+    /// world.
+    #[validate(range(min = 0.0, max = 1.0))]
+    pub ice_cap_latitude: f64,
+
+    /// If defined, every land tile above this elevation becomes
+    /// [Snow](crate::Biome::Snow), regardless of its normal biome. This is
+    /// purely elevation-based, so it's a quick way to get snowy mountain tops
+    /// without tuning the temperature model. Tiles at or below the line are
+    /// unaffected.
+    pub snow_line: Option<Meter>,
+
+    /// Clean up speckled biomes. If enabled, any land tile whose biome
+    /// doesn't match any of its land neighbors gets reassigned to the most
+    /// common biome among itself and those neighbors. Water tiles are never
+    /// changed, and don't count towards the majority. The snow line (if any)
+    /// is applied after smoothing, so it always wins.
+    pub smoothing: bool,
+
+    /// If defined, dry land tiles that are more than this many tiles from the
+    /// nearest ocean ([Ocean](crate::Biome::Ocean) or
+    /// [Coast](crate::Biome::Coast) tiles) become
+    /// [Desert](crate::Biome::Desert), regardless of their normal biome.
+    /// Lakes and rivers don't count as water here, since they sit on land
+    /// tiles. This dries out the interiors of large continents, where
+    /// moisture from the sea can't reach. Distance is measured the same way
+    /// as [Tile::coast_distance](crate::Tile::coast_distance), and only
+    /// tiles whose humidity is already low are affected, so wet interiors
+    /// (e.g. behind a windward coast) keep their biome.
+    pub desert_min_coast_distance: Option<u16>,
 }
 
 /// Config for a particular noise generation function. We use
@@ -152,15 +448,28 @@ pub struct NoiseFnConfig {
     #[validate(range(min = 0.01))]
     pub frequency: f64,
 
-    /// Constant to add to the frequency for each octave. E.g. if we have 3
+    /// Factor to multiply the frequency by for each octave. E.g. if we have 3
     /// octaves, a base frequency of 1.0, and a lacunarity of 2.0, then our
-    /// 3 octaves will be at [1.0, 3.0, 5.0].
+    /// 3 octaves will be at [1.0, 2.0, 4.0].
+    ///
+    /// Must be at least 1. Anything lower makes each octave coarser than the
+    /// last, so the extra octaves just pile more weight onto the same broad
+    /// features instead of adding detail.
+    #[validate(range(min = 1.0))]
     pub lacunarity: f64,
 
     /// Amplification factor for each octave. The first amplitude is always
     /// 1.0, then is multiplied by the persistence for each octave. E.g. with 3
     /// octaves and a persistence of 0.5, your amplitudes will be `[1.0, 0.5,
     /// 0.25]`.
+    ///
+    /// Must be in `(0, 1]`. With a persistence above 1 the amplitudes grow
+    /// exponentially with each octave, so with more than a handful of octaves
+    /// the composite value saturates and nearly every tile ends up at the
+    /// min or max elevation. A negative persistence flips the sign of every
+    /// other octave, which isn't useful either, and a persistence of 0
+    /// silences every octave but the first.
+    #[validate(custom = "validate_persistence")]
     pub persistence: f64,
 
     /// Exponent to apply to elevation values after generation. This is applied
@@ -194,6 +503,17 @@ pub enum NoiseFnType {
 }
 
 impl WorldConfig {
+    /// Get the default config, but with a fixed seed. [Self::default] picks a
+    /// random seed on every call (see [Seed::default]), which is nice for
+    /// users but bad for tests and examples that need the same world every
+    /// time. This gives the same full default config, just reproducibly.
+    pub fn default_with_seed(seed: Seed) -> Self {
+        Self {
+            seed,
+            ..Default::default()
+        }
+    }
+
     /// Convert a string to a numeric value that can be used as a 64-bit RNG
     /// seed in a world config. This will attempt to parse the string as a
     /// number. If that fails, it will hash the string into a number.
@@ -204,6 +524,146 @@ impl WorldConfig {
             hasher.finish()
         })
     }
+
+    /// The number of tiles that a world generated from this config will
+    /// contain. This is determined solely by the radius, and can be useful
+    /// for estimating generation cost before actually generating.
+    pub fn tile_count(&self) -> usize {
+        util::world_len(self.radius)
+    }
+
+    /// Estimate the cost of generating a world from this config, without
+    /// actually generating it. This is very cheap, so UIs can use it to warn
+    /// users before they kick off a huge generation. See
+    /// [GenerationEstimate].
+    pub fn estimate(&self) -> GenerationEstimate {
+        let tile_count = self.tile_count();
+        GenerationEstimate {
+            tile_count,
+            peak_memory_bytes: tile_count * world::BYTES_PER_TILE,
+            runoff_enabled: self.rainfall.enabled,
+        }
+    }
+
+    /// Generate a pronounceable name for the world, e.g. "Valoria". This is
+    /// purely cosmetic, but it's derived deterministically from the seed, so
+    /// the same seed always gets the same name. No other config fields affect
+    /// the name.
+    pub fn generate_name(&self) -> String {
+        const ONSETS: &[&str] = &[
+            "b", "br", "c", "d", "dr", "f", "g", "gr", "h", "k", "l", "m", "n",
+            "p", "r", "s", "sh", "st", "t", "th", "v", "z",
+        ];
+        const VOWELS: &[&str] =
+            &["a", "e", "i", "o", "u", "ae", "ai", "ea", "io", "ou"];
+        const ENDINGS: &[&str] =
+            &["", "", "a", "ia", "on", "or", "us", "ar", "en", "is", "os"];
+
+        // Hash the seed first, so that similar seeds (e.g. 1 and 2) don't
+        // end up with similar names
+        let mut hasher = FnvHasher::default();
+        self.seed.to_u64().hash(&mut hasher);
+        let bytes = hasher.finish().to_le_bytes();
+
+        // 2-3 syllables, then an ending
+        let syllables = 2 + (bytes[0] % 2) as usize;
+        let mut name = String::new();
+        for i in 0..syllables {
+            let byte = bytes[i + 1] as usize;
+            name.push_str(ONSETS[byte % ONSETS.len()]);
+            name.push_str(VOWELS[(byte / ONSETS.len()) % VOWELS.len()]);
+        }
+        name.push_str(ENDINGS[bytes[4] as usize % ENDINGS.len()]);
+
+        // Capitalize the first letter. Everything in the tables is ASCII
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => {
+                first.to_ascii_uppercase().to_string() + chars.as_str()
+            }
+            None => name,
+        }
+    }
+
+    /// Get a variation of this config, which is identical except for a
+    /// deterministically perturbed seed. This makes it easy to browse worlds
+    /// that are "nearby" a world you like: `variant(1)`, `variant(2)`, etc.
+    /// Variant 0 is the original config. The perturbed seed is the resolved
+    /// seed XOR'd with a hash of `n`, so text seeds become int seeds.
+    pub fn variant(&self, n: u32) -> WorldConfig {
+        if n == 0 {
+            return self.clone();
+        }
+
+        let mut hasher = FnvHasher::default();
+        n.hash(&mut hasher);
+        WorldConfig {
+            seed: (self.seed.to_u64() ^ hasher.finish()).into(),
+            ..self.clone()
+        }
+    }
+
+    /// Hash this config into a number. Equal configs always have the same hash
+    /// (within one version of Terra), and any change to the config will
+    /// almost certainly change the hash. This is handy for checking whether a
+    /// saved world came from a particular config, without storing the whole
+    /// config. See [WorldMeta](crate::WorldMeta). [Self::capture_layers] is
+    /// left out of the hash, since it doesn't change the generated world.
+    pub fn config_hash(&self) -> u64 {
+        // Floats can't be hashed directly, so hash the debug representation
+        // instead. Hash maps don't have a stable order, so extra evaporation
+        // gets pulled out and sorted first.
+        let mut extra_evaporation: Vec<_> =
+            self.rainfall.extra_evaporation.iter().collect();
+        extra_evaporation.sort_by_key(|(pos, _)| **pos);
+        let config = WorldConfig {
+            capture_layers: false,
+            rainfall: RainfallConfig {
+                extra_evaporation: TilePointMap::default(),
+                ..self.rainfall.clone()
+            },
+            ..self.clone()
+        };
+
+        let mut hasher = FnvHasher::default();
+        format!("{config:?}").hash(&mut hasher);
+        format!("{extra_evaporation:?}").hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The number of rings at the outer edge of the world that make up the
+    /// elevation edge buffer. See [ElevationConfig::edge_buffer_fraction].
+    /// Wrapping worlds have no edge, so they have no buffer.
+    pub fn edge_buffer_size(&self) -> u16 {
+        if self.wrap {
+            0
+        } else {
+            (self.radius as f64 * self.elevation.edge_buffer_fraction).round()
+                as u16
+        }
+    }
+
+    /// Distance from a tile to the logical center of the world (see
+    /// [Self::center]), i.e. the distance from the tile's position in the
+    /// larger map to the origin of that map. For an uncentered world, this
+    /// is just the distance to the origin.
+    pub fn center_distance(&self, pos: TilePoint) -> usize {
+        // Use i32 so the shifted point can't overflow
+        let x = pos.x() as i32 + self.center.x() as i32;
+        let y = pos.y() as i32 + self.center.y() as i32;
+        ((x.abs() + y.abs() + (x + y).abs()) / 2) as usize
+    }
+
+    /// The radius to wrap neighbor lookups around, if wrapping is enabled.
+    /// This is the form that the hex/cluster logic takes, where `None` means
+    /// positions off the edge of the world are left as-is.
+    pub fn wrap_radius(&self) -> Option<u16> {
+        if self.wrap {
+            Some(self.radius)
+        } else {
+            None
+        }
+    }
 }
 
 impl Default for WorldConfig {
@@ -215,14 +675,23 @@ impl Default for WorldConfig {
         Self {
             seed: Default::default(),
             radius: 100,
+            wrap: false,
+            center: TilePoint::ORIGIN,
+            capture_layers: false,
             elevation: Default::default(),
             rainfall: Default::default(),
+            temperature: Default::default(),
             geo_feature: Default::default(),
+            biome: Default::default(),
         }
     }
 }
 
 impl Default for Seed {
+    /// Pick a random integer seed. **This is nondeterministic**: every call
+    /// gives a different seed, so every call to [WorldConfig::default]
+    /// describes a different world. Use [WorldConfig::default_with_seed] if
+    /// you need the default config to be reproducible.
     fn default() -> Self {
         // Danger! This means the default will vary between calls!
         Self::Int(rand::random())
@@ -241,8 +710,15 @@ impl Default for ElevationConfig {
                 exponent: 0.9,
             },
             rounding_interval: None,
+            smoothing_passes: 0,
+            ridge_strength: 0.0,
+            elevation_offset: Meter(0.0),
             edge_buffer_fraction: 0.25,
             edge_buffer_exponent: 0.7,
+            force_ocean_border: true,
+            guarantee_center_land: false,
+            ocean_floor: None,
+            coast_depth: Meter(3.0),
         }
     }
 }
@@ -253,9 +729,27 @@ impl Default for RainfallConfig {
             enabled: true,
             evaporation_default: Meter3(5.0),
             evaporation_land_scale: 0.35,
+            coastal_evaporation_bonus: 0.0,
             evaporation_spread_distance: 50,
             evaporation_spread_exponent: 0.6,
             rainfall_fraction_limit: 0.03,
+            min_rainfall: Meter3(0.0),
+            extra_evaporation: TilePointMap::default(),
+            max_runoff_elevation: None,
+            runoff_concentration: 1.0,
+            max_backflow_iterations: 1_000_000,
+        }
+    }
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        Self {
+            equator_temperature: Celsius(30.0),
+            pole_temperature: Celsius(-20.0),
+            elevation_lapse_rate: 0.2,
+            hemisphere_offset: 0.0,
+            current_strength: 0.0,
         }
     }
 }
@@ -264,6 +758,151 @@ impl Default for GeoFeatureConfig {
     fn default() -> Self {
         Self {
             river_runoff_traversed_threshold: Meter3(100.0),
+            inland_sea_size_threshold: 100,
+            meander_strength: 0.0,
+        }
+    }
+}
+
+impl Default for BiomeConfig {
+    fn default() -> Self {
+        Self {
+            ice_cap_latitude: 0.8,
+            snow_line: None,
+            smoothing: false,
+            desert_min_coast_distance: None,
+        }
+    }
+}
+
+/// An ocean floor above sea level would raise every ocean tile up to dry land
+fn validate_ocean_floor(ocean_floor: &Meter) -> Result<(), ValidationError> {
+    if *ocean_floor <= world::World::SEA_LEVEL {
+        Ok(())
+    } else {
+        Err(ValidationError::new("ocean_floor_above_sea_level"))
+    }
+}
+
+/// Validator's range check is inclusive, so the open end needs a custom check.
+/// See [NoiseFnConfig::persistence].
+fn validate_persistence(persistence: f64) -> Result<(), ValidationError> {
+    if persistence > 0.0 && persistence <= 1.0 {
+        Ok(())
+    } else {
+        Err(ValidationError::new("persistence_out_of_range"))
+    }
+}
+
+/// Negative evaporation would pull water out of the clouds for free. This is a
+/// struct-level check because field-level errors include the field value, and
+/// a map with tile point keys can't be converted to JSON.
+fn validate_extra_evaporation(
+    config: &RainfallConfig,
+) -> Result<(), ValidationError> {
+    if config
+        .extra_evaporation
+        .values()
+        .all(|evaporation| *evaporation >= Meter3(0.0))
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("negative_extra_evaporation"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_generate_name() {
+        let config = |seed: Seed| WorldConfig {
+            seed,
+            ..Default::default()
+        };
+
+        // Same seed gives the same name, regardless of other config values
+        let name = config(1021522790211909.into()).generate_name();
+        assert_eq!(name, config(1021522790211909.into()).generate_name());
+        assert_eq!(
+            name,
+            WorldConfig {
+                radius: 5,
+                ..config(1021522790211909.into())
+            }
+            .generate_name()
+        );
+        assert!(name.chars().next().unwrap().is_ascii_uppercase());
+        assert!(name.chars().skip(1).all(|c| c.is_ascii_lowercase()));
+
+        // Text seeds work too, since they resolve to an int
+        assert_eq!(
+            config("hello".into()).generate_name(),
+            config("hello".into()).generate_name()
+        );
+
+        // Different seeds should usually give different names
+        let names: HashSet<String> = (0..100u64)
+            .map(|seed| config(seed.into()).generate_name())
+            .collect();
+        assert!(names.len() > 90, "too many duplicate names: {names:?}");
+    }
+
+    #[test]
+    fn test_estimate() {
+        let config = |radius: u16| WorldConfig {
+            radius,
+            ..Default::default()
+        };
+
+        let small = config(10).estimate();
+        assert_eq!(small.tile_count, config(10).tile_count());
+        assert!(small.runoff_enabled);
+        // Memory is proportional to tile count, which grows with radius²
+        let large = config(100).estimate();
+        assert_eq!(large.tile_count, config(100).tile_count());
+        assert!(large.peak_memory_bytes > small.peak_memory_bytes * 50);
+        assert_eq!(
+            large.peak_memory_bytes / large.tile_count,
+            small.peak_memory_bytes / small.tile_count
+        );
+
+        let no_runoff = WorldConfig {
+            rainfall: RainfallConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..config(10)
+        };
+        assert!(!no_runoff.estimate().runoff_enabled);
+    }
+
+    #[test]
+    fn test_variant() {
+        let config = WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 20,
+            ..Default::default()
+        };
+        assert_eq!(config.variant(0), config);
+
+        let seeds: HashSet<u64> =
+            (1..=10).map(|n| config.variant(n).seed.to_u64()).collect();
+        assert_eq!(seeds.len(), 10);
+        assert!(!seeds.contains(&config.seed.to_u64()));
+        for n in 1..=10 {
+            // Variants are stable
+            assert_eq!(config.variant(n), config.variant(n));
+            // Everything but the seed is untouched
+            assert_eq!(
+                WorldConfig {
+                    seed: config.seed.clone(),
+                    ..config.variant(n)
+                },
+                config
+            );
         }
     }
 }
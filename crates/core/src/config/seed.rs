@@ -52,6 +52,26 @@ impl Seed {
             }
         }
     }
+
+    /// Get a sequence of `count` seeds derived from this one, e.g. one for
+    /// each frame of an animation. The first seed is always this seed (as an
+    /// int), and each one after that is a hash of this seed and its index in
+    /// the sequence. The sequence is deterministic, so frame `n` always maps
+    /// to the same world for the same base seed, and extending `count` never
+    /// changes the earlier seeds. Note that neighboring seeds still produce
+    /// entirely unrelated worlds.
+    pub fn sequence(&self, count: usize) -> impl Iterator<Item = Seed> {
+        let base = self.to_u64();
+        (0..count).map(move |i| {
+            if i == 0 {
+                Self::Int(base)
+            } else {
+                let mut hasher = FnvHasher::default();
+                (base, i as u64).hash(&mut hasher);
+                Self::Int(hasher.finish())
+            }
+        })
+    }
 }
 
 impl From<u64> for Seed {
@@ -177,6 +197,38 @@ mod tests {
         assert_eq!(Seed::Text("potato".into()).to_u64(), 6265489318014208823);
     }
 
+    /// Test generating derived seed sequences
+    #[test]
+    fn test_sequence() {
+        let seed = Seed::Int(12506774975058000);
+        let sequence: Vec<Seed> = seed.sequence(5).collect();
+        assert_eq!(sequence.len(), 5);
+        assert_eq!(sequence[0], seed);
+        // Same base gives the same sequence every time
+        assert_eq!(sequence, seed.sequence(5).collect::<Vec<_>>());
+        // Longer sequences just extend shorter ones
+        assert_eq!(sequence[..3], seed.sequence(3).collect::<Vec<_>>());
+        // Every seed should be distinct
+        for (i, a) in sequence.iter().enumerate() {
+            assert!(sequence[i + 1..].iter().all(|b| a != b), "{a} repeated");
+        }
+
+        // A text seed produces the same sequence as its hashed int
+        let text_seed = Seed::Text("potato".into());
+        assert_eq!(
+            text_seed.sequence(5).collect::<Vec<_>>(),
+            Seed::Int(text_seed.to_u64())
+                .sequence(5)
+                .collect::<Vec<_>>()
+        );
+        assert_ne!(
+            sequence,
+            text_seed.sequence(5).collect::<Vec<_>>(),
+            "different bases should give different sequences"
+        );
+        assert_eq!(seed.sequence(0).count(), 0);
+    }
+
     /// Test serialization of seeds
     #[test]
     fn test_serialize() {
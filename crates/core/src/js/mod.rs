@@ -16,7 +16,8 @@
 mod util;
 
 use crate::{
-    js::util::ResultExt, RenderConfig, World, WorldConfig, WorldRenderer,
+    js::util::ResultExt, Meter, RenderConfig, TilePoint, World, WorldConfig,
+    WorldRenderer,
 };
 use wasm_bindgen::{prelude::*, JsCast};
 
@@ -78,16 +79,45 @@ pub fn build_renderer(
     WorldRenderer::new(render_config).into_js()
 }
 
+/// Get the color table for the renderer's configured tile lens, for drawing
+/// legends. See [WorldRenderer::color_table].
+#[wasm_bindgen]
+pub fn get_color_table(renderer: &WorldRenderer) -> ColorTableObject {
+    JsValue::from_serde(&renderer.color_table())
+        .unwrap()
+        .unchecked_into()
+}
+
+/// Set the elevation of a single tile. See [World::set_elevation].
+#[wasm_bindgen]
+pub fn set_elevation(
+    world: &mut World,
+    pos: TilePoint,
+    elevation: Meter,
+) -> Result<(), JsValue> {
+    world.set_elevation(pos, elevation).into_js()
+}
+
+/// Recompute everything that depends on water after editing elevations. See
+/// [World::resimulate_water].
+#[wasm_bindgen]
+pub fn resimulate_water(world: &mut World) -> Result<(), JsValue> {
+    world.resimulate_water().into_js()
+}
+
 /// A type-hacked wrapper around [terra::World::tiles]. This typing can be
 /// cleaned up after https://github.com/rustwasm/wasm-bindgen/issues/111,
 /// then we can use the built-in `.tiles()` on the world instead.
+///
+/// Tiles are returned in the order of [World::tiles_sorted], so the output
+/// lines up with [WorldRenderer::tile_colors_for_lens].
 #[wasm_bindgen]
 pub fn copy_tiles(world: &World) -> TileArray {
     use js_sys::Array;
 
     world
-        .tiles()
-        .values()
+        .tiles_sorted()
+        .into_iter()
         .map(|tile| JsValue::from(tile.clone()))
         .collect::<Array>()
         .unchecked_into()
@@ -101,6 +131,9 @@ const TS_APPEND_CONTENT: &'static str = r#"
 export interface WorldConfigObject {
     seed: string | number;
     radius: number;
+    wrap: boolean;
+    center: { x: number; y: number };
+    capture_layers: boolean;
     elevation: {
         noise_fn: {
             noise_type: 'basic_multi' | 'billow' | 'fbm' | 'hybrid_multi' | 'ridged_multi';
@@ -111,20 +144,48 @@ export interface WorldConfigObject {
             exponent: number;
         };
         rounding_interval: number | undefined;
+        smoothing_passes: number;
+        ridge_strength: number;
+        elevation_offset: number;
         edge_buffer_fraction: number;
         edge_buffer_exponent: number;
+        force_ocean_border: boolean;
+        guarantee_center_land: boolean;
+        ocean_floor: number | undefined;
+        coast_depth: number;
     };
     rainfall: {
         enabled: boolean;
         evaporation_default: number;
         evaporation_land_scale: number;
+        coastal_evaporation_bonus: number;
         evaporation_spread_distance: number;
         evaporation_spread_exponent: number;
         rainfall_fraction_limit: number;
+        min_rainfall: number;
+        extra_evaporation: Array<[{ x: number; y: number }, number]>;
+        max_runoff_elevation: number | undefined;
+        runoff_concentration: number;
+        max_backflow_iterations: number;
+    };
+    temperature: {
+        equator_temperature: number;
+        pole_temperature: number;
+        elevation_lapse_rate: number;
+        hemisphere_offset: number;
+        current_strength: number;
     };
     geo_feature: {
         lake_runoff_threshold: number;
         river_runoff_traversed_threshold: number;
+        inland_sea_size_threshold: number;
+        meander_strength: number;
+    };
+    biome: {
+        ice_cap_latitude: number;
+        snow_line: number | undefined;
+        smoothing: boolean;
+        desert_min_coast_distance: number | undefined;
     };
 }
 
@@ -133,9 +194,50 @@ export interface WorldConfigObject {
  */
 export interface RenderConfigObject {
     vertical_scale: number;
-    tile_lens: 'surface' | 'biome' | 'elevation' | 'humidity' | 'runoff';
+    tile_lens: 'surface' | 'biome' | 'elevation' | 'humidity' | 'fertility' | 'watershed' | 'runoff' | 'terrain' | 'coast_distance';
+    elevation_gradient: Array<[number, { red: number; green: number; blue: number }]> | undefined;
+    palette:
+        | 'standard'
+        | 'realistic'
+        | 'pastel'
+        | 'retro'
+        | 'grayscale'
+        | { custom: Partial<Record<'ocean' | 'coast' | 'snow' | 'desert' | 'alpine' | 'jungle' | 'forest' | 'plains', { red: number; green: number; blue: number }>> };
+    hidden_biomes: Array<'ocean' | 'coast' | 'snow' | 'desert' | 'alpine' | 'jungle' | 'forest' | 'plains'>;
     show_features: boolean;
+    min_lake_depth: number;
+    svg_view_box: [{ x: number; y: number }, { x: number; y: number }] | undefined;
+    svg_padding: number;
+    minimap: boolean;
+    draw_biome_borders: boolean;
+    show_elevation_labels: boolean;
+    position_jitter: number;
+    stl_surface_only: boolean;
+    stl_bevel: number;
+    png_supersample: number;
+    tile_scale: number;
+    hex_orientation: 'pointy_top' | 'flat_top';
 }
+
+/**
+ * See description in the `extern "C"` section below
+ */
+export type ColorTableObject =
+    | {
+        kind: 'discrete';
+        entries: Array<{
+            label: string;
+            color: { red: number; green: number; blue: number };
+        }>;
+    }
+    | {
+        kind: 'gradient';
+        stops: Array<{
+            value: number;
+            label: string;
+            color: { red: number; green: number; blue: number };
+        }>;
+    };
 "#;
 
 #[wasm_bindgen]
@@ -154,6 +256,10 @@ extern "C" {
     #[wasm_bindgen(typescript_type = "RenderConfigObject")]
     pub type RenderConfigObject;
 
+    /// The output of [WorldRenderer::color_table], as a JS object
+    #[wasm_bindgen(typescript_type = "ColorTableObject")]
+    pub type ColorTableObject;
+
     /// Type hack needed until https://github.com/rustwasm/wasm-bindgen/issues/111
     #[wasm_bindgen(typescript_type = "Tile[]")]
     pub type TileArray;
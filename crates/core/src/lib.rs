@@ -6,7 +6,7 @@
 //! ```
 //! use terra::{WorldConfig, World};
 //!
-//! let config = WorldConfig::default();
+//! let config = WorldConfig::default_with_seed(1021522790211909.into());
 //! let world = World::generate(config).unwrap();
 //! println!("{}", world.tiles().len());
 //! // From here you can display/use the world however you like.
@@ -22,6 +22,8 @@
 //!   ([World::from_bin]/[World::to_bin])
 //! - `json` - Import/export worlds to/from JSON format
 //!   ([World::from_json]/[World::to_json])
+//! - `png` - Render worlds as 2D PNG images
+//!   ([WorldRenderer::render_png_with]/[WorldRenderer::render_layers])
 //! - `stl` - Render worlds in 3D STL format ([World::to_stl])
 //! - `svg` - Render worlds in 2D SVG format ([World::to_svg])
 //! - `js` - Enable Wasm entrypoint for direct access from JS
@@ -37,24 +39,39 @@ mod render;
 mod util;
 mod world;
 
+#[cfg(feature = "stl")]
+pub use crate::render::stl::{StlMetadata, StlTriangleRange};
+#[cfg(feature = "json")]
+pub use crate::world::JsonExportOptions;
 pub use crate::{
     config::{
-        ElevationConfig, GeoFeatureConfig, NoiseFnConfig, NoiseFnType,
-        RainfallConfig, Seed, WorldConfig,
+        BiomeConfig, ElevationConfig, GenerationEstimate, GeoFeatureConfig,
+        NoiseFnConfig, NoiseFnType, RainfallConfig, Seed, TemperatureConfig,
+        WorldConfig,
+    },
+    render::{
+        config::RenderConfig,
+        legend::{ColorEntry, ColorTable, GradientStop},
+        raster::rasterize_tiles,
+        unit::{Color3, Color4, Point2},
+        HexOrientation, Palette, TileLens, WorldRenderer,
     },
-    render::{config::RenderConfig, unit::Point2, TileLens, WorldRenderer},
     util::{
         range::{NumRange, RangeValue},
-        unit::{Meter, Meter2, Meter3},
+        unit::{Celsius, Meter, Meter2, Meter3},
     },
     world::{
+        flood_fill,
         hex::{
             HasHexPosition, HexAxialDirection, HexAxis, HexDirection,
-            TileDirection, TileDirectionMap, TilePoint, TilePointMap,
-            VertexDirection,
+            TileBoundingBox, TileDirection, TileDirectionMap, TilePoint,
+            TilePointMap, TilePointSet, VertexDirection,
         },
         tile::Tile,
-        Biome, BiomeType, GeoFeature, World,
+        Biome, BiomeClimate, BiomeType, Continent, DrainageDestination,
+        GenerationWarning, GeoFeature, GeoFeatureKind, TerrainClass, TileEdge,
+        TileGraph, TileNoiseFn, WatershedId, World, WorldGrid, WorldMeta,
+        WorldStats,
     },
 };
 pub use anyhow;
@@ -1,5 +1,9 @@
-use crate::{Meter3, TileLens};
+use crate::{
+    render::unit::Color3, Biome, HexOrientation, Meter, Meter3, Palette,
+    Point2, TileLens,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use validator::Validate;
 
 /// Configuration specific to visually rendering a world. These options have
@@ -11,7 +15,7 @@ use validator::Validate;
 /// Not all render options apply to all render output formats, e.g.
 /// `vertical_scale` is irrelevant for 2D rendering like SVG. The documentation
 /// for each field will list which formats it applies to.
-#[derive(Copy, Clone, Debug, Serialize, Deserialize, Validate)]
+#[derive(Clone, Debug, Serialize, Deserialize, Validate)]
 #[cfg_attr(feature = "bevy", derive(bevy_ecs::system::Resource))]
 #[serde(default)]
 pub struct RenderConfig {
@@ -36,6 +40,40 @@ pub struct RenderConfig {
     /// - SVG
     pub tile_lens: TileLens,
 
+    /// A custom color gradient for [TileLens::Elevation], as a list of
+    /// `(normalized_elevation, color)` stops sorted by ascending elevation.
+    /// Normalized elevation spans `[0, 1]` over the world's elevation range.
+    /// Tiles between two stops get a color interpolated between them. If
+    /// `None` (or empty), a white→red gradient is used. Stops that are out of
+    /// order or outside `[0, 1]` are rejected by
+    /// [WorldRenderer::new](crate::WorldRenderer::new).
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    pub elevation_gradient: Option<Vec<(f64, Color3)>>,
+
+    /// The set of colors used for biomes, by [TileLens::Surface],
+    /// [TileLens::Biome], and every other lens that draws tiles in their
+    /// biome color (e.g. water in [TileLens::Terrain]). This is handy for
+    /// quickly switching up the look of a rendering. Other lens colors, such
+    /// as gradients, aren't affected. See [Palette].
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    /// - PPM/RGBA
+    pub palette: Palette,
+
+    /// Tiles with any of these biomes will be left out of the rendering
+    /// entirely, e.g. hide [Biome::Ocean] and [Biome::Coast] to render only
+    /// land. Hidden tiles are omitted from SVG output, transparent in raster
+    /// output, and have no geometry in STL output.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    /// - PPM/RGBA
+    /// - STL
+    pub hidden_biomes: HashSet<Biome>,
+
     /// Should geographic features (lakes, rivers, etc.) be visible? See
     /// [crate::GeoFeature] for a full list
     ///
@@ -59,6 +97,144 @@ pub struct RenderConfig {
     /// ## Relevant Formats
     /// - SVG
     pub max_runoff_flow: Meter3,
+
+    /// Lake tiles whose water is shallower than this are drawn as their
+    /// underlying biome, rather than as lake water. Very shallow basins can
+    /// technically be lakes, but look like a thin film of water. Depth is
+    /// the tile's runoff divided by its area. This only affects how lakes
+    /// are drawn; the tile's [Lake](crate::GeoFeature::Lake) feature is
+    /// unchanged. 0 draws every lake.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    /// - PPM/RGBA
+    pub min_lake_depth: Meter,
+
+    /// A fixed view box for SVG output, as the `(top_left, bottom_right)`
+    /// corners in screen space. If `None`, the view box will be computed
+    /// automatically to fit the whole world. A fixed box is useful to keep
+    /// framing consistent across multiple renders (e.g. animation frames of
+    /// different worlds).
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    pub svg_view_box: Option<(Point2, Point2)>,
+
+    /// Maximum distance (in screen space units) to randomly shift each tile's
+    /// center during rendering, for a hand-drawn look. This only affects
+    /// rendered output, not the world data. The offset for each tile is
+    /// derived from its position, so renders are reproducible. 0 disables
+    /// jitter.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    ///
+    /// Raster output (PPM/RGBA/PNG) is **not** jittered. Each pixel is
+    /// mapped straight back to the tile underneath it, so there's no tile
+    /// center to shift.
+    #[validate(range(min = 0.0))]
+    pub position_jitter: f64,
+
+    /// Extra space to add on every side of the SVG view box, in screen space
+    /// units. This applies to both fixed and automatic view boxes.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    #[validate(range(min = 0.0))]
+    pub svg_padding: f64,
+
+    /// Draw a small overview of the whole world in the bottom-right corner of
+    /// the SVG, with a rectangle marking the area covered by the view box.
+    /// This is mostly useful along with
+    /// [svg_view_box](Self::svg_view_box), to show where a zoomed-in render
+    /// sits in the world. The overview is always colored by biome, and large
+    /// worlds are downsampled (see
+    /// [World::downsample](crate::World::downsample)) to keep it cheap to
+    /// draw.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    pub minimap: bool,
+
+    /// Draw a thin line along every edge shared by two tiles with different
+    /// biomes, for a "political map" look. Hidden tiles don't get borders.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    pub draw_biome_borders: bool,
+
+    /// Print each tile's elevation (rounded to the nearest meter) in the
+    /// middle of the tile. This is meant for debugging generation on small
+    /// worlds; on big worlds the labels are too small to read, and they bloat
+    /// the output a lot. Hidden tiles don't get labels.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    pub show_elevation_labels: bool,
+
+    /// Render only the top surface of the world, as a continuous height mesh
+    /// connecting the centers of adjacent tiles, rather than an extruded hex
+    /// prism for each tile. This gives a much lighter and smoother mesh, which
+    /// is handy for topographic prints. The surface has no sides or bottom,
+    /// so **the mesh will not be watertight**.
+    ///
+    /// ## Relevant Formats
+    /// - STL
+    pub stl_surface_only: bool,
+
+    /// Size of the chamfer along the top edges of each tile prism in STL
+    /// output, in screen space units. Each corner of the top face is pulled
+    /// this far in toward the center of the tile, and the outer edge is
+    /// dropped by the same amount, so each side gets a 45° slope between the
+    /// top face and the wall. The outline of each tile stays the same, so
+    /// the overall footprint of the model is unaffected. This softens the
+    /// stair-stepping between tiles, at the cost of 12 extra triangles per
+    /// tile. The chamfer is limited to half the tile radius, and never goes
+    /// below the bottom of the tile. 0 gives sharp prisms. Has no effect with
+    /// [stl_surface_only](Self::stl_surface_only).
+    ///
+    /// ## Relevant Formats
+    /// - STL
+    #[validate(range(min = 0.0))]
+    pub stl_bevel: f64,
+
+    /// Supersampling factor for raster output. Each pixel is rendered as an
+    /// N×N block of sub-pixels, which are then averaged together. This
+    /// smooths out the jagged boundaries between tiles, at the cost of N²
+    /// times the work. The output dimensions are unaffected. 1 disables
+    /// supersampling.
+    ///
+    /// ## Relevant Formats
+    /// - PNG
+    /// - PPM/RGBA
+    #[validate(range(min = 1))]
+    pub png_supersample: u8,
+
+    /// Scale factor for tile geometry in screen space. At 1.0, each tile has a
+    /// vertex radius of
+    /// [WorldRenderer::TILE_VERTEX_RADIUS](crate::WorldRenderer::TILE_VERTEX_RADIUS),
+    /// and every other dimension (tile positions, vertices, view boxes, etc.)
+    /// scales along with it. This is handy for integrating with engines that
+    /// expect a particular tile size. Raster output is unaffected, since its
+    /// scale is set in pixels per tile.
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    /// - STL
+    #[validate(range(min = 0.001))]
+    pub tile_scale: f64,
+
+    /// Which way the tiles point in screen space. This rotates the whole
+    /// projection from hex space to screen space, so tile positions,
+    /// vertices, view boxes, raster images, etc. all follow along. See
+    /// [HexOrientation].
+    ///
+    /// ## Relevant Formats
+    /// - SVG
+    /// - STL
+    /// - PNG
+    /// - PPM/RGBA
+    pub hex_orientation: HexOrientation,
 }
 
 impl Default for RenderConfig {
@@ -66,9 +242,24 @@ impl Default for RenderConfig {
         Self {
             vertical_scale: 1.0,
             tile_lens: TileLens::Surface,
+            elevation_gradient: None,
+            palette: Palette::default(),
+            hidden_biomes: HashSet::new(),
             show_features: true,
             max_runoff: Meter3(5.0),
             max_runoff_flow: Meter3(1000.0),
+            min_lake_depth: Meter(0.0),
+            svg_view_box: None,
+            position_jitter: 0.0,
+            svg_padding: 0.0,
+            minimap: false,
+            draw_biome_borders: false,
+            show_elevation_labels: false,
+            stl_surface_only: false,
+            stl_bevel: 0.0,
+            png_supersample: 1,
+            tile_scale: 1.0,
+            hex_orientation: HexOrientation::default(),
         }
     }
 }
@@ -0,0 +1,180 @@
+use crate::{
+    render::{
+        unit::Color3, COAST_FAR_COLOR, COAST_NEAR_COLOR, ELEVATION_HIGH_COLOR,
+        FERTILITY_HIGH_COLOR, GRADIENT_LOW_COLOR, HUMIDITY_HIGH_COLOR,
+        ICE_COLOR, INLAND_SEA_COLOR, LAKE_COLOR, NO_DATA_COLOR,
+        RUNOFF_WATER_COLOR,
+    },
+    Biome, BiomeType, Meter, TerrainClass, TileLens, World, WorldRenderer,
+};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+/// A description of how a [TileLens] maps tile data to colors. This is meant
+/// for front ends that draw their own legends, so that the legend always
+/// matches how terra colors tiles. See [WorldRenderer::color_table].
+///
+/// Some lenses don't fit neatly into a table, so they're approximated:
+/// - [TileLens::Watershed] colors are derived from each watershed's ID, so only
+///   the fixed colors (water biomes and land without a watershed) are listed
+/// - [TileLens::Runoff] blends two values into one color, so only the corners
+///   of the blend are listed
+/// - [TileLens::CoastDistance] only lists the gradient. Water tiles use their
+///   biome color, as in [TileLens::Biome].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ColorTable {
+    /// Each tile falls into one category, and each category has a fixed color
+    Discrete { entries: Vec<ColorEntry> },
+    /// Tile colors are linearly interpolated between these stops, which are
+    /// sorted by ascending value. Values beyond either end get the color of
+    /// the nearest stop.
+    Gradient { stops: Vec<GradientStop> },
+}
+
+/// One category in a [ColorTable::Discrete] table
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ColorEntry {
+    /// Human-readable name of the category, e.g. a biome name
+    pub label: String,
+    pub color: Color3,
+}
+
+/// One stop in a [ColorTable::Gradient] table
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct GradientStop {
+    /// Value of the lens data at this stop, e.g. elevation in meters
+    pub value: f64,
+    /// Human-readable version of the value, including units
+    pub label: String,
+    pub color: Color3,
+}
+
+impl ColorEntry {
+    fn new(label: impl ToString, color: Color3) -> Self {
+        Self {
+            label: label.to_string(),
+            color,
+        }
+    }
+}
+
+impl GradientStop {
+    fn new(value: f64, label: impl ToString, color: Color3) -> Self {
+        Self {
+            value,
+            label: label.to_string(),
+            color,
+        }
+    }
+}
+
+/// Build the color table for a lens. See [WorldRenderer::color_table].
+pub(super) fn color_table(
+    renderer: &WorldRenderer,
+    lens: TileLens,
+) -> ColorTable {
+    let biome_entries = move |biome_type: Option<BiomeType>| {
+        Biome::iter()
+            .filter(move |biome| {
+                biome_type
+                    .map_or(true, |biome_type| biome.biome_type() == biome_type)
+            })
+            .map(move |biome| {
+                ColorEntry::new(biome, renderer.biome_color(biome))
+            })
+    };
+    // Fractional values in [0, 1], displayed as percentages
+    let percent_stops = |high_color: Color3| {
+        vec![
+            GradientStop::new(0.0, "0%", GRADIENT_LOW_COLOR),
+            GradientStop::new(1.0, "100%", high_color),
+        ]
+    };
+
+    match lens {
+        TileLens::Surface => ColorTable::Discrete {
+            entries: biome_entries(None)
+                .chain([
+                    ColorEntry::new("Lake", LAKE_COLOR),
+                    ColorEntry::new("Inland sea", INLAND_SEA_COLOR),
+                    ColorEntry::new("Sea ice", ICE_COLOR),
+                ])
+                .collect(),
+        },
+        TileLens::Biome => ColorTable::Discrete {
+            entries: biome_entries(None).collect(),
+        },
+        TileLens::Elevation => {
+            let default_stops =
+                [(0.0, GRADIENT_LOW_COLOR), (1.0, ELEVATION_HIGH_COLOR)];
+            let stops = match &renderer.render_config().elevation_gradient {
+                Some(stops) if !stops.is_empty() => stops.as_slice(),
+                _ => default_stops.as_slice(),
+            };
+            ColorTable::Gradient {
+                stops: stops
+                    .iter()
+                    .map(|(position, color)| {
+                        // Stops are given in normalized elevation, convert
+                        // back to meters
+                        let elevation = World::ELEVATION_RANGE.min
+                            + World::ELEVATION_RANGE.span() * *position;
+                        GradientStop::new(
+                            elevation.0,
+                            Meter(elevation.0.round()),
+                            *color,
+                        )
+                    })
+                    .collect(),
+            }
+        }
+        TileLens::Humidity => ColorTable::Gradient {
+            stops: percent_stops(HUMIDITY_HIGH_COLOR),
+        },
+        TileLens::Fertility => ColorTable::Gradient {
+            stops: percent_stops(FERTILITY_HIGH_COLOR),
+        },
+        TileLens::Watershed => ColorTable::Discrete {
+            entries: biome_entries(Some(BiomeType::Water))
+                .chain([ColorEntry::new("No watershed", NO_DATA_COLOR)])
+                .collect(),
+        },
+        // These are the corners of the blend in the runoff lens
+        TileLens::Runoff => ColorTable::Discrete {
+            entries: vec![
+                ColorEntry::new("Water", RUNOFF_WATER_COLOR),
+                ColorEntry::new("Dry", Color3::new(0.0, 0.0, 0.0)),
+                ColorEntry::new("Runoff", Color3::new(0.0, 0.0, 1.0)),
+                ColorEntry::new("Runoff flow", Color3::new(0.0, 1.0, 0.0)),
+                ColorEntry::new(
+                    "Runoff and runoff flow",
+                    Color3::new(0.0, 1.0, 1.0),
+                ),
+            ],
+        },
+        TileLens::Terrain => ColorTable::Discrete {
+            entries: biome_entries(Some(BiomeType::Water))
+                .chain(TerrainClass::iter().map(|terrain_class| {
+                    ColorEntry::new(
+                        terrain_class,
+                        renderer.terrain_color(terrain_class),
+                    )
+                }))
+                .collect(),
+        },
+        TileLens::CoastDistance => {
+            let max = WorldRenderer::COAST_DISTANCE_SOFT_MAX;
+            ColorTable::Gradient {
+                stops: vec![
+                    GradientStop::new(0.0, "0 tiles", COAST_NEAR_COLOR),
+                    GradientStop::new(
+                        max as f64,
+                        format!("{max}+ tiles"),
+                        COAST_FAR_COLOR,
+                    ),
+                ],
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod config;
+pub mod legend;
+pub mod raster;
 #[cfg(feature = "stl")]
 pub mod stl;
 #[cfg(feature = "svg")]
@@ -8,15 +10,22 @@ pub mod unit;
 use crate::{
     render::{
         config::RenderConfig,
-        unit::{Color3, Point2},
+        legend::ColorTable,
+        unit::{Color3, Color4, Point2},
     },
-    world::hex::HexThing,
+    world::hex::{HexDirection, HexThing},
     Biome, BiomeType, GeoFeature, HasHexPosition, Meter, Meter3, NumRange,
-    Tile, World,
+    TerrainClass, Tile, TileDirection, TilePoint, VertexDirection, World,
 };
+use anyhow::ensure;
 use derive_more::Display;
+use fnv::FnvHasher;
 use serde::{Deserialize, Serialize};
-use std::f64;
+use std::{
+    collections::HashMap,
+    f64,
+    hash::{Hash, Hasher},
+};
 use strum::EnumString;
 use validator::Validate;
 #[cfg(feature = "js")]
@@ -35,7 +44,9 @@ use wasm_bindgen::prelude::*;
 /// a new renderer.
 ///
 /// ## Supported Formats
-/// - STL (3D, no colors or textures)
+/// - PPM (2D raster, tile colors only)
+/// - PNG layers (2D raster, one image per layer)
+/// - STL (3D, no colors or textures), in either binary or ASCII
 /// - SVG (2D with colors and textures)
 #[cfg_attr(feature = "js", wasm_bindgen)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -72,17 +83,51 @@ impl WorldRenderer {
     pub const TILE_HEIGHT: f64 = Self::TILE_SIDE_RADIUS * 2.0;
     /// Distance **in the X axis only** between the center of two tiles that are
     /// aligned in the Y and one unit apart in the X (i.e. left-to-right).
-    /// See [Self::TILE_VERTEX_RADIUS] for the rendering constant caveat.
+    /// This and [Self::TILE_CENTER_DISTANCE_Y] describe a
+    /// [HexOrientation::FlatTop] layout, which is **not** the default. The
+    /// default [HexOrientation::PointyTop] layout swaps the two, so use
+    /// [Self::tile_center_distance] to account for the configured
+    /// orientation. See [Self::TILE_VERTEX_RADIUS] for the rendering constant
+    /// caveat.
     pub const TILE_CENTER_DISTANCE_X: f64 = Self::TILE_VERTEX_RADIUS * 1.5;
     /// Distance between the center of two tiles that are aligned in the X
     /// and one unit apart in the Y (i.e. up-and-down). See
     /// [Self::TILE_VERTEX_RADIUS] for the rendering constant caveat.
     pub const TILE_CENTER_DISTANCE_Y: f64 = Self::TILE_HEIGHT;
+    /// Color of the water surface drawn over oceans and lakes in 3D
+    /// renderings. This is translucent, so the terrain below shows through.
+    pub const WATER_COLOR: Color4 = Color4 {
+        red: 0.078,
+        green: 0.302,
+        blue: 0.639,
+        alpha: 0.5,
+    };
+    /// Distance from the coast (in tiles) that gets the far end of the color
+    /// gradient in [TileLens::CoastDistance]. Anything further inland gets the
+    /// same color.
+    pub const COAST_DISTANCE_SOFT_MAX: u16 = 20;
+    /// Names of the layers generated by [Self::render_layers], in the order
+    /// they're meant to be stacked, from bottom to top
+    #[cfg(feature = "png")]
+    pub const LAYER_NAMES: [&str; 4] =
+        ["biome", "elevation", "water", "rivers"];
 
     /// Initialize a new renderer with the given options. Returns an error if
     /// the render config is invalid.
     pub fn new(render_config: RenderConfig) -> anyhow::Result<Self> {
         render_config.validate()?;
+        // The validator derive can't express these, so check them by hand
+        if let Some(stops) = &render_config.elevation_gradient {
+            ensure!(
+                stops.iter().all(|(pos, _)| (0.0..=1.0).contains(pos)),
+                "elevation gradient stops must be in [0, 1]"
+            );
+            ensure!(
+                stops.windows(2).all(|window| window[0].0 <= window[1].0),
+                "elevation gradient stops must be sorted by ascending \
+                elevation"
+            );
+        }
         Ok(Self { render_config })
     }
 
@@ -91,41 +136,380 @@ impl WorldRenderer {
         &self.render_config
     }
 
+    /// Should the given tile be left out of renderings? See
+    /// [RenderConfig::hidden_biomes].
+    pub fn is_tile_hidden(&self, tile: &Tile) -> bool {
+        self.render_config.hidden_biomes.contains(&tile.biome())
+    }
+
     /// Convert a point from from hex space to 2D screen space. Useful for
-    /// rendering tiles or other world objects into a visual format.
+    /// rendering tiles or other world objects into a visual format. The
+    /// output is scaled by [RenderConfig::tile_scale] and rotated according
+    /// to [RenderConfig::hex_orientation].
     pub fn hex_to_screen_space<T: Into<f64>>(
         &self,
         point: impl HexThing<Component = T>,
     ) -> Point2 {
-        // This is a simplification of some linear algebra. We need to apply
-        // three transformations, in sequence:
-        // 1. Project onto the plane x+y+z=0
-        // 2. Rotate 45° CCW around z
-        // 3. Rotate 45° CCW around x
-        // This should leave us with a bunch of points on the plane z=0
-        // If you create a 3x3 matrix each transformation, then multiply them
-        // together, you get this matrix:
-        // +-                             -+
-        // |     √2/2     -√2/2          0 |
-        // | (1+√2)/6  (1+√2)/6  (-1-√2)/3 |
-        // | (1-√2)/6  (1-√2)/6  (-1+√2)/3 |
-        // +-                             -+
-        // The math below is just multiplying the vector (x,y,z) by that matrix,
-        // then throwing away the third component to get a 2D (x,y). TBH I'm not
-        // sure why the z component doesn't just spit out 0 anyway, since this
-        // is supposed to be the plane z=0. But the math works so I'm not gonna
-        // question it too much.
-
-        let x: f64 = point.x().into();
-        let y: f64 = point.y().into();
-        let z: f64 = point.z().into();
-        Point2 {
-            x: 2.0f64.sqrt() / 2.0 * x - 2.0f64.sqrt() / 2.0 * y,
-            y: (1.0 + 2.0f64.sqrt()) / 6.0 * x
-                + (1.0 + 2.0f64.sqrt()) / 6.0 * y
-                + (-1.0 - 2.0f64.sqrt()) / 3.0 * z,
+        let point = hex_to_screen_space(point) * self.render_config.tile_scale;
+        match self.render_config.hex_orientation {
+            HexOrientation::PointyTop => point,
+            // Rotate 90° clockwise. Screen y points down, so this sends north
+            // (-y) to east (+x)
+            HexOrientation::FlatTop => Point2::new(-point.y, point.x),
+        }
+    }
+
+    /// Get the distance between the center of a tile and one of its vertices,
+    /// with [RenderConfig::tile_scale] applied. See
+    /// [Self::TILE_VERTEX_RADIUS].
+    pub fn tile_vertex_radius(&self) -> f64 {
+        Self::TILE_VERTEX_RADIUS * self.render_config.tile_scale
+    }
+
+    /// Get the midpoint of one side of a tile, in screen space. This is where a
+    /// river (or border) crossing into the neighbor in that direction should
+    /// meet the edge of the tile. Position jitter is **not** applied.
+    pub fn tile_side_midpoint(
+        &self,
+        tile: &Tile,
+        dir: TileDirection,
+    ) -> Point2 {
+        self.hex_to_screen_space(tile.position().side_midpoint(dir))
+    }
+
+    /// Get the height of one vertex of a tile, for smooth shading. This is the
+    /// mean [height](Self::tile_height) of the tiles that share that vertex:
+    /// the tile itself, plus the two neighbors on either side of the vertex.
+    /// Neighbors that don't exist (off the edge of the world) are left out,
+    /// so vertices along the edge only average the tiles that are there.
+    pub fn vertex_height(
+        &self,
+        world: &World,
+        tile: &Tile,
+        dir: VertexDirection,
+    ) -> f64 {
+        let (left, right) = dir.adjacent_tile_directions();
+        let pos = tile.position();
+        let (sum, count) = [pos.adjacent(left), pos.adjacent(right)]
+            .iter()
+            .filter_map(|adj_pos| world.tiles().get(adj_pos))
+            .fold((self.tile_height(tile), 1), |(sum, count), adj_tile| {
+                (sum + self.tile_height(adj_tile), count + 1)
+            });
+        sum / count as f64
+    }
+
+    /// Get the 6 vertices of a tile in screen space, in
+    /// [VertexDirection::CLOCKWISE] order. These are the corners of the
+    /// hexagon that gets drawn for the tile, so [RenderConfig::tile_scale] and
+    /// [RenderConfig::position_jitter] are both applied.
+    pub fn tile_vertices(&self, tile: &Tile) -> [Point2; 6] {
+        let center = self.jittered_tile_position(tile);
+        let mut vertices = [Point2::default(); 6];
+        for (vertex, dir) in vertices.iter_mut().zip(VertexDirection::CLOCKWISE)
+        {
+            *vertex = center
+                + self.hex_to_screen_space(TilePoint::ORIGIN.vertex(*dir));
+        }
+        vertices
+    }
+
+    /// Get the exact extents of a world in screen space, as `(top_left,
+    /// bottom_right)` corners. This covers every vertex of every tile (see
+    /// [Self::tile_vertices]), not just tile centers, so nothing gets clipped
+    /// if you fit a viewport to it. Hidden tiles are still included, so the
+    /// bounds don't change when you hide biomes. An empty world gives two
+    /// zero points.
+    pub fn screen_bounds(&self, world: &World) -> (Point2, Point2) {
+        if world.tiles().is_empty() {
+            return (Point2::default(), Point2::default());
+        }
+        world
+            .tiles()
+            .values()
+            .flat_map(|tile| self.tile_vertices(tile))
+            .fold(
+                (
+                    Point2::new(f64::INFINITY, f64::INFINITY),
+                    Point2::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+                ),
+                |(min, max), point| {
+                    (
+                        Point2::new(min.x.min(point.x), min.y.min(point.y)),
+                        Point2::new(max.x.max(point.x), max.y.max(point.y)),
+                    )
+                },
+            )
+    }
+
+    /// Get every tile whose center falls inside the given polygon, e.g. a
+    /// lasso selection drawn in an editor. The polygon is a list of vertices
+    /// in screen space (see [Self::hex_to_screen_space]), and is closed
+    /// automatically, so the last vertex shouldn't repeat the first.
+    /// Containment uses the even-odd rule, so self-intersecting polygons
+    /// work, but their overlapping regions count as outside. Position jitter
+    /// is ignored, and hidden tiles are still included. Tiles are sorted by
+    /// position. A polygon with fewer than 3 vertices contains nothing.
+    pub fn tiles_in_polygon<'a>(
+        &self,
+        world: &'a World,
+        polygon: &[Point2],
+    ) -> Vec<&'a Tile> {
+        if polygon.len() < 3 {
+            return Vec::new();
+        }
+        world
+            .tiles_sorted()
+            .into_iter()
+            .filter(|tile| {
+                polygon_contains(
+                    polygon,
+                    self.hex_to_screen_space(tile.position()),
+                )
+            })
+            .collect()
+    }
+
+    /// Render this world as a 2D SVG, like [Self::render_as_svg], but with
+    /// each tile's color coming from the given function instead of the tile
+    /// lens. This is handy for custom data overlays, e.g. a per-tile score
+    /// map, without needing a new [TileLens]. Everything else (hidden biomes,
+    /// features, etc.) works the same. See also [Self::render_ppm_with] and
+    /// [Self::render_png_with]. There's no STL equivalent, since STL can't
+    /// carry colors. To color a model, use the tile ranges from
+    /// [Self::render_as_stl_with_metadata] instead.
+    ///
+    /// **Note**: NOT available to WebAssembly, since it takes a closure.
+    #[cfg(feature = "svg")]
+    pub fn render_svg_with(
+        &self,
+        world: &World,
+        color_fn: impl Fn(&Tile) -> Color3,
+    ) -> String {
+        svg::world_to_svg(world, self, color_fn).to_string()
+    }
+
+    /// Render this world as a 2D binary PPM (P6) image, from a top-down
+    /// perspective. `pixels_per_tile` is the width of a single tile in pixels,
+    /// and must be at least 1, otherwise this returns an error. Geographic
+    /// features aren't drawn. PPM is uncompressed so the output can get big,
+    /// but it requires no extra dependencies.
+    ///
+    /// **Note**: NOT available to WebAssembly, since it returns an error.
+    pub fn render_as_ppm(
+        &self,
+        world: &World,
+        pixels_per_tile: u32,
+    ) -> anyhow::Result<Vec<u8>> {
+        Ok(raster::rasterize_world(world, self, pixels_per_tile)?.to_ppm())
+    }
+
+    /// Render this world into a raw RGBA pixel buffer, from a top-down
+    /// perspective. Returns `(buffer, width, height)`, where the buffer has 4
+    /// bytes per pixel in row-major order, starting from the top-left.
+    /// Pixels outside the world are fully transparent. See
+    /// [Self::render_as_ppm] for a description of `pixels_per_tile`.
+    ///
+    /// **Note**: NOT available to WebAssembly, since it returns an error.
+    pub fn render_to_rgba(
+        &self,
+        world: &World,
+        pixels_per_tile: u32,
+    ) -> anyhow::Result<(Vec<u8>, u32, u32)> {
+        let raster = raster::rasterize_world(world, self, pixels_per_tile)?;
+        Ok((raster.to_rgba(), raster.width(), raster.height()))
+    }
+
+    /// Render this world as a 2D binary PPM (P6) image, like
+    /// [Self::render_as_ppm], but with each tile's color coming from the given
+    /// function instead of the tile lens. See [Self::render_svg_with].
+    ///
+    /// **Note**: NOT available to WebAssembly, since it takes a closure.
+    pub fn render_ppm_with(
+        &self,
+        world: &World,
+        pixels_per_tile: u32,
+        color_fn: impl Fn(&Tile) -> Color3,
+    ) -> anyhow::Result<Vec<u8>> {
+        let raster = raster::rasterize_world_with(
+            world,
+            self,
+            pixels_per_tile,
+            color_fn,
+        )?;
+        Ok(raster.to_ppm())
+    }
+
+    /// Render this world as a 2D RGBA PNG image, with each tile's color coming
+    /// from the given function instead of the tile lens. The image has the
+    /// same pixels as [Self::render_ppm_with], except that background pixels
+    /// are transparent. See [Self::render_svg_with].
+    ///
+    /// **Note**: NOT available to WebAssembly, since it takes a closure.
+    #[cfg(feature = "png")]
+    pub fn render_png_with(
+        &self,
+        world: &World,
+        pixels_per_tile: u32,
+        color_fn: impl Fn(&Tile) -> Color3,
+    ) -> anyhow::Result<Vec<u8>> {
+        let raster = raster::rasterize_world_with(
+            world,
+            self,
+            pixels_per_tile,
+            color_fn,
+        )?;
+        raster.to_png()
+    }
+
+    /// Render this world as a set of separate 2D raster layers, so they can be
+    /// composited in an image editor. Returns a map of layer name to an RGBA
+    /// PNG image. The layers are (see [Self::LAYER_NAMES]):
+    /// - `biome` - Every tile, colored by [TileLens::Biome]
+    /// - `elevation` - Every tile, colored by [TileLens::Elevation]
+    /// - `water` - Only water tiles, colored by [TileLens::Surface]
+    /// - `rivers` - Only tiles that a river flows through
+    ///
+    /// Pixels not covered by a layer are transparent, and every layer has the
+    /// same dimensions, so they line up when stacked. Rivers are drawn per
+    /// tile rather than as lines, since rasterization has no detail below the
+    /// tile level. Hidden tiles (see [Self::is_tile_hidden]) are left out of
+    /// every layer. See [Self::render_as_ppm] for a description of
+    /// `pixels_per_tile`.
+    ///
+    /// **Note**: NOT available to WebAssembly, since it returns a map.
+    #[cfg(feature = "png")]
+    pub fn render_layers(
+        &self,
+        world: &World,
+        pixels_per_tile: u32,
+    ) -> anyhow::Result<HashMap<String, Vec<u8>>> {
+        raster::render_layers(world, self, pixels_per_tile)
+    }
+
+    /// Render this world into an STL model (see [Self::render_as_stl]), along
+    /// with a JSON sidecar that maps ranges of triangles to the tiles they
+    /// came from, including each tile's biome and features. STL has no way
+    /// to carry colors or materials, so this allows other tools to texture
+    /// the model. Return value is `(stl, metadata_json)`. See
+    /// [stl::StlMetadata] for the sidecar format.
+    ///
+    /// **Note**: NOT available to WebAssembly, since it returns a tuple.
+    #[cfg(all(feature = "stl", feature = "json"))]
+    pub fn render_as_stl_with_metadata(
+        &self,
+        world: &World,
+    ) -> (Vec<u8>, String) {
+        let (mesh, metadata) = stl::world_to_stl_with_metadata(world, self);
+        // Panic here indicates a bug in the metadata format
+        let metadata = serde_json::to_string(&metadata)
+            .expect("error serializing STL metadata");
+        (stl::mesh_to_binary_stl(&mesh), metadata)
+    }
+
+    /// Get the compass bearing from one tile to another, in degrees, based on
+    /// their positions in screen space. 0° is north (up, i.e. toward
+    /// negative screen `y`), and the bearing increases clockwise, so 90° is
+    /// east (positive screen `x`). The output is in `[0, 360)`. If the two
+    /// tiles are the same, the bearing is 0°.
+    pub fn bearing(&self, from: TilePoint, to: TilePoint) -> f64 {
+        let offset =
+            self.hex_to_screen_space(to) - self.hex_to_screen_space(from);
+        if offset == Point2::default() {
+            return 0.0;
+        }
+        // atan2 normally measures counter-clockwise from +x, so swap the
+        // arguments (and flip y) to measure clockwise from north instead
+        offset.x.atan2(-offset.y).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Describe how the configured [TileLens] maps tile data to colors, as
+    /// data. This lets front ends draw their own legends that always match
+    /// the colors terra renders. See [ColorTable] for what's included for
+    /// each lens.
+    pub fn color_table(&self) -> ColorTable {
+        legend::color_table(self, self.render_config.tile_lens)
+    }
+
+    /// Get a pseudo-random offset for the tile at the given position, within
+    /// [RenderConfig::position_jitter] of the origin. This is derived purely
+    /// from the position, so it's the same every time.
+    fn position_jitter(&self, position: TilePoint) -> Point2 {
+        let max_jitter = self.render_config.position_jitter;
+        if max_jitter == 0.0 {
+            return Point2::default();
+        }
+
+        let mut hasher = FnvHasher::default();
+        position.hash(&mut hasher);
+        let hash = hasher.finish();
+        // Split the hash into two values in [0, 1)
+        let a = (hash >> 32) as f64 / (1u64 << 32) as f64;
+        let b = (hash & 0xffff_ffff) as f64 / (1u64 << 32) as f64;
+        // Pick a uniformly random point within the jitter circle
+        let distance = max_jitter * a.sqrt();
+        let angle = b * 2.0 * f64::consts::PI;
+        Point2::new(distance * angle.cos(), distance * angle.sin())
+    }
+}
+
+/// Is the point inside the polygon? This casts a ray from the point toward +x
+/// and counts how many of the polygon's edges it crosses. An odd number of
+/// crossings means the point is inside.
+fn polygon_contains(polygon: &[Point2], point: Point2) -> bool {
+    let mut inside = false;
+    // Pair each vertex with the one before it, wrapping around to close the
+    // polygon
+    let previous = polygon.iter().cycle().skip(polygon.len() - 1);
+    for (a, b) in polygon.iter().zip(previous) {
+        // Only edges that straddle the ray's y can cross it. Treating one end
+        // as inclusive and the other as exclusive makes sure a ray passing
+        // through a vertex only counts it once.
+        if (a.y > point.y) != (b.y > point.y) {
+            let crossing_x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < crossing_x {
+                inside = !inside;
+            }
         }
     }
+    inside
+}
+
+/// Convert a point from from hex space to 2D screen space, at the default tile
+/// scale. This doesn't depend on any render settings, so it's available outside
+/// of [WorldRenderer] for use within the crate. See
+/// [WorldRenderer::hex_to_screen_space].
+pub(crate) fn hex_to_screen_space<T: Into<f64>>(
+    point: impl HexThing<Component = T>,
+) -> Point2 {
+    // This is a simplification of some linear algebra. We need to apply
+    // three transformations, in sequence:
+    // 1. Project onto the plane x+y+z=0
+    // 2. Rotate 45° CCW around z
+    // 3. Rotate 45° CCW around x
+    // This should leave us with a bunch of points on the plane z=0
+    // If you create a 3x3 matrix each transformation, then multiply them
+    // together, you get this matrix:
+    // +-                             -+
+    // |     √2/2     -√2/2          0 |
+    // | (1+√2)/6  (1+√2)/6  (-1-√2)/3 |
+    // | (1-√2)/6  (1-√2)/6  (-1+√2)/3 |
+    // +-                             -+
+    // The math below is just multiplying the vector (x,y,z) by that matrix,
+    // then throwing away the third component to get a 2D (x,y). TBH I'm not
+    // sure why the z component doesn't just spit out 0 anyway, since this
+    // is supposed to be the plane z=0. But the math works so I'm not gonna
+    // question it too much.
+
+    let x: f64 = point.x().into();
+    let y: f64 = point.y().into();
+    let z: f64 = point.z().into();
+    Point2 {
+        x: 2.0f64.sqrt() / 2.0 * x - 2.0f64.sqrt() / 2.0 * y,
+        y: (1.0 + 2.0f64.sqrt()) / 6.0 * x
+            + (1.0 + 2.0f64.sqrt()) / 6.0 * y
+            + (-1.0 - 2.0f64.sqrt()) / 3.0 * z,
+    }
 }
 
 // Wasm-friendly API
@@ -137,10 +521,36 @@ impl WorldRenderer {
         self.hex_to_screen_space(tile.position())
     }
 
+    /// Get the position of a tile in screen space, with
+    /// [RenderConfig::position_jitter] applied. With no jitter, this is the
+    /// same as [Self::tile_position].
+    pub fn jittered_tile_position(&self, tile: &Tile) -> Point2 {
+        self.tile_position(tile) + self.position_jitter(tile.position())
+    }
+
     /// Get the distance between the center of a tile and the midpoint of one
     /// of its sides. Useful for scaling tiles in certain render contexts.
+    /// [RenderConfig::tile_scale] is applied.
     pub fn tile_side_radius(&self) -> f64 {
-        Self::TILE_SIDE_RADIUS
+        Self::TILE_SIDE_RADIUS * self.render_config.tile_scale
+    }
+
+    /// Get the distance between neighboring tile centers along each screen
+    /// axis. This is [Self::TILE_CENTER_DISTANCE_X] and
+    /// [Self::TILE_CENTER_DISTANCE_Y], swapped for
+    /// [HexOrientation::PointyTop], with [RenderConfig::tile_scale] applied.
+    pub fn tile_center_distance(&self) -> Point2 {
+        let distance = match self.render_config.hex_orientation {
+            HexOrientation::FlatTop => Point2::new(
+                Self::TILE_CENTER_DISTANCE_X,
+                Self::TILE_CENTER_DISTANCE_Y,
+            ),
+            HexOrientation::PointyTop => Point2::new(
+                Self::TILE_CENTER_DISTANCE_Y,
+                Self::TILE_CENTER_DISTANCE_X,
+            ),
+        };
+        distance * self.render_config.tile_scale
     }
 
     /// Get the height that a tile's geometry should have. This will convert
@@ -152,6 +562,13 @@ impl WorldRenderer {
         self.elevation_to_height(tile.elevation())
     }
 
+    /// Is the water on this tile deep enough to be drawn as a lake? See
+    /// [RenderConfig::min_lake_depth]. This doesn't check if the tile is
+    /// actually a lake.
+    fn is_lake_deep_enough(&self, tile: &Tile) -> bool {
+        tile.runoff() / Tile::AREA >= self.render_config.min_lake_depth
+    }
+
     /// Get the height of sea level, in absolute coordinates
     pub fn sea_level_height(&self) -> f64 {
         self.elevation_to_height(World::SEA_LEVEL)
@@ -167,39 +584,70 @@ impl WorldRenderer {
         zeroed_elevation.0 * self.render_config.vertical_scale
     }
 
-    /// Compute the color of a tile based on current render settings. The tile
-    /// lens in the render config controls what data the color is derived from.
-    pub fn tile_color(&self, tile: &Tile) -> Color3 {
-        match self.render_config.tile_lens {
+    /// Compute the color of a tile using the given lens. This ignores the lens
+    /// in the render config, in case the caller wants to switch lenses without
+    /// creating a new renderer.
+    fn tile_color_for_lens(&self, tile: &Tile, lens: TileLens) -> Color3 {
+        match lens {
             // See TileLens definition for a description of each lens type
             TileLens::Surface => {
-                if tile.features().contains(&GeoFeature::Lake) {
-                    Color3::new_int(72, 192, 240)
+                // Land ice is already covered by the snow biome
+                if tile.features().contains(&GeoFeature::Ice)
+                    && tile.biome().biome_type() == BiomeType::Water
+                {
+                    ICE_COLOR
+                } else if tile.features().contains(&GeoFeature::InlandSea)
+                    && self.is_lake_deep_enough(tile)
+                {
+                    INLAND_SEA_COLOR
+                } else if tile.features().contains(&GeoFeature::Lake)
+                    && self.is_lake_deep_enough(tile)
+                {
+                    LAKE_COLOR
                 } else {
                     self.biome_color(tile.biome())
                 }
             }
             TileLens::Biome => self.biome_color(tile.biome()),
             TileLens::Elevation => {
-                let normal_elev =
-                    World::ELEVATION_RANGE.normalize(tile.elevation()).0 as f32;
-                // 0 -> white
-                // 1 -> red
-                Color3::new(1.0, 1.0 - normal_elev, 1.0 - normal_elev)
-            }
-            TileLens::Humidity => {
-                let humidity = tile.humidity() as f32;
-                // 0 -> white
-                // 1 -> green
-                Color3::new(1.0 - humidity, 1.0, 1.0 - humidity)
+                let normal_elev = tile.elevation_normalized();
+                match &self.render_config.elevation_gradient {
+                    Some(stops) if !stops.is_empty() => {
+                        gradient_color(stops, normal_elev)
+                    }
+                    // 0 -> white
+                    // 1 -> red
+                    _ => GRADIENT_LOW_COLOR
+                        .lerp(ELEVATION_HIGH_COLOR, normal_elev as f32),
+                }
             }
+            // 0 -> white
+            // 1 -> green
+            TileLens::Humidity => GRADIENT_LOW_COLOR
+                .lerp(HUMIDITY_HIGH_COLOR, tile.humidity() as f32),
+            // 0 -> white
+            // 1 -> rich soil brown
+            TileLens::Fertility => GRADIENT_LOW_COLOR
+                .lerp(FERTILITY_HIGH_COLOR, tile.fertility() as f32),
+            TileLens::Watershed => match tile.watershed() {
+                Some(watershed) => {
+                    // Derive a stable color from the watershed ID. Keep each
+                    // component away from 0 so no watershed is too dark
+                    let mut hasher = FnvHasher::default();
+                    watershed.hash(&mut hasher);
+                    let [r, g, b, ..] = hasher.finish().to_le_bytes();
+                    Color3::new_int(r / 2 + 100, g / 2 + 100, b / 2 + 100)
+                }
+                None if tile.is_water_biome() => self.biome_color(tile.biome()),
+                None => NO_DATA_COLOR,
+            },
             TileLens::Runoff => {
                 // This coloring is based on two aspects: runoff (how much water
                 // collected on the tile) AND runoff egress (how much water
                 // flowed over the tile without staying there). Runoff controls
                 // blue, runoff egress controls green.
                 if tile.biome().biome_type() == BiomeType::Water {
-                    Color3::new(0.5, 0.5, 0.5)
+                    RUNOFF_WATER_COLOR
                 } else {
                     // Neither value we use here has a hard cap, so we use
                     // arbitrary max values based on what's common/reasonable,
@@ -217,21 +665,83 @@ impl WorldRenderer {
                     Color3::new(0.0, normal_runoff_egress, normal_runoff)
                 }
             }
+            TileLens::Terrain if tile.is_water_biome() => {
+                self.biome_color(tile.biome())
+            }
+            TileLens::Terrain => self.terrain_color(tile.terrain_class()),
+            TileLens::CoastDistance => match tile.coast_distance() {
+                Some(distance) => {
+                    // Distance has no hard cap, so anything past the soft max
+                    // just gets the far color
+                    let normal_distance = (distance as f32
+                        / Self::COAST_DISTANCE_SOFT_MAX as f32)
+                        .min(1.0);
+                    // 0 -> sand
+                    // 1 -> deep purple
+                    COAST_NEAR_COLOR.lerp(COAST_FAR_COLOR, normal_distance)
+                }
+                None if tile.is_water_biome() => self.biome_color(tile.biome()),
+                None => NO_DATA_COLOR,
+            },
+        }
+    }
+
+    /// Compute the color of a tile based on current render settings. The tile
+    /// lens in the render config controls what data the color is derived from.
+    pub fn tile_color(&self, tile: &Tile) -> Color3 {
+        self.tile_color_for_lens(tile, self.render_config.tile_lens)
+    }
+
+    /// Compute the color of a tile, with transparency. The RGB components are
+    /// the same as [Self::tile_color]. Hidden tiles (see
+    /// [Self::is_tile_hidden]) are fully transparent, and under
+    /// [TileLens::Surface], water tiles get the alpha of
+    /// [Self::WATER_COLOR]. Everything else is opaque.
+    pub fn tile_color_rgba(&self, tile: &Tile) -> Color4 {
+        let color = self.tile_color(tile);
+        if self.is_tile_hidden(tile) {
+            color.with_alpha(0.0)
+        } else if self.render_config.tile_lens == TileLens::Surface
+            && tile.is_water_biome()
+        {
+            color.with_alpha(Self::WATER_COLOR.alpha)
+        } else {
+            color.into()
         }
     }
 
-    /// Map a biome to its preset color.
+    /// Compute the color of every tile in the world, using the given lens
+    /// rather than the one in the render config. The colors are returned as a
+    /// flat RGB buffer, 3 bytes per tile, in the order of
+    /// [World::tiles_sorted]. This lets JS switch lenses with a single call,
+    /// rather than one call per tile.
+    pub fn tile_colors_for_lens(
+        &self,
+        world: &World,
+        lens: TileLens,
+    ) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(world.tiles().len() * 3);
+        for tile in world.tiles_sorted() {
+            let (r, g, b) = self.tile_color_for_lens(tile, lens).to_ints();
+            buffer.extend([r, g, b]);
+        }
+        buffer
+    }
+
+    /// Map a biome to its color, according to [RenderConfig::palette].
     pub fn biome_color(&self, biome: Biome) -> Color3 {
-        match biome {
-            Biome::Ocean => Color3::new_int(20, 77, 163),
-            Biome::Coast => Color3::new_int(32, 166, 178),
-
-            Biome::Snow => Color3::new_int(191, 191, 191),
-            Biome::Desert => Color3::new_int(214, 204, 107),
-            Biome::Alpine => Color3::new_int(99, 122, 99),
-            Biome::Jungle => Color3::new_int(43, 179, 31),
-            Biome::Forest => Color3::new_int(23, 122, 0),
-            Biome::Plains => Color3::new_int(173, 201, 115),
+        self.render_config.palette.biome_color(biome)
+    }
+
+    /// Map a terrain class to its preset color, for [TileLens::Terrain].
+    pub fn terrain_color(&self, terrain_class: TerrainClass) -> Color3 {
+        match terrain_class {
+            TerrainClass::Peak => Color3::new_int(230, 57, 70),
+            TerrainClass::Ridge => Color3::new_int(244, 162, 97),
+            TerrainClass::Slope => Color3::new_int(233, 216, 166),
+            TerrainClass::Valley => Color3::new_int(82, 183, 136),
+            TerrainClass::Pit => Color3::new_int(29, 53, 87),
+            TerrainClass::Flat => Color3::new_int(200, 200, 200),
         }
     }
 
@@ -269,7 +779,7 @@ impl WorldRenderer {
     /// SVG in a string.
     #[cfg(feature = "svg")]
     pub fn render_as_svg(&self, world: &World) -> String {
-        let svg = svg::world_to_svg(world, self);
+        let svg = svg::world_to_svg(world, self, |tile| self.tile_color(tile));
         svg.to_string()
     }
 
@@ -279,11 +789,16 @@ impl WorldRenderer {
     #[cfg(feature = "stl")]
     pub fn render_as_stl(&self, world: &World) -> Vec<u8> {
         let mesh = stl::world_to_stl(world, self);
-        let mut buffer = Vec::<u8>::new();
-        // Panic here indicates a bug in our STL mesh format
-        stl_io::write_stl(&mut buffer, mesh.iter())
-            .expect("error serializing STL");
-        buffer
+        stl::mesh_to_binary_stl(&mesh)
+    }
+
+    /// Render this world into an STL model, using the ASCII format rather than
+    /// binary. The output is significantly larger than [Self::render_as_stl],
+    /// so this is mostly useful for inspecting small worlds by hand.
+    #[cfg(feature = "stl")]
+    pub fn render_as_stl_ascii(&self, world: &World) -> String {
+        let mesh = stl::world_to_stl(world, self);
+        stl::mesh_to_ascii_stl(&mesh)
     }
 }
 
@@ -312,8 +827,175 @@ pub enum TileLens {
     Elevation,
     /// Color is a gradient based on humidity.
     Humidity,
+    /// Color is a gradient based on fertility.
+    Fertility,
+    /// Each watershed gets its own arbitrary color, so that drainage regions
+    /// are easy to tell apart. Water uses its biome color.
+    Watershed,
     /// Color is based on a combination of runoff and total runoff egress.
     Runoff,
+    /// Each [TerrainClass] (peak, ridge, etc.) gets its own static color.
+    /// Water uses its biome color.
+    Terrain,
+    /// Color is a gradient based on distance to the nearest coastline. Water
+    /// uses its biome color.
+    CoastDistance,
+}
+
+/// Which way hexagons point in screen space. The two orientations are a 90°
+/// rotation of each other, so switching between them swaps the width and
+/// height of any rendering. See [RenderConfig::hex_orientation].
+#[cfg_attr(feature = "js", wasm_bindgen)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Display,
+    Eq,
+    PartialEq,
+    EnumString,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HexOrientation {
+    /// Each tile has a vertex at the top and bottom, so neighbors line up in
+    /// rows from left to right
+    #[default]
+    PointyTop,
+    /// Each tile has a flat side at the top and bottom, so neighbors line up
+    /// in columns from top to bottom
+    FlatTop,
+}
+
+/// A set of biome colors, for quickly switching up the look of a rendering.
+/// See [RenderConfig::palette].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Palette {
+    /// The standard terra colors
+    #[default]
+    Standard,
+    /// Muted, earthy colors, closer to what you'd see in satellite imagery
+    Realistic,
+    /// Soft, light colors
+    Pastel,
+    /// Bold colors from the old 16-color CGA palette
+    Retro,
+    /// Shades of gray, based on the brightness of the standard colors
+    Grayscale,
+    /// Explicit colors for some or all biomes. Any biome that isn't included
+    /// gets its standard color.
+    Custom(HashMap<Biome, Color3>),
+}
+
+impl Palette {
+    /// Get the color of a biome in this palette
+    pub fn biome_color(&self, biome: Biome) -> Color3 {
+        match self {
+            Self::Standard => match biome {
+                Biome::Ocean => Color3::new_int(20, 77, 163),
+                Biome::Coast => Color3::new_int(32, 166, 178),
+
+                Biome::Snow => Color3::new_int(191, 191, 191),
+                Biome::Desert => Color3::new_int(214, 204, 107),
+                Biome::Alpine => Color3::new_int(99, 122, 99),
+                Biome::Jungle => Color3::new_int(43, 179, 31),
+                Biome::Forest => Color3::new_int(23, 122, 0),
+                Biome::Plains => Color3::new_int(173, 201, 115),
+            },
+            Self::Realistic => match biome {
+                Biome::Ocean => Color3::new_int(17, 52, 97),
+                Biome::Coast => Color3::new_int(64, 125, 150),
+
+                Biome::Snow => Color3::new_int(240, 240, 236),
+                Biome::Desert => Color3::new_int(222, 196, 146),
+                Biome::Alpine => Color3::new_int(128, 124, 112),
+                Biome::Jungle => Color3::new_int(34, 96, 38),
+                Biome::Forest => Color3::new_int(58, 94, 52),
+                Biome::Plains => Color3::new_int(150, 164, 96),
+            },
+            Self::Pastel => match biome {
+                Biome::Ocean => Color3::new_int(150, 190, 230),
+                Biome::Coast => Color3::new_int(170, 225, 230),
+
+                Biome::Snow => Color3::new_int(245, 245, 250),
+                Biome::Desert => Color3::new_int(250, 230, 180),
+                Biome::Alpine => Color3::new_int(190, 200, 190),
+                Biome::Jungle => Color3::new_int(160, 220, 160),
+                Biome::Forest => Color3::new_int(140, 200, 150),
+                Biome::Plains => Color3::new_int(210, 230, 170),
+            },
+            Self::Retro => match biome {
+                Biome::Ocean => Color3::new_int(0, 0, 170),
+                Biome::Coast => Color3::new_int(0, 170, 170),
+
+                Biome::Snow => Color3::new_int(255, 255, 255),
+                Biome::Desert => Color3::new_int(255, 255, 85),
+                Biome::Alpine => Color3::new_int(170, 170, 170),
+                Biome::Jungle => Color3::new_int(85, 255, 85),
+                Biome::Forest => Color3::new_int(0, 170, 0),
+                Biome::Plains => Color3::new_int(170, 85, 0),
+            },
+            Self::Grayscale => {
+                // Perceived brightness, so e.g. yellow stays lighter than blue
+                let color = Self::Standard.biome_color(biome);
+                let luma = 0.299 * color.red
+                    + 0.587 * color.green
+                    + 0.114 * color.blue;
+                Color3::new(luma, luma, luma)
+            }
+            Self::Custom(colors) => colors
+                .get(&biome)
+                .copied()
+                .unwrap_or_else(|| Self::Standard.biome_color(biome)),
+        }
+    }
+}
+
+// Fixed colors used by the tile lenses. These are shared with [legend], so that
+// color tables always match what actually gets rendered.
+const ICE_COLOR: Color3 = Color3::new_int(240, 248, 255);
+const INLAND_SEA_COLOR: Color3 = Color3::new_int(40, 122, 196);
+const LAKE_COLOR: Color3 = Color3::new_int(72, 192, 240);
+/// Land tiles that are missing data for the lens, e.g. no watershed
+const NO_DATA_COLOR: Color3 = Color3::new_int(128, 128, 128);
+const RUNOFF_WATER_COLOR: Color3 = Color3 {
+    red: 0.5,
+    green: 0.5,
+    blue: 0.5,
+};
+/// The low end of the elevation, humidity, and fertility gradients
+const GRADIENT_LOW_COLOR: Color3 = Color3::new_int(255, 255, 255);
+const ELEVATION_HIGH_COLOR: Color3 = Color3::new_int(255, 0, 0);
+const HUMIDITY_HIGH_COLOR: Color3 = Color3::new_int(0, 255, 0);
+const FERTILITY_HIGH_COLOR: Color3 = Color3::new_int(101, 67, 33);
+const COAST_NEAR_COLOR: Color3 = Color3::new_int(255, 250, 205);
+const COAST_FAR_COLOR: Color3 = Color3::new_int(90, 50, 130);
+/// Color of rivers, for the output formats that draw them
+#[cfg(any(feature = "png", feature = "svg"))]
+const RIVER_COLOR: Color3 = Color3::new_int(72, 192, 240);
+
+/// Pick a color from a gradient defined by a list of `(position, color)` stops.
+/// Stops must be sorted by ascending position. Values between two stops are
+/// linearly interpolated between their colors, and values beyond the first or
+/// last stop get that stop's color. Panics if the stop list is empty.
+fn gradient_color(stops: &[(f64, Color3)], value: f64) -> Color3 {
+    let (first_pos, first_color) = stops[0];
+    if value <= first_pos {
+        return first_color;
+    }
+    for window in stops.windows(2) {
+        let (low_pos, low_color) = window[0];
+        let (high_pos, high_color) = window[1];
+        if value <= high_pos {
+            let t = (value - low_pos) / (high_pos - low_pos);
+            return low_color.lerp(high_color, t as f32);
+        }
+    }
+    stops[stops.len() - 1].1
 }
 
 #[cfg(test)]
@@ -364,4 +1046,76 @@ mod tests {
             Point2::new(5.656854, 1.609476)
         );
     }
+
+    #[test]
+    fn test_tile_side_midpoint() {
+        let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+        let tile = Tile::new_test(TilePoint::new_xy(2, -1));
+
+        for dir in [
+            TileDirection::NNE,
+            TileDirection::E,
+            TileDirection::SSE,
+            TileDirection::SSW,
+            TileDirection::W,
+            TileDirection::NNW,
+        ] {
+            // The midpoint should be exactly halfway between the two vertices
+            // on either end of the side
+            let (vertex_a, vertex_b) = dir.adjacent_vertex_directions();
+            let vertex_a =
+                renderer.hex_to_screen_space(tile.position().vertex(vertex_a));
+            let vertex_b =
+                renderer.hex_to_screen_space(tile.position().vertex(vertex_b));
+            assert_approx_eq!(
+                renderer.tile_side_midpoint(&tile, dir),
+                (vertex_a + vertex_b) / 2.0
+            );
+        }
+    }
+
+    #[test]
+    fn test_bearing() {
+        let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+        let origin = TilePoint::ORIGIN;
+
+        // Straight up the screen, i.e. one step NNE plus one step NNW
+        let north = origin
+            .adjacent(TileDirection::NNE)
+            .adjacent(TileDirection::NNW);
+        assert_approx_eq!(renderer.bearing(origin, north), 0.0);
+        // Straight across the screen to the right
+        let east = TilePoint::new_xy(1, -1);
+        assert_approx_eq!(renderer.bearing(origin, east), 90.0);
+        assert_approx_eq!(renderer.bearing(north, origin), 180.0);
+        assert_approx_eq!(renderer.bearing(east, origin), 270.0);
+        assert_approx_eq!(renderer.bearing(origin, origin), 0.0);
+    }
+
+    #[test]
+    fn test_gradient_color() {
+        let blue = Color3::new(0.0, 0.0, 1.0);
+        let green = Color3::new(0.0, 1.0, 0.0);
+        let white = Color3::new(1.0, 1.0, 1.0);
+        let stops = [(0.2, blue), (0.6, green), (1.0, white)];
+        let assert_color = |value, expected: Color3| {
+            let actual = gradient_color(&stops, value);
+            assert_approx_eq!(actual.red, expected.red);
+            assert_approx_eq!(actual.green, expected.green);
+            assert_approx_eq!(actual.blue, expected.blue);
+        };
+
+        // Outside the stops, we get the nearest end
+        assert_color(0.0, blue);
+        assert_color(0.2, blue);
+        // Between two stops, interpolate
+        assert_color(0.3, Color3::new(0.0, 0.25, 0.75));
+        assert_color(0.6, green);
+        assert_color(0.9, Color3::new(0.75, 1.0, 0.75));
+        assert_color(1.0, white);
+
+        // A single stop is a flat color
+        assert_eq!(gradient_color(&[(0.5, green)], 0.1), green);
+        assert_eq!(gradient_color(&[(0.5, green)], 0.9), green);
+    }
 }
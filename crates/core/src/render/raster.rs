@@ -0,0 +1,469 @@
+//! This module provides logic for rasterizing a world into a grid of pixels.
+//! It has no external dependencies, so it's always available. Raster output
+//! formats (e.g. PPM, raw RGBA, and PNG with the `png` feature) are built on
+//! top of this, as well as gridded data exports (see
+//! [WorldGrid](crate::WorldGrid)).
+
+use crate::{
+    render::{
+        unit::{Color3, Point2},
+        HexOrientation, WorldRenderer,
+    },
+    HasHexPosition, Tile, TilePoint, World,
+};
+#[cfg(feature = "png")]
+use crate::{
+    render::{TileLens, RIVER_COLOR},
+    GeoFeature,
+};
+use anyhow::{anyhow, ensure};
+#[cfg(feature = "png")]
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A world rasterized into a grid of pixels. Each pixel holds the color of the
+/// tile that covers it. Pixels that aren't covered by any tile (i.e. the
+/// background around the edges of the world) hold `None`.
+#[derive(Clone, Debug)]
+pub struct Raster {
+    width: u32,
+    height: u32,
+    /// Pixels in row-major order, starting from the top-left
+    pixels: Vec<Option<Color3>>,
+}
+
+impl Raster {
+    /// Color used for background pixels in formats that don't support
+    /// transparency
+    pub const BACKGROUND_COLOR: Color3 = Color3::new_int(0, 0, 0);
+
+    /// Width of the raster, in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height of the raster, in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Convert this raster to a raw RGBA buffer, 4 bytes per pixel in
+    /// row-major order. Background pixels are fully transparent black. This
+    /// is suitable for direct upload to a GPU texture or JS `ImageData`.
+    pub fn to_rgba(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(self.pixels.len() * 4);
+        for pixel in &self.pixels {
+            match pixel {
+                Some(color) => {
+                    let (r, g, b) = color.to_ints();
+                    buffer.extend([r, g, b, u8::MAX]);
+                }
+                None => buffer.extend([0, 0, 0, 0]),
+            }
+        }
+        buffer
+    }
+
+    /// Encode this raster as an RGBA PNG image. Background pixels are fully
+    /// transparent.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> anyhow::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        let mut encoder =
+            png::Encoder::new(&mut buffer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgba())?;
+        writer.finish()?;
+        Ok(buffer)
+    }
+
+    /// Encode this raster as a binary PPM (P6) image. Background pixels are
+    /// filled with [Self::BACKGROUND_COLOR].
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let header = format!("P6\n{} {}\n255\n", self.width, self.height);
+        let mut buffer =
+            Vec::with_capacity(header.len() + self.pixels.len() * 3);
+        buffer.extend(header.as_bytes());
+        for pixel in &self.pixels {
+            let (r, g, b) = pixel.unwrap_or(Self::BACKGROUND_COLOR).to_ints();
+            buffer.extend([r, g, b]);
+        }
+        buffer
+    }
+}
+
+/// Rasterize a world into pixels, from a top-down perspective. Tile colors are
+/// determined by the renderer's lens, and hidden tiles (see
+/// [RenderConfig::hidden_biomes](crate::RenderConfig::hidden_biomes)) are
+/// left as background. Geographic features such as rivers are not drawn.
+/// `pixels_per_tile` is the number of pixels across the width of a single tile
+/// (vertex to vertex), and must be at least 1, otherwise this returns an
+/// error. If
+/// [RenderConfig::png_supersample](crate::RenderConfig::png_supersample)
+/// is above 1, each pixel is the average of a block of sub-pixels.
+pub fn rasterize_world(
+    world: &World,
+    renderer: &WorldRenderer,
+    pixels_per_tile: u32,
+) -> anyhow::Result<Raster> {
+    rasterize_world_with(world, renderer, pixels_per_tile, |tile| {
+        renderer.tile_color(tile)
+    })
+}
+
+/// Same as [rasterize_world], but tile colors come from `color_fn` instead of
+/// the renderer's lens
+pub(crate) fn rasterize_world_with(
+    world: &World,
+    renderer: &WorldRenderer,
+    pixels_per_tile: u32,
+    color_fn: impl Fn(&Tile) -> Color3,
+) -> anyhow::Result<Raster> {
+    rasterize_world_filtered(world, renderer, pixels_per_tile, |tile| {
+        Some(color_fn(tile))
+    })
+}
+
+/// Same as [rasterize_world_with], but `color_fn` can return `None` to leave a
+/// tile as background
+fn rasterize_world_filtered(
+    world: &World,
+    renderer: &WorldRenderer,
+    pixels_per_tile: u32,
+    color_fn: impl Fn(&Tile) -> Option<Color3>,
+) -> anyhow::Result<Raster> {
+    let supersample = renderer.render_config.png_supersample as u32;
+    let samples = sample_world(world, renderer, pixels_per_tile, supersample)?;
+    let subpixels: Vec<Option<Color3>> = samples
+        .tiles
+        .into_iter()
+        .map(|tile| {
+            tile.filter(|tile| !renderer.is_tile_hidden(tile))
+                .and_then(&color_fn)
+        })
+        .collect();
+
+    // Box-downsample each block of sub-pixels into one pixel. Indexes are
+    // computed as usize, since the sub-pixel count can exceed u32.
+    let width = samples.width / supersample;
+    let height = samples.height / supersample;
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = (0.0, 0.0, 0.0);
+            let mut hits = 0;
+            for sub_y in 0..supersample {
+                for sub_x in 0..supersample {
+                    let row = (y * supersample + sub_y) as usize;
+                    let column = (x * supersample + sub_x) as usize;
+                    let i = row * samples.width as usize + column;
+                    if let Some(color) = subpixels[i] {
+                        sum.0 += color.red;
+                        sum.1 += color.green;
+                        sum.2 += color.blue;
+                        hits += 1;
+                    }
+                }
+            }
+
+            // Colors are only averaged over sub-pixels that hit a tile, so
+            // the background doesn't bleed into the edge of the world. A
+            // pixel that's mostly background is left as background.
+            let pixel = if hits * 2 >= supersample * supersample {
+                let hits = hits as f32;
+                Some(Color3::new(
+                    (sum.0 / hits).min(1.0),
+                    (sum.1 / hits).min(1.0),
+                    (sum.2 / hits).min(1.0),
+                ))
+            } else {
+                None
+            };
+            pixels.push(pixel);
+        }
+    }
+
+    Ok(Raster {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Rasterize a world into a set of separate layers, and encode each one as a
+/// PNG (see [Raster::to_png]). See
+/// [WorldRenderer::render_layers] for a description of each layer.
+#[cfg(feature = "png")]
+pub(crate) fn render_layers(
+    world: &World,
+    renderer: &WorldRenderer,
+    pixels_per_tile: u32,
+) -> anyhow::Result<HashMap<String, Vec<u8>>> {
+    let render = |color_fn: &dyn Fn(&Tile) -> Option<Color3>| {
+        rasterize_world_filtered(world, renderer, pixels_per_tile, color_fn)
+            .and_then(|raster| raster.to_png())
+    };
+    let [biome, elevation, water, rivers] = WorldRenderer::LAYER_NAMES;
+    [
+        (
+            biome,
+            render(&|tile| {
+                Some(renderer.tile_color_for_lens(tile, TileLens::Biome))
+            }),
+        ),
+        (
+            elevation,
+            render(&|tile| {
+                Some(renderer.tile_color_for_lens(tile, TileLens::Elevation))
+            }),
+        ),
+        (
+            water,
+            render(&|tile| {
+                tile.is_water_biome().then(|| {
+                    renderer.tile_color_for_lens(tile, TileLens::Surface)
+                })
+            }),
+        ),
+        (
+            rivers,
+            render(&|tile| {
+                let has_river = tile.features().iter().any(|feature| {
+                    matches!(
+                        feature,
+                        GeoFeature::RiverEntrance { .. }
+                            | GeoFeature::RiverExit { .. }
+                    )
+                });
+                has_river.then_some(RIVER_COLOR)
+            }),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, png)| png.map(|png| (name.to_owned(), png)))
+    .collect()
+}
+
+/// Rasterize a world into scanlines, from a top-down perspective. This is a
+/// building block for custom raster renderers, so they don't have to
+/// reimplement hex rasterization. The iterator yields one item per row of
+/// pixels, from top to bottom, even if the row is empty. Each item holds the
+/// row index and the horizontal spans of pixels in that row, from left to
+/// right. Each span is a run of consecutive pixels covered by a single tile,
+/// along with that tile's color. Pixels not covered by any tile, and hidden
+/// tiles (see [WorldRenderer::is_tile_hidden]), aren't included in any span.
+///
+/// The pixel grid is the same as in [rasterize_world], but supersampling is
+/// **not** applied, since each span maps to exactly one tile. Returns an error
+/// under the same conditions as [rasterize_world].
+pub fn rasterize_tiles<'a>(
+    renderer: &'a WorldRenderer,
+    world: &'a World,
+    pixels_per_tile: u32,
+) -> anyhow::Result<impl Iterator<Item = (u32, Vec<(Range<u32>, Color3)>)> + 'a>
+{
+    let samples = sample_world(world, renderer, pixels_per_tile, 1)?;
+    let width = samples.width as usize;
+    let tiles = samples.tiles;
+    Ok((0..samples.height).map(move |y| {
+        let start = y as usize * width;
+        (y, row_spans(renderer, &tiles[start..start + width]))
+    }))
+}
+
+/// Collapse one row of sampled tiles into spans of the same tile. See
+/// [rasterize_tiles].
+fn row_spans(
+    renderer: &WorldRenderer,
+    row: &[Option<&Tile>],
+) -> Vec<(Range<u32>, Color3)> {
+    let position = |tile: Option<&Tile>| tile.map(|tile| tile.position());
+    let mut spans = Vec::new();
+    let mut x = 0;
+    while x < row.len() {
+        let tile = row[x];
+        let start = x;
+        // Extend the span over every pixel that hits the same tile
+        while x < row.len() && position(row[x]) == position(tile) {
+            x += 1;
+        }
+        if let Some(tile) = tile.filter(|tile| !renderer.is_tile_hidden(tile)) {
+            spans.push((start as u32..x as u32, renderer.tile_color(tile)));
+        }
+    }
+    spans
+}
+
+/// A square grid of sample points laid over a world, in screen space, along
+/// with the tile under each sample point
+#[derive(Clone, Debug)]
+pub(crate) struct TileSamples<'a> {
+    /// Number of columns in the grid. With supersampling, this is the number
+    /// of sub-cells rather than cells.
+    pub width: u32,
+    /// Number of rows in the grid. With supersampling, this is the number of
+    /// sub-cells rather than cells.
+    pub height: u32,
+    /// Top-left corner of the grid, in screen space
+    pub min: Point2,
+    /// Bottom-right corner of the grid, in screen space
+    pub max: Point2,
+    /// The tile under the center of each grid cell (or sub-cell), in
+    /// row-major order starting from the top-left. `None` if the cell is
+    /// outside the world.
+    pub tiles: Vec<Option<&'a Tile>>,
+}
+
+/// Sample a world on a square grid, from a top-down perspective. The grid is
+/// laid out in the renderer's screen space (see
+/// [WorldRenderer::hex_to_screen_space]), so it follows the configured
+/// orientation. It covers the entire world, with enough margin to fit the
+/// outer half of the edge tiles. `pixels_per_tile` is the number of grid cells
+/// across the width of a single tile (vertex to vertex). It comes straight
+/// from the user, so if it's 0 this returns an error. Each cell is split into
+/// `supersample × supersample` sub-cells, which are sampled individually.
+/// `supersample` must also be at least 1, and 1 disables supersampling.
+pub(crate) fn sample_world<'a>(
+    world: &'a World,
+    renderer: &WorldRenderer,
+    pixels_per_tile: u32,
+    supersample: u32,
+) -> anyhow::Result<TileSamples<'a>> {
+    ensure!(pixels_per_tile > 0, "pixels per tile must be at least 1");
+    // This comes from the render config, which is already validated
+    assert!(supersample > 0, "supersample must be at least 1");
+    let tiles = world.tiles();
+    if tiles.is_empty() {
+        return Ok(TileSamples {
+            width: 0,
+            height: 0,
+            min: Point2::default(),
+            max: Point2::default(),
+            tiles: Vec::new(),
+        });
+    }
+
+    // Find the extent of the world in screen space, with enough room on each
+    // side to fit the outer half of the edge tiles
+    let (min, max) = tiles.values().map(|tile| tile.position()).fold(
+        (
+            Point2::new(f64::INFINITY, f64::INFINITY),
+            Point2::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |(min, max), pos| {
+            let point = renderer.hex_to_screen_space(pos);
+            (
+                Point2::new(min.x.min(point.x), min.y.min(point.y)),
+                Point2::new(max.x.max(point.x), max.y.max(point.y)),
+            )
+        },
+    );
+    let vertex_radius = renderer.tile_vertex_radius();
+    let margin = Point2::new(vertex_radius, vertex_radius);
+    let min = min - margin;
+    let max = max + margin;
+
+    // Pixels per unit of screen space. The tile scale cancels out here, so
+    // it has no effect on the output.
+    let scale = pixels_per_tile as f64 / (vertex_radius * 2.0);
+    let width = ((max.x - min.x) * scale).ceil() as u32;
+    let height = ((max.y - min.y) * scale).ceil() as u32;
+
+    // Sub-cells per unit of screen space. A big enough world or scale can
+    // overflow the dimensions, so those get rejected.
+    let sub_scale = scale * supersample as f64;
+    let too_big = || {
+        anyhow!(
+            "image at {} pixels per tile with {}x supersampling is too big",
+            pixels_per_tile,
+            supersample
+        )
+    };
+    let sub_width = width.checked_mul(supersample).ok_or_else(too_big)?;
+    let sub_height = height.checked_mul(supersample).ok_or_else(too_big)?;
+    let len = (sub_width as usize)
+        .checked_mul(sub_height as usize)
+        .ok_or_else(too_big)?;
+
+    let mut samples = Vec::with_capacity(len);
+    for y in 0..sub_height {
+        for x in 0..sub_width {
+            // Sample from the center of each pixel
+            let point = min
+                + Point2::new(
+                    (x as f64 + 0.5) / sub_scale,
+                    (y as f64 + 0.5) / sub_scale,
+                );
+            samples.push(tiles.get(&screen_to_tile(renderer, point)));
+        }
+    }
+
+    Ok(TileSamples {
+        width: sub_width,
+        height: sub_height,
+        min,
+        // Rounding up the dimensions pushes the far edge out a bit
+        max: min + Point2::new(width as f64 / scale, height as f64 / scale),
+        tiles: samples,
+    })
+}
+
+/// Find the tile whose center is closest (in hex space) to a point in the
+/// renderer's screen space. This is the inverse of
+/// [WorldRenderer::hex_to_screen_space], plus rounding to the nearest tile.
+/// The returned tile may not exist in the world.
+fn screen_to_tile(renderer: &WorldRenderer, point: Point2) -> TilePoint {
+    // Undo the orientation and scale, to get back to the default screen space
+    let config = &renderer.render_config;
+    let point = match config.hex_orientation {
+        HexOrientation::PointyTop => point,
+        HexOrientation::FlatTop => Point2::new(point.y, -point.x),
+    };
+    let point =
+        Point2::new(point.x / config.tile_scale, point.y / config.tile_scale);
+
+    // Since x+y+z=0, screen x only depends on x-y, and screen y only depends
+    // on x+y. See hex_to_screen_space for the forward transformation.
+    let x_minus_y = point.x / (2.0f64.sqrt() / 2.0);
+    let x_plus_y = point.y / ((1.0 + 2.0f64.sqrt()) / 2.0);
+    let x = (x_plus_y + x_minus_y) / 2.0;
+    let y = (x_plus_y - x_minus_y) / 2.0;
+    TilePoint::nearest(x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RenderConfig;
+
+    #[test]
+    fn test_screen_to_tile() {
+        for hex_orientation in
+            [HexOrientation::PointyTop, HexOrientation::FlatTop]
+        {
+            let renderer = WorldRenderer::new(RenderConfig {
+                tile_scale: 2.0,
+                hex_orientation,
+                ..Default::default()
+            })
+            .unwrap();
+            for x in -5..=5 {
+                for y in -5..=5 {
+                    let pos = TilePoint::new_xy(x, y);
+                    let center = renderer.hex_to_screen_space(pos);
+                    assert_eq!(screen_to_tile(&renderer, center), pos);
+                    // Nudging off-center should still land on the same tile
+                    assert_eq!(
+                        screen_to_tile(
+                            &renderer,
+                            center + Point2::new(0.4, -0.4)
+                        ),
+                        pos
+                    );
+                }
+            }
+        }
+    }
+}
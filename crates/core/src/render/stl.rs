@@ -4,45 +4,223 @@
 use crate::{
     render::{unit::Point2, WorldRenderer},
     world::hex::{TileDirectionMap, VertexDirection},
-    HasHexPosition, HexDirection, Tile, TileDirection, World,
+    Biome, GeoFeatureKind, HasHexPosition, HexDirection, Tile, TileDirection,
+    TilePoint, World,
 };
-use stl_io::{Normal, Triangle, Vertex};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, ops::Range};
+use stl_io::{Normal, Triangle, Vector, Vertex};
 use strum::IntoEnumIterator;
 
+/// A sidecar for an STL model, describing which triangles belong to which
+/// tile. STL can't carry colors or materials, so this lets other tools
+/// texture the model themselves, e.g. by biome. See
+/// [WorldRenderer::render_as_stl_with_metadata].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StlMetadata {
+    /// Total number of triangles in the model
+    pub triangle_count: usize,
+    /// Triangle ranges for each tile that appears in the model, sorted by
+    /// where they start. Each tile's triangles are contiguous, and the ranges
+    /// never overlap. Together they cover every triangle in the model.
+    pub ranges: Vec<StlTriangleRange>,
+}
+
+/// The triangles in an STL model that were generated from a single tile
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StlTriangleRange {
+    /// Index of the first triangle, inclusive
+    pub start: usize,
+    /// Index after the last triangle, i.e. exclusive
+    pub end: usize,
+    pub position: TilePoint,
+    pub biome: Biome,
+    /// Kinds of each geographic feature on the tile, in the same order as
+    /// [Tile::features]
+    pub features: Vec<GeoFeatureKind>,
+}
+
 /// Render the given world as an STL model. STL only carries geometric data,
 /// so no colors/textures. There's no dominant convention around which axis
 /// should be up in an STL, so here we consider the **Y axis to be up and
 /// down** to be consistent with the demo.
 pub fn world_to_stl(world: &World, renderer: &WorldRenderer) -> Vec<Triangle> {
+    build_mesh(world, renderer, |_, _| {})
+}
+
+/// Render the given world as an STL model (see [world_to_stl]), and also
+/// describe which triangles came from which tile
+#[cfg(feature = "json")]
+pub fn world_to_stl_with_metadata(
+    world: &World,
+    renderer: &WorldRenderer,
+) -> (Vec<Triangle>, StlMetadata) {
+    let mut ranges = Vec::new();
+    let mesh = build_mesh(world, renderer, |tile, range| {
+        ranges.push(StlTriangleRange {
+            start: range.start,
+            end: range.end,
+            position: tile.position(),
+            biome: tile.biome(),
+            features: tile
+                .features()
+                .iter()
+                .map(|feature| feature.kind())
+                .collect(),
+        });
+    });
+    let metadata = StlMetadata {
+        triangle_count: mesh.len(),
+        ranges,
+    };
+    (mesh, metadata)
+}
+
+/// Build the STL mesh for a world. After each tile's triangles are added,
+/// `on_tile` is called with the tile and the range of indexes that its
+/// triangles occupy in the mesh. Tiles that don't add any triangles are
+/// skipped.
+fn build_mesh(
+    world: &World,
+    renderer: &WorldRenderer,
+    mut on_tile: impl FnMut(&Tile, Range<usize>),
+) -> Vec<Triangle> {
+    if renderer.render_config.stl_surface_only {
+        return world_to_stl_surface(world, renderer, on_tile);
+    }
+
     let tiles = world.tiles();
     let mut mesh =
         Vec::with_capacity(tiles.len() * TileSolid::TRIANGLES_PER_TILE);
 
-    for tile in tiles.values() {
+    for tile in tiles.values().filter(|tile| !renderer.is_tile_hidden(tile)) {
+        let start = mesh.len();
         let solid = TileSolid::new(world, renderer, tile);
         solid.add_to_mesh(&mut mesh);
+        on_tile(tile, start..mesh.len());
+    }
+
+    mesh
+}
+
+/// Render only the top surface of the world, as a height mesh. Each vertex of
+/// the mesh is the center of a tile, at that tile's height, and each triangle
+/// connects three mutually adjacent tiles. Triangles that would include a
+/// hidden tile or run off the edge of the world are skipped. See
+/// [RenderConfig::stl_surface_only](crate::RenderConfig::stl_surface_only).
+///
+/// Each triangle is attributed to the tile that it's generated from, which is
+/// the one whose sectors it covers.
+fn world_to_stl_surface(
+    world: &World,
+    renderer: &WorldRenderer,
+    mut on_tile: impl FnMut(&Tile, Range<usize>),
+) -> Vec<Triangle> {
+    let normal = Normal::new([0.0, 0.0, 0.0]);
+    let tiles = world.tiles();
+    let vertex = |pos: TilePoint| {
+        let tile = tiles
+            .get(&pos)
+            .filter(|tile| !renderer.is_tile_hidden(tile))?;
+        let p = renderer.hex_to_screen_space(pos);
+        Some(Vertex::new([
+            p.x as f32,
+            renderer.tile_height(tile) as f32,
+            p.y as f32,
+        ]))
+    };
+
+    // The centers of all the tiles form a triangular lattice, where each
+    // point is a corner of six triangles. The sectors between two consecutive
+    // pairs of neighbors cover one triangle of each orientation, so if every
+    // tile only takes those two, we get each triangle exactly once.
+    let clockwise = TileDirection::CLOCKWISE;
+    let mut mesh = Vec::with_capacity(tiles.len() * 2);
+    for (pos, tile) in tiles {
+        let start = mesh.len();
+        for i in 0..2 {
+            let corners = [
+                vertex(*pos),
+                vertex(pos.adjacent(clockwise[i])),
+                vertex(pos.adjacent(clockwise[i + 1])),
+            ];
+            if let [Some(v1), Some(v2), Some(v3)] = corners {
+                // Neighbors go clockwise, so flip to get counter-clockwise
+                // vertices (right-hand rule) when viewed from above
+                mesh.push(Triangle {
+                    normal,
+                    vertices: [v1, v3, v2],
+                });
+            }
+        }
+        if mesh.len() > start {
+            on_tile(tile, start..mesh.len());
+        }
     }
 
     mesh
 }
 
+/// Serialize a mesh into binary STL
+pub fn mesh_to_binary_stl(mesh: &[Triangle]) -> Vec<u8> {
+    let mut buffer = Vec::<u8>::new();
+    // Panic here indicates a bug in our STL mesh format
+    stl_io::write_stl(&mut buffer, mesh.iter()).expect("error serializing STL");
+    buffer
+}
+
+/// Serialize a mesh into ASCII STL. This is much bigger than the binary
+/// format, but it's human-readable which makes it handy for debugging small
+/// models.
+pub fn mesh_to_ascii_stl(mesh: &[Triangle]) -> String {
+    fn write_vector(output: &mut String, prefix: &str, vector: &Vector<f32>) {
+        // Writing to a string can't fail
+        writeln!(
+            output,
+            "{} {} {} {}",
+            prefix, vector[0], vector[1], vector[2]
+        )
+        .unwrap();
+    }
+
+    let mut output = String::from("solid terra\n");
+    for triangle in mesh {
+        write_vector(&mut output, "  facet normal", &triangle.normal);
+        output.push_str("    outer loop\n");
+        for vertex in &triangle.vertices {
+            write_vector(&mut output, "      vertex", vertex);
+        }
+        output.push_str("    endloop\n");
+        output.push_str("  endfacet\n");
+    }
+    output.push_str("endsolid terra\n");
+    output
+}
+
 /// A convenience struct for converting a tile into STL triangles.
 #[derive(Clone, Debug)]
 struct TileSolid {
     perimeter_points_2d: Vec<Point2>,
     bottom_perimeter_vertices: Vec<Vertex>,
-    top_y: f32,
+    /// Corners of the top face. With a bevel, these are pulled in from the
+    /// perimeter.
     top_perimeter_vertices: Vec<Vertex>,
+    /// Height of the outer edge of the top, where the walls end. Without a
+    /// bevel, this is the same as the top.
+    shoulder_y: f32,
+    beveled: bool,
+    /// Shoulder height of each neighbor, which is where our walls stop
     adjacents_y: TileDirectionMap<f32>,
 }
 
 impl TileSolid {
-    /// AT MOST 20 triangles per tile:
+    /// AT MOST 32 triangles per tile:
     /// - 4 for the top
+    /// - 2 per side for the bevel, times 6 sides, if beveling is enabled
     /// - MAXIMUM of 2 per side, times 6 sides
     ///   - These get culled if adjacent to a taller tile
     /// - 4 for the bottom
-    const TRIANGLES_PER_TILE: usize = 20;
+    const TRIANGLES_PER_TILE: usize = 32;
 
     /// Vertex indices for the vertices of tile's face (top or bottom). Together
     /// these form the triangles that make up the face. Starts at the top-left
@@ -65,30 +243,69 @@ impl TileSolid {
             .map(|p| Vertex::new([p.x as f32, 0.0, p.y as f32]))
             .collect();
 
+        let bevel = Self::bevel(renderer);
         let top_y = renderer.tile_height(tile) as f32;
+        let shoulder_y = Self::shoulder_y(bevel, top_y);
+        // Pull each corner of the top in toward the center. Scaling the
+        // corners is the same as moving them in a fixed distance, since
+        // they're all the same distance from the center.
+        let center = renderer.hex_to_screen_space(tile.position());
+        let inset_scale = 1.0
+            - bevel
+                / (WorldRenderer::TILE_VERTEX_RADIUS
+                    * renderer.render_config.tile_scale);
         let top_perimeter_vertices: Vec<_> = perimeter_points_2d
             .iter()
-            .map(|p| Vertex::new([p.x as f32, top_y, p.y as f32]))
+            .map(|p| {
+                let p = if bevel > 0.0 {
+                    center + (*p - center) * inset_scale
+                } else {
+                    *p
+                };
+                Vertex::new([p.x as f32, top_y, p.y as f32])
+            })
             .collect();
 
         let pos = tile.position();
         let adjacents_y = TileDirection::iter()
             .filter_map(|dir| {
                 let adj_pos = pos.adjacent(dir);
-                let adj_tile = world.tiles().get(&adj_pos)?;
-                Some((dir, renderer.tile_height(adj_tile) as f32))
+                // Hidden neighbors are treated like the edge of the world, so
+                // we still get a wall on that side
+                let adj_tile = world
+                    .tiles()
+                    .get(&adj_pos)
+                    .filter(|adj_tile| !renderer.is_tile_hidden(adj_tile))?;
+                let adj_top_y = renderer.tile_height(adj_tile) as f32;
+                Some((dir, Self::shoulder_y(bevel, adj_top_y)))
             })
             .collect();
 
         Self {
             perimeter_points_2d,
             bottom_perimeter_vertices,
-            top_y,
             top_perimeter_vertices,
+            shoulder_y,
+            beveled: bevel > 0.0,
             adjacents_y,
         }
     }
 
+    /// Get the size of the bevel, limited to half the tile radius. See
+    /// [RenderConfig::stl_bevel](crate::RenderConfig::stl_bevel).
+    fn bevel(renderer: &WorldRenderer) -> f64 {
+        let render_config = &renderer.render_config;
+        render_config.stl_bevel.min(
+            WorldRenderer::TILE_VERTEX_RADIUS * render_config.tile_scale / 2.0,
+        )
+    }
+
+    /// Get the height of the outer edge of a tile's top face. The bevel can't
+    /// go below the bottom of the tile.
+    fn shoulder_y(bevel: f64, top_y: f32) -> f32 {
+        top_y - (bevel as f32).min(top_y.max(0.0))
+    }
+
     /// Convert this tile to triangle soup and add them to the soup pot.
     fn add_to_mesh(self, mesh: &mut Vec<Triangle>) {
         // Normals are bullshit anyway, most programs don't respect them
@@ -117,26 +334,47 @@ impl TileSolid {
             mesh.push(Triangle { normal, vertices })
         }
 
-        // For each side of the hexagon, draw 2 triangles
+        // For each side of the hexagon, draw 2 triangles (plus 2 more for
+        // the bevel)
         for (i, dir) in TileDirection::iter().enumerate() {
             let bottom_y = *self.adjacents_y.get(&dir).unwrap_or(&0.0);
 
+            // Some setup variables
+            let i1 = i;
+            let i2 = (i + 1) % 6;
+            let p1 = self.perimeter_points_2d[i1];
+            let p2 = self.perimeter_points_2d[i2];
+            let shoulder_v1 =
+                Vertex::new([p1.x as f32, self.shoulder_y, p1.y as f32]);
+            let shoulder_v2 =
+                Vertex::new([p2.x as f32, self.shoulder_y, p2.y as f32]);
+
+            // The bevel slopes down from the edge of the top face to the
+            // shoulder. It's shaped (and sliced up) just like a side face, so
+            // see the diagram below.
+            if self.beveled {
+                let top_v1 = self.top_perimeter_vertices[i1];
+                let top_v2 = self.top_perimeter_vertices[i2];
+                mesh.push(Triangle {
+                    normal,
+                    vertices: [shoulder_v2, top_v2, top_v1],
+                });
+                mesh.push(Triangle {
+                    normal,
+                    vertices: [top_v1, shoulder_v1, shoulder_v2],
+                });
+            }
+
             // If the adjacent tile in this direction is taller, then no need
             // to draw a side here because it won't be visible.
-            if bottom_y <= self.top_y {
-                // Some setup variables
-                let i1 = i;
-                let i2 = (i + 1) % 6;
-                let p1 = self.perimeter_points_2d[i1];
-                let p2 = self.perimeter_points_2d[i2];
-
+            if bottom_y <= self.shoulder_y {
                 let bottom_v1 =
                     Vertex::new([p1.x as f32, bottom_y, p1.y as f32]);
                 let bottom_v2 =
                     Vertex::new([p2.x as f32, bottom_y, p2.y as f32]);
 
-                let top_v1 = self.top_perimeter_vertices[i1];
-                let top_v2 = self.top_perimeter_vertices[i2];
+                let top_v1 = shoulder_v1;
+                let top_v2 = shoulder_v2;
 
                 // Side face - here's what each one looks like from the OUTSIDE
                 // We slice it into two triangles for our triangle soup
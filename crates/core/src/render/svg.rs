@@ -1,63 +1,106 @@
 use crate::{
-    render::{unit::Color3, WorldRenderer},
+    render::{
+        unit::{Color3, Point2},
+        WorldRenderer, RIVER_COLOR,
+    },
     world::hex::HexDirection,
-    GeoFeature, HasHexPosition, Tile, TilePoint, VertexDirection, World,
+    GeoFeature, HasHexPosition, Tile, TileDirection, TilePoint,
+    VertexDirection, World,
 };
 use svg::{
     node::{
-        element::{Group, Line, Polygon},
+        self,
+        element::{Group, Line, Polygon, Rectangle, Text},
         Comment,
     },
     Document,
 };
 
-const RIVER_COLOR: Color3 = Color3::new_int(72, 192, 240);
+const BIOME_BORDER_COLOR: Color3 = Color3::new_int(0, 0, 0);
+/// Width of biome borders, relative to the tile radius
+const BIOME_BORDER_WIDTH: f64 = 0.08;
+const ELEVATION_LABEL_COLOR: Color3 = Color3::new_int(0, 0, 0);
+/// Font size of elevation labels, relative to the tile radius
+const ELEVATION_LABEL_SIZE: f64 = 0.6;
+const MINIMAP_REGION_COLOR: Color3 = Color3::new_int(255, 0, 0);
+/// Worlds bigger than this get downsampled before being drawn in the minimap
+const MINIMAP_MAX_RADIUS: u16 = 25;
+/// Width of the minimap, as a fraction of the width of the view box
+const MINIMAP_WIDTH_FRACTION: f64 = 0.25;
+/// Gap between the minimap and the edges of the view box, as a fraction of the
+/// width of the view box
+const MINIMAP_MARGIN_FRACTION: f64 = 0.02;
 
 /// Render a world as an SVG. This will be a 2D top-down rendering, in full
-/// color
-pub fn world_to_svg(world: &World, renderer: &WorldRenderer) -> Document {
-    // Set the view box based on the world size. Each of these values is the
-    // distance from the center of the viewbox to the outer edge. So the
-    // width/height will be double that value
-    let radius = world.config().radius as f64;
-    // Distance from center of origin tile to center of right-most tile,
-    // **plus** the center of that right-most tile to its right-most edge
-    let view_box_max_x = (radius * WorldRenderer::TILE_CENTER_DISTANCE_X
-        + WorldRenderer::TILE_VERTEX_RADIUS)
-        .ceil();
-    // Distance from the center of origin tile to center of bottom-most tile,
-    // **plus** the center of that bottom-most tile to its bottom edge
-    let view_box_max_y = (radius * WorldRenderer::TILE_CENTER_DISTANCE_Y
-        + WorldRenderer::TILE_SIDE_RADIUS)
-        .ceil();
+/// color. Each tile is filled with the color from `color_fn`.
+pub fn world_to_svg(
+    world: &World,
+    renderer: &WorldRenderer,
+    color_fn: impl Fn(&Tile) -> Color3,
+) -> Document {
+    let render_config = renderer.render_config();
+    let region = render_config
+        .svg_view_box
+        .unwrap_or_else(|| renderer.screen_bounds(world));
+    let padding =
+        Point2::new(render_config.svg_padding, render_config.svg_padding);
+    let top_left = region.0 - padding;
+    let bottom_right = region.1 + padding;
 
     let mut document = Document::new()
         .set(
             "viewBox",
             (
                 // Top-left corner
-                -view_box_max_x,
-                -view_box_max_y,
+                top_left.x,
+                top_left.y,
                 // Width and height
-                view_box_max_x * 2.0,
-                view_box_max_y * 2.0,
+                bottom_right.x - top_left.x,
+                bottom_right.y - top_left.y,
             ),
         )
         .set("shape-rendering", "crispEdges")
         .add(Comment::new(format!("\n{:#?}\n", world.config())));
 
-    for tile in world.tiles().values() {
-        let polygon = draw_tile(renderer, tile);
+    for tile in world
+        .tiles()
+        .values()
+        .filter(|tile| !renderer.is_tile_hidden(tile))
+    {
+        let polygon = draw_tile(renderer, tile, color_fn(tile));
         document = document.add(polygon);
     }
 
+    // Borders go on top of all the tiles, so they don't get covered up by
+    // their neighbors
+    if render_config.draw_biome_borders {
+        document = document.add(draw_biome_borders(world, renderer));
+    }
+
+    if render_config.show_elevation_labels {
+        document = document.add(draw_elevation_labels(world, renderer));
+    }
+
+    if render_config.minimap && !world.tiles().is_empty() {
+        document = document.add(draw_minimap(
+            world,
+            renderer,
+            region,
+            (top_left, bottom_right),
+        ));
+    }
+
     document
 }
 
 /// Generate an SVG polygon for a single tile
-fn draw_tile(world_renderer: &WorldRenderer, tile: &Tile) -> Group {
+fn draw_tile(
+    world_renderer: &WorldRenderer,
+    tile: &Tile,
+    color: Color3,
+) -> Group {
     let pos = tile.position();
-    let pos2d = world_renderer.hex_to_screen_space(pos);
+    let pos2d = world_renderer.jittered_tile_position(tile);
 
     // Start with the main tile hexagon
     let mut group = Group::new()
@@ -66,31 +109,20 @@ fn draw_tile(world_renderer: &WorldRenderer, tile: &Tile) -> Group {
         .add(Comment::new(pos.to_string())) // Readability!
         .add(
             Polygon::new()
-                // Generate vertices for the tile. This attribute ends up being
-                // the same for every tile, but we can't really pull this code
-                // out because the SVG lib forces us to clone the vec every
-                // time anyway. So it's just easier to leave it like this
-                .set(
-                    "points",
-                    VertexDirection::CLOCKWISE
-                        .iter()
-                        .map(|dir| {
-                            let vertex_hex = TilePoint::ORIGIN.vertex(*dir);
-                            let vertex_2d =
-                                world_renderer.hex_to_screen_space(vertex_hex);
-                            (vertex_2d.x, vertex_2d.y)
-                        })
-                        .collect::<Vec<_>>(),
-                )
+                // This attribute ends up being the same for every tile, but
+                // the SVG lib forces us to clone the vec every time anyway, so
+                // there's no point in caching it
+                .set("points", hexagon_points(world_renderer, 1.0))
                 // Set color
-                .set("fill", world_renderer.tile_color(tile).to_html()),
+                .set("fill", color.to_html()),
         );
 
     // Add overlays for each geo feature
     if world_renderer.render_config().show_features {
         for feature in tile.features() {
             match feature {
-                GeoFeature::Lake => {} // This is covered by TileLens::Surface
+                // These are covered by TileLens::Surface
+                GeoFeature::Lake | GeoFeature::InlandSea | GeoFeature::Ice => {}
                 GeoFeature::RiverEntrance { direction, volume }
                 | GeoFeature::RiverExit { direction, volume } => {
                     let side_midpoint = world_renderer.hex_to_screen_space(
@@ -117,3 +149,174 @@ fn draw_tile(world_renderer: &WorldRenderer, tile: &Tile) -> Group {
 
     group
 }
+
+/// Generate an SVG group with a line along each edge between two tiles of
+/// different biomes
+fn draw_biome_borders(world: &World, world_renderer: &WorldRenderer) -> Group {
+    let mut group = Group::new()
+        .set("class", "biome-borders")
+        .set("stroke", BIOME_BORDER_COLOR.to_html())
+        .set(
+            "stroke-width",
+            BIOME_BORDER_WIDTH
+                * WorldRenderer::TILE_VERTEX_RADIUS
+                * world_renderer.render_config().tile_scale,
+        );
+
+    for tile in world
+        .tiles_sorted()
+        .into_iter()
+        .filter(|tile| !world_renderer.is_tile_hidden(tile))
+    {
+        // Every edge is shared by two tiles, so only check half of the
+        // directions to draw each one once
+        for direction in &TileDirection::CLOCKWISE[..3] {
+            let differs = world
+                .wrapping_neighbor(tile.position(), *direction)
+                .filter(|adj_tile| !world_renderer.is_tile_hidden(adj_tile))
+                .map_or(false, |adj_tile| adj_tile.biome() != tile.biome());
+            if differs {
+                // The edge runs between the vertices on either end of the
+                // side facing the neighbor
+                let center = world_renderer.jittered_tile_position(tile);
+                let (v1, v2) = direction.flanking_directions();
+                let [p1, p2] = [v1, v2].map(|dir| {
+                    center
+                        + world_renderer
+                            .hex_to_screen_space(TilePoint::ORIGIN.vertex(dir))
+                });
+                group = group.add(
+                    Line::new()
+                        .set("class", "biome-border")
+                        .set("x1", p1.x)
+                        .set("y1", p1.y)
+                        .set("x2", p2.x)
+                        .set("y2", p2.y),
+                );
+            }
+        }
+    }
+
+    group
+}
+
+/// Generate an SVG group with a text label in the middle of each tile, showing
+/// its elevation
+fn draw_elevation_labels(
+    world: &World,
+    world_renderer: &WorldRenderer,
+) -> Group {
+    let mut group = Group::new()
+        .set("class", "elevation-labels")
+        .set("fill", ELEVATION_LABEL_COLOR.to_html())
+        .set(
+            "font-size",
+            ELEVATION_LABEL_SIZE
+                * WorldRenderer::TILE_VERTEX_RADIUS
+                * world_renderer.render_config().tile_scale,
+        )
+        .set("text-anchor", "middle")
+        .set("dominant-baseline", "central");
+
+    for tile in world
+        .tiles_sorted()
+        .into_iter()
+        .filter(|tile| !world_renderer.is_tile_hidden(tile))
+    {
+        let center = world_renderer.jittered_tile_position(tile);
+        // Cast to an int so we don't get "-0"
+        let label = (tile.elevation().0.round() as i64).to_string();
+        group = group.add(
+            Text::new()
+                .set("class", "elevation-label")
+                .set("x", center.x)
+                .set("y", center.y)
+                .add(node::Text::new(label)),
+        );
+    }
+
+    group
+}
+
+/// Generate the vertices of a tile hexagon centered on the origin, in screen
+/// space. `scale` is a multiplier on the size of the hexagon.
+fn hexagon_points(
+    world_renderer: &WorldRenderer,
+    scale: f64,
+) -> Vec<(f64, f64)> {
+    VertexDirection::CLOCKWISE
+        .iter()
+        .map(|dir| {
+            let vertex_hex = TilePoint::ORIGIN.vertex(*dir);
+            let vertex_2d =
+                world_renderer.hex_to_screen_space(vertex_hex) * scale;
+            (vertex_2d.x, vertex_2d.y)
+        })
+        .collect()
+}
+
+/// Generate an SVG group with a biome-colored overview of the whole world,
+/// plus a rectangle outlining `region`. The overview is drawn in the same
+/// screen space as the full world, then the whole group is shrunk down into
+/// the bottom-right corner of `view_box`. That way the region rectangle lines
+/// up with the overview without any extra math.
+fn draw_minimap(
+    world: &World,
+    world_renderer: &WorldRenderer,
+    region: (Point2, Point2),
+    view_box: (Point2, Point2),
+) -> Group {
+    // Round up, so the overview never exceeds the max radius
+    let factor = ((world.config().radius + MINIMAP_MAX_RADIUS - 1)
+        / MINIMAP_MAX_RADIUS)
+        .max(1);
+    let overview = world.downsample(factor);
+    // Each downsampled tile covers roughly `factor` tiles in each direction,
+    // so scale up positions and hexagons to match the full world
+    let scale = factor as f64;
+
+    let (world_min, world_max) = world_renderer.screen_bounds(world);
+    let world_size = world_max - world_min;
+    let view_size = view_box.1 - view_box.0;
+    let minimap_scale = view_size.x * MINIMAP_WIDTH_FRACTION / world_size.x;
+    let margin = view_size.x * MINIMAP_MARGIN_FRACTION;
+    let corner =
+        view_box.1 - world_size * minimap_scale - Point2::new(margin, margin);
+
+    let mut group = Group::new().set("class", "minimap").set(
+        "transform",
+        format!(
+            "translate({} {}) scale({}) translate({} {})",
+            corner.x, corner.y, minimap_scale, -world_min.x, -world_min.y
+        ),
+    );
+
+    let points = hexagon_points(world_renderer, scale);
+    for tile in overview.tiles().values() {
+        let pos2d = world_renderer.hex_to_screen_space(tile.position()) * scale;
+        group = group.add(
+            Polygon::new()
+                .set("transform", format!("translate({} {})", pos2d.x, pos2d.y))
+                .set("points", points.clone())
+                .set(
+                    "fill",
+                    world_renderer.biome_color(tile.biome()).to_html(),
+                ),
+        );
+    }
+
+    let (top_left, bottom_right) = region;
+    group.add(
+        Rectangle::new()
+            .set("class", "minimap-region")
+            .set("x", top_left.x)
+            .set("y", top_left.y)
+            .set("width", bottom_right.x - top_left.x)
+            .set("height", bottom_right.y - top_left.y)
+            .set("fill", "none")
+            .set("stroke", MINIMAP_REGION_COLOR.to_html())
+            // Keep the outline visible no matter how much the group is shrunk
+            .set("stroke-width", 1)
+            .set("vector-effect", "non-scaling-stroke"),
+    )
+}
@@ -50,7 +50,7 @@ impl Point2 {
 /// An RGB color. Values are stored as floats between 0 and 1 (inclusive).
 /// This uses f32 because the extra precision from f64 is pointless.
 #[cfg_attr(feature = "js", wasm_bindgen)]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Color3 {
     pub red: f32,
     pub green: f32,
@@ -102,11 +102,35 @@ impl Color3 {
         )
     }
 
+    /// Linearly interpolate between this color and another. `t` is clamped to
+    /// `[0, 1]`, where 0 gives this color and 1 gives the other.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let t = Self::COMPONENT_RANGE.clamp(t);
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        // Mixing two in-range values with t in [0, 1] stays in range, so it's
+        // safe to bypass the constructor
+        Self {
+            red: mix(self.red, other.red),
+            green: mix(self.green, other.green),
+            blue: mix(self.blue, other.blue),
+        }
+    }
+
     /// Convert this color to an HTML color code: `#rrggbb`
     pub fn to_html(self) -> String {
         let (r, g, b) = self.to_ints();
         format!("#{r:02x}{g:02x}{b:02x}")
     }
+
+    /// Add an alpha channel to this color. The alpha is clamped to `[0, 1]`.
+    pub fn with_alpha(self, alpha: f32) -> Color4 {
+        Color4 {
+            red: self.red,
+            green: self.green,
+            blue: self.blue,
+            alpha: Self::COMPONENT_RANGE.clamp(alpha),
+        }
+    }
 }
 
 // Scale a color by a constant
@@ -122,3 +146,125 @@ impl ops::Mul<f32> for Color3 {
         Self { red, green, blue }
     }
 }
+
+/// An RGBA color, i.e. a [Color3] plus an alpha (opacity) channel. Values are
+/// stored as floats between 0 and 1 (inclusive). An alpha of 0 is fully
+/// transparent, 1 is fully opaque. Stick to [Color3] for anything that's
+/// always opaque.
+#[cfg_attr(feature = "js", wasm_bindgen)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Color4 {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Color4 {
+    /// Create a new RGBA color with components in the range [0.0, 1.0]. Panic
+    /// if any of the components are out of range
+    pub fn new(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        assert!(
+            Color3::COMPONENT_RANGE.contains(alpha),
+            "Color component alpha must be in {}, but was {}",
+            Color3::COMPONENT_RANGE,
+            alpha
+        );
+        Color3::new(red, green, blue).with_alpha(alpha)
+    }
+
+    /// Create a new RGBA color from integer components in the [0,255] range.
+    pub const fn new_int(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Self {
+            red: red as f32 / 255.0,
+            green: green as f32 / 255.0,
+            blue: blue as f32 / 255.0,
+            alpha: alpha as f32 / 255.0,
+        }
+    }
+
+    /// Get the RGB components of this color, dropping the alpha
+    pub fn rgb(self) -> Color3 {
+        Color3 {
+            red: self.red,
+            green: self.green,
+            blue: self.blue,
+        }
+    }
+
+    /// Convert this number to a set of 4 bytes: `(red, green, blue, alpha)`
+    pub fn to_ints(self) -> (u8, u8, u8, u8) {
+        let (r, g, b) = self.rgb().to_ints();
+        (r, g, b, (self.alpha * 255.0) as u8)
+    }
+}
+
+impl From<Color3> for Color4 {
+    /// Convert to a fully opaque color
+    fn from(color: Color3) -> Self {
+        color.with_alpha(1.0)
+    }
+}
+
+impl From<Color4> for Color3 {
+    /// Drop the alpha channel
+    fn from(color: Color4) -> Self {
+        color.rgb()
+    }
+}
+
+// Scale a color by a constant. Alpha is left as is.
+impl ops::Mul<f32> for Color4 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        (self.rgb() * rhs).with_alpha(self.alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color4_new() {
+        let color = Color4::new(0.1, 0.2, 0.3, 0.4);
+        assert_eq!(color.rgb(), Color3::new(0.1, 0.2, 0.3));
+        assert_eq!(color.alpha, 0.4);
+        assert_eq!(
+            Color4::new_int(0, 51, 255, 255),
+            Color4::new(0.0, 0.2, 1.0, 1.0)
+        );
+        assert_eq!(Color4::new_int(255, 0, 0, 51).to_ints(), (255, 0, 0, 51));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_color4_new_invalid_alpha() {
+        Color4::new(0.0, 0.0, 0.0, 1.5);
+    }
+
+    #[test]
+    fn test_color4_clamp() {
+        let color = Color3::new(0.5, 0.5, 0.5);
+        assert_eq!(color.with_alpha(2.0).alpha, 1.0);
+        assert_eq!(color.with_alpha(-1.0).alpha, 0.0);
+
+        // Scaling clamps the color, but doesn't touch alpha
+        let scaled = Color4::new(0.5, 0.25, 0.0, 0.5) * 3.0;
+        assert_eq!(scaled, Color4::new(1.0, 0.75, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_color4_conversion() {
+        let rgb = Color3::new(0.1, 0.2, 0.3);
+        let rgba: Color4 = rgb.into();
+        assert_eq!(rgba.alpha, 1.0);
+        assert_eq!(Color3::from(rgba), rgb);
+
+        // Converting to RGB and back loses the alpha
+        let translucent = rgb.with_alpha(0.5);
+        assert_eq!(translucent.rgb(), rgb);
+        assert_eq!(Color4::from(translucent.rgb()), rgba);
+    }
+}
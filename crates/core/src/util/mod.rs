@@ -125,6 +125,41 @@ pub mod serde_tile_point_map_to_vec {
     }
 }
 
+// Serialize a TilePointMap as a list of (position, value) pairs. This is like
+// serde_tile_point_map_to_vec, but for values that don't know their own
+// position. Pairs are serialized in order of position, so the output is stable.
+pub mod serde_tile_point_map_to_pairs {
+    use crate::{TilePoint, TilePointMap};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serialize a tile point map as a list of pairs
+    pub fn serialize<T, S>(
+        map: &TilePointMap<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize,
+        S: Serializer,
+    {
+        let mut pairs: Vec<(&TilePoint, &T)> = map.iter().collect();
+        pairs.sort_by_key(|(pos, _)| **pos);
+        serializer.collect_seq(pairs)
+    }
+
+    /// Deserialize a list of pairs into a map. If the same position appears
+    /// more than once, the last value wins.
+    pub fn deserialize<'de, T, D>(
+        deserializer: D,
+    ) -> Result<TilePointMap<T>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        let pairs: Vec<(TilePoint, T)> = Vec::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,4 @@
-use crate::{Meter, Meter2, Meter3};
+use crate::{Celsius, Meter, Meter2, Meter3};
 use anyhow::anyhow;
 use derive_more::Display;
 use rand::{
@@ -83,6 +83,16 @@ impl Rangeable<f64> for Meter3 {
     }
 }
 
+impl Rangeable<f64> for Celsius {
+    fn zero() -> Self {
+        0.0.into()
+    }
+
+    fn one() -> Self {
+        1.0.into()
+    }
+}
+
 /// A range between two numeric values, inclusive on both ends.
 ///
 /// ## Type Parameters
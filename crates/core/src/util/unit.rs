@@ -149,3 +149,41 @@ impl ops::Div<Meter2> for Meter3 {
         Meter(self.0 / rhs.0)
     }
 }
+
+/// Unit used for temperature. Note that this is subject to the same artistic
+/// license as the other units, since elevations are themselves abstract.
+#[cfg_attr(feature = "js", wasm_bindgen)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Display,
+    PartialEq,
+    PartialOrd,
+    From,
+    Into,
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    AddAssign,
+    SubAssign,
+    MulAssign,
+    DivAssign,
+    Sum,
+    Serialize,
+    Deserialize,
+)]
+#[display(fmt = "{} °C", "self.0")]
+pub struct Celsius(pub f64);
+
+#[cfg(feature = "js")]
+#[wasm_bindgen]
+impl Celsius {
+    #[wasm_bindgen(constructor)]
+    pub fn new(value: f64) -> Self {
+        Self(value)
+    }
+}
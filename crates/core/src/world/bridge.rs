@@ -0,0 +1,108 @@
+use crate::world::{
+    coast,
+    flood::flood_fill,
+    hex::{HasHexPosition, TilePoint, TilePointMap, TilePointSet},
+    tile::Tile,
+};
+use std::collections::{HashSet, VecDeque};
+
+/// Find narrow strips of land that connect two or more larger land regions.
+/// This is a morphological opening: land is eroded down to the tiles that are
+/// too far from the coast to be part of any strip `max_width` tiles wide,
+/// then those cores are grown back out by the same distance. Anything wide
+/// survives the round trip, and whatever land is left over is narrow. Each
+/// connected piece of leftover land that touches at least two separate
+/// regions is a bridge. Pieces that only touch one region (e.g. peninsulas)
+/// aren't included.
+///
+/// Very short bridges can get swallowed by the regions on either end while
+/// they're being grown back, so they won't be found. Each bridge is sorted by
+/// position, and bridges are sorted by their first position.
+pub(crate) fn land_bridges(
+    tiles: &TilePointMap<Tile>,
+    wrap_radius: Option<u16>,
+    max_width: u16,
+) -> Vec<Vec<TilePoint>> {
+    // A strip N tiles wide never gets more than this far from the coast
+    let core_distance = (max_width + 1) / 2;
+    let coast_distances =
+        coast::water_distances(tiles, Tile::is_water_biome, wrap_radius);
+    let is_land =
+        |pos: &TilePoint| tiles.get(pos).map_or(false, Tile::is_land_biome);
+
+    // Land that can't reach any coast is as thick as it gets
+    let mut land: Vec<TilePoint> = tiles
+        .values()
+        .filter(|tile| tile.is_land_biome())
+        .map(|tile| tile.position())
+        .collect();
+    land.sort();
+    let cores = land.iter().copied().filter(|pos| {
+        coast_distances
+            .get(pos)
+            .map_or(true, |distance| *distance >= core_distance)
+    });
+
+    // Grow the cores back out, one ring at a time
+    let mut regions = TilePointSet::default();
+    let mut queue: VecDeque<(TilePoint, u16)> = VecDeque::new();
+    for pos in cores {
+        regions.insert(pos);
+        queue.push_back((pos, 0));
+    }
+    while let Some((pos, distance)) = queue.pop_front() {
+        if distance >= core_distance {
+            continue;
+        }
+        for adj_pos in pos.adjacents_wrapping(wrap_radius) {
+            if is_land(&adj_pos) && regions.insert(adj_pos) {
+                queue.push_back((adj_pos, distance + 1));
+            }
+        }
+    }
+
+    // Give each wide region an ID, so we can tell which ones each strip of
+    // narrow land touches
+    let mut region_ids: TilePointMap<usize> = TilePointMap::default();
+    let mut region_count = 0;
+    for pos in &land {
+        if regions.contains(pos) && !region_ids.contains_key(pos) {
+            for region_pos in flood_fill(
+                *pos,
+                |pos| pos.adjacents_wrapping(wrap_radius),
+                |pos, _| regions.contains(pos),
+            ) {
+                region_ids.insert(region_pos, region_count);
+            }
+            region_count += 1;
+        }
+    }
+
+    let mut visited = TilePointSet::default();
+    let mut bridges = Vec::new();
+    for pos in &land {
+        if regions.contains(pos) || visited.contains(pos) {
+            continue;
+        }
+        let strip = flood_fill(
+            *pos,
+            |pos| pos.adjacents_wrapping(wrap_radius),
+            |pos, _| is_land(pos) && !regions.contains(pos),
+        );
+        visited.extend(strip.iter().copied());
+
+        let touched: HashSet<usize> = strip
+            .iter()
+            .flat_map(|pos| pos.adjacents_wrapping(wrap_radius))
+            .filter_map(|adj_pos| region_ids.get(&adj_pos).copied())
+            .collect();
+        if touched.len() >= 2 {
+            let mut bridge: Vec<TilePoint> = strip.into_iter().collect();
+            bridge.sort();
+            bridges.push(bridge);
+        }
+    }
+    // Land is visited in order, so bridges are already sorted by their first
+    // (lowest) tile
+    bridges
+}
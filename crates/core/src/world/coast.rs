@@ -0,0 +1,150 @@
+use crate::{
+    render::{hex_to_screen_space, unit::Point2},
+    world::{
+        hex::{
+            HasHexPosition, HexDirection, TileDirection, TilePoint,
+            TilePointMap, TileVertexPoint,
+        },
+        tile::Tile,
+    },
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Calculate how far every land tile is from the coast, in tiles. Coastline
+/// tiles (land tiles with at least one water neighbor) have a distance of 0,
+/// their land neighbors have 1, and so on. Distances are found with a
+/// breadth-first search starting from every coastline tile at once. Water
+/// tiles are left out, as is land that can't reach any coast (e.g. a world
+/// with no water at all). If `wrap_radius` is given, neighbors wrap around
+/// the edges of the world.
+pub(crate) fn coast_distances(
+    tiles: &TilePointMap<Tile>,
+    wrap_radius: Option<u16>,
+) -> TilePointMap<u16> {
+    water_distances(tiles, Tile::is_water_biome, wrap_radius)
+}
+
+/// Same as [coast_distances], but works on any kind of tile by using the given
+/// function to decide which tiles are water. Every other tile is considered
+/// land. This allows distances to be calculated mid-generation, before every
+/// tile has its final biome.
+pub(crate) fn water_distances<T: HasHexPosition<Point = TilePoint>>(
+    tiles: &TilePointMap<T>,
+    is_water: impl Fn(&T) -> bool,
+    wrap_radius: Option<u16>,
+) -> TilePointMap<u16> {
+    let mut distances: TilePointMap<u16> = TilePointMap::default();
+    let mut queue = VecDeque::new();
+
+    // Seed the search with every coastline tile
+    for tile in tiles.values().filter(|tile| !is_water(*tile)) {
+        let pos = tile.position();
+        let is_coastline = pos
+            .adjacents_wrapping(wrap_radius)
+            .filter_map(|adj_pos| tiles.get(&adj_pos))
+            .any(&is_water);
+        if is_coastline {
+            distances.insert(pos, 0);
+            queue.push_back(pos);
+        }
+    }
+
+    // Spread inland, one ring at a time
+    while let Some(pos) = queue.pop_front() {
+        let distance = distances[&pos];
+        for adj_pos in pos.adjacents_wrapping(wrap_radius) {
+            let is_unvisited_land = tiles
+                .get(&adj_pos)
+                .map_or(false, |adj_tile| !is_water(adj_tile))
+                && !distances.contains_key(&adj_pos);
+            if is_unvisited_land {
+                distances.insert(adj_pos, distance.saturating_add(1));
+                queue.push_back(adj_pos);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Trace the sea-level contour between land and water tiles, as polylines in
+/// screen space. Each tile side shared by a land tile and a water tile is one
+/// segment of the contour, and is represented by its midpoint. Segments are
+/// chained together through the vertices they share, walking clockwise around
+/// the land (in hex space), so land is consistently on the same side of every
+/// polyline.
+///
+/// A closed loop (around an island, or around a lake within a continent) has
+/// its first point repeated at the end. Sides along the edge of the world
+/// don't count, since there's no water on the other side, so a coastline that
+/// runs into the edge comes out as an open polyline instead. This doesn't
+/// wrap, even for wrapping worlds, because screen space doesn't.
+pub(crate) fn coastline_contours(
+    tiles: &TilePointMap<Tile>,
+) -> Vec<Vec<Point2>> {
+    // Every land-water side, ordered the same way on every call. Each one
+    // runs from one vertex to the next, clockwise around its land tile
+    let mut land_tiles: Vec<&Tile> =
+        tiles.values().filter(|tile| tile.is_land_biome()).collect();
+    land_tiles.sort_by_key(|tile| tile.position());
+    let segments: Vec<(TileVertexPoint, TileVertexPoint, Point2)> = land_tiles
+        .into_iter()
+        .flat_map(|tile| {
+            let pos = tile.position();
+            TileDirection::CLOCKWISE.iter().filter_map(move |&dir| {
+                let is_coast = tiles
+                    .get(&pos.adjacent(dir))
+                    .map_or(false, |adj_tile| adj_tile.is_water_biome());
+                is_coast.then(|| {
+                    let (start_dir, end_dir) = dir.adjacent_vertex_directions();
+                    (
+                        pos.vertex(start_dir),
+                        pos.vertex(end_dir),
+                        hex_to_screen_space(pos.side_midpoint(dir)),
+                    )
+                })
+            })
+        })
+        .collect();
+
+    // Three tiles meet at each vertex, so at most two of the sides that touch
+    // it are coast, and at most one of those leaves it
+    let outgoing: HashMap<TileVertexPoint, usize> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, (start, _, _))| (*start, i))
+        .collect();
+    let ends: HashSet<TileVertexPoint> =
+        segments.iter().map(|(_, end, _)| *end).collect();
+
+    // Start with segments that nothing leads into, which are the beginnings
+    // of open polylines. Whatever's left after that must be part of a loop.
+    let starts = segments
+        .iter()
+        .enumerate()
+        .filter(|(_, (start, _, _))| !ends.contains(start))
+        .chain(segments.iter().enumerate())
+        .map(|(i, _)| i);
+    let mut visited = vec![false; segments.len()];
+    let mut contours = Vec::new();
+    for first in starts {
+        if visited[first] {
+            continue;
+        }
+
+        let mut contour = Vec::new();
+        let mut current = Some(first);
+        while let Some(i) = current.filter(|i| !visited[*i]) {
+            visited[i] = true;
+            let (_, end, midpoint) = segments[i];
+            contour.push(midpoint);
+            current = outgoing.get(&end).copied();
+        }
+        // If we stopped because we came back around, close the loop
+        if current == Some(first) {
+            contour.push(segments[first].2);
+        }
+        contours.push(contour);
+    }
+    contours
+}
@@ -0,0 +1,92 @@
+use crate::{
+    util::unit::{Meter, Meter2},
+    world::{
+        hex::{Cluster, HasHexPosition, TilePoint, TilePointMap},
+        tile::Tile,
+        World,
+    },
+};
+
+/// A contiguous cluster of land tiles in a world. Any two land tiles that are
+/// adjacent belong to the same continent, so islands are just small
+/// continents. See [World::continents].
+#[derive(Clone, Debug)]
+pub struct Continent<'a> {
+    /// Always non-empty, and sorted by position
+    tiles: Vec<&'a Tile>,
+}
+
+impl<'a> Continent<'a> {
+    /// Find every continent in a world, ordered by ID
+    pub(crate) fn find_all(world: &'a World) -> Vec<Self> {
+        // Cluster over a copy of just the land flags, to avoid having to clone
+        // or mutably borrow all the tiles
+        let world_tiles = world.tiles();
+        let mut land: TilePointMap<bool> = world_tiles
+            .iter()
+            .map(|(pos, tile)| (*pos, tile.is_land_biome()))
+            .collect();
+        Cluster::predicate_wrapping(
+            &mut land,
+            world.config().wrap_radius(),
+            |is_land| *is_land,
+        )
+        .into_iter()
+        .map(move |cluster| {
+            let mut tiles: Vec<&Tile> = cluster
+                .tiles()
+                .keys()
+                .map(move |pos| &world_tiles[pos])
+                .collect();
+            tiles.sort_by_key(|tile| tile.position());
+            Self { tiles }
+        })
+        .collect()
+    }
+
+    /// A stable identifier for this continent, which is the position of its
+    /// lowest tile (by position ordering). This doesn't depend on the order
+    /// that continents are found in, so it can be used to match up continents
+    /// between calls.
+    pub fn id(&self) -> TilePoint {
+        self.tiles[0].position()
+    }
+
+    /// All the tiles in this continent, sorted by position
+    pub fn tiles(&self) -> &[&'a Tile] {
+        &self.tiles
+    }
+
+    /// Total surface area of this continent
+    pub fn area(&self) -> Meter2 {
+        Tile::AREA * self.tiles.len() as f64
+    }
+
+    /// Total length of this continent's coastline, i.e. the number of tile
+    /// sides shared between one of its tiles and an adjacent water tile,
+    /// times [Tile::SIDE_LENGTH]. Sides along the edge of an unwrapped world
+    /// don't count, since there's no water on the other side. The world must
+    /// be the one this continent came from.
+    pub fn coastline_length(&self, world: &World) -> Meter {
+        let wrap_radius = world.config().wrap_radius();
+        let world_tiles = world.tiles();
+        let coast_sides = self
+            .tiles
+            .iter()
+            .flat_map(|tile| tile.position().adjacents_wrapping(wrap_radius))
+            .filter(|adj_pos| {
+                world_tiles
+                    .get(adj_pos)
+                    .map_or(false, |adj_tile| adj_tile.is_water_biome())
+            })
+            .count();
+        Tile::SIDE_LENGTH * coast_sides as f64
+    }
+
+    /// Does this continent include the tile at the given position?
+    pub fn contains(&self, position: TilePoint) -> bool {
+        self.tiles
+            .binary_search_by_key(&position, |tile| tile.position())
+            .is_ok()
+    }
+}
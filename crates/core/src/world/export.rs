@@ -0,0 +1,145 @@
+use crate::{
+    util::unit::{Celsius, Meter, Meter3},
+    world::{
+        hex::{TileDirectionValues, TilePoint},
+        tile::Tile,
+        Biome, DrainageDestination, GeoFeature, TerrainClass, WatershedId,
+        World, WorldMeta,
+    },
+    WorldConfig,
+};
+use serde::Serialize;
+
+/// Options to control which tile fields are included when exporting a world
+/// to JSON with [World::to_json_with]. Position, elevation, and biome are
+/// always included. Leaving out fields can make the output **much** smaller,
+/// which is handy for integrations that don't need the full tile data. The
+/// default includes everything, which gives the same tile data as
+/// [World::to_json].
+///
+/// **Note:** Filtered output can't be loaded back in with
+/// [World::from_json], because the fields that were left out are required.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct JsonExportOptions {
+    /// Include each tile's rainfall
+    pub include_rainfall: bool,
+
+    /// Include each tile's temperature, humidity, and fertility
+    pub include_climate: bool,
+
+    /// Include each tile's runoff, runoff traversal, drainage, and watershed
+    pub include_runoff: bool,
+
+    /// Include each tile's geographic features, terrain class, and coast
+    /// distance
+    pub include_features: bool,
+}
+
+impl JsonExportOptions {
+    /// Options that leave out every optional field, so that each tile only
+    /// has its position, elevation, and biome
+    pub fn minimal() -> Self {
+        Self {
+            include_rainfall: false,
+            include_climate: false,
+            include_runoff: false,
+            include_features: false,
+        }
+    }
+}
+
+impl Default for JsonExportOptions {
+    fn default() -> Self {
+        Self {
+            include_rainfall: true,
+            include_climate: true,
+            include_runoff: true,
+            include_features: true,
+        }
+    }
+}
+
+/// A filtered view of a world, for serialization. See [JsonExportOptions].
+#[derive(Serialize)]
+pub(super) struct WorldExport<'a> {
+    config: &'a WorldConfig,
+    tiles: Vec<TileExport<'a>>,
+    meta: &'a WorldMeta,
+}
+
+impl<'a> WorldExport<'a> {
+    pub fn new(world: &'a World, options: JsonExportOptions) -> Self {
+        Self {
+            config: &world.config,
+            tiles: world
+                .tiles_sorted()
+                .into_iter()
+                .map(|tile| TileExport::new(tile, options))
+                .collect(),
+            meta: &world.meta,
+        }
+    }
+}
+
+/// A filtered view of a tile, for serialization. Field names match [Tile], so
+/// that filtered output is a subset of the full output. Any field that's
+/// `None` is left out entirely.
+#[derive(Serialize)]
+struct TileExport<'a> {
+    position: TilePoint,
+    elevation: Meter,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rainfall: Option<Meter3>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<Celsius>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    humidity: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fertility: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runoff: Option<Meter3>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runoff_traversed: Option<&'a TileDirectionValues<Meter3>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drainage: Option<&'a DrainageDestination>,
+    // A tile's watershed is optional itself, so the outer option controls
+    // whether it's included, and the inner one can still serialize to null
+    #[serde(skip_serializing_if = "Option::is_none")]
+    watershed: Option<Option<WatershedId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    terrain_class: Option<TerrainClass>,
+    // Same deal as watershed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    coast_distance: Option<Option<u16>>,
+    biome: Biome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    features: Option<&'a [GeoFeature]>,
+}
+
+impl<'a> TileExport<'a> {
+    fn new(tile: &'a Tile, options: JsonExportOptions) -> Self {
+        let climate = options.include_climate;
+        let runoff = options.include_runoff;
+        let features = options.include_features;
+        // Full serialization skips traversal if it's been cleared, so do the
+        // same here
+        let runoff_traversed = Some(&tile.runoff_traversed)
+            .filter(|traversed| runoff && **traversed != Default::default());
+        Self {
+            position: tile.position,
+            elevation: tile.elevation,
+            rainfall: Some(tile.rainfall).filter(|_| options.include_rainfall),
+            temperature: Some(tile.temperature).filter(|_| climate),
+            humidity: Some(tile.humidity).filter(|_| climate),
+            fertility: Some(tile.fertility).filter(|_| climate),
+            runoff: Some(tile.runoff).filter(|_| runoff),
+            runoff_traversed,
+            drainage: Some(&tile.drainage).filter(|_| runoff),
+            watershed: Some(tile.watershed).filter(|_| runoff),
+            terrain_class: Some(tile.terrain_class).filter(|_| features),
+            coast_distance: Some(tile.coast_distance).filter(|_| features),
+            biome: tile.biome,
+            features: Some(tile.features.as_slice()).filter(|_| features),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::world::hex::{TilePoint, TilePointSet};
+use std::collections::VecDeque;
+
+/// Flood-fill outward from a starting position, and collect every position
+/// that was reached. This is a breadth-first search, so positions are visited
+/// in order of increasing cost, where the cost of a position is the minimum
+/// number of steps it takes to get there from `start` (so `start` itself has
+/// a cost of 0).
+///
+/// - `neighbors` gives the positions that are one step away from a position.
+///   Typically this is [TilePoint::adjacents] or
+///   [TilePoint::adjacents_wrapping].
+/// - `accept` decides whether the fill can spread onto a position, given its
+///   cost. Rejected positions aren't included in the output, and the fill won't
+///   spread past them. This is where you'd check that a position is actually in
+///   the world, has the right biome, etc. It can also enforce a cost limit,
+///   e.g. to only fill within some number of steps.
+///
+/// `accept` is called at most once per position. If it rejects `start`, the
+/// output is empty.
+///
+/// ```
+/// use terra::{flood_fill, TilePoint};
+///
+/// // Everything within 2 steps of the origin
+/// let filled =
+///     flood_fill(TilePoint::ORIGIN, TilePoint::adjacents, |_, cost| cost <= 2);
+/// assert_eq!(filled.len(), 19);
+/// ```
+pub fn flood_fill<I: IntoIterator<Item = TilePoint>>(
+    start: TilePoint,
+    neighbors: impl Fn(TilePoint) -> I,
+    accept: impl Fn(&TilePoint, usize) -> bool,
+) -> TilePointSet {
+    let mut filled = TilePointSet::default();
+    // Everything that's been passed to accept, so we never check twice
+    let mut visited = TilePointSet::default();
+    let mut queue = VecDeque::new();
+
+    visited.insert(start);
+    if accept(&start, 0) {
+        filled.insert(start);
+        queue.push_back((start, 0));
+    }
+
+    while let Some((pos, cost)) = queue.pop_front() {
+        for neighbor in neighbors(pos) {
+            if visited.insert(neighbor) && accept(&neighbor, cost + 1) {
+                filled.insert(neighbor);
+                queue.push_back((neighbor, cost + 1));
+            }
+        }
+    }
+
+    filled
+}
@@ -1,11 +1,24 @@
 use crate::{
-    util::{cmp_unwrap, range::NumRange, unit::Meter},
+    util::{
+        cmp_unwrap,
+        range::NumRange,
+        unit::{Celsius, Meter},
+    },
     world::{
-        generate::{Generate, WorldBuilder},
+        coast,
+        generate::{Generate, TileBuilder, WorldBuilder},
+        hex::{HasHexPosition, HexDirection, TileDirection, TilePointMap},
         Biome, World,
     },
+    BiomeConfig,
 };
 
+/// Land tiles below this humidity count as dry for
+/// [BiomeConfig::desert_min_coast_distance]. This is around the humidity of
+/// the driest non-desert benchmark points, so it catches arid tiles that
+/// happen to sit closer to a plains or alpine point than a desert one.
+const DESERT_MAX_HUMIDITY: f64 = 0.25;
+
 /// A benchmark point that defines what biome to use for a particular elevation
 /// and humidity. Elevation is first, humidity is second.
 struct BiomePoint(Biome, Meter, f64);
@@ -36,6 +49,62 @@ const POINTS: &[BiomePoint] = &[
     BiomePoint(Biome::Jungle, Meter(0.25), 0.85),
 ];
 
+/// Find the biome whose benchmark point is nearest to the given elevation and
+/// humidity. See [BiomeGenerator] for a description of the mapping.
+fn nearest_biome(elevation: Meter, humidity: f64) -> Biome {
+    // We're going to normalize all the elevations so we can use a consistent
+    // set of coefficients below. We don't want to map from the full range
+    // though, because 99% of the tiles below sea level we won't be touching
+    // (since they're already set to ocean). So map from just above-sea-level
+    // elevations. We may end up with a few tiles outside the target range of
+    // [0,1], but that's fine because the logic will still give them a biome
+    // of some sort.
+    let elev_input_range =
+        NumRange::new(World::SEA_LEVEL, World::ELEVATION_RANGE.max);
+    let elevation_norm = elev_input_range.normalize(elevation);
+
+    // Do a naive search to find the nearest point. This is O(n) which isn't
+    // particularly efficient, but since the number of points is pretty low,
+    // it's fine.
+    let (biome, _) = POINTS
+        .iter()
+        .map(|p| (p.0, p.distance_to(elevation_norm, humidity)))
+        .min_by(|(_, d_a), (_, d_b)| cmp_unwrap(d_a, d_b))
+        .unwrap(); // safe because we know POINTS is never empty
+    biome
+}
+
+impl BiomeConfig {
+    /// Get the biome that a land tile with the given properties would be
+    /// assigned during generation. This doesn't require generating a world,
+    /// so it's handy for previews like a live Whittaker diagram.
+    ///
+    /// `temperature` is only used for polar ice caps: pass the tile's
+    /// temperature if it's at or beyond [Self::ice_cap_latitude], and `None`
+    /// otherwise. Some parts of generation can't be captured by a single
+    /// tile, so they aren't accounted for here: [Biome::Ocean] and
+    /// [Biome::Coast] depend on whether the tile is connected to the ocean,
+    /// so this always gives a land biome, and [Self::smoothing] depends on
+    /// the tile's neighbors.
+    pub fn classify(
+        &self,
+        elevation: Meter,
+        humidity: f64,
+        temperature: Option<Celsius>,
+    ) -> Biome {
+        let above_snow_line = self
+            .snow_line
+            .map_or(false, |snow_line| elevation > snow_line);
+        let frozen = temperature
+            .map_or(false, |temperature| temperature <= World::FREEZING_POINT);
+        if above_snow_line || frozen {
+            Biome::Snow
+        } else {
+            nearest_biome(elevation, humidity)
+        }
+    }
+}
+
 /// Generate a biome for every tile that doesn't already have one. Biomes are
 /// defined based on elevation and humidity. In order to define the mapping,
 /// we turn each tile's elevation and humidity into a 2D point. Then we have a
@@ -46,15 +115,7 @@ pub struct BiomeGenerator;
 
 impl Generate for BiomeGenerator {
     fn generate(&self, world: &mut WorldBuilder) {
-        // We're going to normalize all the elevations so we can use a
-        // consistent set of coefficients below. We don't want to map from the
-        // full range though, because 99% of the tiles below sea level we won't
-        // be touching (since they're already set to ocean). So map from just
-        // above-sea-level elevations. We may end up with a few tiles outside
-        // the target range of [0,1], but that's fine because the logic will
-        // still give them a biome of some sort.
-        let elev_input_range =
-            NumRange::new(World::SEA_LEVEL, World::ELEVATION_RANGE.max);
+        let biome_config = world.config.biome;
 
         // Set the biome for each tile, but don't overwrite any existing biomes
         for tile in world
@@ -62,20 +123,188 @@ impl Generate for BiomeGenerator {
             .values_mut()
             .filter(|tile| tile.biome_opt().is_none())
         {
-            // Normalize these values so we don't have to update this code when
-            // we change the elevation/humidity range bounds
-            let elevation_norm = elev_input_range.normalize(tile.elevation());
-            let humidity = tile.humidity();
-
-            // Do a naive search to find the nearest point. This is O(n) which
-            // isn't particularly efficient, but since the number of points is
-            // pretty low, it's fine.
-            let (biome, _) = POINTS
-                .iter()
-                .map(|p| (p.0, p.distance_to(elevation_norm, humidity)))
-                .min_by(|(_, d_a), (_, d_b)| cmp_unwrap(d_a, d_b))
-                .unwrap(); // safe because we know POINTS is never empty
-            tile.set_biome(biome);
+            tile.set_biome(nearest_biome(tile.elevation(), tile.humidity()));
+        }
+
+        // Dry out continental interiors. At this point the only water
+        // biomes are from the ocean generator, so this matches the coast
+        // distance on the finished tiles.
+        if let Some(min_distance) = biome_config.desert_min_coast_distance {
+            let distances = coast::water_distances(
+                &world.tiles,
+                TileBuilder::is_water_biome,
+                world.config.wrap_radius(),
+            );
+            for (pos, distance) in distances {
+                let tile = world.tiles.get_mut(&pos).unwrap();
+                if distance > min_distance
+                    && tile.humidity() < DESERT_MAX_HUMIDITY
+                {
+                    tile.set_biome(Biome::Desert);
+                }
+            }
+        }
+
+        if biome_config.smoothing {
+            smooth_biomes(&mut world.tiles, world.config.wrap_radius());
+        }
+
+        // The snow line trumps everything else. This has to match
+        // BiomeConfig::classify
+        if let Some(snow_line) = biome_config.snow_line {
+            for tile in world.tiles.values_mut().filter(|tile| {
+                !tile.is_water_biome() && tile.elevation() > snow_line
+            }) {
+                tile.set_biome(Biome::Snow);
+            }
+        }
+    }
+}
+
+/// Reassign every isolated land tile, i.e. one whose biome doesn't match any
+/// of its land neighbors, to the most common biome among itself and its land
+/// neighbors. Ties go to the tile's own biome first, then to whichever biome
+/// comes first going clockwise around the tile. A tile whose neighbors are all
+/// different from each other keeps its biome. Every reassignment is based on
+/// the biomes from *before* smoothing, so the order tiles are visited in
+/// doesn't matter.
+fn smooth_biomes(
+    tiles: &mut TilePointMap<TileBuilder>,
+    wrap_radius: Option<u16>,
+) {
+    let mut reassignments = Vec::new();
+    for tile in tiles.values().filter(|tile| !tile.is_water_biome()) {
+        let biome = tile.biome();
+        let neighbor_biomes: Vec<Biome> = TileDirection::CLOCKWISE
+            .iter()
+            .filter_map(|dir| {
+                tiles.get(&tile.position().adjacent_wrapping(*dir, wrap_radius))
+            })
+            .filter(|adj_tile| !adj_tile.is_water_biome())
+            .map(TileBuilder::biome)
+            .collect();
+        if neighbor_biomes.is_empty() || neighbor_biomes.contains(&biome) {
+            continue;
+        }
+
+        // Count up each biome, keeping the first-seen order for tie breaking.
+        // The tile's own biome goes first with a count of 1.
+        let mut counts: Vec<(Biome, usize)> = vec![(biome, 1)];
+        for neighbor_biome in neighbor_biomes {
+            match counts.iter_mut().find(|(b, _)| *b == neighbor_biome) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((neighbor_biome, 1)),
+            }
+        }
+        // max_by_key returns the *last* max, so reverse to favor the first
+        let (majority, _) = counts
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .unwrap(); // safe because counts always has the tile's own biome
+        if majority != biome {
+            reassignments.push((tile.position(), majority));
+        }
+    }
+
+    for (pos, biome) in reassignments {
+        tiles.get_mut(&pos).unwrap().set_biome(biome);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TilePoint, WorldConfig};
+
+    #[test]
+    fn test_smooth_biomes() {
+        // A lone desert tile in the middle of a forest, with one ocean
+        // neighbor and a lone jungle tile off to the side
+        let center = TilePoint::ORIGIN;
+        let ocean_pos = center.adjacent(TileDirection::E);
+        let jungle_pos = TilePoint::new_xy(-3, 0);
+        let mut tiles: TilePointMap<TileBuilder> = TilePointMap::default();
+        for radius in 0..=4 {
+            for pos in center.ring(radius) {
+                let biome = if pos == center {
+                    Biome::Desert
+                } else if pos == ocean_pos {
+                    Biome::Ocean
+                } else if pos == jungle_pos {
+                    Biome::Jungle
+                } else {
+                    Biome::Forest
+                };
+                let mut tile = TileBuilder::new(pos);
+                tile.set_biome(biome);
+                tiles.insert(pos, tile);
+            }
+        }
+
+        smooth_biomes(&mut tiles, None);
+        assert_eq!(tiles[&center].biome(), Biome::Forest);
+        assert_eq!(tiles[&jungle_pos].biome(), Biome::Forest);
+        // Water is exempt, even though it's surrounded by forest now
+        assert_eq!(tiles[&ocean_pos].biome(), Biome::Ocean);
+        assert!(tiles
+            .iter()
+            .filter(|(pos, _)| **pos != ocean_pos)
+            .all(|(_, tile)| tile.biome() == Biome::Forest));
+    }
+
+    /// Dry tiles far from the ocean should turn into desert, but dry tiles on
+    /// the coast and wet tiles in the interior should be left alone
+    #[test]
+    fn test_desert_min_coast_distance() {
+        let generate = |desert_min_coast_distance| {
+            let config = WorldConfig {
+                radius: 10,
+                biome: BiomeConfig {
+                    desert_min_coast_distance,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut world = WorldBuilder::new(&config);
+            // An island surrounded by a ring of ocean. Everything is arid
+            // plains, except for a wet spot in the middle.
+            for tile in world.tiles.values_mut() {
+                let pos = tile.position();
+                let d = pos.distance_to(TilePoint::ORIGIN);
+                if d == 10 {
+                    tile.set_biome(Biome::Ocean);
+                } else {
+                    tile.set_elevation(Meter(20.0));
+                    tile.set_humidity(if d == 0 { 0.6 } else { 0.2 });
+                }
+            }
+            BiomeGenerator.generate(&mut world);
+            world
+                .tiles
+                .values()
+                .map(|tile| (tile.position(), tile.biome()))
+                .collect::<TilePointMap<Biome>>()
+        };
+
+        let before = generate(None);
+        assert_eq!(before[&TilePoint::ORIGIN], Biome::Forest);
+        assert!(before.values().all(|biome| matches!(
+            biome,
+            Biome::Ocean | Biome::Plains | Biome::Forest
+        )));
+
+        // The outermost ring of land is 0 tiles from the coast, so with a
+        // min distance of 3, the dry tiles in ring 5 and inward should turn
+        // into desert
+        let after = generate(Some(3));
+        for (pos, biome) in &after {
+            let expected = match pos.distance_to(TilePoint::ORIGIN) {
+                0 => Biome::Forest,
+                1..=5 => Biome::Desert,
+                _ => before[pos],
+            };
+            assert_eq!(*biome, expected, "{pos:?}");
         }
     }
 }
@@ -1,10 +1,41 @@
 use crate::{
+    config::NoiseFnType,
     util::{self, range::NumRange, unit::Meter},
     world::{
         generate::{noise::TileNoiseFn, Generate, WorldBuilder},
-        hex::{HasHexPosition, TilePoint},
+        hex::{HasHexPosition, TilePointMap},
         World,
     },
+    NoiseFnConfig,
+};
+
+/// How far below sea level the outermost ring of tiles is pushed, if
+/// [ElevationConfig::force_ocean_border](crate::ElevationConfig::force_ocean_border)
+/// is enabled
+const OCEAN_BORDER_DEPTH: Meter = Meter(1.0);
+
+/// Radius of the guaranteed landmass around the origin, as a fraction of the
+/// world radius, if
+/// [ElevationConfig::guarantee_center_land](crate::ElevationConfig::guarantee_center_land)
+/// is enabled. Every tile within this distance is above sea level.
+const CENTER_LAND_RADIUS_FRACTION: f64 = 0.1;
+
+/// Height above sea level of the peak of the guaranteed landmass at the
+/// origin. This only needs to be enough to keep the landmass dry, so it's
+/// kept low to avoid planting a mountain in the middle of every world.
+const CENTER_LAND_PEAK_HEIGHT: Meter = Meter(10.0);
+
+/// Noise function used to place mountain ranges, if
+/// [ElevationConfig::ridge_strength](crate::ElevationConfig::ridge_strength)
+/// is enabled. Ridged noise peaks along thin lines, and the high exponent
+/// squashes everything but the crests of those lines down to nothing.
+const RIDGE_NOISE_FN: NoiseFnConfig = NoiseFnConfig {
+    noise_type: NoiseFnType::RidgedMulti,
+    octaves: 3,
+    frequency: 1.0,
+    lacunarity: 2.0,
+    persistence: 0.5,
+    exponent: 3.0,
 };
 
 /// Generate an elevation map using a noise function.
@@ -14,6 +45,7 @@ pub struct ElevationGenerator;
 impl Generate for ElevationGenerator {
     fn generate(&self, world: &mut WorldBuilder) {
         let elev_config = world.config.elevation;
+        let center = world.config.center;
         let noise_fn: TileNoiseFn<Meter> = TileNoiseFn::new(
             &mut world.rng,
             elev_config.noise_fn,
@@ -23,18 +55,70 @@ impl Generate for ElevationGenerator {
         // Buffer size is given as a fraction of the total radius, we need
         // to convert that to a [start,stop] range
         let radius = world.config.radius as f64;
-        let buffer_size = (radius * elev_config.edge_buffer_fraction).round();
+        let buffer_size = world.config.edge_buffer_size() as f64;
         // +1 because the lower bound is inclusive
         let buffer_range = NumRange::new(radius - buffer_size + 1.0, radius);
+        // Same deal for the ocean border, it only applies if there's an edge
+        let force_ocean_border =
+            elev_config.force_ocean_border && !world.config.wrap;
+        // Always guarantee at least the origin and its neighbors
+        let center_land_radius =
+            (radius * CENTER_LAND_RADIUS_FRACTION).ceil().max(1.0);
+
+        // Sample the noise function for every tile up front, so that
+        // smoothing can look at each tile's neighbors
+        let mut noise_values: TilePointMap<Meter> = world
+            .tiles
+            .keys()
+            .map(|pos| (*pos, noise_fn.get_offset(*pos, center).inner()))
+            .collect();
+        // Raise up mountain ranges along the ridges of a second noise fn. Only
+        // create the function if we need it, so that the RNG stream is
+        // untouched when ranges are disabled
+        if elev_config.ridge_strength > 0.0 {
+            let ridge_fn: TileNoiseFn<f64> = TileNoiseFn::new(
+                &mut world.rng,
+                RIDGE_NOISE_FN,
+                NumRange::new(0.0, 1.0),
+            );
+            let ridge_height =
+                World::ELEVATION_RANGE.span() * elev_config.ridge_strength;
+            for (pos, value) in noise_values.iter_mut() {
+                let ridge = ridge_fn.get_offset(*pos, center).inner();
+                *value =
+                    World::ELEVATION_RANGE.clamp(*value + ridge_height * ridge);
+            }
+        }
+        if elev_config.elevation_offset != Meter(0.0) {
+            for value in noise_values.values_mut() {
+                *value = World::ELEVATION_RANGE
+                    .clamp(*value + elev_config.elevation_offset);
+            }
+        }
+        for _ in 0..elev_config.smoothing_passes {
+            noise_values = smooth(&noise_values, world.config.wrap_radius());
+        }
+        world.capture_layer("raw_elevation", |_| {
+            noise_values
+                .iter()
+                .map(|(pos, elevation)| (*pos, elevation.0))
+                .collect()
+        });
 
         for tile in world.tiles.values_mut() {
             let pos = tile.position();
-            let d = pos.distance_to(TilePoint::ORIGIN) as f64;
+            // Measure from the logical center, so chunks of a bigger map
+            // agree with each other. Anything past the edge gets treated as
+            // part of the outermost ring.
+            let d = world.config.center_distance(pos) as f64;
+            let buffer_d = d.min(radius);
 
             // Determine the range of potential elevation outputs for this tile.
             // For most tiles it's static, but for some the edge buffer will
             // restrict that range
-            let elev_range: NumRange<Meter, f64> = if buffer_range.contains(d) {
+            let elev_range: NumRange<Meter, f64> = if buffer_range
+                .contains(buffer_d)
+            {
                 // This tile is near the edge of the world, so we want to push
                 // it down a bit. The further out it is, the more we push it.
 
@@ -46,7 +130,7 @@ impl Generate for ElevationGenerator {
                     // Convert the value to a fraction representing its distance
                     // from the outermost edge. 0 will be the outermost ring,
                     // 1 will be the innermost ring **of the buffer**
-                    .value(d)
+                    .value(buffer_d)
                     .normalize()
                     .invert()
                     // Apply exponent curve
@@ -71,8 +155,8 @@ impl Generate for ElevationGenerator {
             // This noise value will span the elevation range (ish)
             // TODO https://github.com/LucasPickering/terra-rs/issues/19
             // Figure out why values aren't spanning the full elevation range
-            let elevation: Meter = noise_fn
-                .get(pos)
+            let elevation: Meter = World::ELEVATION_RANGE
+                .value(noise_values[&pos])
                 // Map to our output range which may be compressed by the buffer
                 .map_to(elev_range)
                 // Round to nearest multiple of the specified interval (if any)
@@ -83,7 +167,73 @@ impl Generate for ElevationGenerator {
                     None => val,
                 })
                 .inner();
+            // Raise up the center of the world to a cone, which hits sea
+            // level one ring past the guaranteed radius
+            let elevation = if elev_config.guarantee_center_land
+                && d <= center_land_radius
+            {
+                let cone = NumRange::new(0.0, center_land_radius + 1.0)
+                    .value(d)
+                    .normalize()
+                    .invert()
+                    .convert::<Meter>()
+                    .map_to(NumRange::new(
+                        World::SEA_LEVEL,
+                        World::SEA_LEVEL + CENTER_LAND_PEAK_HEIGHT,
+                    ))
+                    .inner();
+                if cone > elevation {
+                    cone
+                } else {
+                    elevation
+                }
+            } else {
+                elevation
+            };
+            // Sink the outermost ring below sea level, so the world is always
+            // surrounded by water
+            let border_elevation = World::SEA_LEVEL - OCEAN_BORDER_DEPTH;
+            let elevation = if force_ocean_border
+                && d >= radius
+                && elevation > border_elevation
+            {
+                border_elevation
+            } else {
+                elevation
+            };
+            // Flatten out anything that's deeper than the ocean floor
+            let elevation = match elev_config.ocean_floor {
+                Some(floor)
+                    if elevation < World::SEA_LEVEL && elevation < floor =>
+                {
+                    floor
+                }
+                _ => elevation,
+            };
             tile.set_elevation(elevation);
         }
     }
 }
+
+/// Apply one pass of a low-pass filter, where each value is replaced by the
+/// average of itself and its neighbors. Tiles on the edge of the world just
+/// average with whatever neighbors they have, unless the world wraps (see
+/// [TilePoint::wrap](crate::TilePoint::wrap)). Since each output is an average
+/// of input values, the output is guaranteed to stay within the input range.
+fn smooth(
+    values: &TilePointMap<Meter>,
+    wrap_radius: Option<u16>,
+) -> TilePointMap<Meter> {
+    values
+        .iter()
+        .map(|(pos, value)| {
+            let (sum, count) = pos
+                .adjacents_wrapping(wrap_radius)
+                .filter_map(|adj_pos| values.get(&adj_pos))
+                .fold((*value, 1), |(sum, count), adj_value| {
+                    (sum + *adj_value, count + 1)
+                });
+            (*pos, sum / count as f64)
+        })
+        .collect()
+}
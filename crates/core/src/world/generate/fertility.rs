@@ -0,0 +1,111 @@
+use crate::{
+    util::{range::NumRange, unit::Celsius},
+    world::{
+        generate::{Generate, WorldBuilder},
+        hex::{HasHexPosition, TilePointMap},
+        Meter,
+    },
+};
+
+/// The temperature at which land is most fertile
+const OPTIMAL_TEMPERATURE: Celsius = Celsius(20.0);
+/// How far the temperature can stray from [OPTIMAL_TEMPERATURE] before land
+/// becomes completely infertile
+const TEMPERATURE_TOLERANCE: f64 = 20.0;
+/// The slope (max elevation difference to any land neighbor) at which land
+/// becomes completely infertile. Anything flatter scales linearly up to full
+/// fertility on totally flat land.
+const MAX_FERTILE_SLOPE: Meter = Meter(10.0);
+
+/// Generate a fertility score for each tile, in `[0, 1]`. Fertility combines
+/// humidity, temperature and local slope: wet, temperate, flat land is the
+/// most fertile, while dry, cold/hot or steep land scores low. Water tiles
+/// always have 0 fertility. This has to run **after humidity, temperature and
+/// biome generation**.
+#[derive(Debug)]
+pub struct FertilityGenerator;
+
+impl Generate for FertilityGenerator {
+    fn generate(&self, world: &mut WorldBuilder) {
+        let wrap_radius = world.config.wrap_radius();
+
+        // Slope needs to look at neighbors, so calculate everything up front
+        // then apply it in a second pass
+        let fertilities: TilePointMap<f64> = world
+            .tiles
+            .values()
+            .map(|tile| {
+                let pos = tile.position();
+                if tile.is_water_biome() {
+                    return (pos, 0.0);
+                }
+
+                // Slope is the steepest drop/rise to any land neighbor. Water
+                // neighbors don't count, so coastal land isn't penalized
+                let slope = pos
+                    .adjacents_wrapping(wrap_radius)
+                    .filter_map(|adj_pos| world.tiles.get(&adj_pos))
+                    .filter(|adj_tile| !adj_tile.is_water_biome())
+                    .map(|adj_tile| {
+                        (tile.elevation() - adj_tile.elevation()).0.abs()
+                    })
+                    .fold(0.0, f64::max);
+
+                (
+                    pos,
+                    calc_fertility(
+                        tile.humidity(),
+                        tile.temperature(),
+                        Meter(slope),
+                    ),
+                )
+            })
+            .collect();
+
+        for tile in world.tiles.values_mut() {
+            tile.set_fertility(fertilities[&tile.position()]);
+        }
+    }
+}
+
+/// Calculate fertility for a single land tile. Each input is mapped to a
+/// factor in `[0, 1]`, and the factors are multiplied together so that any
+/// single bad trait (e.g. being a desert) makes the land infertile.
+fn calc_fertility(humidity: f64, temperature: Celsius, slope: Meter) -> f64 {
+    let unit_range = NumRange::new(0.0, 1.0);
+    let temperature_factor = unit_range.clamp(
+        1.0 - (temperature - OPTIMAL_TEMPERATURE).0.abs()
+            / TEMPERATURE_TOLERANCE,
+    );
+    let slope_factor = unit_range.clamp(1.0 - slope.0 / MAX_FERTILE_SLOPE.0);
+    let fertility =
+        unit_range.clamp(humidity) * temperature_factor * slope_factor;
+    // Sanity check
+    debug_assert!(unit_range.contains(fertility));
+    fertility
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::World;
+
+    #[test]
+    fn test_calc_fertility() {
+        // Wet, temperate and flat is as good as it gets
+        let ideal = calc_fertility(1.0, OPTIMAL_TEMPERATURE, Meter(0.0));
+        assert_eq!(ideal, 1.0);
+
+        let temperate_wet_flat = calc_fertility(0.8, Celsius(18.0), Meter(0.5));
+        let steep_desert = calc_fertility(0.05, Celsius(35.0), Meter(8.0));
+        assert!(
+            temperate_wet_flat > steep_desert,
+            "{temperate_wet_flat} <= {steep_desert}"
+        );
+
+        // Frozen, bone dry, or cliff-like land is totally infertile
+        assert_eq!(calc_fertility(1.0, World::FREEZING_POINT, Meter(0.0)), 0.0);
+        assert_eq!(calc_fertility(0.0, OPTIMAL_TEMPERATURE, Meter(0.0)), 0.0);
+        assert_eq!(calc_fertility(1.0, OPTIMAL_TEMPERATURE, Meter(50.0)), 0.0);
+    }
+}
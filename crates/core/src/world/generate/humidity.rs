@@ -0,0 +1,25 @@
+use crate::world::{
+    generate::{Generate, WorldBuilder},
+    World,
+};
+
+/// Generate humidity for each tile. Currently humidity is derived purely from
+/// rainfall, by mapping it through [World::RAINFALL_SOFT_RANGE], but it's
+/// stored separately so that it can diverge from rainfall in the future (e.g.
+/// coastal humidity or fog). This has to run **after rainfall generation**.
+#[derive(Debug)]
+pub struct HumidityGenerator;
+
+impl Generate for HumidityGenerator {
+    fn generate(&self, world: &mut WorldBuilder) {
+        for tile in world.tiles.values_mut() {
+            let humidity = World::RAINFALL_SOFT_RANGE
+                .value(tile.rainfall())
+                .clamp()
+                .convert::<f64>()
+                .normalize()
+                .inner();
+            tile.set_humidity(humidity);
+        }
+    }
+}
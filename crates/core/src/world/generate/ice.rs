@@ -0,0 +1,31 @@
+use crate::world::{
+    generate::{Generate, WorldBuilder},
+    hex::HasHexPosition,
+    latitude, Biome, GeoFeature, World,
+};
+
+/// Generate polar ice caps. Any tile beyond the configured ice cap latitude
+/// that's at or below freezing gets the [GeoFeature::Ice] feature, and land
+/// tiles with ice are converted to [Biome::Snow]. This has to run **after
+/// temperature and biome generation**.
+#[derive(Debug)]
+pub struct IceGenerator;
+
+impl Generate for IceGenerator {
+    fn generate(&self, world: &mut WorldBuilder) {
+        let ice_cap_latitude = world.config.biome.ice_cap_latitude;
+        let radius = world.config.radius;
+
+        for tile in world.tiles.values_mut() {
+            let abs_latitude = latitude(tile.position(), radius).abs();
+            if abs_latitude >= ice_cap_latitude
+                && tile.temperature() <= World::FREEZING_POINT
+            {
+                tile.add_feature(GeoFeature::Ice);
+                if !tile.is_water_biome() {
+                    tile.set_biome(Biome::Snow);
+                }
+            }
+        }
+    }
+}
@@ -1,22 +1,34 @@
 mod biome;
 mod elevation;
+mod fertility;
+mod humidity;
+mod ice;
 mod noise;
 mod ocean;
 mod rainfall;
 mod runoff;
+mod temperature;
 mod water_feature;
 mod wind;
 
 use crate::{
     timed, unwrap,
-    util::{self, unit::Meter3},
+    util::{
+        self,
+        unit::{Celsius, Meter3},
+    },
     world::{
+        coast,
         generate::{
             biome::BiomeGenerator,
             elevation::ElevationGenerator,
+            fertility::FertilityGenerator,
+            humidity::HumidityGenerator,
+            ice::IceGenerator,
             ocean::OceanGenerator,
             rainfall::RainfallGenerator,
             runoff::{RunoffGenerator, RunoffPattern},
+            temperature::TemperatureGenerator,
             water_feature::WaterFeatureGenerator,
             wind::WindGenerator,
         },
@@ -24,14 +36,37 @@ use crate::{
             HasHexPosition, HexAxialDirection, TileDirection, TileDirectionMap,
             TilePoint, TilePointMap,
         },
-        Biome, BiomeType, GeoFeature, Meter, Tile, World, WorldConfig,
+        terrain, watershed, Biome, BiomeType, GeoFeature, Meter, TerrainClass,
+        Tile, World, WorldConfig,
     },
 };
+use anyhow::anyhow;
 use fnv::FnvBuildHasher;
 use log::info;
-use rand::SeedableRng;
+use rand::{RngCore, SeedableRng};
 use rand_pcg::Pcg64;
-use std::{cmp, fmt::Debug};
+use std::{
+    cmp,
+    collections::HashMap,
+    fmt::Debug,
+    mem,
+    panic::{self, AssertUnwindSafe},
+};
+
+pub use self::noise::TileNoiseFn;
+
+/// Approximate number of bytes of memory used for each tile at the peak of
+/// generation. The peak is when the builder tiles are converted to finished
+/// tiles, since both maps are in memory at once. This counts the inline size
+/// of each map entry, plus slack for the map's unused capacity. Heap data
+/// (e.g. geographic features) and intermediate generation data (e.g. runoff
+/// basins) aren't counted, so this is a lower bound.
+pub(crate) const BYTES_PER_TILE: usize = {
+    let builder_entry = mem::size_of::<(TilePoint, TileBuilder)>();
+    let tile_entry = mem::size_of::<(TilePoint, Tile)>();
+    // Hash maps keep at least 1/8 of their capacity empty
+    (builder_entry + tile_entry) * 8 / 7
+};
 
 /// A container for generating a new world. This applies a series of generators
 /// in sequence to create the world. These fields are public to allow for
@@ -53,8 +88,10 @@ pub struct WorldBuilder<'a> {
     /// mutate the config**.
     pub config: &'a WorldConfig,
 
-    /// RNG provider.
-    pub rng: Pcg64,
+    /// RNG provider. Callers can supply their own RNG source (see
+    /// [Self::with_rng]) without every generator having to be generic over
+    /// it. See [BuilderRng].
+    pub rng: BuilderRng<'a>,
 
     /// All the tiles in the world. These individual tiles will be mutated
     /// during world generation, but tiles can never be added/removed/moved!
@@ -63,12 +100,42 @@ pub struct WorldBuilder<'a> {
     /// Direction of the world's prevailing wind. Initialized by
     /// [WindGenerator], and is guaranteed to be populated after that.
     pub wind_direction: Option<HexAxialDirection>,
+
+    /// Snapshots of intermediate data, keyed by layer name. Only populated
+    /// if [WorldConfig::capture_layers] is enabled. See [Self::capture_layer].
+    pub layers: HashMap<String, TilePointMap<f64>>,
+}
+
+/// Everything produced by [WorldBuilder::generate_world], to be assembled into
+/// a [World]
+#[derive(Debug)]
+pub struct GeneratedWorld {
+    pub tiles: TilePointMap<Tile>,
+    pub wind_direction: HexAxialDirection,
+    pub layers: HashMap<String, TilePointMap<f64>>,
+    /// RNG state from just before the climate steps (everything after
+    /// oceans), so they can be re-run later with the same random draws. See
+    /// [Self::rng_state].
+    pub climate_rng: Option<Pcg64>,
 }
 
 impl<'a> WorldBuilder<'a> {
-    /// Initialize a builder that will construct a new world. **This assumes
-    /// that the given config is already validated!**
+    /// Initialize a builder that will construct a new world. The RNG is
+    /// seeded from the config's seed. **This assumes that the given config is
+    /// already validated!**
     pub fn new(config: &'a WorldConfig) -> Self {
+        let rng = Pcg64::seed_from_u64(config.seed.to_u64());
+        Self::with_builder_rng(config, BuilderRng::Pcg(rng))
+    }
+
+    /// Initialize a builder that will construct a new world, pulling all
+    /// randomness from the given RNG instead of the config's seed. **This
+    /// assumes that the given config is already validated!**
+    pub fn with_rng(config: &'a WorldConfig, rng: impl RngCore + 'a) -> Self {
+        Self::with_builder_rng(config, BuilderRng::Custom(Box::new(rng)))
+    }
+
+    fn with_builder_rng(config: &'a WorldConfig, rng: BuilderRng<'a>) -> Self {
         // Initialize each tile
         let tiles = timed!("World initialization", {
             let capacity = util::world_len(config.radius);
@@ -99,40 +166,231 @@ impl<'a> WorldBuilder<'a> {
         info!("Initialized world with {} tiles", tiles.len());
         Self {
             config,
-            rng: Pcg64::seed_from_u64(config.seed.to_u64()),
+            rng,
             tiles,
             wind_direction: None,
+            layers: HashMap::new(),
+        }
+    }
+
+    /// Capture the current state of the RNG, so that generation can be
+    /// checkpointed between steps and resumed later with
+    /// [Self::set_rng_state]. [Self::generate_world] uses this to checkpoint
+    /// the climate steps for [World::resimulate_water]. Returns `None` if the
+    /// builder is using a custom RNG from [Self::with_rng], since its state
+    /// can't be captured.
+    pub fn rng_state(&self) -> Option<Pcg64> {
+        match &self.rng {
+            BuilderRng::Pcg(rng) => Some(rng.clone()),
+            BuilderRng::Custom(_) => None,
         }
     }
 
+    /// Restore an RNG state captured by [Self::rng_state]. This replaces the
+    /// current RNG entirely, including a custom one.
+    pub fn set_rng_state(&mut self, state: Pcg64) {
+        self.rng = BuilderRng::Pcg(state);
+    }
+
     /// Get the prevailing wind direction of this world. Panics if
     /// the wind direction hasn't be initialized yet.
     pub fn wind_direction(&self) -> HexAxialDirection {
         self.wind_direction.expect("wind direction not initialized")
     }
 
+    /// Save a snapshot of some intermediate data under the given name, so it
+    /// can be inspected on the finished world via [World::layer]. The
+    /// snapshot is only computed if [WorldConfig::capture_layers] is enabled,
+    /// so disabled captures cost nothing. Capturing the same name twice
+    /// replaces the older snapshot.
+    pub fn capture_layer(
+        &mut self,
+        name: &str,
+        layer: impl FnOnce(&Self) -> TilePointMap<f64>,
+    ) {
+        if self.config.capture_layers {
+            let layer = layer(self);
+            self.layers.insert(name.to_owned(), layer);
+        }
+    }
+
     /// Generate a world by running a series of generation steps sequentially.
-    /// Must be run from a blank slate. Outputs the finalized set of tiles.
-    pub fn generate_world(mut self) -> TilePointMap<Tile> {
+    /// Must be run from a blank slate. Outputs the finalized set of tiles,
+    /// along with the prevailing wind direction and any captured layers.
+    /// Returns an error if any generation step panics (see
+    /// [Self::apply_generator]).
+    pub fn generate_world(mut self) -> anyhow::Result<GeneratedWorld> {
         // Run each generation step. The order is very important!
-        self.apply_generator(ElevationGenerator);
-        self.apply_generator(WindGenerator);
-        self.apply_generator(OceanGenerator);
-        self.apply_generator(RainfallGenerator);
-        self.apply_generator(RunoffGenerator);
-        self.apply_generator(WaterFeatureGenerator);
-        self.apply_generator(BiomeGenerator);
-
-        // Build each tile into its final value
-        self.tiles
+        self.apply_generator(ElevationGenerator)?;
+        self.apply_generator(WindGenerator)?;
+        self.apply_generator(OceanGenerator)?;
+        let climate_rng = self.rng_state();
+        self.apply_climate_generators()?;
+        let wind_direction = self.wind_direction();
+        let layers = mem::take(&mut self.layers);
+        Ok(GeneratedWorld {
+            tiles: self.build_tiles(),
+            wind_direction,
+            layers,
+            climate_rng,
+        })
+    }
+
+    /// Re-run every generation step downstream of elevation and oceans,
+    /// starting from a finished set of tiles. Only the elevations and ocean
+    /// biomes are kept, and everything else (temperature, rainfall, runoff,
+    /// features, land biomes, etc.) is generated from scratch. The given tiles
+    /// replace the builder's blank ones, so this works on partial worlds too.
+    /// Outputs the finalized set of tiles. To get the same random draws as the
+    /// original generation, restore [GeneratedWorld::climate_rng] with
+    /// [Self::set_rng_state] first. See [World::resimulate_water].
+    pub fn resimulate_water(
+        mut self,
+        tiles: &TilePointMap<Tile>,
+        wind_direction: HexAxialDirection,
+    ) -> anyhow::Result<TilePointMap<Tile>> {
+        self.tiles = tiles
+            .iter()
+            .map(|(pos, tile)| {
+                let mut builder = TileBuilder::new(*pos);
+                builder.set_elevation(tile.elevation());
+                // Same as what the ocean generator would do
+                if tile.is_water_biome() {
+                    builder.set_biome(tile.biome());
+                    builder.set_runoff(Meter3(0.0));
+                }
+                (*pos, builder)
+            })
+            .collect();
+        self.wind_direction = Some(wind_direction);
+
+        // Temperature depends on elevation, so it needs a redo too
+        self.apply_climate_generators()?;
+        Ok(self.build_tiles())
+    }
+
+    /// Run every generation step downstream of elevation, wind, and oceans.
+    /// These are shared between [Self::generate_world] and
+    /// [Self::resimulate_water], so the two can't drift apart.
+    fn apply_climate_generators(&mut self) -> anyhow::Result<()> {
+        self.apply_generator(TemperatureGenerator)?;
+        self.apply_generator(RainfallGenerator)?;
+        self.apply_generator(HumidityGenerator)?;
+        self.apply_generator(RunoffGenerator)?;
+        self.apply_generator(WaterFeatureGenerator)?;
+        self.apply_generator(BiomeGenerator)?;
+        self.apply_generator(IceGenerator)?;
+        self.apply_generator(FertilityGenerator)?;
+        Ok(())
+    }
+
+    /// Convert each fully generated tile into its final value, then compute
+    /// any data that depends on finished neighbors. This has to run after
+    /// all generation steps are done.
+    fn build_tiles(self) -> TilePointMap<Tile> {
+        let mut tiles: TilePointMap<Tile> = self
+            .tiles
             .into_iter()
             .map(|(pos, tile)| (pos, tile.build()))
-            .collect()
+            .collect();
+
+        // Watersheds are derived from the final drainage of every tile, and
+        // terrain classes and coast distances look at each tile's neighbors,
+        // so they all have to wait until every tile is built
+        let wrap_radius = self.config.wrap_radius();
+        let watersheds = watershed::watersheds(&tiles, wrap_radius);
+        let terrain_classes = terrain::terrain_classes(&tiles, wrap_radius);
+        let coast_distances = coast::coast_distances(&tiles, wrap_radius);
+        for (pos, tile) in tiles.iter_mut() {
+            tile.watershed = watersheds.get(pos).copied();
+            tile.terrain_class = terrain_classes[pos];
+            tile.coast_distance = coast_distances.get(pos).copied();
+        }
+        tiles
+    }
+
+    /// Run only the elevation step of generation, and output the elevation of
+    /// each tile. This is much faster than [Self::generate_world], which makes
+    /// it useful for quick previews. Since elevation is the first generation
+    /// step, the output is identical to the elevations of a full world
+    /// generated with the same config.
+    pub fn generate_elevation(mut self) -> anyhow::Result<TilePointMap<Meter>> {
+        self.apply_generator(ElevationGenerator)?;
+        Ok(self
+            .tiles
+            .into_iter()
+            .map(|(pos, tile)| (pos, tile.elevation()))
+            .collect())
+    }
+
+    /// A helper to run a generation step on this builder. Generators only
+    /// panic because of internal bugs, but we don't want one of those to take
+    /// down a long-running host process. So any panic is caught and converted
+    /// to an error, which names the step that failed. The builder's tiles may
+    /// be left half-generated after an error, so the builder should be thrown
+    /// away.
+    ///
+    /// **Note:** This relies on unwinding, so it has no effect when panics
+    /// abort, which is the case on Wasm.
+    fn apply_generator(
+        &mut self,
+        generator: impl Debug + Generate,
+    ) -> anyhow::Result<()> {
+        let label = format!("{generator:?}");
+        timed!(
+            &label,
+            panic::catch_unwind(AssertUnwindSafe(|| generator.generate(self)))
+        )
+        .map_err(|payload| {
+            // Panic payloads are almost always the formatted message
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_owned());
+            anyhow!("internal generation error in {}: {}", label, message)
+        })
+    }
+}
+
+/// The RNG used during world generation. The default RNG is stored directly,
+/// so that its state can be captured and restored (see
+/// [WorldBuilder::rng_state]). Any other RNG is boxed, so generators don't
+/// have to be generic over it.
+pub enum BuilderRng<'a> {
+    /// The standard RNG, seeded from the world config
+    Pcg(Pcg64),
+    /// A custom RNG, supplied via [WorldBuilder::with_rng]
+    Custom(Box<dyn RngCore + 'a>),
+}
+
+impl<'a> RngCore for BuilderRng<'a> {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            Self::Pcg(rng) => rng.next_u32(),
+            Self::Custom(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            Self::Pcg(rng) => rng.next_u64(),
+            Self::Custom(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            Self::Pcg(rng) => rng.fill_bytes(dest),
+            Self::Custom(rng) => rng.fill_bytes(dest),
+        }
     }
 
-    /// A helper to run a generation step on this builder.
-    fn apply_generator(&mut self, generator: impl Debug + Generate) {
-        timed!(&format!("{generator:?}"), generator.generate(self))
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            Self::Pcg(rng) => rng.try_fill_bytes(dest),
+            Self::Custom(rng) => rng.try_fill_bytes(dest),
+        }
     }
 }
 
@@ -146,7 +404,8 @@ trait Generate {
     /// in any way. This function _can_ panic, but only because of internal
     /// bugs. Any implementation of this function _should_ be able to handle
     /// any input without returning an error, so any error is considered
-    /// exceptional.
+    /// exceptional. Panics are caught by [WorldBuilder::apply_generator] and
+    /// returned to the caller as errors.
     fn generate(&self, world: &mut WorldBuilder);
 }
 
@@ -160,12 +419,16 @@ trait Generate {
 pub struct TileBuilder {
     position: TilePoint,
     elevation: Option<Meter>,
+    temperature: Option<Celsius>,
     rainfall: Option<Meter3>,
+    humidity: Option<f64>,
+    fertility: Option<f64>,
     biome: Option<Biome>,
     runoff: Option<Meter3>,
     /// A static pattern that indicates how runoff flows out of this tile. See
     /// [RunoffPattern] for more info. This is only used during world
-    /// generation, so it gets thrown away when the full world is built.
+    /// generation, so it gets thrown away when the full world is built (aside
+    /// from a drainage summary, see [Tile::drainage]).
     runoff_pattern: Option<RunoffPattern>,
     runoff_traversed: TileDirectionMap<Meter3>,
     features: Vec<GeoFeature>,
@@ -176,7 +439,10 @@ impl TileBuilder {
         Self {
             position,
             elevation: None,
+            temperature: None,
             rainfall: None,
+            humidity: None,
+            fertility: None,
             runoff: None,
             runoff_pattern: None,
             runoff_traversed: TileDirectionMap::default(),
@@ -189,14 +455,29 @@ impl TileBuilder {
     /// on this builder are uninitialized.
     pub fn build(self) -> Tile {
         let position = self.position;
+        // Water tiles (and all tiles, if rainfall is disabled) never get a
+        // runoff pattern, so they have no drainage
+        let drainage = self
+            .runoff_pattern
+            .as_ref()
+            .map(RunoffPattern::drainage)
+            .unwrap_or_default();
         Tile {
             position,
             elevation: self.elevation(),
+            temperature: self.temperature(),
             rainfall: self.rainfall(),
+            humidity: self.humidity(),
+            fertility: self.fertility(),
             runoff: self.runoff(),
             biome: self.biome(),
             features: self.features,
             runoff_traversed: self.runoff_traversed.into(),
+            drainage,
+            // Filled in once the whole world is built
+            watershed: None,
+            terrain_class: TerrainClass::Flat,
+            coast_distance: None,
         }
     }
 
@@ -214,6 +495,20 @@ impl TileBuilder {
         self.elevation = Some(elevation);
     }
 
+    /// See [Tile::temperature]. Panics if temperature is unset.
+    pub fn temperature(&self) -> Celsius {
+        unwrap!(
+            self.temperature,
+            "temperature not initialized for {:?}",
+            self
+        )
+    }
+
+    /// Set the temperature for this tile.
+    pub fn set_temperature(&mut self, temperature: Celsius) {
+        self.temperature = Some(temperature);
+    }
+
     /// See [Tile::rainfall]. Panics if rainfall is unset.
     pub fn rainfall(&self) -> Meter3 {
         unwrap!(self.rainfall, "rainfall not initialized for {:?}", self)
@@ -229,15 +524,31 @@ impl TileBuilder {
         }
     }
 
-    /// See [Tile::humidity]. Panics if rainfall is unset.
+    /// See [Tile::humidity]. Panics if humidity is unset.
     pub fn humidity(&self) -> f64 {
-        let rainfall = self.rainfall();
-        World::RAINFALL_SOFT_RANGE
-            .value(rainfall)
-            .clamp()
-            .convert::<f64>()
-            .normalize()
-            .inner()
+        unwrap!(self.humidity, "humidity not initialized for {:?}", self)
+    }
+
+    /// Set the humidity for this tile. Values outside of `[0, 1]` are clamped
+    /// into that range, so that a humidity generator doesn't have to be
+    /// careful about floating point error at the edges. NaN is left as-is.
+    pub fn set_humidity(&mut self, humidity: f64) {
+        self.humidity = Some(humidity.clamp(0.0, 1.0));
+    }
+
+    /// See [Tile::fertility]. Panics if fertility is unset.
+    pub fn fertility(&self) -> f64 {
+        unwrap!(self.fertility, "fertility not initialized for {:?}", self)
+    }
+
+    /// Set the fertility for this tile. Panics if the given value is outside
+    /// of `[0, 1]`.
+    pub fn set_fertility(&mut self, fertility: f64) {
+        if (0.0..=1.0).contains(&fertility) {
+            self.fertility = Some(fertility);
+        } else {
+            panic!("cannot set fertility {fertility} for {self:?}, must be in [0, 1]")
+        }
     }
 
     /// Set the biome for this tile.
@@ -332,6 +643,48 @@ impl TileBuilder {
         &self.runoff_traversed
     }
 
+    /// See [Tile::runoff_egress].
+    pub fn runoff_egress(&self) -> Meter3 {
+        -self
+            .runoff_traversed
+            .values()
+            .filter(|v| **v < Meter3(0.0))
+            .copied()
+            .sum::<Meter3>()
+    }
+
+    /// Move all the runoff tracked on one side of this tile over to another
+    /// side. This is for rerouting a river after runoff has already been
+    /// simulated. The runoff level of the tile is unaffected.
+    pub fn move_runoff_traversed(
+        &mut self,
+        from_direction: TileDirection,
+        to_direction: TileDirection,
+    ) {
+        if let Some(runoff) = self.runoff_traversed.remove(&from_direction) {
+            *self.runoff_traversed.entry(to_direction).or_default() += runoff;
+        }
+    }
+
+    /// Track some runoff passing straight through this tile, entering from
+    /// one side and leaving from another. Like
+    /// [Self::move_runoff_traversed], this is for rerouting a river after
+    /// runoff has already been simulated, so the runoff level of the tile is
+    /// unaffected. Panics if the amount is negative.
+    pub fn add_runoff_passthrough(
+        &mut self,
+        runoff: Meter3,
+        from_direction: TileDirection,
+        to_direction: TileDirection,
+    ) {
+        assert!(
+            runoff >= Meter3(0.0),
+            "cannot add negative runoff {runoff} for {self:?}"
+        );
+        *self.runoff_traversed.entry(from_direction).or_default() += runoff;
+        *self.runoff_traversed.entry(to_direction).or_default() -= runoff;
+    }
+
     /// See [Tile::biome]. Panics if biome is unset.
     pub fn biome(&self) -> Biome {
         unwrap!(self.biome, "biome not initialized for {:?}", self)
@@ -352,6 +705,11 @@ impl TileBuilder {
         }
     }
 
+    /// See [Tile::features].
+    pub fn features(&self) -> &[GeoFeature] {
+        &self.features
+    }
+
     /// Add a new geographic feature to this tile. Panics if the tile
     /// already has that feature.
     pub fn add_feature(&mut self, feature: GeoFeature) {
@@ -361,6 +719,16 @@ impl TileBuilder {
             panic!("feature {feature:?} already exists for {self:?}")
         }
     }
+
+    /// Swap out one geographic feature on this tile for another, keeping its
+    /// place in the feature list. Panics if the tile doesn't have the old
+    /// feature.
+    pub fn replace_feature(&mut self, old: GeoFeature, new: GeoFeature) {
+        match self.features.iter_mut().find(|feature| **feature == old) {
+            Some(feature) => *feature = new,
+            None => panic!("feature {old:?} doesn't exist for {self:?}"),
+        }
+    }
 }
 
 impl HasHexPosition for TileBuilder {
@@ -369,3 +737,315 @@ impl HasHexPosition for TileBuilder {
         self.position
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::hex::HexCoordinateValue;
+
+    /// A generation step with a bug in it
+    #[derive(Debug)]
+    struct PanickingGenerator;
+
+    impl Generate for PanickingGenerator {
+        fn generate(&self, _: &mut WorldBuilder) {
+            panic!("something went horribly wrong");
+        }
+    }
+
+    #[test]
+    fn test_apply_generator_panic() {
+        let config = WorldConfig {
+            radius: 2,
+            ..Default::default()
+        };
+        let mut world = WorldBuilder::new(&config);
+        world.apply_generator(ElevationGenerator).unwrap();
+
+        let error = world.apply_generator(PanickingGenerator).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "internal generation error in PanickingGenerator: \
+            something went horribly wrong"
+        );
+    }
+
+    /// Resuming from an RNG checkpoint should always produce the same world
+    #[test]
+    fn test_rng_checkpoint() {
+        let config = WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 10,
+            ..Default::default()
+        };
+        let mut world = WorldBuilder::new(&config);
+        world.apply_generator(ElevationGenerator).unwrap();
+        let rng_state = world.rng_state().unwrap();
+        let checkpoint = world.tiles;
+
+        // Run every step after elevation, starting from the checkpoint
+        let resume = || -> String {
+            let mut world = WorldBuilder::new(&config);
+            world.tiles = checkpoint.clone();
+            world.set_rng_state(rng_state.clone());
+            world.apply_generator(WindGenerator).unwrap();
+            world.apply_generator(OceanGenerator).unwrap();
+            world.apply_generator(TemperatureGenerator).unwrap();
+            world.apply_generator(RainfallGenerator).unwrap();
+            world.apply_generator(HumidityGenerator).unwrap();
+            world.apply_generator(RunoffGenerator).unwrap();
+            world.apply_generator(WaterFeatureGenerator).unwrap();
+            world.apply_generator(BiomeGenerator).unwrap();
+            world.apply_generator(IceGenerator).unwrap();
+            world.apply_generator(FertilityGenerator).unwrap();
+            let tiles = world.build_tiles();
+            let mut tiles: Vec<_> = tiles.into_iter().collect();
+            tiles.sort_by_key(|(pos, _)| *pos);
+            format!("{tiles:?}")
+        };
+        let first = resume();
+        assert_eq!(first, resume());
+
+        // And the resumed world should match one generated in a single pass
+        let tiles = WorldBuilder::new(&config).generate_world().unwrap().tiles;
+        let mut tiles: Vec<_> = tiles.into_iter().collect();
+        tiles.sort_by_key(|(pos, _)| *pos);
+        assert_eq!(first, format!("{tiles:?}"));
+
+        // Custom RNGs can't be checkpointed
+        let world = WorldBuilder::with_rng(&config, Pcg64::seed_from_u64(0));
+        assert!(world.rng_state().is_none());
+    }
+
+    /// Clouds should drop most of their water on the windward side of a
+    /// ridge, leaving less and less for the leeward side
+    #[test]
+    fn test_rainfall_profile_rain_shadow() {
+        let config = WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 10,
+            rainfall: crate::RainfallConfig {
+                evaporation_land_scale: 0.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let wind_direction = HexAxialDirection::X_POS;
+
+        // Ocean on the windward edge, then a ridge running perpendicular to
+        // the wind with its crest at x=2
+        let mut builder = WorldBuilder::new(&config);
+        for tile in builder.tiles.values_mut() {
+            let x = tile.position().x();
+            let elevation = if x < -5 {
+                -20.0
+            } else {
+                (80.0 - 10.0 * (x - 2).abs() as f64).max(5.0)
+            };
+            tile.set_elevation(Meter(elevation));
+        }
+        builder.wind_direction = Some(wind_direction);
+        builder.apply_generator(OceanGenerator).unwrap();
+        builder.apply_generator(TemperatureGenerator).unwrap();
+        builder.apply_generator(RainfallGenerator).unwrap();
+        builder.apply_generator(HumidityGenerator).unwrap();
+        builder.apply_generator(RunoffGenerator).unwrap();
+        builder.apply_generator(WaterFeatureGenerator).unwrap();
+        builder.apply_generator(BiomeGenerator).unwrap();
+        builder.apply_generator(IceGenerator).unwrap();
+        builder.apply_generator(FertilityGenerator).unwrap();
+        let world = World {
+            config: config.clone(),
+            tiles: builder.build_tiles(),
+            meta: crate::WorldMeta {
+                wind_direction: Some(wind_direction),
+                ..crate::WorldMeta::new(&config)
+            },
+            layers: HashMap::new(),
+            climate_rng: None,
+        };
+
+        let profile = world.rainfall_profile_along_wind(TilePoint::ORIGIN);
+        let xs: Vec<i16> = profile.iter().map(|(pos, _)| pos.x()).collect();
+        assert_eq!(xs, (-10..=10).collect::<Vec<_>>());
+
+        let crest_rainfall = profile[12].1;
+        let leeward: Vec<Meter3> = profile[13..]
+            .iter()
+            .map(|(_, rainfall)| *rainfall)
+            .collect();
+        assert!(leeward[0] < crest_rainfall);
+        for pair in leeward.windows(2) {
+            assert!(
+                pair[1] < pair[0],
+                "rainfall should decrease past the crest: {profile:?}"
+            );
+        }
+    }
+
+    /// Land with ocean directly upwind should be pulled toward the ocean's
+    /// temperature when currents are enabled, and nothing else should change
+    #[test]
+    fn test_ocean_currents() {
+        let generate_temperatures = |current_strength: f64| {
+            let config = WorldConfig {
+                seed: 1021522790211909.into(),
+                radius: 10,
+                temperature: crate::TemperatureConfig {
+                    current_strength,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            // Ocean on the windward edge, then a plateau that's much colder
+            // than the sea
+            let mut builder = WorldBuilder::new(&config);
+            for tile in builder.tiles.values_mut() {
+                if tile.position().x() < -5 {
+                    tile.set_elevation(Meter(-20.0));
+                    tile.set_biome(Biome::Ocean);
+                } else {
+                    tile.set_elevation(Meter(100.0));
+                }
+            }
+            builder.wind_direction = Some(HexAxialDirection::X_POS);
+            builder.apply_generator(TemperatureGenerator).unwrap();
+            builder
+                .tiles
+                .into_iter()
+                .map(|(pos, tile)| (pos, tile.temperature()))
+                .collect::<TilePointMap<Celsius>>()
+        };
+        let without_currents = generate_temperatures(0.0);
+        let with_currents = generate_temperatures(0.5);
+
+        let mut coastal_count = 0;
+        for (pos, temperature) in &with_currents {
+            // The wind blows toward +x, so it comes from these neighbors
+            let upwind_ocean: Vec<Celsius> =
+                [TileDirection::NNE, TileDirection::E]
+                    .into_iter()
+                    .map(|dir| pos.adjacent(dir))
+                    .filter(|adj_pos| adj_pos.x() < -5)
+                    .filter_map(|adj_pos| {
+                        without_currents.get(&adj_pos).copied()
+                    })
+                    .collect();
+            let original = without_currents[pos];
+            if pos.x() < -5 || upwind_ocean.is_empty() {
+                assert_eq!(*temperature, original, "{pos} shouldn't change");
+            } else {
+                // Halfway between the land and the average of the ocean
+                let ocean = upwind_ocean.iter().copied().sum::<Celsius>()
+                    / upwind_ocean.len() as f64;
+                assert!(*temperature > original, "{pos} should be warmer");
+                assert!(
+                    (temperature.0 - (original.0 + ocean.0) / 2.0).abs() < 1e-9
+                );
+                coastal_count += 1;
+            }
+        }
+        assert!(coastal_count > 0);
+    }
+
+    /// Even on terrain where the backflow takes forever to settle, the
+    /// iteration cap should stop it early without breaking anything
+    #[test]
+    fn test_max_backflow_iterations() {
+        let simulate = |max_backflow_iterations: u32| {
+            let config = WorldConfig {
+                seed: 1021522790211909.into(),
+                radius: 10,
+                rainfall: crate::RainfallConfig {
+                    max_backflow_iterations,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            // An egg crate: a grid of one-tile pits, each of which fills up
+            // and spills into its neighbors, surrounded by a ring of ocean.
+            // Each pit collects about 15m³, which is more than it can hold
+            let mut builder = WorldBuilder::new(&config);
+            for tile in builder.tiles.values_mut() {
+                let pos = tile.position();
+                if pos.distance_to(TilePoint::ORIGIN) == 10 {
+                    tile.set_elevation(Meter(-10.0));
+                    tile.set_biome(Biome::Ocean);
+                } else if (pos.x() - pos.y()).rem_euclid(3) == 0 {
+                    tile.set_elevation(Meter(1.0));
+                } else {
+                    tile.set_elevation(Meter(10.0));
+                }
+                tile.set_rainfall(Meter3(5.0));
+            }
+            builder.apply_generator(RunoffGenerator).unwrap();
+
+            let runoff: TilePointMap<Meter3> = builder
+                .tiles
+                .values()
+                .filter(|tile| !tile.is_water_biome())
+                .map(|tile| (tile.position(), tile.runoff()))
+                .collect();
+            let total_rainfall = Meter3(5.0) * runoff.len() as f64;
+            let total_runoff: Meter3 = runoff.values().copied().sum();
+            assert!(runoff
+                .values()
+                .all(|runoff| runoff.0.is_finite() && *runoff >= Meter3(0.0)));
+            assert!(total_runoff > Meter3(0.0));
+            assert!(total_runoff <= total_rainfall + Meter3(1.0e-6));
+            runoff
+        };
+
+        // With a tiny cap, most of the basins never get a chance to spread
+        let capped = simulate(3);
+        let settled =
+            simulate(crate::RainfallConfig::default().max_backflow_iterations);
+        assert_ne!(capped, settled);
+    }
+
+    /// Find the row (z coordinate) with the highest average temperature
+    fn warmest_row(hemisphere_offset: f64) -> i16 {
+        let config = WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 10,
+            temperature: crate::TemperatureConfig {
+                hemisphere_offset,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        // Flat land, so latitude is the only thing that matters
+        let mut builder = WorldBuilder::new(&config);
+        for tile in builder.tiles.values_mut() {
+            tile.set_elevation(Meter(0.0));
+        }
+        builder.wind_direction = Some(HexAxialDirection::X_POS);
+        builder.apply_generator(TemperatureGenerator).unwrap();
+
+        let mut rows: HashMap<i16, Vec<f64>> = HashMap::new();
+        for tile in builder.tiles.values() {
+            rows.entry(tile.position().z())
+                .or_default()
+                .push(tile.temperature().0);
+        }
+        rows.into_iter()
+            .map(|(z, temperatures)| {
+                (
+                    z,
+                    temperatures.iter().sum::<f64>()
+                        / temperatures.len() as f64,
+                )
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_hemisphere_offset() {
+        assert_eq!(warmest_row(0.0), 0);
+        // Latitude is z/radius, so this puts the warmest line 5 rows north
+        assert_eq!(warmest_row(0.5), 5);
+        assert_eq!(warmest_row(-0.5), -5);
+    }
+}
@@ -23,6 +23,24 @@ impl<T: Debug + NoiseFn<f64, 3>> NoiseFnTrait for T {}
 ///
 /// This type can optionally also do transparent conversions on the output type,
 /// e.g. if you are using a newtype that wraps `f64`.
+///
+/// This is the same mapping that world generation uses for elevation, so it's
+/// also handy for generating your own fields of values over a world:
+///
+/// ```
+/// use rand::SeedableRng;
+/// use rand_pcg::Pcg64;
+/// use terra::{ElevationConfig, NumRange, TileNoiseFn, TilePoint};
+///
+/// let mut rng = Pcg64::seed_from_u64(1234);
+/// let range: NumRange<f64> = NumRange::new(0.0, 10.0);
+/// let noise_fn: TileNoiseFn =
+///     TileNoiseFn::new(&mut rng, ElevationConfig::default().noise_fn, range);
+/// for point in [TilePoint::ORIGIN, TilePoint::new_xy(3, -1)] {
+///     let value = noise_fn.get(point).inner();
+///     assert!(range.contains(value));
+/// }
+/// ```
 #[derive(Debug)]
 pub struct TileNoiseFn<T: Rangeable<f64> = f64> {
     /// The noise generation function
@@ -53,8 +71,7 @@ where
     /// Initialize a new function for some underlying noise fn type.
     ///
     /// ### Arguments
-    /// - `world_config` - The overall world config, needed for seed and world
-    /// radius.
+    /// - `rng` - RNG used to pick a seed for the underlying noise function.
     /// - `config` - Configuration for the underlying noise function.
     /// - `output_range` - The output range of this function. Noise values will
     /// be mapped to this range during generation.
@@ -76,13 +93,30 @@ where
 
     /// Get the function output at the given point
     pub fn get(&self, point: TilePoint) -> RangeValue<T, f64> {
+        self.get_offset(point, TilePoint::ORIGIN)
+    }
+
+    /// Get the function output at the given point, shifted by `offset`. The
+    /// two are added as floats, so big offsets can't overflow. See
+    /// [WorldConfig::center](crate::WorldConfig::center).
+    pub(crate) fn get_offset(
+        &self,
+        point: TilePoint,
+        offset: TilePoint,
+    ) -> RangeValue<T, f64> {
         // Scale each point value down. See INPUT_SCALE doc comment for why we
         // need it
         let fn_output = self.noise_fn.get([
-            point.x() as f64 / Self::INPUT_SCALE,
-            point.y() as f64 / Self::INPUT_SCALE,
-            point.z() as f64 / Self::INPUT_SCALE,
+            (point.x() as f64 + offset.x() as f64) / Self::INPUT_SCALE,
+            (point.y() as f64 + offset.y() as f64) / Self::INPUT_SCALE,
+            (point.z() as f64 + offset.z() as f64) / Self::INPUT_SCALE,
         ]);
+        self.map_noise_output(fn_output)
+    }
+
+    /// Map a raw value from the underlying noise function to our output range,
+    /// applying the exponent along the way
+    fn map_noise_output(&self, fn_output: f64) -> RangeValue<T, f64> {
         Self::NOISE_FN_OUTPUT_RANGE
             .value(fn_output)
             // Noise functions are *supposed to* output in a fixed range, but
@@ -90,7 +124,19 @@ where
             .clamp()
             // Map to [0,1] so we can apply the exponent
             .normalize()
-            .apply(|val| val.powf(self.config.exponent))
+            // Clamp again, in case normalization put us a hair outside [0,1].
+            // A fractional exponent on a negative number gives NaN
+            .clamp()
+            .apply(|val| {
+                let val = val.powf(self.config.exponent);
+                debug_assert!(
+                    !val.is_nan(),
+                    "noise value {fn_output} became NaN after applying \
+                    exponent {}",
+                    self.config.exponent
+                );
+                val
+            })
             // Convert to type T so we can map to the output range
             .convert() // f64 -> T
             .map_to(self.output_range)
@@ -151,3 +197,43 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{util::unit::Meter, ElevationConfig, World};
+    use rand::SeedableRng;
+    use rand_pcg::Pcg64;
+
+    #[test]
+    fn test_map_noise_output_edges() {
+        // Fractional exponents are the ones that can produce NaN
+        let config = NoiseFnConfig {
+            exponent: 0.7,
+            ..ElevationConfig::default().noise_fn
+        };
+        let noise_fn: TileNoiseFn<Meter> = TileNoiseFn::new(
+            &mut Pcg64::seed_from_u64(0),
+            config,
+            World::ELEVATION_RANGE,
+        );
+
+        for fn_output in [
+            -1.0,
+            1.0,
+            -1.0 - f64::EPSILON,
+            1.0 + f64::EPSILON,
+            -1.0 + f64::EPSILON,
+            -5.0,
+            5.0,
+            0.0,
+        ] {
+            let value = noise_fn.map_noise_output(fn_output).inner();
+            assert!(value.0.is_finite(), "{fn_output} mapped to {value}");
+            assert!(
+                World::ELEVATION_RANGE.contains(value),
+                "{fn_output} mapped to {value}"
+            );
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    util::{range::NumRange, unit::Meter},
+    util::range::NumRange,
     world::{
         generate::{Generate, WorldBuilder},
         hex::Cluster,
@@ -10,8 +10,6 @@ use crate::{
 use rand::Rng;
 
 const MAYBE_OCEAN_SIZE_RANGE: NumRange<f32> = NumRange::new(5000.0, 10000.0);
-// Any ocean tile at or above this elevation will be coastal
-const MIN_COAST_ELEV: Meter = Meter(-3.0);
 
 /// A generator to create oceans at/below sea level.
 #[derive(Debug)]
@@ -19,10 +17,22 @@ pub struct OceanGenerator;
 
 impl Generate for OceanGenerator {
     fn generate(&self, world: &mut WorldBuilder) {
+        // Any ocean tile at or above this elevation will be coastal
+        let min_coast_elev =
+            World::SEA_LEVEL - world.config.elevation.coast_depth;
+        // If enabled, any water touching the edge of the world is ocean. The
+        // elevation generator already sank the outermost ring, so the whole
+        // edge will be part of one of these clusters.
+        let force_ocean_border =
+            world.config.elevation.force_ocean_border && !world.config.wrap;
+        let radius = world.config.radius as usize;
+
         // Find all clusters of tiles that are entirely below sea level
-        let clusters = Cluster::predicate(&mut world.tiles, |tile| {
-            tile.elevation() <= World::SEA_LEVEL
-        });
+        let clusters = Cluster::predicate_wrapping(
+            &mut world.tiles,
+            world.config.wrap_radius(),
+            |tile| tile.elevation() <= World::SEA_LEVEL,
+        );
 
         for cluster in clusters {
             // The odds of this cluster becoming an ocean are proportional to
@@ -30,10 +40,15 @@ impl Generate for OceanGenerator {
             // Clusters at/above the max size have a chance of 1. Anything in
             // between is proportional to its size.
             let threshold: f32 = world.rng.gen_range(MAYBE_OCEAN_SIZE_RANGE);
-            if cluster.tiles().len() as f32 >= threshold {
+            let touches_border = force_ocean_border
+                && cluster
+                    .tiles()
+                    .keys()
+                    .any(|pos| world.config.center_distance(*pos) >= radius);
+            if touches_border || cluster.tiles().len() as f32 >= threshold {
                 // Update every tile in this cluster to be coast/ocean
                 for (_, tile) in cluster.into_tiles() {
-                    let biome = if tile.elevation() >= MIN_COAST_ELEV {
+                    let biome = if tile.elevation() >= min_coast_elev {
                         Biome::Coast
                     } else {
                         Biome::Ocean
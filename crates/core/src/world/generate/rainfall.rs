@@ -2,7 +2,9 @@ use crate::{
     util::{range::NumRange, unit::Meter3},
     world::{
         generate::{Generate, TileBuilder, WorldBuilder},
-        hex::{HexAxialDirection, HexAxis, TilePoint, TilePointMap},
+        hex::{
+            HasHexPosition, HexAxialDirection, HexAxis, TilePoint, TilePointMap,
+        },
         World,
     },
     WorldConfig,
@@ -38,6 +40,20 @@ impl Generate for RainfallGenerator {
                 cloud_line.precipitate_and_evaporate(&mut world.tiles);
                 cloud_line.advance();
             }
+            world.capture_layer("raw_rainfall", |world| {
+                world
+                    .tiles
+                    .iter()
+                    .map(|(pos, tile)| (*pos, tile.rainfall().0))
+                    .collect()
+            });
+
+            let min_rainfall = world.config.rainfall.min_rainfall;
+            for tile in world.tiles.values_mut() {
+                if tile.rainfall() < min_rainfall {
+                    tile.set_rainfall(min_rainfall);
+                }
+            }
         } else {
             for tile in world.tiles.values_mut() {
                 tile.set_rainfall(Meter3(0.0));
@@ -115,15 +131,47 @@ impl<'a> CloudLine<'a> {
             .inner()
     }
 
+    /// Is the tile at the given position water that borders land? This will
+    /// always be false if there's no coastal evaporation bonus, so we can skip
+    /// the neighbor lookups.
+    fn is_coastal_water(
+        &self,
+        tiles: &TilePointMap<TileBuilder>,
+        pos: TilePoint,
+    ) -> bool {
+        self.config.rainfall.coastal_evaporation_bonus > 0.0
+            && tiles.get(&pos).map_or(false, |tile| tile.is_water_biome())
+            && pos.adjacents_wrapping(self.config.wrap_radius()).any(
+                |adj_pos| {
+                    tiles
+                        .get(&adj_pos)
+                        .map_or(false, |adj_tile| !adj_tile.is_water_biome())
+                },
+            )
+    }
+
     /// Calculate how much water vapor this tile produces when the clouds pass
-    /// over it.
-    fn calc_evaporation(&self, tile: &TileBuilder) -> Meter3 {
-        if tile.is_water_biome() {
+    /// over it. `coastal` should indicate if the tile is water that borders
+    /// land (see [Self::is_coastal_water]). Any extra evaporation from the
+    /// config is added on top.
+    fn calc_evaporation(&self, tile: &TileBuilder, coastal: bool) -> Meter3 {
+        let extra = self
+            .config
+            .rainfall
+            .extra_evaporation
+            .get(&tile.position())
+            .copied()
+            .unwrap_or_default();
+        let base = if coastal {
+            self.config.rainfall.evaporation_default
+                * (1.0 + self.config.rainfall.coastal_evaporation_bonus)
+        } else if tile.is_water_biome() {
             self.config.rainfall.evaporation_default
         } else {
             self.config.rainfall.evaporation_default
                 * self.config.rainfall.evaporation_land_scale
-        }
+        };
+        base + extra
     }
 
     /// Simulation precipitation on the current line, then simulation
@@ -141,8 +189,9 @@ impl<'a> CloudLine<'a> {
         #[allow(clippy::needless_range_loop)]
         for i in 0..self.cloud_volumes.len() {
             let pos = self.index_to_pos(i);
+            let coastal = self.is_coastal_water(tiles, pos);
             if let Some(tile) = tiles.get_mut(&pos) {
-                evaporation[i] = self.calc_evaporation(tile);
+                evaporation[i] = self.calc_evaporation(tile, coastal);
                 // Each tile receives some fraction of the current water
                 // available in the cloud
                 let rainfall =
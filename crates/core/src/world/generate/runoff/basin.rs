@@ -4,7 +4,8 @@ use crate::{
     world::{
         generate::{
             runoff::pattern::{
-                RunoffDestination, RunoffDestinationMap, RunoffPattern,
+                sum_destinations, RunoffDestination, RunoffDestinationMap,
+                RunoffPattern,
             },
             TileBuilder,
         },
@@ -92,13 +93,18 @@ pub struct Basin {
 
 impl Basin {
     /// Initialize a new basin around the given terminal tile. This will remove
-    /// any runoff on the tile and move it into the basin's runoff storage.
-    pub fn new(terminal: &mut TileBuilder) -> Self {
+    /// any runoff on the tile and move it into the basin's runoff storage. If
+    /// `wrap_radius` is given, the basin can grow across the edges of a
+    /// wrapping world with that radius.
+    pub fn new(terminal: &mut TileBuilder, wrap_radius: Option<u16>) -> Self {
         let term_pos = terminal.position();
         Self {
             key: ResolvedBasinKey(term_pos),
             terminals: vec![term_pos],
-            tiles: Cluster::new(iter::once((term_pos, ())).collect()),
+            tiles: Cluster::new_wrapping(
+                iter::once((term_pos, ())).collect(),
+                wrap_radius,
+            ),
             total_elev: terminal.elevation(),
             runoff: terminal.clear_runoff(),
             prev_donors: HashSet::default(),
@@ -210,14 +216,13 @@ impl Basin {
         // distributed anything
         if !distributed.is_empty() {
             assert_approx_eq!(
-                distributed.values().copied().sum::<Meter3>().0,
+                sum_destinations(&distributed).0,
                 runoff_to_distribute.0
             );
         }
 
         // This runoff no longer belongs to us, so remove it from this basin
-        let total_distributed_runoff: Meter3 =
-            distributed.values().copied().sum();
+        let total_distributed_runoff: Meter3 = sum_destinations(&distributed);
         self.runoff -= total_distributed_runoff;
 
         distributed
@@ -258,12 +263,15 @@ impl Basins {
     /// runoff pattern data, we will assume that any tile with runoff on it is a
     /// terminal. As such, this should only be called **after** runoff has
     /// been pushed out to all the terminal tiles.
-    pub fn new(continent: &mut TilePointIndexMap<&mut TileBuilder>) -> Self {
+    pub fn new(
+        continent: &mut TilePointIndexMap<&mut TileBuilder>,
+        wrap_radius: Option<u16>,
+    ) -> Self {
         // Create one basin per terminal tile
         let mut basins = HashMap::default();
         for tile in continent.values_mut() {
             if tile.runoff() > Meter3(0.0) {
-                let basin = Basin::new(tile);
+                let basin = Basin::new(tile, wrap_radius);
                 basins.insert(basin.key, basin);
             }
         }
@@ -288,9 +296,13 @@ impl Basins {
     }
 
     /// Iterate over all basin keys. This will NOT include alias keys, only
-    /// primary keys.
-    pub fn keys(&self) -> impl Iterator<Item = TilePoint> + '_ {
-        self.basins.keys().map(|key| key.0)
+    /// primary keys. Keys are sorted, so that the order in which basins get
+    /// processed doesn't depend on hash map iteration order.
+    pub fn keys(&self) -> impl Iterator<Item = TilePoint> {
+        let mut keys: Vec<TilePoint> =
+            self.basins.keys().map(|key| key.0).collect();
+        keys.sort();
+        keys.into_iter()
     }
 
     /// Move the basins out of this struct.
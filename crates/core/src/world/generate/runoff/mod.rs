@@ -12,7 +12,7 @@ use crate::{
         generate::{
             runoff::{
                 basin::{Basin, Basins},
-                pattern::RunoffDestination,
+                pattern::{sorted_destinations, RunoffDestination},
             },
             Generate, TileBuilder, WorldBuilder,
         },
@@ -26,7 +26,7 @@ use crate::{
 };
 use assert_approx_eq::assert_approx_eq;
 use fnv::FnvBuildHasher;
-use log::trace;
+use log::{trace, warn};
 use std::{
     cmp::Ordering,
     collections::{HashMap, VecDeque},
@@ -50,14 +50,29 @@ impl Generate for RunoffGenerator {
         // If rainfall sim is disabled then there won't be any runoff to
         // simulate, so we might as well skip this step too
         if world.config.rainfall.enabled {
-            let continents = Cluster::predicate(&mut world.tiles, |tile| {
-                !tile.is_water_biome()
-            });
+            let wrap_radius = world.config.wrap_radius();
+            let max_runoff_elevation =
+                world.config.rainfall.max_runoff_elevation;
+            let runoff_concentration =
+                world.config.rainfall.runoff_concentration;
+            let max_backflow_iterations =
+                world.config.rainfall.max_backflow_iterations;
+            let continents = Cluster::predicate_wrapping(
+                &mut world.tiles,
+                wrap_radius,
+                |tile| !tile.is_water_biome(),
+            );
             // Hypothetically we could run these simulations in parallel since
             // each continent is independent, but skipping that for
             // now cause Wasm.
             for continent in continents {
-                let mut continent = Continent::new(continent.into_tiles());
+                let mut continent = Continent::new(
+                    continent.into_tiles(),
+                    wrap_radius,
+                    max_runoff_elevation,
+                    runoff_concentration,
+                    max_backflow_iterations,
+                );
                 continent.sim_continent_runoff();
             }
         } else {
@@ -84,10 +99,29 @@ struct Continent<'a> {
     /// not be added to or removed from. **These tiles will be sorted by
     /// ascending elevation**. Individual tiles can be mutated as well.
     tiles: TilePointIndexMap<&'a mut TileBuilder>,
+    /// If defined, runoff can flow across the edges of a wrapping world with
+    /// this radius. See [TilePoint::wrap].
+    wrap_radius: Option<u16>,
+    /// If defined, basin water levels are capped at this elevation. See
+    /// [RainfallConfig::max_runoff_elevation](crate::RainfallConfig::max_runoff_elevation).
+    max_runoff_elevation: Option<Meter>,
+    /// Exponent applied to elevation differences when splitting runoff
+    /// between lower neighbors. See
+    /// [RainfallConfig::runoff_concentration](crate::RainfallConfig::runoff_concentration).
+    runoff_concentration: f64,
+    /// Maximum number of basin updates during backflow. See
+    /// [RainfallConfig::max_backflow_iterations](crate::RainfallConfig::max_backflow_iterations).
+    max_backflow_iterations: u32,
 }
 
 impl<'a> Continent<'a> {
-    fn new(mut tiles: TilePointIndexMap<&'a mut TileBuilder>) -> Self {
+    fn new(
+        mut tiles: TilePointIndexMap<&'a mut TileBuilder>,
+        wrap_radius: Option<u16>,
+        max_runoff_elevation: Option<Meter>,
+        runoff_concentration: f64,
+        max_backflow_iterations: u32,
+    ) -> Self {
         let (&id, _) =
             tiles.first().expect("cannot initialize empty continent");
 
@@ -97,7 +131,14 @@ impl<'a> Continent<'a> {
         // easily calculate where water ends up for each tile.
         tiles.sort_by(|_, a, _, b| cmp_elev(a, b));
 
-        Self { id, tiles }
+        Self {
+            id,
+            tiles,
+            wrap_radius,
+            max_runoff_elevation,
+            runoff_concentration,
+            max_backflow_iterations,
+        }
     }
 
     /// Simulate runoff for a single continent. Each continent is an independent
@@ -170,7 +211,9 @@ impl<'a> Continent<'a> {
             // This is a list of (direction,elevation_diff) pairs
             let mut recipients: Vec<(TileDirection, Meter)> = Vec::new();
             for dir in TileDirection::iter() {
-                let adj_pos = source_tile.position().adjacent(dir);
+                let adj_pos = source_tile
+                    .position()
+                    .adjacent_wrapping(dir, self.wrap_radius);
                 let adj_elev = match self.tiles.get(&adj_pos) {
                     // Adjacent tile isn't part of this continent, so it's
                     // either ocean or outside the world. Either way, it's an
@@ -190,23 +233,39 @@ impl<'a> Continent<'a> {
             }
 
             // Distribute the water to our neighbors. Each neighbor gets an
-            // amount proportional to the elevation different
-            // between us and them. I.e. steeper slopes get more
-            // water.
-
-            let total_elev_diff: Meter =
-                recipients.iter().map(|(_, elev_diff)| *elev_diff).sum();
+            // amount proportional to the elevation difference between us and
+            // them, raised to the concentration exponent. I.e. steeper slopes
+            // get more water. Differences are scaled down by the biggest one
+            // first, so that big exponents can't overflow.
+            let max_elev_diff = recipients
+                .iter()
+                .map(|(_, elev_diff)| elev_diff.0)
+                .fold(0.0, f64::max);
+            let weights: Vec<(TileDirection, f64)> = recipients
+                .into_iter()
+                .map(|(dir, elev_diff)| {
+                    (
+                        dir,
+                        (elev_diff.0 / max_elev_diff)
+                            .powf(self.runoff_concentration),
+                    )
+                })
+                .collect();
+            let total_weight: f64 =
+                weights.iter().map(|(_, weight)| *weight).sum();
 
             // For each adjacent lower tile, mark it as an exit in the pattern
             let mut runoff_pattern = RunoffPattern::new(source_tile.position());
-            for (dir, elev_diff) in recipients {
-                let adj_pos = source_tile.position().adjacent(dir);
+            for (dir, weight) in weights {
+                let adj_pos = source_tile
+                    .position()
+                    .adjacent_wrapping(dir, self.wrap_radius);
                 runoff_pattern.add_exit(
                     dir,
                     // This is why the tiles have to be ascending by elevation,
                     // because we back-reference the lower tiles
                     runoff_patterns.get(&adj_pos),
-                    elev_diff.0 / total_elev_diff.0,
+                    weight / total_weight,
                 );
             }
             runoff_patterns.insert(source_tile.position(), runoff_pattern);
@@ -269,7 +328,8 @@ impl<'a> Continent<'a> {
             // to each adjacent tile
 
             for (dir, amt) in distribution {
-                let adj_pos = source_pos.adjacent(dir);
+                let adj_pos =
+                    source_pos.adjacent_wrapping(dir, self.wrap_radius);
                 // If the adjacent tile is in our continent, add our runoff to
                 // if. If not, then it must be ocean so the runoff gets deleted
                 if let Some(adj_tile) = self.tiles.get_mut(&adj_pos) {
@@ -288,21 +348,41 @@ impl<'a> Continent<'a> {
     /// when one basin overflows into another but they DON'T join.
     fn simulate_backflow(&mut self) {
         // Initialize a basin for each terminal
-        let mut basins = Basins::new(&mut self.tiles);
+        let mut basins = Basins::new(&mut self.tiles, self.wrap_radius);
 
         // For each basin, attempt to spread out to its constituents. If one
         // basin overflows into another, then the recipient basin will be
         // re-queued. We'll continue until all runoff is settled. This will
         // eventually converge because we have logic to prevent cyclic overflow.
         let mut basin_queue: VecDeque<TilePoint> = basins.keys().collect();
+        let mut iterations = 0;
         while let Some(basin_key) = basin_queue.pop_front() {
+            // Bail out on pathological terrain. Any runoff that's still
+            // waiting to spread out just stays where it is, which can't add
+            // runoff, so the sanity checks still hold
+            if iterations >= self.max_backflow_iterations {
+                warn!(
+                    "Backflow for continent {} didn't settle after {} \
+                    iterations, finalizing {} unsettled basin(s) early",
+                    self.id,
+                    iterations,
+                    basin_queue.len() + 1
+                );
+                break;
+            }
+            iterations += 1;
+
             let basin = basins.get_mut(basin_key);
             // Spread out water out as far as possible
             let overflow_distribution = self.grow_basin(basin);
 
             // If this basin overflowed into other(s), then do some processing
             // for each one
-            for (overflow_dest, overflow_vol) in overflow_distribution {
+            // Process destinations in a stable order, since it controls the
+            // order of the basin queue and which basins get joined
+            for (overflow_dest, overflow_vol) in
+                sorted_destinations(&overflow_distribution)
+            {
                 // If the overflow destination is a terminal tile (as opposed to
                 // ocean), then push the overflow runoff into that basin
                 if let RunoffDestination::Terminal(other_basin_key) =
@@ -348,7 +428,14 @@ impl<'a> Continent<'a> {
         // Now we know that each cluster is finalized, we can distribute the
         // runoff accordingly
         for basin in basins.into_basins() {
-            let runoff_elev = basin.runoff_elevation();
+            let mut runoff_elev = basin.runoff_elevation();
+            // If the water level is capped, anything above the cap spills
+            // into the ocean. This drops runoff from the continent, which is
+            // fine since the sanity checks only guard against *adding* runoff
+            match self.max_runoff_elevation {
+                Some(cap) if runoff_elev > cap => runoff_elev = cap,
+                _ => {}
+            }
             for pos in basin.tiles().tiles().keys() {
                 let tile = unwrap!(
                     self.tiles.get_mut(pos),
@@ -366,6 +453,11 @@ impl<'a> Continent<'a> {
                     && runoff_height <= Meter(0.0)
                 {
                     Meter3(0.0)
+                } else if runoff_height < Meter(0.0) {
+                    // The tile pokes up above the capped water level, so it
+                    // can't hold any runoff
+                    debug_assert!(self.max_runoff_elevation.is_some());
+                    Meter3(0.0)
                 } else {
                     // Convert Meter -> Meter3
                     runoff_height * Tile::AREA
@@ -410,7 +502,11 @@ impl<'a> Continent<'a> {
             .adjacents()
             .iter()
             .filter_map(|pos| self.tiles.get(pos))
-            .min_by(|a, b| cmp_elev(a, b))
+            // Break elevation ties by position, so the choice of candidate
+            // doesn't depend on the iteration order of the adjacents set
+            .min_by(|a, b| {
+                cmp_elev(a, b).then_with(|| a.position().cmp(&b.position()))
+            })
         {
             // Just a sanity check. We expect every tile that's not a terminal
             // to have no runoff on it. (and all terminals are initialized to
@@ -458,3 +554,52 @@ impl<'a> Continent<'a> {
         HashMap::default() // This won't allocate for an empty map
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{world::DrainageDestination, WorldConfig};
+
+    /// Build an all-land world where one edge tile is a pit and its neighbor
+    /// across the seam slopes down into it, then return where that neighbor
+    /// drains to
+    fn seam_drainage(wrap: bool) -> DrainageDestination {
+        let config = WorldConfig {
+            radius: 2,
+            wrap,
+            ..Default::default()
+        };
+        let pit = TilePoint::new_xy(2, 0);
+        let slope = pit.adjacent_wrapping(TileDirection::W, Some(2));
+        // Make sure the two are actually on opposite sides of the world
+        assert_ne!(slope, pit.adjacent(TileDirection::W));
+
+        let mut world = WorldBuilder::new(&config);
+        for tile in world.tiles.values_mut() {
+            let elevation = match tile.position() {
+                pos if pos == pit => Meter(10.0),
+                pos if pos == slope => Meter(30.0),
+                _ => Meter(50.0),
+            };
+            tile.set_elevation(elevation);
+            tile.set_rainfall(Meter3(1.0));
+        }
+
+        RunoffGenerator.generate(&mut world);
+        world.tiles[&slope].runoff_pattern().drainage()
+    }
+
+    #[test]
+    fn test_runoff_across_seam() {
+        // With wrapping, the only way downhill is across the seam into the pit
+        assert_eq!(
+            seam_drainage(true),
+            DrainageDestination::Terminals {
+                terminals: vec![TilePoint::new_xy(2, 0)],
+                ocean: false,
+            }
+        );
+        // Without wrapping, the edge of the world is a sink
+        assert!(seam_drainage(false).reaches_ocean());
+    }
+}
@@ -1,13 +1,18 @@
 use crate::{
-    world::hex::{HasHexPosition, TileDirection, TileDirectionMap, TilePoint},
+    world::{
+        hex::{HasHexPosition, TileDirection, TileDirectionMap, TilePoint},
+        DrainageDestination,
+    },
     Meter3,
 };
 use assert_approx_eq::assert_approx_eq;
 use fnv::FnvBuildHasher;
-use std::collections::HashMap;
+use std::{collections::HashMap, iter::Sum};
 
-/// Runoff can terminate at either the ocean or at specific tile.
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+/// Runoff can terminate at either the ocean or at specific tile. Destinations
+/// are ordered so that we can process them in a stable order (see
+/// [sorted_destinations]).
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RunoffDestination {
     Ocean,
     Terminal(TilePoint),
@@ -16,6 +21,26 @@ pub enum RunoffDestination {
 pub type RunoffDestinationMap<T> =
     HashMap<RunoffDestination, T, FnvBuildHasher>;
 
+/// Get the entries of a destination map, sorted by destination. Hash map
+/// iteration order depends on how the map was built (capacity, insertion
+/// order) and even on the target's pointer width, so anything that
+/// accumulates floats or makes order-dependent decisions over a destination
+/// map should go through this instead.
+pub fn sorted_destinations<T: Copy>(
+    map: &RunoffDestinationMap<T>,
+) -> Vec<(RunoffDestination, T)> {
+    let mut entries: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    entries.sort_by_key(|(destination, _)| *destination);
+    entries
+}
+
+/// Sum all the values in a destination map, in a stable order. Float addition
+/// isn't associative, so summing in hash map order could give slightly
+/// different results for the same set of values.
+pub fn sum_destinations<T: Copy + Sum>(map: &RunoffDestinationMap<T>) -> T {
+    sorted_destinations(map).into_iter().map(|(_, v)| v).sum()
+}
+
 /// A runoff pattern is essentially a way of memoizing parts of the runoff
 /// generation process. When we calculate runoff, we start at the lowest tiles
 /// and for each one, figure out how its runoff will flow to its neighbors,
@@ -79,6 +104,33 @@ impl RunoffPattern {
         self.exits.is_empty()
     }
 
+    /// Summarize where runoff from this pattern's source tile ends up. This
+    /// drops the fractional info, and just keeps the set of destinations.
+    pub fn drainage(&self) -> DrainageDestination {
+        // A terminal has no destinations, all its runoff stays on itself
+        if self.is_terminal() {
+            return DrainageDestination::Terminals {
+                terminals: vec![self.position],
+                ocean: false,
+            };
+        }
+
+        let mut terminals = Vec::new();
+        let mut ocean = false;
+        for (destination, _) in sorted_destinations(&self.destinations) {
+            match destination {
+                RunoffDestination::Ocean => ocean = true,
+                RunoffDestination::Terminal(pos) => terminals.push(pos),
+            }
+        }
+
+        if terminals.is_empty() {
+            DrainageDestination::Ocean
+        } else {
+            DrainageDestination::Terminals { terminals, ocean }
+        }
+    }
+
     /// Distribute the given runoff quantity to each of this pattern's exits.
     /// The returned map indicates how much runoff each exit direction
     /// receives. The values of the returned map will always sum to the input
@@ -119,7 +171,7 @@ impl RunoffPattern {
         // We need to scale up the remaining destinations so that they still sum
         // to 1. Since the old sum was 1, we can just divide each remaining
         // value by the new sum to get back to 1
-        let filtered_sum: f64 = filtered_destinations.values().sum();
+        let filtered_sum: f64 = sum_destinations(&filtered_destinations);
         for value in filtered_destinations.values_mut() {
             *value /= filtered_sum;
         }
@@ -128,7 +180,7 @@ impl RunoffPattern {
         // 1.0. If we filtered the destinations down to empty though, then
         // obviously they can't add up to one, so skip that case
         if !filtered_destinations.is_empty() {
-            assert_approx_eq!(filtered_destinations.values().sum::<f64>(), 1.0);
+            assert_approx_eq!(sum_destinations(&filtered_destinations), 1.0);
         }
 
         filtered_destinations
@@ -174,6 +226,30 @@ impl RunoffPattern {
                 .or_default() += factor;
         }
     }
+
+    /// Send a share of this tile's runoff out through an exit, after runoff
+    /// has already been simulated (e.g. when a river is rerouted through this
+    /// tile). All existing exits and destinations are scaled down by the
+    /// same share, so everything still sums to 1. The exit can be a new one
+    /// or an existing one.
+    pub fn divert_exit(
+        &mut self,
+        dir: TileDirection,
+        other_pattern: Option<&RunoffPattern>,
+        factor: f64,
+    ) {
+        for value in self
+            .exits
+            .values_mut()
+            .chain(self.destinations.values_mut())
+        {
+            *value *= 1.0 - factor;
+        }
+        // add_exit overwrites the exit factor, so hang onto the old one
+        let existing = self.exits.get(&dir).copied().unwrap_or(0.0);
+        self.add_exit(dir, other_pattern, factor);
+        self.exits.insert(dir, existing + factor);
+    }
 }
 
 impl HasHexPosition for RunoffPattern {
@@ -211,4 +287,99 @@ mod tests {
         );
         assert_approx_eq!(output.get(&RunoffDestination::Ocean).unwrap(), 0.75);
     }
+
+    #[test]
+    fn test_drainage() {
+        // Terminal drains to itself
+        let terminal = RunoffPattern::new(TilePoint::new_xy(0, 1));
+        assert_eq!(
+            terminal.drainage(),
+            DrainageDestination::Terminals {
+                terminals: vec![TilePoint::new_xy(0, 1)],
+                ocean: false
+            }
+        );
+
+        // Slope that only leads to the ocean
+        let mut sink = RunoffPattern::new(TilePoint::new_xy(0, 0));
+        sink.add_exit(TileDirection::W, None, 1.0);
+        assert_eq!(sink.drainage(), DrainageDestination::Ocean);
+
+        // Split between the ocean and the terminal
+        let mut split = RunoffPattern::new(TilePoint::new_xy(1, 0));
+        split.add_exit(TileDirection::E, None, 0.5);
+        split.add_exit(TileDirection::NNW, Some(&terminal), 0.5);
+        assert_eq!(
+            split.drainage(),
+            DrainageDestination::Terminals {
+                terminals: vec![TilePoint::new_xy(0, 1)],
+                ocean: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_divert_exit() {
+        let terminal = RunoffPattern::new(TilePoint::new_xy(0, 1));
+        let mut sink = RunoffPattern::new(TilePoint::new_xy(0, 0));
+        sink.add_exit(TileDirection::W, None, 1.0);
+
+        // Diverting toward a terminal adds it to the drainage, and all the
+        // factors still sum to 1
+        sink.divert_exit(TileDirection::NNW, Some(&terminal), 0.25);
+        assert_approx_eq!(sink.exits[&TileDirection::W], 0.75);
+        assert_approx_eq!(sink.exits[&TileDirection::NNW], 0.25);
+        assert_approx_eq!(sum_destinations(&sink.destinations), 1.0);
+        assert_eq!(
+            sink.drainage(),
+            DrainageDestination::Terminals {
+                terminals: vec![TilePoint::new_xy(0, 1)],
+                ocean: true
+            }
+        );
+
+        // Diverting through an existing exit adds onto it
+        sink.divert_exit(TileDirection::W, None, 0.5);
+        assert_approx_eq!(sink.exits[&TileDirection::W], 0.875);
+        assert_approx_eq!(sink.exits[&TileDirection::NNW], 0.125);
+        assert_approx_eq!(sum_destinations(&sink.destinations), 1.0);
+    }
+
+    /// The result of filtering should be bit-for-bit identical regardless of
+    /// the order in which the pattern's destination map iterates
+    #[test]
+    fn test_filter_destinations_order_independent() {
+        let destinations = [
+            (RunoffDestination::Ocean, 0.1),
+            (RunoffDestination::Terminal(TilePoint::new_xy(0, 1)), 0.2),
+            (RunoffDestination::Terminal(TilePoint::new_xy(1, 0)), 0.3),
+            (RunoffDestination::Terminal(TilePoint::new_xy(-1, 1)), 0.15),
+            (RunoffDestination::Terminal(TilePoint::new_xy(2, -3)), 0.25),
+        ];
+        let excluding = [TilePoint::new_xy(2, -3)];
+
+        let mut pattern_a = RunoffPattern::new(TilePoint::new_xy(0, 0));
+        pattern_a.destinations.extend(destinations);
+
+        // Use a big capacity and reversed insertion order, which will give
+        // this map a different iteration order from the first one
+        let mut pattern_b = RunoffPattern::new(TilePoint::new_xy(0, 0));
+        pattern_b.destinations =
+            HashMap::with_capacity_and_hasher(1024, FnvBuildHasher::default());
+        pattern_b
+            .destinations
+            .extend(destinations.into_iter().rev());
+
+        let output_a = pattern_a.filter_destinations(&excluding);
+        let output_b = pattern_b.filter_destinations(&excluding);
+        assert_eq!(output_a.len(), 4);
+        assert_eq!(
+            sorted_destinations(&output_a),
+            sorted_destinations(&output_b)
+        );
+        assert_eq!(
+            sum_destinations(&output_a).to_bits(),
+            sum_destinations(&output_b).to_bits()
+        );
+    }
 }
@@ -0,0 +1,117 @@
+use crate::{
+    util::unit::Celsius,
+    world::{
+        generate::{Generate, WorldBuilder},
+        hex::{HasHexPosition, HexDirection, TilePoint},
+        latitude, World,
+    },
+};
+
+/// Generate a temperature for each tile, based on latitude and elevation.
+/// Temperature scales linearly from the equator to the poles, then each tile
+/// is cooled according to how far above sea level it is. If ocean currents
+/// are enabled, coastal land is then pulled toward the temperature of the
+/// ocean upwind of it. This has to run **after elevation, wind, and ocean
+/// generation**.
+#[derive(Debug)]
+pub struct TemperatureGenerator;
+
+impl Generate for TemperatureGenerator {
+    fn generate(&self, world: &mut WorldBuilder) {
+        let cfg = &world.config.temperature;
+        let radius = world.config.radius;
+
+        for tile in world.tiles.values_mut() {
+            // 0 at the warmest line, 1 at either pole
+            let abs_latitude = thermal_latitude(
+                latitude(tile.position(), radius),
+                cfg.hemisphere_offset,
+            );
+            let sea_level_temperature = cfg.equator_temperature
+                + (cfg.pole_temperature - cfg.equator_temperature)
+                    * abs_latitude;
+
+            // Water tiles are at sea level as far as the air is concerned
+            let altitude = (tile.elevation() - World::SEA_LEVEL).0.max(0.0);
+            tile.set_temperature(
+                sea_level_temperature
+                    - Celsius(altitude * cfg.elevation_lapse_rate),
+            );
+        }
+
+        if cfg.current_strength > 0.0 {
+            apply_ocean_currents(world);
+        }
+    }
+}
+
+/// Get how far a latitude is from the warmest line of the world, in `[0, 1]`.
+/// The line sits at `hemisphere_offset`, and each side of it is scaled
+/// separately so that both poles end up at 1. See
+/// [TemperatureConfig::hemisphere_offset](crate::TemperatureConfig::hemisphere_offset).
+fn thermal_latitude(latitude: f64, hemisphere_offset: f64) -> f64 {
+    let relative = latitude - hemisphere_offset;
+    // Distance from the line to the pole on this side
+    let span = if relative >= 0.0 {
+        1.0 - hemisphere_offset
+    } else {
+        1.0 + hemisphere_offset
+    };
+    if span > 0.0 {
+        (relative.abs() / span).min(1.0)
+    } else {
+        // The line is right on the pole, so there's no hemisphere here
+        0.0
+    }
+}
+
+/// Shift the temperature of each land tile with ocean directly upwind toward
+/// the temperature of that ocean. See
+/// [TemperatureConfig::current_strength](crate::TemperatureConfig::current_strength).
+fn apply_ocean_currents(world: &mut WorldBuilder) {
+    let strength = world.config.temperature.current_strength;
+    let wrap_radius = world.config.wrap_radius();
+    // The wind blows toward a vertex, so the two tiles on the opposite side
+    // are the ones it's coming from
+    let (upwind_a, upwind_b) = world
+        .wind_direction()
+        .to_vertex_direction()
+        .opposite()
+        .flanking_directions();
+
+    // Calculate everything up front, so the adjustments don't compound
+    let adjustments: Vec<(TilePoint, Celsius)> = world
+        .tiles
+        .values()
+        .filter(|tile| !tile.is_water_biome())
+        .filter_map(|tile| {
+            let pos = tile.position();
+            let ocean_temperatures: Vec<Celsius> = [upwind_a, upwind_b]
+                .into_iter()
+                .filter_map(|dir| {
+                    world.tiles.get(&pos.adjacent_wrapping(dir, wrap_radius))
+                })
+                .filter(|adj_tile| adj_tile.is_water_biome())
+                .map(|adj_tile| adj_tile.temperature())
+                .collect();
+            if ocean_temperatures.is_empty() {
+                return None;
+            }
+
+            let ocean_temperature =
+                ocean_temperatures.iter().copied().sum::<Celsius>()
+                    / ocean_temperatures.len() as f64;
+            let temperature = tile.temperature();
+            Some((
+                pos,
+                temperature + (ocean_temperature - temperature) * strength,
+            ))
+        })
+        .collect();
+
+    for (pos, temperature) in adjustments {
+        if let Some(tile) = world.tiles.get_mut(&pos) {
+            tile.set_temperature(temperature);
+        }
+    }
+}
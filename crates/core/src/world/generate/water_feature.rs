@@ -1,11 +1,17 @@
 use crate::{
     world::{
         generate::{Generate, WorldBuilder},
-        hex::TileDirection,
+        hex::{Cluster, HexDirection, TileDirection, TilePoint},
         GeoFeature,
     },
-    Meter3,
+    Meter, Meter3,
 };
+use rand::{seq::SliceRandom, Rng};
+use strum::IntoEnumIterator;
+
+/// River segments that drop more than this between tiles are too steep to
+/// meander. See [crate::GeoFeatureConfig::meander_strength].
+const MEANDER_MAX_GRADIENT: Meter = Meter(2.0);
 
 /// A generator that creates lakes and rivers based on runoff level, runoff
 /// ingress, and runoff egress. This has to run AFTER runoff simulation.
@@ -46,5 +52,215 @@ impl Generate for WaterFeatureGenerator {
                 }
             }
         }
+
+        if cfg.meander_strength > 0.0 {
+            meander_rivers(world, cfg.meander_strength);
+        }
+
+        // Any lake that's big enough gets promoted to an inland sea
+        let lakes = Cluster::predicate_wrapping(
+            &mut world.tiles,
+            world.config.wrap_radius(),
+            |tile| tile.features().contains(&GeoFeature::Lake),
+        );
+        for lake in lakes {
+            if lake.tiles().len() >= cfg.inland_sea_size_threshold {
+                for (_, tile) in lake.into_tiles() {
+                    tile.add_feature(GeoFeature::InlandSea);
+                }
+            }
+        }
+    }
+}
+
+/// Reroute gently sloped river segments through a neighboring tile, to give
+/// rivers some bends. Each segment from tile A to tile B that's eligible has a
+/// `strength` chance of being replaced by A→C→B, where C is a tile adjacent
+/// to both. C has to be dry land with no river on it already, and its
+/// elevation has to be between A's and B's, so the river still flows downhill
+/// the whole way. Segments that flow into water are left alone, so river
+/// mouths don't move. Runoff ingress/egress and drainage are updated along
+/// the new path.
+fn meander_rivers(world: &mut WorldBuilder, strength: f64) {
+    let wrap_radius = world.config.wrap_radius();
+    let has_river = |features: &[GeoFeature]| {
+        features.iter().any(|feature| {
+            matches!(
+                feature,
+                GeoFeature::RiverEntrance { .. } | GeoFeature::RiverExit { .. }
+            )
+        })
+    };
+
+    // Grab every segment up front, in a stable order so that the RNG is
+    // consumed deterministically. Segments created by rerouting aren't
+    // rerouted again.
+    let tiles = &world.tiles;
+    let mut positions: Vec<TilePoint> = tiles.keys().copied().collect();
+    positions.sort();
+    let segments: Vec<(TilePoint, TileDirection, Meter3)> = positions
+        .into_iter()
+        .flat_map(move |pos| {
+            tiles[&pos].features().iter().filter_map(move |feature| {
+                match feature {
+                    GeoFeature::RiverExit { direction, volume } => {
+                        Some((pos, *direction, *volume))
+                    }
+                    _ => None,
+                }
+            })
+        })
+        .collect();
+
+    for (from_pos, direction, volume) in segments {
+        let to_pos = from_pos.adjacent_wrapping(direction, wrap_radius);
+        let from = &world.tiles[&from_pos];
+        let to = match world.tiles.get(&to_pos) {
+            Some(to) if !to.is_water_biome() => to,
+            _ => continue,
+        };
+        // Find the other end of the segment, so we can move it too
+        let entrance = to.features().iter().copied().find(|feature| {
+            matches!(
+                feature,
+                GeoFeature::RiverEntrance { direction: entrance, .. }
+                    if *entrance == direction.opposite()
+            )
+        });
+        let entrance_volume = match entrance {
+            Some(GeoFeature::RiverEntrance { volume, .. }) => volume,
+            _ => continue,
+        };
+        let (high, low) = (from.elevation(), to.elevation());
+        if high - low > MEANDER_MAX_GRADIENT || !world.rng.gen_bool(strength) {
+            continue;
+        }
+
+        // Find every tile next to both ends of the segment that the river
+        // could bend through, along with the directions A→C and C→B
+        let detours: Vec<(TilePoint, TileDirection, TileDirection)> =
+            TileDirection::iter()
+                .filter(|dir| *dir != direction)
+                .filter_map(|from_dir| {
+                    let pos = from_pos.adjacent_wrapping(from_dir, wrap_radius);
+                    let to_dir = TileDirection::iter().find(|dir| {
+                        pos.adjacent_wrapping(*dir, wrap_radius) == to_pos
+                    })?;
+                    let tile = world.tiles.get(&pos)?;
+                    let elevation = tile.elevation();
+                    // Terminals are left alone, since other tiles' drainage
+                    // points at them
+                    let eligible = !tile.is_water_biome()
+                        && tile.runoff() <= Meter3(0.0)
+                        && !tile.runoff_pattern().is_terminal()
+                        && !has_river(tile.features())
+                        && elevation <= high
+                        && elevation >= low;
+                    eligible.then_some((pos, from_dir, to_dir))
+                })
+                .collect();
+        let (detour_pos, from_dir, to_dir) =
+            match detours.choose(&mut world.rng) {
+                Some(detour) => *detour,
+                None => continue,
+            };
+
+        // Move the features and the runoff flow along with them, so runoff
+        // ingress/egress still lines up with the rivers
+        let from = world.tiles.get_mut(&from_pos).unwrap();
+        from.replace_feature(
+            GeoFeature::RiverExit { direction, volume },
+            GeoFeature::RiverExit {
+                direction: from_dir,
+                volume,
+            },
+        );
+        from.move_runoff_traversed(direction, from_dir);
+
+        // The detour now sends water to the end of the segment, so its
+        // drainage has to include wherever that water goes. The new exit
+        // gets the river's share of all the water leaving the detour.
+        let to_pattern = world.tiles[&to_pos].runoff_pattern().clone();
+        let detour = world.tiles.get_mut(&detour_pos).unwrap();
+        let river_share = volume.0 / (volume + detour.runoff_egress()).0;
+        let mut pattern = detour.runoff_pattern().clone();
+        pattern.divert_exit(to_dir, Some(&to_pattern), river_share);
+        detour.set_runoff_pattern(pattern);
+        detour.add_runoff_passthrough(volume, from_dir.opposite(), to_dir);
+        detour.add_feature(GeoFeature::RiverEntrance {
+            direction: from_dir.opposite(),
+            volume,
+        });
+        detour.add_feature(GeoFeature::RiverExit {
+            direction: to_dir,
+            volume,
+        });
+
+        let to = world.tiles.get_mut(&to_pos).unwrap();
+        to.replace_feature(
+            GeoFeature::RiverEntrance {
+                direction: direction.opposite(),
+                volume: entrance_volume,
+            },
+            GeoFeature::RiverEntrance {
+                direction: to_dir.opposite(),
+                volume: entrance_volume,
+            },
+        );
+        to.move_runoff_traversed(direction.opposite(), to_dir.opposite());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        world::hex::{HasHexPosition, TilePoint},
+        GeoFeatureConfig, WorldConfig,
+    };
+
+    #[test]
+    fn test_inland_sea() {
+        let config = WorldConfig {
+            radius: 5,
+            geo_feature: GeoFeatureConfig {
+                inland_sea_size_threshold: 5,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut world = WorldBuilder::new(&config);
+
+        // Big lake is the origin and all its neighbors (7 tiles), small lake is
+        // a single tile off on its own
+        let big_lake: Vec<TilePoint> = TilePoint::ORIGIN
+            .adjacents()
+            .chain([TilePoint::ORIGIN])
+            .collect();
+        let small_lake = TilePoint::new_xy(4, -4);
+        for tile in world.tiles.values_mut() {
+            let pos = tile.position();
+            if big_lake.contains(&pos) || pos == small_lake {
+                tile.set_runoff(Meter3(1.0));
+            } else {
+                tile.set_runoff(Meter3(0.0));
+            }
+        }
+
+        WaterFeatureGenerator.generate(&mut world);
+
+        for pos in &big_lake {
+            let features = world.tiles[pos].features();
+            assert!(features.contains(&GeoFeature::Lake));
+            assert!(
+                features.contains(&GeoFeature::InlandSea),
+                "Expected {pos} to be an inland sea"
+            );
+        }
+        let features = world.tiles[&small_lake].features();
+        assert!(features.contains(&GeoFeature::Lake));
+        assert!(!features.contains(&GeoFeature::InlandSea));
+        // Dry tiles get nothing
+        assert!(world.tiles[&TilePoint::new_xy(-4, 4)].features().is_empty());
     }
 }
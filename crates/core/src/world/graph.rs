@@ -0,0 +1,89 @@
+use crate::world::{
+    hex::{HasHexPosition, TileDirection, TilePoint, TilePointMap},
+    tile::Tile,
+    World,
+};
+
+/// The tiles of a world as an undirected graph, where each tile is a node and
+/// each pair of adjacent tiles is an edge. Nodes are referred to by index,
+/// which makes it easy to plug into graph algorithms (centrality,
+/// connectivity, etc.) that expect dense IDs. See [World::adjacency].
+#[derive(Clone, Debug)]
+pub struct TileGraph<'a> {
+    nodes: Vec<&'a Tile>,
+    indexes: TilePointMap<usize>,
+    edges: Vec<TileEdge>,
+}
+
+/// One edge in a [TileGraph], connecting two adjacent tiles. Edges are
+/// undirected, so each adjacent pair appears only once.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TileEdge {
+    /// Index of the first node
+    pub from: usize,
+
+    /// Index of the second node
+    pub to: usize,
+
+    /// Direction from the first node to the second. The direction from the
+    /// second node back to the first is the opposite of this.
+    pub direction: TileDirection,
+}
+
+impl<'a> TileGraph<'a> {
+    /// To avoid listing each edge twice, we only look for neighbors in half
+    /// of the directions. The other half are covered by the neighbor looking
+    /// back at us.
+    const EDGE_DIRECTIONS: [TileDirection; 3] =
+        [TileDirection::NNE, TileDirection::E, TileDirection::SSE];
+
+    pub(crate) fn new(world: &'a World) -> Self {
+        let nodes = world.tiles_sorted();
+        let indexes: TilePointMap<usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| (tile.position(), i))
+            .collect();
+
+        let wrap_radius = world.config().wrap_radius();
+        let mut edges = Vec::new();
+        for (from, tile) in nodes.iter().enumerate() {
+            for direction in Self::EDGE_DIRECTIONS {
+                let adj_pos =
+                    tile.position().adjacent_wrapping(direction, wrap_radius);
+                if let Some(&to) = indexes.get(&adj_pos) {
+                    edges.push(TileEdge {
+                        from,
+                        to,
+                        direction,
+                    });
+                }
+            }
+        }
+
+        Self {
+            nodes,
+            indexes,
+            edges,
+        }
+    }
+
+    /// All the nodes in the graph, sorted by position. A node's index in this
+    /// slice is its ID in [Self::edges].
+    pub fn nodes(&self) -> &[&'a Tile] {
+        &self.nodes
+    }
+
+    /// All the edges in the graph. Each adjacent pair of tiles appears once.
+    /// If the world wraps, edges are also included between tiles on opposite
+    /// edges of the world.
+    pub fn edges(&self) -> &[TileEdge] {
+        &self.edges
+    }
+
+    /// Get the node index of the tile at a position. Returns `None` if the
+    /// position isn't in the world.
+    pub fn node_index(&self, position: TilePoint) -> Option<usize> {
+        self.indexes.get(&position).copied()
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{
+    render::{raster, unit::Point2},
+    world::{tile::Tile, World},
+    RenderConfig, WorldRenderer,
+};
+
+/// A world sampled onto a square grid, with one band of float values for
+/// each of a few tile properties. This is meant for feeding worlds into GIS
+/// tools and other software that doesn't understand hex grids. Each grid cell
+/// takes its values from the tile under the center of that cell. Cells that
+/// aren't covered by any tile (i.e. the corners around a hexagonal world)
+/// hold [Self::NO_DATA]. See [World::to_grid].
+///
+/// The grid is laid out in screen space (see
+/// [WorldRenderer](crate::WorldRenderer)), with the first row at the north
+/// edge of the world. Since screen space y increases going south, the extents
+/// of the grid are given as the top-left ([Self::min]) and bottom-right
+/// ([Self::max]) corners.
+#[derive(Clone, Debug)]
+pub struct WorldGrid {
+    width: u32,
+    height: u32,
+    min: Point2,
+    max: Point2,
+    elevation: Vec<f32>,
+    rainfall: Vec<f32>,
+    runoff: Vec<f32>,
+}
+
+impl WorldGrid {
+    /// Value for cells that aren't covered by any tile
+    pub const NO_DATA: f32 = f32::NAN;
+    /// Magic bytes at the start of the [binary format](Self::to_bytes)
+    pub const MAGIC: [u8; 4] = *b"TRGD";
+    /// Current version of the [binary format](Self::to_bytes)
+    pub const VERSION: u32 = 1;
+
+    pub(crate) fn new(world: &World, resolution: u32) -> anyhow::Result<Self> {
+        // Grids always use the default screen space layout
+        let renderer = WorldRenderer::new(RenderConfig::default())?;
+        let samples = raster::sample_world(world, &renderer, resolution, 1)?;
+        let band = |f: fn(&Tile) -> f64| -> Vec<f32> {
+            samples
+                .tiles
+                .iter()
+                .map(|tile| tile.map_or(Self::NO_DATA, |tile| f(tile) as f32))
+                .collect()
+        };
+
+        Ok(Self {
+            width: samples.width,
+            height: samples.height,
+            min: samples.min,
+            max: samples.max,
+            elevation: band(|tile| tile.elevation().0),
+            rainfall: band(|tile| tile.rainfall().0),
+            runoff: band(|tile| tile.runoff().0),
+        })
+    }
+
+    /// Number of columns in the grid
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Number of rows in the grid
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Top-left (north-west) corner of the grid, in screen space
+    pub fn min(&self) -> Point2 {
+        self.min
+    }
+
+    /// Bottom-right (south-east) corner of the grid, in screen space
+    pub fn max(&self) -> Point2 {
+        self.max
+    }
+
+    /// Width (and height) of a single grid cell, in screen space
+    pub fn cell_size(&self) -> f64 {
+        if self.width == 0 {
+            0.0
+        } else {
+            (self.max.x - self.min.x) / self.width as f64
+        }
+    }
+
+    /// Elevation of each cell, in meters
+    pub fn elevation(&self) -> &[f32] {
+        &self.elevation
+    }
+
+    /// Rainfall of each cell, in cubic meters
+    pub fn rainfall(&self) -> &[f32] {
+        &self.rainfall
+    }
+
+    /// Runoff of each cell, in cubic meters
+    pub fn runoff(&self) -> &[f32] {
+        &self.runoff
+    }
+
+    /// All bands in the grid, with their names, in the order they appear in
+    /// the [binary format](Self::to_bytes). Each band holds one value per
+    /// cell, in row-major order starting from the top-left.
+    pub fn bands(&self) -> [(&'static str, &[f32]); 3] {
+        [
+            ("elevation", self.elevation.as_slice()),
+            ("rainfall", self.rainfall.as_slice()),
+            ("runoff", self.runoff.as_slice()),
+        ]
+    }
+
+    /// Serialize this grid into a simple binary band format. All numbers are
+    /// little-endian. The layout is:
+    ///
+    /// - Header
+    ///   - [Self::MAGIC] (4 bytes)
+    ///   - [Self::VERSION] (`u32`)
+    ///   - Width, height, and number of bands (`u32` each)
+    ///   - Min x, min y, max x, max y (`f64` each), see [Self::min] and
+    ///     [Self::max]
+    /// - Each band, in the order of [Self::bands]
+    ///   - Length of the band name in bytes (`u8`), then the UTF-8 name
+    ///   - `width * height` values (`f32` each), in row-major order starting
+    ///     from the top-left. Cells outside the world are NaN.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let bands = self.bands();
+        let mut buffer = Vec::new();
+        buffer.extend(Self::MAGIC);
+        buffer.extend(Self::VERSION.to_le_bytes());
+        buffer.extend(self.width.to_le_bytes());
+        buffer.extend(self.height.to_le_bytes());
+        buffer.extend((bands.len() as u32).to_le_bytes());
+        for value in [self.min.x, self.min.y, self.max.x, self.max.y] {
+            buffer.extend(value.to_le_bytes());
+        }
+
+        for (name, values) in bands {
+            buffer.push(name.len() as u8);
+            buffer.extend(name.as_bytes());
+            for value in values {
+                buffer.extend(value.to_le_bytes());
+            }
+        }
+        buffer
+    }
+}
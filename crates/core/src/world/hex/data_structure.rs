@@ -1,4 +1,4 @@
-use crate::world::hex::{TileDirection, TilePoint};
+use crate::world::hex::{HexCoordinateValue, TileDirection, TilePoint};
 use fnv::FnvBuildHasher;
 use indexmap::{map::Entry, IndexMap};
 use serde::{Deserialize, Serialize};
@@ -23,7 +23,7 @@ pub type TileDirectionMap<T> = HashMap<TileDirection, T, FnvBuildHasher>;
 /// especially post-world generation because we won't have to add or remove
 /// values at that point. Having static fields makes serialization in external
 /// apps a bit easier.
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct TileDirectionValues<T: Copy + Clone + Debug + PartialEq + Serialize>
 {
     pub northeast: T,
@@ -72,6 +72,100 @@ impl<T: Copy + Clone + Debug + Default + PartialEq + Serialize>
     }
 }
 
+/// A rectangular region of tile positions, in `(x, y)` coordinates. Both
+/// corners are inclusive. Since the axes of the hex coordinate system aren't
+/// perpendicular, this region is a rhombus on screen, but it makes for a
+/// simple 2D grid to lay out per-tile data in. Positions in the box are
+/// ordered in rows of increasing `y`, where each row runs in increasing `x`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileBoundingBox {
+    min: TilePoint,
+    max: TilePoint,
+}
+
+impl TileBoundingBox {
+    /// Create a box spanning the two given corners, in any order
+    pub fn new(a: TilePoint, b: TilePoint) -> Self {
+        Self {
+            min: TilePoint::new_xy(a.x().min(b.x()), a.y().min(b.y())),
+            max: TilePoint::new_xy(a.x().max(b.x()), a.y().max(b.y())),
+        }
+    }
+
+    /// Get the smallest box that contains all the given points. Returns `None`
+    /// if there are no points.
+    pub fn from_points(
+        points: impl IntoIterator<Item = TilePoint>,
+    ) -> Option<Self> {
+        points.into_iter().fold(None, |bbox, point| {
+            Some(match bbox {
+                None => Self::new(point, point),
+                Some(bbox) => Self::new(
+                    TilePoint::new_xy(
+                        bbox.min.x().min(point.x()),
+                        bbox.min.y().min(point.y()),
+                    ),
+                    TilePoint::new_xy(
+                        bbox.max.x().max(point.x()),
+                        bbox.max.y().max(point.y()),
+                    ),
+                ),
+            })
+        })
+    }
+
+    /// The corner of the box with the lowest `x` and `y`
+    pub fn min(&self) -> TilePoint {
+        self.min
+    }
+
+    /// The corner of the box with the highest `x` and `y`
+    pub fn max(&self) -> TilePoint {
+        self.max
+    }
+
+    /// Number of columns in the box, i.e. the span of `x` values
+    pub fn width(&self) -> usize {
+        (self.max.x() as i32 - self.min.x() as i32) as usize + 1
+    }
+
+    /// Number of rows in the box, i.e. the span of `y` values
+    pub fn height(&self) -> usize {
+        (self.max.y() as i32 - self.min.y() as i32) as usize + 1
+    }
+
+    /// Total number of positions in the box
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// Is the given position within this box?
+    pub fn contains(&self, point: TilePoint) -> bool {
+        (self.min.x()..=self.max.x()).contains(&point.x())
+            && (self.min.y()..=self.max.y()).contains(&point.y())
+    }
+
+    /// Get the index of a position within this box's row ordering. Returns
+    /// `None` if the position is outside the box.
+    pub fn index(&self, point: TilePoint) -> Option<usize> {
+        if !self.contains(point) {
+            return None;
+        }
+        let col = (point.x() as i32 - self.min.x() as i32) as usize;
+        let row = (point.y() as i32 - self.min.y() as i32) as usize;
+        Some(row * self.width() + col)
+    }
+
+    /// Iterate over every position in this box, in row order. See
+    /// [Self::index].
+    pub fn points(&self) -> impl Iterator<Item = TilePoint> {
+        let Self { min, max } = *self;
+        (min.y()..=max.y()).flat_map(move |y| {
+            (min.x()..=max.x()).map(move |x| TilePoint::new_xy(x, y))
+        })
+    }
+}
+
 /// A cluster is a set of contiguous tile points. All items in a cluster are
 /// adjacent to at least one other item in the cluster (unless the cluster is a
 /// singular item).
@@ -79,6 +173,9 @@ impl<T: Copy + Clone + Debug + Default + PartialEq + Serialize>
 pub struct Cluster<T> {
     tiles: TilePointIndexMap<T>,
     adjacents: TilePointSet,
+    /// If defined, adjacency wraps around a world of this radius. See
+    /// [TilePoint::wrap].
+    wrap_radius: Option<u16>,
 }
 
 impl<T: Debug> Cluster<T> {
@@ -92,13 +189,24 @@ impl<T: Debug> Cluster<T> {
     /// The predicate returns a result, to allow for fallible operations during
     /// the check. If any predicate returns an error, the function will abort
     /// and return an error.
-    pub fn predicate<P: Fn(&T) -> bool>(
+    ///
+    /// Clustering is deterministic: the same set of tiles will always yield
+    /// the same clusters, in the same order, regardless of the iteration order
+    /// of the input map. Clusters are ordered by their lowest position, and
+    /// that lowest position is always the first tile in each cluster, so it
+    /// can be used as a stable ID for the cluster.
+    ///
+    /// If `wrap_radius` is given, adjacency wraps around the edges of a world
+    /// with that radius. This means a cluster can span the seam of a wrapping
+    /// world.
+    pub fn predicate_wrapping<P: Fn(&T) -> bool>(
         tiles: &mut TilePointMap<T>,
+        wrap_radius: Option<u16>,
         predicate: P,
     ) -> Vec<Cluster<&'_ mut T>> {
         // Here's our algorithm:
         // - Create a pool of items that have yet to be clustered
-        // - Grab a random item from the pool
+        // - Grab the lowest (by position) item from the pool
         // - If it matches the predicate, do a BFS out from that item, including
         //   all items that match the predicate
         // - Once we run out of matchings items, consider the cluster complete
@@ -106,14 +214,25 @@ impl<T: Debug> Cluster<T> {
 
         // Copy our map into one that will hold the remaining items left to
         // check
-        let mut remaining: TilePointIndexMap<&mut T> =
+        let mut remaining: TilePointMap<&mut T> =
             tiles.iter_mut().map(|(pos, t)| (*pos, t)).collect();
         let mut clusters: Vec<Cluster<&mut T>> = Vec::new();
 
+        // Hash map iteration order isn't stable, so seed each cluster from a
+        // sorted list of positions instead. The BFS itself always visits
+        // neighbors in the same order, so this makes the whole thing stable
+        let mut seeds: Vec<TilePoint> = remaining.keys().copied().collect();
+        seeds.sort_unstable();
+
         // Grab the first unchecked item and start building a cluster around it.
         // This loop runs once per generated cluster, plus once per each failed
-        // attempt at a cluster (where the first item fails the predicate)
-        while let Some(first_entry) = remaining.pop() {
+        // attempt at a cluster (where the first item fails the predicate).
+        // Seeds that already got pulled into a cluster are skipped.
+        for seed in seeds {
+            let first_entry = match remaining.remove_entry(&seed) {
+                Some(entry) => entry,
+                None => continue,
+            };
             let mut cluster = TilePointIndexMap::default();
             // Start our BFS. We'll use a queue of the next items to check, and
             // seed it with our first item. It doesn't seem to matter if we
@@ -135,33 +254,42 @@ impl<T: Debug> Cluster<T> {
                     // map and add them to the queue
                     let rem = &mut remaining;
                     bfs_queue.extend(
-                        pos.adjacents().filter_map(move |adj_pos| {
-                            rem.remove_entry(&adj_pos)
-                        }),
+                        pos.adjacents_wrapping(wrap_radius).filter_map(
+                            move |adj_pos| rem.remove_entry(&adj_pos),
+                        ),
                     );
                 }
             }
 
             if !cluster.is_empty() {
-                clusters.push(Cluster::new(cluster));
+                clusters.push(Cluster::new_wrapping(cluster, wrap_radius));
             }
         }
 
         clusters
     }
 
-    pub fn new(tiles: TilePointIndexMap<T>) -> Self {
+    /// Create a new cluster whose adjacency wraps around the edges of a world
+    /// with the given radius (if any). See [TilePoint::wrap].
+    pub fn new_wrapping(
+        tiles: TilePointIndexMap<T>,
+        wrap_radius: Option<u16>,
+    ) -> Self {
         // Initialize the set of all tiles that are adjacent to (but not in) the
         // cluster
         let mut adjacents = TilePointSet::default();
         for pos in tiles.keys() {
-            for adj in pos.adjacents() {
+            for adj in pos.adjacents_wrapping(wrap_radius) {
                 if !tiles.contains_key(&adj) {
                     adjacents.insert(adj);
                 }
             }
         }
-        Self { tiles, adjacents }
+        Self {
+            tiles,
+            adjacents,
+            wrap_radius,
+        }
     }
 
     /// A reference to the map of tiles in this cluster
@@ -207,7 +335,7 @@ impl<T: Debug> Cluster<T> {
         }
         let tiles = &self.tiles; // cause closure capturing is kinda dumb
         let new_neighbors = pos
-            .adjacents()
+            .adjacents_wrapping(self.wrap_radius)
             .filter(|adj_pos| !tiles.contains_key(adj_pos));
         self.adjacents.extend(new_neighbors);
     }
@@ -235,6 +363,84 @@ impl<T: Debug> Cluster<T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bounding_box() {
+        let bbox = TileBoundingBox::from_points([
+            TilePoint::new_xy(2, -1),
+            TilePoint::new_xy(-1, 3),
+            TilePoint::new_xy(0, 0),
+        ])
+        .unwrap();
+        assert_eq!(
+            bbox,
+            TileBoundingBox::new(
+                TilePoint::new_xy(2, 3),
+                TilePoint::new_xy(-1, -1)
+            )
+        );
+        assert_eq!(bbox.min(), TilePoint::new_xy(-1, -1));
+        assert_eq!(bbox.max(), TilePoint::new_xy(2, 3));
+        assert_eq!((bbox.width(), bbox.height(), bbox.area()), (4, 5, 20));
+        assert!(!bbox.contains(TilePoint::new_xy(3, 0)));
+        assert_eq!(bbox.index(TilePoint::new_xy(3, 0)), None);
+
+        // Points are in index order, and cover the whole box
+        let points: Vec<TilePoint> = bbox.points().collect();
+        assert_eq!(points.len(), bbox.area());
+        for (i, point) in points.into_iter().enumerate() {
+            assert!(bbox.contains(point));
+            assert_eq!(bbox.index(point), Some(i));
+        }
+
+        assert_eq!(TileBoundingBox::from_points(Vec::new()), None);
+    }
+
+    /// Clustering should give the same clusters, in the same order, no matter
+    /// how the input map was built
+    #[test]
+    fn test_cluster_predicate_deterministic() {
+        // Build a hex-shaped set of tiles with assorted values
+        let radius: i16 = 8;
+        let entries: Vec<(TilePoint, i16)> = (-radius..=radius)
+            .flat_map(|x| (-radius..=radius).map(move |y| (x, y)))
+            .filter(|(x, y)| (x + y).abs() <= radius)
+            .map(|(x, y)| (TilePoint::new_xy(x, y), x * y + x - y))
+            .collect();
+        // Carve out a bunch of separate clusters
+        let predicate = |v: &i16| v % 3 == 0 || v % 5 == 0;
+
+        let mut map_a: TilePointMap<i16> = entries.iter().copied().collect();
+        // Use a different capacity and insertion order, which will change the
+        // iteration order of the map
+        let mut map_b: TilePointMap<i16> =
+            HashMap::with_capacity_and_hasher(4096, FnvBuildHasher::default());
+        map_b.extend(entries.iter().rev().copied());
+
+        let cluster_keys =
+            |map: &mut TilePointMap<i16>| -> Vec<Vec<TilePoint>> {
+                Cluster::predicate_wrapping(map, None, predicate)
+                    .iter()
+                    .map(|cluster| cluster.tiles().keys().copied().collect())
+                    .collect()
+            };
+        let keys_a = cluster_keys(&mut map_a);
+        assert!(keys_a.len() > 1);
+        assert_eq!(keys_a, cluster_keys(&mut map_b));
+        // Run it again on the same map, for good measure
+        assert_eq!(keys_a, cluster_keys(&mut map_a));
+
+        // Each cluster's first tile is its lowest, and clusters are sorted by
+        // that first tile
+        for tiles in &keys_a {
+            assert_eq!(tiles[0], *tiles.iter().min().unwrap());
+        }
+        let first_tiles: Vec<TilePoint> =
+            keys_a.iter().map(|tiles| tiles[0]).collect();
+        let mut sorted = first_tiles.clone();
+        sorted.sort_unstable();
+        assert_eq!(first_tiles, sorted);
+    }
+
     #[test]
     fn test_hex_direction_map_to_values() {
         // Make sure each direction is mapped correctly
@@ -83,9 +83,22 @@ pub trait HexThing: HexCoordinateValue {
 /// The x and y coordinates are stored as `i16`s. We'll never have a world with
 /// a radius of more than 32k (that'd be ~4 billion tiles), so this saves on
 /// a lot of memory.
+///
+/// Tile points are ordered by `x`, then `y`. This ordering has no geometric
+/// meaning, it just gives us a stable way to sort tiles.
 #[cfg_attr(feature = "js", wasm_bindgen)]
 #[derive(
-    Copy, Clone, Debug, PartialEq, Eq, Hash, Display, Serialize, Deserialize,
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Display,
+    Serialize,
+    Deserialize,
 )]
 #[display(fmt = "({}, {}, {})", "self.x()", "self.y()", "self.z()")]
 pub struct TilePoint {
@@ -154,6 +167,75 @@ impl TilePoint {
         TileDirection::iter().map(move |dir| self.adjacent(dir))
     }
 
+    /// Get the location of the tile adjacent to this one, wrapping around the
+    /// edges of the world if `wrap_radius` is given. See [Self::wrap].
+    pub fn adjacent_wrapping(
+        self,
+        direction: TileDirection,
+        wrap_radius: Option<u16>,
+    ) -> TilePoint {
+        let adjacent = self.adjacent(direction);
+        match wrap_radius {
+            Some(radius) => adjacent.wrap(radius),
+            None => adjacent,
+        }
+    }
+
+    /// Get an iterator of all the tile points directly adjacent to this one,
+    /// wrapping around the edges of the world if `wrap_radius` is given. The
+    /// iterator will always contain exactly 6 values, although on very small
+    /// worlds some of them may be duplicates.
+    pub fn adjacents_wrapping(
+        self,
+        wrap_radius: Option<u16>,
+    ) -> impl Iterator<Item = TilePoint> {
+        TileDirection::iter()
+            .map(move |dir| self.adjacent_wrapping(dir, wrap_radius))
+    }
+
+    /// Map this point onto a toroidal world of the given radius. The world is
+    /// treated as one copy in an infinite tiling of hexagonal worlds, so a
+    /// point that falls off one edge comes back in on the opposite edge.
+    /// Points already within the world are returned unchanged.
+    pub fn wrap(self, radius: u16) -> TilePoint {
+        // https://www.redblobgames.com/grids/hexagons/#wraparound
+        // Use i32 so the math can't overflow for big worlds
+        let r = radius as i32;
+        // Centers of the six neighboring copies of the world, as (x, y)
+        let mirrors = [
+            (2 * r + 1, -r),
+            (r + 1, -2 * r - 1),
+            (-r, -r - 1),
+            (-2 * r - 1, r),
+            (-r - 1, 2 * r + 1),
+            (r, r + 1),
+        ];
+        let distance = |x: i32, y: i32| (x.abs() + y.abs() + (x + y).abs()) / 2;
+
+        // Keep hopping to the closest copy of the world until we land in the
+        // main one. For anything just off the edge this is a single hop.
+        let (mut x, mut y) = (self.x as i32, self.y as i32);
+        while distance(x, y) > r {
+            let (mx, my) = mirrors
+                .into_iter()
+                .min_by_key(|(mx, my)| distance(x - mx, y - my))
+                .unwrap();
+            x -= mx;
+            y -= my;
+        }
+        Self::new_xy(x as i16, y as i16)
+    }
+
+    /// Shift this point by the given offset, i.e. add the two points together.
+    /// Returns `None` if any component of the result doesn't fit in an `i16`.
+    pub fn checked_translate(self, offset: TilePoint) -> Option<TilePoint> {
+        let x = self.x.checked_add(offset.x)?;
+        let y = self.y.checked_add(offset.y)?;
+        // z is derived from x and y, so make sure it's representable too
+        x.checked_add(y)?.checked_neg()?;
+        Some(Self::new_xy(x, y))
+    }
+
     /// Calculate the path distance between two tiles, meaning the number of
     /// hops it takes to get from one to the other. 0 if the points are equal,
     /// 1 if the tiles are adjacent, 2 if there is 1 tile between them, etc.
@@ -166,6 +248,72 @@ impl TilePoint {
             // are always separated by two cube edges
             / 2
     }
+
+    /// Get every tile that's exactly `radius` steps away from this one, i.e.
+    /// a hexagonal ring around this tile. The ring starts at the west corner
+    /// and goes clockwise. A ring of radius 0 is just this tile, otherwise it
+    /// holds `6 * radius` tiles.
+    pub fn ring(self, radius: u16) -> Vec<TilePoint> {
+        // https://www.redblobgames.com/grids/hexagons/#rings
+        if radius == 0 {
+            return vec![self];
+        }
+
+        // Walk out to the west corner, then walk along each of the 6 sides.
+        // Each side runs parallel to the direction two steps clockwise of the
+        // corner that it starts from.
+        let mut point =
+            (0..radius).fold(self, |point, _| point.adjacent(TileDirection::W));
+        let mut ring = Vec::with_capacity(6 * radius as usize);
+        for &direction in TileDirection::CLOCKWISE {
+            for _ in 0..radius {
+                ring.push(point);
+                point = point.adjacent(direction);
+            }
+        }
+        ring
+    }
+
+    /// Get every tile along a straight line from this tile to another, in
+    /// order, including both endpoints. Consecutive tiles in the line are
+    /// always adjacent, so the line holds `self.distance_to(other) + 1` tiles.
+    pub fn line_to(self, other: TilePoint) -> Vec<TilePoint> {
+        // https://www.redblobgames.com/grids/hexagons/#line-drawing
+        let distance = self.distance_to(other);
+        if distance == 0 {
+            return vec![self];
+        }
+
+        // Nudge the line off-center a tiny bit, so that a line running exactly
+        // along the edge between two tiles always rounds to the same side. The
+        // implied nudge on z is -3e-6, so the components still sum to 0.
+        let (x0, y0) = (self.x as f64 + 1e-6, self.y as f64 + 2e-6);
+        let (x1, y1) = (other.x as f64 + 1e-6, other.y as f64 + 2e-6);
+        (0..=distance)
+            .map(|i| {
+                let t = i as f64 / distance as f64;
+                Self::nearest(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+            })
+            .collect()
+    }
+
+    /// Find the tile closest to a fractional hex position, given as `(x, y)`.
+    /// `z` is implied, since `x + y + z = 0`.
+    pub(crate) fn nearest(x: f64, y: f64) -> TilePoint {
+        // https://www.redblobgames.com/grids/hexagons/#rounding
+        let z = -x - y;
+
+        // Round each component, then fix whichever one strayed the furthest so
+        // that we still have x+y+z=0
+        let (mut rx, mut ry, rz) = (x.round(), y.round(), z.round());
+        let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+        if dx > dy && dx > dz {
+            rx = -ry - rz;
+        } else if dy > dz {
+            ry = -rx - rz;
+        }
+        TilePoint::new_xy(rx as i16, ry as i16)
+    }
 }
 
 impl HexCoordinateValue for TilePoint {
@@ -435,7 +583,9 @@ pub trait HasHexPosition: Sized {
 ///
 /// See this page for more info (we use "pointy topped" tiles):
 /// https://www.redblobgames.com/grids/hexagons/#coordinates-cube
-#[derive(Copy, Clone, Debug, EnumIter, PartialEq, Eq, Hash)]
+#[derive(
+    Copy, Clone, Debug, EnumIter, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
 pub enum HexAxis {
     X,
     Y,
@@ -450,7 +600,7 @@ pub enum HexAxis {
 ///
 /// See this page for more info (we use "pointy topped" tiles):
 /// https://www.redblobgames.com/grids/hexagons/#coordinates-cube
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct HexAxialDirection {
     pub axis: HexAxis,
     pub positive: bool,
@@ -510,6 +660,51 @@ impl HexAxialDirection {
             -1
         }
     }
+
+    /// Get a vector of a single step along this axial direction
+    pub fn to_vector(self) -> HexVector<i16> {
+        let signum = self.signum();
+        match self.axis {
+            HexAxis::X => HexVector::new(signum, 0, 0),
+            HexAxis::Y => HexVector::new(0, signum, 0),
+            HexAxis::Z => HexVector::new(0, 0, signum),
+        }
+    }
+
+    /// Get the [VertexDirection] that lines up with this axial direction.
+    /// This is the inverse of [VertexDirection::to_axial].
+    pub fn to_vertex_direction(self) -> VertexDirection {
+        match (self.axis, self.positive) {
+            (HexAxis::Z, true) => VertexDirection::N,
+            (HexAxis::X, false) => VertexDirection::ENE,
+            (HexAxis::Y, true) => VertexDirection::ESE,
+            (HexAxis::Z, false) => VertexDirection::S,
+            (HexAxis::X, true) => VertexDirection::WSW,
+            (HexAxis::Y, false) => VertexDirection::WNW,
+        }
+    }
+
+    /// Get the [TileDirection] closest to this axial direction. An axial
+    /// direction points at a vertex, which sits exactly between two tile
+    /// directions, so this breaks the tie by rounding clockwise. E.g.
+    /// [Self::Z_POS] (north) becomes [TileDirection::NNE].
+    pub fn to_tile_direction(self) -> TileDirection {
+        let (_, clockwise) =
+            self.to_vertex_direction().adjacent_tile_directions();
+        clockwise
+    }
+}
+
+impl From<VertexDirection> for HexAxialDirection {
+    fn from(direction: VertexDirection) -> Self {
+        direction.to_axial()
+    }
+}
+
+impl From<HexAxialDirection> for VertexDirection {
+    fn from(direction: HexAxialDirection) -> Self {
+        direction.to_vertex_direction()
+    }
 }
 
 /// A linear direction in a hex world. Similar to how the [HexPoint] trait
@@ -537,6 +732,16 @@ pub trait HexDirection: 'static + Copy + Eq + Sized {
     /// whatever direction first follows North in the clockwise ordering.
     const CLOCKWISE: &'static [Self];
 
+    /// The other class of directions that interleaves with this one around
+    /// the compass. Each direction in this class sits exactly between two
+    /// directions of the other class, and vice versa.
+    type Other: HexDirection;
+
+    /// Get the two directions of the [Self::Other] class that flank this
+    /// direction on the compass, in clockwise order. E.g. for a
+    /// [TileDirection], these are the vertices at either end of that side.
+    fn flanking_directions(self) -> (Self::Other, Self::Other);
+
     /// Get the index of the given direction within the clockwise ordering of
     /// this class
     fn clockwise_index(self) -> usize {
@@ -590,6 +795,12 @@ impl HexDirection for TileDirection {
     const CLOCKWISE: &'static [Self] =
         &[Self::NNE, Self::E, Self::SSE, Self::SSW, Self::W, Self::NNW];
 
+    type Other = VertexDirection;
+
+    fn flanking_directions(self) -> (VertexDirection, VertexDirection) {
+        self.adjacent_vertex_directions()
+    }
+
     /// Get an vector offset that would move a point one tile in this direction
     fn to_vector(self) -> HexVector<i16> {
         match self {
@@ -617,6 +828,15 @@ impl TileDirection {
         let right = clockwise[(index + 1) % clockwise.len()];
         (left, right)
     }
+
+    /// Get the two axial directions that make up this tile direction. These
+    /// line up with the vertices at either end of this side (see
+    /// [Self::adjacent_vertex_directions]), and the sum of their vectors is
+    /// this direction's vector.
+    pub fn to_axial(self) -> (HexAxialDirection, HexAxialDirection) {
+        let (left, right) = self.adjacent_vertex_directions();
+        (left.to_axial(), right.to_axial())
+    }
 }
 
 /// The 6 directions you can go from the center of a tile to one of its
@@ -649,6 +869,12 @@ impl HexDirection for VertexDirection {
     const CLOCKWISE: &'static [Self] =
         &[Self::N, Self::ENE, Self::ESE, Self::S, Self::WSW, Self::WNW];
 
+    type Other = TileDirection;
+
+    fn flanking_directions(self) -> (TileDirection, TileDirection) {
+        self.adjacent_tile_directions()
+    }
+
     /// Get an vector offset that would translate a point from the center
     /// of a tile to one of its vertices (in the corresponding direction).
     /// 3 of these offsets are a single step, while the others are two steps.
@@ -693,12 +919,71 @@ impl VertexDirection {
         let right = clockwise[index];
         (left, right)
     }
+
+    /// Get the axial direction that lines up with this vertex direction. Each
+    /// vertex sits at the end of one of the three axes. The two-step vectors
+    /// from [HexDirection::to_vector] are equivalent to a single step in the
+    /// opposite direction on the remaining axis, since a step of `(1, 1, 1)`
+    /// doesn't move a vertex on screen.
+    pub fn to_axial(self) -> HexAxialDirection {
+        match self {
+            Self::N => HexAxialDirection::Z_POS,
+            Self::ENE => HexAxialDirection::X_NEG,
+            Self::ESE => HexAxialDirection::Y_POS,
+            Self::S => HexAxialDirection::Z_NEG,
+            Self::WSW => HexAxialDirection::X_POS,
+            Self::WNW => HexAxialDirection::Y_NEG,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_line_to() {
+        let p0 = TilePoint::ORIGIN;
+        assert_eq!(p0.line_to(p0), vec![p0]);
+
+        // Straight shot along one direction
+        let p1 = TilePoint::new_xy(3, -3);
+        assert_eq!(
+            p0.line_to(p1),
+            vec![p0, TilePoint::new_xy(1, -1), TilePoint::new_xy(2, -2), p1]
+        );
+
+        // Diagonal line should still be a connected path
+        let p2 = TilePoint::new_xy(4, -7);
+        let line = p2.line_to(p0);
+        assert_eq!(line.len(), p0.distance_to(p2) + 1);
+        assert_eq!(line.first(), Some(&p2));
+        assert_eq!(line.last(), Some(&p0));
+        for pair in line.windows(2) {
+            assert_eq!(pair[0].distance_to(pair[1]), 1);
+        }
+    }
+
+    #[test]
+    fn test_ring() {
+        let p0 = TilePoint::new_xy(2, -1);
+        assert_eq!(p0.ring(0), vec![p0]);
+
+        let ring = p0.ring(1);
+        assert_eq!(ring.len(), 6);
+        assert!(p0.adjacents().all(|adj| ring.contains(&adj)));
+
+        let ring = p0.ring(3);
+        assert_eq!(ring.len(), 18);
+        assert_eq!(ring[0], TilePoint::new_xy(5, -4));
+        for (i, point) in ring.iter().enumerate() {
+            assert_eq!(p0.distance_to(*point), 3);
+            // Consecutive tiles should be adjacent, including wrapping around
+            // from the last tile to the first
+            assert_eq!(point.distance_to(ring[(i + 1) % ring.len()]), 1);
+        }
+    }
+
     #[test]
     fn test_distance_to() {
         let p0 = TilePoint::ORIGIN;
@@ -718,6 +1003,38 @@ mod tests {
         assert_eq!(p2.distance_to(p3), 2);
     }
 
+    #[test]
+    fn test_wrap() {
+        let radius = 3;
+        // Points inside the world are untouched
+        for pos in [TilePoint::ORIGIN, TilePoint::new_xy(3, -3)] {
+            assert_eq!(pos.wrap(radius), pos);
+        }
+
+        // Stepping off an edge brings us back on the opposite edge
+        let edge = TilePoint::new_xy(3, 0);
+        for dir in TileDirection::iter() {
+            let wrapped = edge.adjacent_wrapping(dir, Some(radius));
+            assert!(wrapped.distance_to(TilePoint::ORIGIN) <= 3);
+            // Stepping back should always return us to where we started
+            assert_eq!(
+                wrapped.adjacent_wrapping(dir.opposite(), Some(radius)),
+                edge
+            );
+        }
+        assert_eq!(
+            edge.adjacent_wrapping(TileDirection::W, Some(radius)),
+            TilePoint::new_xy(-3, 2)
+        );
+        assert_eq!(
+            edge.adjacent_wrapping(TileDirection::W, None),
+            edge.adjacent(TileDirection::W)
+        );
+
+        // A full world away in any direction lands on the same tile
+        assert_eq!(TilePoint::new_xy(7, -3).wrap(radius), TilePoint::ORIGIN);
+    }
+
     #[test]
     fn test_adjacent_vertex_directions() {
         assert_eq!(
@@ -773,4 +1090,121 @@ mod tests {
             (TileDirection::W, TileDirection::NNW)
         );
     }
+
+    #[test]
+    fn test_flanking_directions() {
+        for dir in TileDirection::CLOCKWISE {
+            assert_eq!(
+                dir.flanking_directions(),
+                dir.adjacent_vertex_directions()
+            );
+        }
+        for dir in VertexDirection::CLOCKWISE {
+            assert_eq!(
+                dir.flanking_directions(),
+                dir.adjacent_tile_directions()
+            );
+        }
+    }
+
+    #[test]
+    fn test_vertex_direction_to_axial() {
+        let expected = [
+            (VertexDirection::N, HexAxialDirection::Z_POS),
+            (VertexDirection::ENE, HexAxialDirection::X_NEG),
+            (VertexDirection::ESE, HexAxialDirection::Y_POS),
+            (VertexDirection::S, HexAxialDirection::Z_NEG),
+            (VertexDirection::WSW, HexAxialDirection::X_POS),
+            (VertexDirection::WNW, HexAxialDirection::Y_NEG),
+        ];
+        for (vertex_dir, axial_dir) in expected {
+            assert_eq!(vertex_dir.to_axial(), axial_dir);
+            assert_eq!(HexAxialDirection::from(vertex_dir), axial_dir);
+            assert_eq!(axial_dir.to_vertex_direction(), vertex_dir);
+            assert_eq!(VertexDirection::from(axial_dir), vertex_dir);
+
+            // The vertex vector should match the axial vector, give or take a
+            // step of (1, 1, 1)
+            let vertex_vec = vertex_dir.to_vector();
+            let axial_vec = axial_dir.to_vector();
+            let diff = vertex_vec - axial_vec;
+            assert!(
+                diff.x == diff.y && diff.y == diff.z,
+                "{vertex_dir:?}: {vertex_vec} doesn't line up with {axial_vec}"
+            );
+        }
+
+        // Walking the vertices clockwise should walk the axes clockwise too
+        let offset = HexAxialDirection::CLOCKWISE
+            .iter()
+            .position(|dir| *dir == VertexDirection::N.to_axial())
+            .unwrap();
+        for (i, vertex_dir) in VertexDirection::CLOCKWISE.iter().enumerate() {
+            assert_eq!(
+                vertex_dir.to_axial(),
+                HexAxialDirection::CLOCKWISE[(i + offset) % 6]
+            );
+        }
+    }
+
+    #[test]
+    fn test_axial_to_tile_direction() {
+        let expected = [
+            (HexAxialDirection::Z_POS, TileDirection::NNE),
+            (HexAxialDirection::X_NEG, TileDirection::E),
+            (HexAxialDirection::Y_POS, TileDirection::SSE),
+            (HexAxialDirection::Z_NEG, TileDirection::SSW),
+            (HexAxialDirection::X_POS, TileDirection::W),
+            (HexAxialDirection::Y_NEG, TileDirection::NNW),
+        ];
+        for (axial_dir, tile_dir) in expected {
+            assert_eq!(axial_dir.to_tile_direction(), tile_dir);
+            // The side should be the first one clockwise of the vertex that
+            // the axial direction points to
+            let (start, _) = tile_dir.adjacent_vertex_directions();
+            assert_eq!(start, axial_dir.to_vertex_direction());
+        }
+    }
+
+    #[test]
+    fn test_tile_direction_to_axial() {
+        let expected = [
+            (
+                TileDirection::NNE,
+                (HexAxialDirection::Z_POS, HexAxialDirection::X_NEG),
+            ),
+            (
+                TileDirection::E,
+                (HexAxialDirection::X_NEG, HexAxialDirection::Y_POS),
+            ),
+            (
+                TileDirection::SSE,
+                (HexAxialDirection::Y_POS, HexAxialDirection::Z_NEG),
+            ),
+            (
+                TileDirection::SSW,
+                (HexAxialDirection::Z_NEG, HexAxialDirection::X_POS),
+            ),
+            (
+                TileDirection::W,
+                (HexAxialDirection::X_POS, HexAxialDirection::Y_NEG),
+            ),
+            (
+                TileDirection::NNW,
+                (HexAxialDirection::Y_NEG, HexAxialDirection::Z_POS),
+            ),
+        ];
+        for (tile_dir, axial_dirs) in expected {
+            assert_eq!(tile_dir.to_axial(), axial_dirs);
+
+            // One step to a neighbor is one step along each axial direction
+            let tile_vec = tile_dir.to_vector();
+            let sum = axial_dirs.0.to_vector() + axial_dirs.1.to_vector();
+            assert_eq!(
+                (tile_vec.x, tile_vec.y, tile_vec.z),
+                (sum.x, sum.y, sum.z),
+                "{tile_dir:?}"
+            );
+        }
+    }
 }
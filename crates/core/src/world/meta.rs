@@ -0,0 +1,47 @@
+use crate::{HexAxialDirection, TileBoundingBox, WorldConfig};
+use serde::{Deserialize, Serialize};
+
+/// Provenance info about how a world was generated. This is stored (and
+/// serialized) alongside the world, so that tools can detect saved worlds that
+/// came from an incompatible version of Terra, or from a different config
+/// than expected. See [World::meta](crate::World::meta).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorldMeta {
+    /// Version of Terra that generated the world. Empty for worlds that were
+    /// saved before this was tracked, or that were assembled from tiles alone
+    /// (e.g. [World::from_json_tiles_only](crate::World::from_json_tiles_only)).
+    pub terra_version: String,
+
+    /// Hash of the generating config. See [WorldConfig::config_hash].
+    pub config_hash: u64,
+
+    /// The resolved numeric seed of the generating config. See
+    /// [Seed::to_u64](crate::Seed::to_u64).
+    pub created_seed: u64,
+
+    /// If this world is just one region of a larger world (see
+    /// [World::to_json_region](crate::World::to_json_region)), this is the
+    /// box that the region was cut from. `None` for complete worlds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<TileBoundingBox>,
+
+    /// The prevailing wind direction that rainfall was simulated with. `None`
+    /// for worlds that were saved before this was tracked, or that were
+    /// assembled from tiles alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_direction: Option<HexAxialDirection>,
+}
+
+impl WorldMeta {
+    /// Build metadata for a world generated from the given config, by this
+    /// version of Terra
+    pub(crate) fn new(config: &WorldConfig) -> Self {
+        Self {
+            terra_version: env!("CARGO_PKG_VERSION").to_owned(),
+            config_hash: config.config_hash(),
+            created_seed: config.seed.to_u64(),
+            region: None,
+            wind_direction: None,
+        }
+    }
+}
@@ -1,27 +1,61 @@
+mod bridge;
+mod coast;
+mod continent;
+#[cfg(feature = "json")]
+mod export;
+mod flood;
 mod generate;
+mod graph;
+mod grid;
 pub mod hex;
+mod meta;
+mod stats;
+mod terrain;
 pub mod tile;
+mod watershed;
 
 use crate::{
+    render::{hex_to_screen_space, unit::Point2},
     timed,
     util::{
+        cmp_unwrap,
         range::NumRange,
-        unit::{Meter, Meter3},
+        unit::{Celsius, Meter, Meter3},
     },
     world::{
-        generate::WorldBuilder,
-        hex::{TileDirection, TilePointMap},
+        generate::{GeneratedWorld, WorldBuilder},
+        hex::{
+            HasHexPosition, HexAxialDirection, HexAxis, HexCoordinateValue,
+            HexDirection, TileBoundingBox, TileDirection, TilePoint,
+            TilePointMap, TilePointSet,
+        },
         tile::Tile,
     },
     WorldConfig,
 };
-use anyhow::Context;
-use log::info;
+use anyhow::{anyhow, bail, ensure, Context};
+pub use continent::Continent;
+use derive_more::Display;
+#[cfg(feature = "json")]
+pub use export::JsonExportOptions;
+pub use flood::flood_fill;
+pub use generate::TileNoiseFn;
+pub(crate) use generate::BYTES_PER_TILE;
+pub use graph::{TileEdge, TileGraph};
+pub use grid::WorldGrid;
+use log::{info, warn};
+pub use meta::WorldMeta;
+use rand::{seq::SliceRandom, RngCore, SeedableRng};
+use rand_pcg::Pcg64;
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+pub use stats::{BiomeClimate, WorldStats};
+use std::{collections::HashMap, fmt::Debug};
+use strum::{EnumIter, IntoEnumIterator};
+pub use terrain::TerrainClass;
 use validator::Validate;
 #[cfg(feature = "js")]
 use wasm_bindgen::prelude::*;
+pub use watershed::WatershedId;
 
 /// A fully generated world. Contains a collection of tiles as well the
 /// configuration that was used to generate this world.
@@ -42,6 +76,27 @@ use wasm_bindgen::prelude::*;
 /// reloaded via [World::from_bin]. Currently the binary format is
 /// [CBOR](https://cbor.io/), but that is subject to change so beware of that if
 /// you write other programs that load the format.
+///
+/// ### Compatibility
+/// Every generated tile field is required when loading a world, in both
+/// formats. Saves from older versions of Terra that are missing some of
+/// those fields (e.g. humidity) are rejected with an error, rather than
+/// loaded with zeroed-out data. Regenerate those worlds from their config
+/// instead.
+///
+/// ## Determinism
+/// Generation is deterministic based on the config, so the same config will
+/// always generate the same world on the same platform. Across platforms (e.g.
+/// native vs Wasm), there are a few known spots where floating point results
+/// can diverge slightly:
+///
+/// - `powf`, which is used for the noise exponent and the evaporation spread
+///   exponent, is not guaranteed to give identical results across
+///   platforms/libm implementations
+/// - Any float accumulation over a hash map depends on iteration order, which
+///   can change based on pointer width. Runoff simulation avoids this by always
+///   summing and processing in sorted order. If you add new accumulations over
+///   hash maps during generation, make sure to do the same.
 #[cfg_attr(feature = "js", wasm_bindgen)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct World {
@@ -54,6 +109,24 @@ pub struct World {
     // Serialize as a vec because tile points can't be keys
     #[serde(with = "crate::util::serde_tile_point_map_to_vec")]
     tiles: TilePointMap<Tile>,
+
+    /// Info about how this world was generated. Worlds saved before this was
+    /// tracked get a default (empty) value.
+    #[serde(default)]
+    meta: WorldMeta,
+
+    /// Intermediate data captured during generation, if
+    /// [WorldConfig::capture_layers] was enabled. These aren't serialized,
+    /// and aren't carried over to worlds derived from this one (e.g. via
+    /// [Self::translate]), since they may not line up with the new tiles.
+    #[serde(skip)]
+    layers: HashMap<String, TilePointMap<f64>>,
+
+    /// Checkpoint of the generation RNG from just before the climate steps,
+    /// used by [Self::resimulate_water] to replay the same random draws. Like
+    /// layers, this isn't serialized or carried over to derived worlds.
+    #[serde(skip)]
+    climate_rng: Option<Pcg64>,
 }
 
 // Non-Wasm API
@@ -73,12 +146,92 @@ impl World {
     /// values. We use this range to map to humidity.
     pub const RAINFALL_SOFT_RANGE: NumRange<Meter3, f64> =
         NumRange::new(Meter3(0.0), Meter3(5.0));
+    /// Water freezes at or below this temperature
+    pub const FREEZING_POINT: Celsius = Celsius(0.0);
+    /// Value used in a [biome map](Self::biome_map) for positions in the
+    /// bounding box that don't have a tile
+    pub const BIOME_MAP_EMPTY: u8 = u8::MAX;
 
     /// Get a reference to the config that defines this world.
     pub fn config(&self) -> &WorldConfig {
         &self.config
     }
 
+    /// Get info about how this world was generated: the version of Terra and
+    /// a hash of the config. For a world loaded from a file, this is the info
+    /// from when it was originally generated. See [WorldMeta].
+    pub fn meta(&self) -> &WorldMeta {
+        &self.meta
+    }
+
+    /// Get an intermediate data layer that was captured during generation, by
+    /// name. Layers are only captured if [WorldConfig::capture_layers] was
+    /// enabled, see that field for a list of the available layers. Returns
+    /// `None` if the layer wasn't captured, or if this world wasn't generated
+    /// directly (e.g. it was loaded from a file).
+    pub fn layer(&self, name: &str) -> Option<&TilePointMap<f64>> {
+        self.layers.get(name)
+    }
+
+    /// Get the checkpointed state of the generation RNG from just before the
+    /// climate steps (temperature, rainfall, runoff, etc.). This is what
+    /// [Self::resimulate_water] resumes from. Returns `None` if the world
+    /// was generated from a custom RNG (see [Self::generate_with_rng]), or if
+    /// this world wasn't generated directly (e.g. it was loaded from a file).
+    pub fn rng_checkpoint(&self) -> Option<&Pcg64> {
+        self.climate_rng.as_ref()
+    }
+
+    /// Set the elevation of a single tile. Nothing that depends on elevation
+    /// (temperature, rainfall, runoff, biomes, etc.) is updated, so call
+    /// [Self::resimulate_water] once you're done editing. Returns an error if
+    /// there's no tile at the position, or the elevation is outside
+    /// [Self::ELEVATION_RANGE].
+    pub fn set_elevation(
+        &mut self,
+        pos: TilePoint,
+        elevation: Meter,
+    ) -> anyhow::Result<()> {
+        Self::ELEVATION_RANGE.ensure_contains(elevation)?;
+        let tile = self
+            .tiles
+            .get_mut(&pos)
+            .ok_or_else(|| anyhow!("tile {} is not in the world", pos))?;
+        tile.elevation = elevation;
+        Ok(())
+    }
+
+    /// Recompute everything that depends on water after editing elevations
+    /// (see [Self::set_elevation]), without regenerating the world from
+    /// scratch. This re-runs temperature, rainfall, runoff, geographic
+    /// features, and biomes (plus everything derived from those, like
+    /// fertility and watersheds), updating every tile in place. Oceans are
+    /// left where they are, even if the ground under them was raised, and
+    /// land biomes are picked again from scratch. Returns an error if the
+    /// wind direction isn't known (i.e. [WorldMeta::wind_direction] is
+    /// `None`), or if any generation step fails.
+    ///
+    /// River meandering (see
+    /// [GeoFeatureConfig::meander_strength](crate::GeoFeatureConfig::meander_strength))
+    /// is random, so resimulation resumes from the RNG checkpoint taken
+    /// during generation (see [Self::rng_checkpoint]). That way an unedited
+    /// world comes out exactly the same, and edits only change the rivers
+    /// they actually touch. If there's no checkpoint (e.g. the world was
+    /// loaded from a file), a fresh RNG seeded from the config is used
+    /// instead, so meandered rivers may take different bends than they did
+    /// originally.
+    pub fn resimulate_water(&mut self) -> anyhow::Result<()> {
+        let wind_direction = self.meta.wind_direction.ok_or_else(|| {
+            anyhow!("cannot resimulate water, wind direction is unknown")
+        })?;
+        let mut builder = WorldBuilder::new(&self.config);
+        if let Some(rng) = &self.climate_rng {
+            builder.set_rng_state(rng.clone());
+        }
+        self.tiles = builder.resimulate_water(&self.tiles, wind_direction)?;
+        Ok(())
+    }
+
     /// Get a reference to the map of tiles that make up this world.
     pub fn tiles(&self) -> &TilePointMap<Tile> {
         &self.tiles
@@ -89,23 +242,982 @@ impl World {
         self.tiles
     }
 
+    /// Get all tiles in this world, sorted by position. The tile map has no
+    /// meaningful iteration order, so use this whenever you need to output
+    /// tile data in an order that's consistent between calls (e.g. to line up
+    /// multiple buffers of per-tile data).
+    pub fn tiles_sorted(&self) -> Vec<&Tile> {
+        let mut tiles: Vec<&Tile> = self.tiles.values().collect();
+        tiles.sort_by_key(|tile| tile.position());
+        tiles
+    }
+
+    /// Get every `every_n`th tile in this world, in sorted order (see
+    /// [Self::tiles_sorted]), starting with the first. This is a cheap,
+    /// deterministic subsample, e.g. for drawing a sparse preview of a huge
+    /// world. The sample always has `ceil(len / every_n)` tiles. Panics if
+    /// `every_n` is 0.
+    pub fn sample_tiles(
+        &self,
+        every_n: usize,
+    ) -> impl Iterator<Item = &Tile> + '_ {
+        assert!(every_n > 0, "sample interval must be positive");
+        self.tiles_sorted().into_iter().step_by(every_n)
+    }
+
+    /// Get all tiles in this world, sorted by a custom key, e.g. for "top N"
+    /// queries like the wettest tiles. Tiles with equal keys are ordered by
+    /// position, so the output is always the same for a given world. Wrap the
+    /// key in [Reverse](std::cmp::Reverse) to sort in descending order.
+    pub fn tiles_sorted_by<K: Ord>(
+        &self,
+        key: impl Fn(&Tile) -> K,
+    ) -> Vec<&Tile> {
+        // Start from position order, then rely on the sort being stable
+        let mut tiles = self.tiles_sorted();
+        tiles.sort_by_key(|tile| key(tile));
+        tiles
+    }
+
+    /// Get the tile with the highest elevation. If multiple tiles are tied for
+    /// highest, the one with the lowest position wins. Returns `None` only if
+    /// the world has no tiles.
+    pub fn highest_tile(&self) -> Option<&Tile> {
+        // max_by returns the _last_ max, so reverse to let the first tile win
+        // ties
+        self.tiles_sorted()
+            .into_iter()
+            .rev()
+            .max_by(|a, b| cmp_unwrap(&a.elevation(), &b.elevation()))
+    }
+
+    /// Sample this world onto a square grid of floats, with one band each for
+    /// elevation, rainfall, and runoff. `resolution` is the number of grid
+    /// cells across the width of a single tile (vertex to vertex), and must
+    /// be at least 1, otherwise this returns an error. This uses the same
+    /// sampling as raster rendering (e.g.
+    /// [WorldRenderer::render_as_ppm](crate::WorldRenderer::render_as_ppm)),
+    /// so cell `(x, y)` of the grid lines up with pixel `(x, y)` of the image.
+    pub fn to_grid(&self, resolution: u32) -> anyhow::Result<WorldGrid> {
+        WorldGrid::new(self, resolution)
+    }
+
+    /// Get all the tiles along a straight line between two positions, in order
+    /// from `from` to `to`. Positions along the line that are outside the
+    /// world are skipped. See [TilePoint::line_to]. This is useful for
+    /// plotting a profile of the terrain, e.g. elevation along a cut across
+    /// the map.
+    pub fn cross_section(&self, from: TilePoint, to: TilePoint) -> Vec<&Tile> {
+        from.line_to(to)
+            .into_iter()
+            .filter_map(|pos| self.tiles.get(&pos))
+            .collect()
+    }
+
+    /// Check if a viewer standing on one tile can see another tile, taking
+    /// terrain into account. Both the viewer and the target are considered to
+    /// be `eye_height` above the ground on their tiles. The sightline is a
+    /// straight line between those two points, and it's blocked if any tile
+    /// along the way (see [TilePoint::line_to]) has an elevation above the
+    /// line at that point. Tiles are treated as flat columns, so a tile that
+    /// exactly touches the sightline doesn't block it. Returns `false` if
+    /// either endpoint isn't in the world.
+    pub fn has_line_of_sight(
+        &self,
+        from: TilePoint,
+        to: TilePoint,
+        eye_height: Meter,
+    ) -> bool {
+        let (from_tile, to_tile) =
+            match (self.tiles.get(&from), self.tiles.get(&to)) {
+                (Some(from_tile), Some(to_tile)) => (from_tile, to_tile),
+                _ => return false,
+            };
+        let from_height = from_tile.elevation() + eye_height;
+        let to_height = to_tile.elevation() + eye_height;
+
+        let line = from.line_to(to);
+        let steps = (line.len() - 1) as f64;
+        // The endpoints can't block themselves, so only check in between
+        line.iter()
+            .enumerate()
+            .skip(1)
+            .take(line.len().saturating_sub(2))
+            .all(|(i, pos)| match self.tiles.get(pos) {
+                Some(tile) => {
+                    let t = i as f64 / steps;
+                    let sightline = from_height + (to_height - from_height) * t;
+                    tile.elevation() <= sightline
+                }
+                // Positions outside the world can't block anything
+                None => true,
+            })
+    }
+
+    /// Get the rainfall of every tile along the prevailing wind's path through
+    /// the given position, in order from the windward edge of the world to
+    /// the leeward edge. This is the same path that the rainfall simulation
+    /// carries clouds along, so it's handy for checking how terrain affects
+    /// rainfall, e.g. rain shadows behind mountains. Returns an empty list if
+    /// the position isn't in the world, or if the world doesn't know its wind
+    /// direction (see [WorldMeta::wind_direction]).
+    pub fn rainfall_profile_along_wind(
+        &self,
+        through: TilePoint,
+    ) -> Vec<(TilePoint, Meter3)> {
+        let wind_direction = match self.meta.wind_direction {
+            Some(wind_direction) if self.tiles.contains_key(&through) => {
+                wind_direction
+            }
+            _ => return Vec::new(),
+        };
+
+        // Clouds move along the wind axis, so the coordinate of that axis
+        // changes along the path while the other two trade off
+        let radius = self.config.radius as i16;
+        let signum = wind_direction.signum();
+        (-radius..=radius)
+            .map(|offset| {
+                let coax = offset * signum;
+                match wind_direction.axis {
+                    HexAxis::X => TilePoint::new_xy(coax, through.y()),
+                    HexAxis::Y => TilePoint::new_yz(coax, through.z()),
+                    HexAxis::Z => TilePoint::new_xz(through.x(), coax),
+                }
+            })
+            .filter_map(|pos| Some((pos, self.tiles.get(&pos)?.rainfall())))
+            .collect()
+    }
+
+    /// Get the direction that the prevailing wind blows at the given
+    /// position, as the closest [TileDirection] (see
+    /// [HexAxialDirection::to_tile_direction]). This is handy for drawing
+    /// wind arrows or for gameplay. Wind is currently the same across the
+    /// whole world, so this only depends on the position to check that it's
+    /// in the world. Returns `None` if it isn't, or if the world doesn't know
+    /// its wind direction (see [WorldMeta::wind_direction]).
+    pub fn wind_at(&self, pos: TilePoint) -> Option<TileDirection> {
+        if !self.tiles.contains_key(&pos) {
+            return None;
+        }
+        self.meta
+            .wind_direction
+            .map(HexAxialDirection::to_tile_direction)
+    }
+
+    /// Get the elevations of the six neighbors of a tile, in
+    /// [TileDirection::CLOCKWISE] order. Neighbors that are outside the world
+    /// are `None`. Neighbors do **not** wrap around the edge of the world. If
+    /// the given position itself isn't in the world, its neighbors are still
+    /// looked up as normal.
+    pub fn neighbor_elevations(&self, pos: TilePoint) -> [Option<Meter>; 6] {
+        let mut elevations = [None; 6];
+        for (elevation, dir) in
+            elevations.iter_mut().zip(TileDirection::CLOCKWISE)
+        {
+            *elevation =
+                self.tiles.get(&pos.adjacent(*dir)).map(Tile::elevation);
+        }
+        elevations
+    }
+
+    /// Suggest a good starting tile for a game: flat, temperate, hospitable
+    /// land that's next to water. Each land tile is scored on those criteria,
+    /// and the highest score wins. Ties go to the lowest position, so the
+    /// result is always the same for a given world. Tiles covered by a lake
+    /// aren't considered. Returns `None` if there are no land tiles.
+    pub fn suggested_spawn(&self) -> Option<TilePoint> {
+        // The most comfortable temperature, and how far off from it we can go
+        // before the temperature score bottoms out
+        const IDEAL_TEMPERATURE: Celsius = Celsius(15.0);
+        const TEMPERATURE_TOLERANCE: f64 = 25.0;
+
+        let score_tile = |tile: &Tile| -> f64 {
+            let biome_score = match tile.biome() {
+                Biome::Plains => 1.0,
+                Biome::Forest => 0.8,
+                Biome::Jungle => 0.5,
+                Biome::Desert => 0.2,
+                Biome::Alpine => 0.1,
+                Biome::Snow | Biome::Ocean | Biome::Coast => 0.0,
+            };
+
+            // Penalize the steepest step to any neighbor, relative to the full
+            // range of elevations
+            let max_step = self
+                .neighbor_elevations(tile.position())
+                .iter()
+                .flatten()
+                .map(|elevation| (*elevation - tile.elevation()).0.abs())
+                .fold(0.0, f64::max);
+            let flatness = 1.0 - max_step / Self::ELEVATION_RANGE.span().0;
+
+            let temperature_score = (1.0
+                - (tile.temperature() - IDEAL_TEMPERATURE).0.abs()
+                    / TEMPERATURE_TOLERANCE)
+                .max(0.0);
+
+            // Water is the most important criteria, so it's weighted heaviest.
+            // Rivers and lakes count, as well as the sea.
+            let has_river = tile.features().iter().any(|feature| {
+                matches!(
+                    feature,
+                    GeoFeature::RiverEntrance { .. }
+                        | GeoFeature::RiverExit { .. }
+                )
+            });
+            let near_water = has_river
+                || tile
+                    .position()
+                    .adjacents()
+                    .filter_map(|pos| self.tiles.get(&pos))
+                    .any(|neighbor| {
+                        neighbor.is_water_biome()
+                            || neighbor.features().contains(&GeoFeature::Lake)
+                    });
+            let water_score = if near_water { 2.0 } else { 0.0 };
+
+            biome_score + flatness + temperature_score + water_score
+        };
+
+        let mut best: Option<(TilePoint, f64)> = None;
+        for tile in self.tiles_sorted() {
+            if !tile.is_land_biome()
+                || tile.features().contains(&GeoFeature::Lake)
+            {
+                continue;
+            }
+            let score = score_tile(tile);
+            match best {
+                // Strict comparison, so the earliest tile wins ties
+                Some((_, best_score)) if best_score >= score => {}
+                _ => best = Some((tile.position(), score)),
+            }
+        }
+        best.map(|(pos, _)| pos)
+    }
+
+    /// Get a compact map of the biome of every tile. The returned buffer has
+    /// one byte per position in the bounding box of the world, in the box's
+    /// row order (see [TileBoundingBox::index]). Each byte is the biome's
+    /// discriminant (i.e. `biome as u8`), or [Self::BIOME_MAP_EMPTY] for
+    /// positions in the box that don't have a tile, such as the corners
+    /// around a hexagonal world.
+    pub fn biome_map(&self) -> (Vec<u8>, TileBoundingBox) {
+        let bbox = TileBoundingBox::from_points(self.tiles.keys().copied())
+            // Every world has at least one tile
+            .expect("world has no tiles");
+        let buffer = bbox
+            .points()
+            .map(|pos| {
+                self.tiles
+                    .get(&pos)
+                    .map_or(Self::BIOME_MAP_EMPTY, |tile| tile.biome() as u8)
+            })
+            .collect();
+        (buffer, bbox)
+    }
+
+    /// Iterate over all tiles in this world in a pseudo-random order. The
+    /// order is determined entirely by the given seed (it doesn't depend on
+    /// the world's seed), so the same seed always gives the same order. Useful
+    /// for sampling tiles without any bias toward their position.
+    pub fn shuffled_tiles(&self, seed: u64) -> impl Iterator<Item = &Tile> {
+        let mut tiles = self.tiles_sorted();
+        tiles.shuffle(&mut Pcg64::seed_from_u64(seed));
+        tiles.into_iter()
+    }
+
+    /// Get the latitude of a position in this world, in `[-1, 1]`. 0 is the
+    /// equator, which runs horizontally through the center of the world. 1 is
+    /// the north pole (top edge) and -1 is the south pole (bottom edge).
+    pub fn latitude(&self, position: TilePoint) -> f64 {
+        latitude(position, self.config.radius)
+    }
+
+    /// Get all tiles that are directly adjacent to the given position. Tiles
+    /// on the edge of the world will have fewer than 6 neighbors.
+    pub fn adjacent_tiles(
+        &self,
+        position: TilePoint,
+    ) -> impl Iterator<Item = &Tile> + '_ {
+        position
+            .adjacents()
+            .filter_map(move |adj_pos| self.tiles.get(&adj_pos))
+    }
+
+    /// Find the closest tile to `from` that matches a predicate, by searching
+    /// outward one [ring](TilePoint::ring) at a time. `from` itself is checked
+    /// first. If multiple matching tiles are the same distance away, the one
+    /// with the lowest position wins. Returns `None` if no tile in the world
+    /// matches. Distance is measured in tiles, and the search does **not**
+    /// wrap around the edge of the world.
+    pub fn nearest(
+        &self,
+        from: TilePoint,
+        predicate: impl Fn(&Tile) -> bool,
+    ) -> Option<&Tile> {
+        // Once we're this far out, we're guaranteed to be past every tile
+        let max_distance =
+            from.distance_to(TilePoint::ORIGIN) + self.config.radius as usize;
+        (0..=max_distance).find_map(|distance| {
+            from.ring(distance as u16)
+                .into_iter()
+                .filter_map(|pos| self.tiles.get(&pos))
+                .filter(|tile| predicate(tile))
+                .min_by_key(|tile| tile.position())
+        })
+    }
+
+    /// Find the `n` closest tiles to `from` that match a predicate, searching
+    /// outward one ring at a time like [Self::nearest]. The output is sorted
+    /// by distance from `from`, with ties broken by position, so it's the
+    /// same on every call. If fewer than `n` tiles in the world match, all of
+    /// them are returned. Like [Self::nearest], the search doesn't wrap.
+    pub fn nearest_n(
+        &self,
+        from: TilePoint,
+        n: usize,
+        predicate: impl Fn(&Tile) -> bool,
+    ) -> Vec<&Tile> {
+        // Once we're this far out, we're guaranteed to be past every tile
+        let max_distance =
+            from.distance_to(TilePoint::ORIGIN) + self.config.radius as usize;
+        let mut found = Vec::new();
+        for distance in 0..=max_distance {
+            if found.len() >= n {
+                break;
+            }
+            let mut ring: Vec<&Tile> = from
+                .ring(distance as u16)
+                .into_iter()
+                .filter_map(|pos| self.tiles.get(&pos))
+                .filter(|tile| predicate(tile))
+                .collect();
+            ring.sort_by_key(|tile| tile.position());
+            found.extend(ring);
+        }
+        // The last ring may have put us over
+        found.truncate(n);
+        found
+    }
+
+    /// Get the adjacency graph of this world, with one node per tile and one
+    /// edge per pair of adjacent tiles. Edges wrap around the edges of the
+    /// world if wrapping is enabled. Useful for running your own graph
+    /// algorithms over the world without rebuilding adjacency yourself.
+    pub fn adjacency(&self) -> TileGraph<'_> {
+        TileGraph::new(self)
+    }
+
+    /// Get every continent in this world, i.e. each contiguous cluster of land
+    /// tiles. Continents are ordered by their [ID](Continent::id), so the
+    /// order is stable between calls. Adjacency wraps around the edges of the
+    /// world if wrapping is enabled, so a continent can span the seam.
+    pub fn continents(&self) -> Vec<Continent<'_>> {
+        Continent::find_all(self)
+    }
+
+    /// Find land bridges, i.e. narrow strips of land (at most `max_width`
+    /// tiles across) that connect two or more wider land regions. These are
+    /// natural chokepoints, e.g. isthmuses. Strips that only lead to one
+    /// region, like peninsulas, aren't bridges. Very short bridges (e.g. a
+    /// pinch between two regions only a couple tiles long) blend into the
+    /// regions on either end, so they won't be found. Each bridge is sorted by
+    /// position, and bridges are sorted by their first position. Adjacency
+    /// wraps around the edges of the world if wrapping is enabled.
+    pub fn land_bridges(&self, max_width: u16) -> Vec<Vec<TilePoint>> {
+        bridge::land_bridges(&self.tiles, self.config.wrap_radius(), max_width)
+    }
+
+    /// Trace the sea-level contour of this world as polylines in screen space
+    /// (see [crate::hex]), connecting the midpoints of every tile side between
+    /// land and water. This is useful for exporting clean vector coastlines,
+    /// e.g. for GIS. Each island produces a closed loop, with its first point
+    /// repeated at the end, as does each lake. Coastlines that run into the
+    /// edge of the world produce open polylines. These don't wrap, even if
+    /// [WorldConfig::wrap] is enabled. Polylines are ordered the same way on
+    /// every call.
+    pub fn coastline_contours(&self) -> Vec<Vec<Point2>> {
+        coast::coastline_contours(&self.tiles)
+    }
+
+    /// Count how many pairs of adjacent tiles there are for each combination
+    /// of biomes, e.g. how often desert borders jungle. Pairs are unordered,
+    /// so each key is ordered as `(a, b)` where `a <= b`, and the reverse
+    /// `(b, a)` never appears. Pairs of the same biome are included, and
+    /// combinations that never occur are omitted. Each adjacent pair is
+    /// counted once, including pairs across the seam of a wrapping world (see
+    /// [Self::adjacency]).
+    pub fn biome_adjacency_matrix(&self) -> HashMap<(Biome, Biome), usize> {
+        let graph = self.adjacency();
+        let nodes = graph.nodes();
+        let mut counts = HashMap::new();
+        for edge in graph.edges() {
+            let a = nodes[edge.from].biome();
+            let b = nodes[edge.to].biome();
+            let key = if a <= b { (a, b) } else { (b, a) };
+            *counts.entry(key).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Label every land tile with the watershed it drains to, computed from
+    /// each tile's [drainage](Tile::drainage). All tiles that drain to the
+    /// same outlet share a [WatershedId]. Water tiles, and land tiles with no
+    /// drainage data (e.g. if rainfall simulation was disabled), are left out.
+    pub fn watersheds(&self) -> TilePointMap<WatershedId> {
+        watershed::watersheds(&self.tiles, self.config.wrap_radius())
+    }
+
+    /// Classify every tile as a peak, ridge, slope, valley, pit, or flat, by
+    /// counting how many of its neighbors are higher and lower than it. See
+    /// [TerrainClass] for details. This is computed from elevation alone, so
+    /// it matches [Tile::terrain_class] for any generated world.
+    pub fn terrain_features(&self) -> TilePointMap<TerrainClass> {
+        terrain::terrain_classes(&self.tiles, self.config.wrap_radius())
+    }
+
+    /// Trace ridge lines (drainage divides) through the world, by following
+    /// steepest ascent from each saddle up to a peak. This is the opposite of
+    /// how rivers flow, which is steepest descent. Each line starts at a
+    /// saddle, climbs in elevation with every step, and ends at a tile
+    /// classified as [TerrainClass::Peak] (see [Self::terrain_features]). A
+    /// saddle has one line for each separate arc of higher neighbors around
+    /// it, so usually two. Like [Self::terrain_features], this is computed
+    /// from elevation alone.
+    pub fn ridge_lines(&self) -> Vec<Vec<TilePoint>> {
+        terrain::ridge_lines(&self.tiles, self.config.wrap_radius())
+    }
+
+    /// Calculate the distance from every land tile to the nearest coastline,
+    /// in tiles. Coastline tiles (land with at least one water neighbor) are 0,
+    /// and distance increases by 1 for each step inland. Water tiles are left
+    /// out, as is land that can't reach any coast. This matches
+    /// [Tile::coast_distance] for any generated world.
+    pub fn coast_distance_field(&self) -> TilePointMap<u16> {
+        coast::coast_distances(&self.tiles, self.config.wrap_radius())
+    }
+
+    /// Count how many times each kind of geographic feature appears across
+    /// all tiles. A tile with multiple features of the same kind (e.g. two
+    /// river entrances) contributes one to the count for each of them. Kinds
+    /// that never appear are omitted.
+    pub fn feature_counts(&self) -> HashMap<GeoFeatureKind, usize> {
+        stats::feature_counts(self.tiles.values())
+    }
+
+    /// Check that the river features in this world are consistent with each
+    /// other. Every [GeoFeature::RiverExit] that flows onto a land tile must
+    /// have a matching [GeoFeature::RiverEntrance] on that neighbor (and vice
+    /// versa), and no tile can have both an entrance and exit in the same
+    /// direction. This is intended for tests and post-generation sanity
+    /// checks. Returns an error describing the first violation found.
+    pub fn validate_features(&self) -> anyhow::Result<()> {
+        let has_feature =
+            |tile: &Tile, feature_dir: TileDirection, exit: bool| {
+                tile.features().iter().any(|feature| match feature {
+                    GeoFeature::RiverEntrance { direction, .. } => {
+                        !exit && *direction == feature_dir
+                    }
+                    GeoFeature::RiverExit { direction, .. } => {
+                        exit && *direction == feature_dir
+                    }
+                    _ => false,
+                })
+            };
+
+        for tile in self.tiles_sorted() {
+            let pos = tile.position();
+            for feature in tile.features() {
+                let (direction, exit) = match feature {
+                    GeoFeature::RiverEntrance { direction, .. } => {
+                        (*direction, false)
+                    }
+                    GeoFeature::RiverExit { direction, .. } => {
+                        (*direction, true)
+                    }
+                    _ => continue,
+                };
+
+                if has_feature(tile, direction, !exit) {
+                    bail!(
+                        "tile {} has both a river entrance and exit \
+                        in direction {:?}",
+                        pos,
+                        direction
+                    );
+                }
+
+                // Rivers can flow out into water (or off the edge of the
+                // world), but anything that flows in must have come from the
+                // neighbor
+                let neighbor = self.wrapping_neighbor(pos, direction);
+                let matched = neighbor.map_or(false, |neighbor| {
+                    has_feature(neighbor, direction.opposite(), !exit)
+                });
+                let required =
+                    !exit || neighbor.map_or(false, Tile::is_land_biome);
+                if required && !matched {
+                    bail!(
+                        "river {} tile {pos} in direction {direction:?} has no \
+                        matching {} on the neighboring tile",
+                        if exit { "exit from" } else { "entrance to" },
+                        if exit { "entrance" } else { "exit" },
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every tile in this world has complete, sane data: a finite
+    /// elevation, and finite, non-negative rainfall and runoff. Every finished
+    /// [Tile] always has a biome, so those don't need to be checked. Like
+    /// [Self::validate_features], this is intended for tests and sanity checks
+    /// on custom generation pipelines. Returns an error describing the first
+    /// offending tile.
+    pub fn validate_complete(&self) -> anyhow::Result<()> {
+        for tile in self.tiles_sorted() {
+            let pos = tile.position();
+            let elevation = tile.elevation();
+            if !elevation.0.is_finite() {
+                bail!(
+                    "tile {} has non-finite elevation {}: {:?}",
+                    pos,
+                    elevation,
+                    tile
+                );
+            }
+            for (name, value) in
+                [("rainfall", tile.rainfall()), ("runoff", tile.runoff())]
+            {
+                if !(value.0.is_finite() && value.0 >= 0.0) {
+                    bail!(
+                        "tile {} has invalid {} {}: {:?}",
+                        pos,
+                        name,
+                        value,
+                        tile
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Total length of all rivers in the world, measured in tiles. Each tile
+    /// that a river flows through (i.e. each tile with at least one
+    /// [GeoFeature::RiverEntrance] or [GeoFeature::RiverExit]) counts as one
+    /// segment, regardless of how many rivers pass through it.
+    pub fn total_river_length(&self) -> usize {
+        stats::total_river_length(self.tiles.values())
+    }
+
+    /// Density of the river network, in river segments (see
+    /// [Self::total_river_length]) per unit of land area (see [Tile::AREA]).
+    /// Wetter worlds have higher densities. Returns 0 if there is no land.
+    pub fn drainage_density(&self) -> f64 {
+        stats::drainage_density(self.tiles.values())
+    }
+
+    /// Compute aggregate statistics about this world. See [WorldStats].
+    pub fn stats(&self) -> WorldStats {
+        WorldStats::from_tiles(self.tiles.values())
+    }
+
+    /// Get the most common biome in the world, and the fraction of all tiles
+    /// that have it, in `(0, 1]`. If multiple biomes are tied, the lowest one
+    /// (by ordering) wins. This is also included in [WorldStats]. Returns
+    /// `None` if the world has no tiles.
+    pub fn dominant_biome(&self) -> Option<(Biome, f64)> {
+        let mut biome_counts = HashMap::new();
+        for tile in self.tiles.values() {
+            *biome_counts.entry(tile.biome()).or_default() += 1;
+        }
+        stats::dominant_biome(&biome_counts, self.tiles.len())
+    }
+
+    /// Get the average elevation, rainfall, and runoff of each biome. This is
+    /// handy for checking that biome classification matches intent, e.g.
+    /// that jungles really are wetter than deserts. Biomes that don't appear
+    /// in the world are omitted. See [BiomeClimate].
+    pub fn biome_climate_summary(&self) -> HashMap<Biome, BiomeClimate> {
+        stats::biome_climate_summary(self.tiles.values())
+    }
+
+    /// Compute aggregate statistics about this world, leaving out every tile
+    /// in the edge buffer (see [WorldConfig::edge_buffer_size]). The buffer
+    /// is pushed down into the ocean, so in [Self::stats] it inflates the
+    /// water counts. This gives a better picture of the interior of the
+    /// world, where the interesting stuff happens. For wrapping worlds (which
+    /// have no buffer), this is the same as [Self::stats].
+    pub fn stats_excluding_buffer(&self) -> WorldStats {
+        let interior_radius =
+            (self.config.radius - self.config.edge_buffer_size()) as usize;
+        // The buffer is measured from the logical center, same as in
+        // generation
+        let config = &self.config;
+        WorldStats::from_tiles(self.tiles.values().filter(move |tile| {
+            config.center_distance(tile.position()) <= interior_radius
+        }))
+    }
+
+    /// Average some value over each ring of the world, i.e. each set of tiles
+    /// that are the same distance from the origin. Element `i` of the output
+    /// is the mean of `f` over ring `i`, for every ring from the center (0)
+    /// out to the radius. This is handy for seeing how values change with
+    /// distance from the center, e.g. to check the effect of the edge buffer.
+    /// Rings that have no tiles (e.g. in a partial world from a region export,
+    /// see [WorldMeta::region]) are NaN.
+    pub fn radial_profile(&self, f: impl Fn(&Tile) -> f64) -> Vec<f64> {
+        let ring_count = self.config.radius as usize + 1;
+        let mut sums = vec![0.0; ring_count];
+        let mut counts = vec![0usize; ring_count];
+        for tile in self.tiles.values() {
+            let ring = tile.position().distance_to(TilePoint::ORIGIN);
+            if let (Some(sum), Some(count)) =
+                (sums.get_mut(ring), counts.get_mut(ring))
+            {
+                *sum += f(tile);
+                *count += 1;
+            }
+        }
+        sums.into_iter()
+            .zip(counts)
+            .map(|(sum, count)| sum / count as f64)
+            .collect()
+    }
+
+    /// Score how "interesting" this world is, in `[0, 1]`. This is useful for
+    /// ranking worlds when searching randomly through seeds or configs. The
+    /// score is a weighted sum of three heuristics, each in `[0, 1]`:
+    ///
+    /// - **Land/water balance (40%)**: 1 when exactly half the tiles are land,
+    ///   falling linearly to 0 for a world of all land or all water
+    /// - **Biome diversity (40%)**: Shannon entropy of the biome counts,
+    ///   normalized so that 1 means every biome is equally common
+    /// - **Water features (20%)**: half for having any rivers, and half for
+    ///   having any lakes (including inland seas)
+    ///
+    /// An empty world scores 0.
+    pub fn interestingness(&self) -> f64 {
+        const BALANCE_WEIGHT: f64 = 0.4;
+        const DIVERSITY_WEIGHT: f64 = 0.4;
+        const WATER_FEATURE_WEIGHT: f64 = 0.2;
+
+        if self.tiles.is_empty() {
+            return 0.0;
+        }
+        // Number of biome variants, for normalizing entropy
+        let biome_count = Biome::iter().count() as f64;
+        let stats = self.stats();
+        let tile_count = stats.tile_count as f64;
+
+        let land_count: usize = stats
+            .biome_counts
+            .iter()
+            .filter(|(biome, _)| biome.biome_type() == BiomeType::Land)
+            .map(|(_, count)| count)
+            .sum();
+        let land_fraction = land_count as f64 / tile_count;
+        let balance = 1.0 - (land_fraction - 0.5).abs() * 2.0;
+
+        // Sort so the float accumulation doesn't depend on hash map order
+        let mut biome_counts: Vec<usize> =
+            stats.biome_counts.values().copied().collect();
+        biome_counts.sort_unstable();
+        let entropy: f64 = biome_counts
+            .into_iter()
+            .map(|count| {
+                let p = count as f64 / tile_count;
+                -p * p.ln()
+            })
+            .sum();
+        let diversity = entropy / biome_count.ln();
+
+        let has_rivers = stats.total_river_length > 0;
+        let has_lakes =
+            stats.feature_counts.contains_key(&GeoFeatureKind::Lake);
+        let water_features =
+            (has_rivers as u8 as f64 + has_lakes as u8 as f64) / 2.0;
+
+        (BALANCE_WEIGHT * balance
+            + DIVERSITY_WEIGHT * diversity
+            + WATER_FEATURE_WEIGHT * water_features)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Fraction of this world's tiles, in `[0, 1]`, that differ from the
+    /// other world. A tile counts as changed if its biome or elevation is
+    /// different from the tile at the same position in the other world. This
+    /// is a cheap way to measure the impact of a config change, e.g. for
+    /// instant feedback while tuning. Returns an error if the two worlds have
+    /// different radii. An empty world gives 0.
+    pub fn change_fraction(&self, other: &World) -> anyhow::Result<f64> {
+        ensure!(
+            self.config.radius == other.config.radius,
+            "cannot compare worlds with different radii ({} and {})",
+            self.config.radius,
+            other.config.radius
+        );
+        if self.tiles.is_empty() {
+            return Ok(0.0);
+        }
+
+        let changed = self
+            .tiles
+            .iter()
+            .filter(|(pos, tile)| match other.tiles.get(pos) {
+                Some(other_tile) => {
+                    tile.biome() != other_tile.biome()
+                        || tile.elevation() != other_tile.elevation()
+                }
+                // Only possible if one of the worlds has been translated
+                None => true,
+            })
+            .count();
+        Ok(changed as f64 / self.tiles.len() as f64)
+    }
+
+    /// Get the tile adjacent to the given position in a particular direction.
+    /// If the world wraps (see [WorldConfig::wrap]), stepping off one edge of
+    /// the world lands on the opposite edge, so every tile has exactly 6
+    /// neighbors. If it doesn't wrap, this returns `None` for neighbors off
+    /// the edge of the world.
+    pub fn wrapping_neighbor(
+        &self,
+        position: TilePoint,
+        direction: TileDirection,
+    ) -> Option<&Tile> {
+        let adj_pos =
+            position.adjacent_wrapping(direction, self.config.wrap_radius());
+        self.tiles.get(&adj_pos)
+    }
+
+    /// Get all land tiles that border at least one water tile, sorted by
+    /// position. Not to be confused with [Biome::Coast], which is a
+    /// classification for _water_ tiles near land.
+    pub fn coastline(&self) -> Vec<&Tile> {
+        let mut tiles: Vec<&Tile> = self
+            .tiles
+            .values()
+            .filter(|tile| {
+                tile.is_land_biome()
+                    && self
+                        .adjacent_tiles(tile.position())
+                        .any(Tile::is_water_biome)
+            })
+            .collect();
+        tiles.sort_by_key(|tile| tile.position());
+        tiles
+    }
+
+    /// Find out where runoff from the tile at the given position ends up. See
+    /// [DrainageDestination] for more info. Returns `None` if there is no tile
+    /// at the position.
+    pub fn drainage_destination(
+        &self,
+        position: TilePoint,
+    ) -> Option<&DrainageDestination> {
+        self.tiles.get(&position).map(Tile::drainage)
+    }
+
     /// Generate a new world with the given config. This operation could take
     /// several seconds, depending on the world size and complexity. Returns
-    /// an error if the given config is invalid. Panics only in the case of
-    /// internal bugs in the generation algorithm. Please report any panics
-    /// on the git repo.
+    /// an error if the given config is invalid. Internal bugs in the
+    /// generation algorithm cause a panic, which is caught and returned as an
+    /// error naming the generation step that failed. Please report any such
+    /// errors on the git repo.
+    ///
+    /// **On Wasm, panics abort rather than unwind,** so they can't be caught.
+    /// An internal bug there will still kill the Wasm instance.
     pub fn generate(config: WorldConfig) -> anyhow::Result<Self> {
+        // Use the builder's own seeded RNG (rather than passing one in) so
+        // its state can be checkpointed for [Self::resimulate_water]
+        Self::generate_from(config, |config| {
+            WorldBuilder::new(config).generate_world()
+        })
+    }
+
+    /// Generate a new world with the given config, using the given RNG as the
+    /// source of all randomness. The config's seed is ignored (other than
+    /// being stored on the world), so two worlds generated from identically
+    /// seeded RNGs with the same config will be identical. Returns an error if
+    /// the given config is invalid.
+    pub fn generate_with_rng(
+        config: WorldConfig,
+        rng: impl RngCore + SeedableRng,
+    ) -> anyhow::Result<Self> {
+        Self::generate_from(config, |config| {
+            WorldBuilder::with_rng(config, rng).generate_world()
+        })
+    }
+
+    /// Validate the config, run the given generation function, and assemble
+    /// its output into a world
+    fn generate_from(
+        config: WorldConfig,
+        generate: impl FnOnce(&WorldConfig) -> anyhow::Result<GeneratedWorld>,
+    ) -> anyhow::Result<Self> {
         info!("Generating world with config {:#?}", config);
 
         config.validate().context("invalid config")?;
 
-        let tiles = timed!(
-            "World generation",
+        let generated =
+            timed!("World generation", log::Level::Info, generate(&config))?;
+
+        let meta = WorldMeta {
+            wind_direction: Some(generated.wind_direction),
+            ..WorldMeta::new(&config)
+        };
+        Ok(Self {
+            config,
+            tiles: generated.tiles,
+            meta,
+            layers: generated.layers,
+            climate_rng: generated.climate_rng,
+        })
+    }
+
+    /// Generate a new world with the given config, and also check the result
+    /// for signs of a degenerate config. Some configs are technically valid
+    /// but produce poor worlds (e.g. all ocean). Any such problems are
+    /// returned as non-fatal warnings alongside the world. See
+    /// [GenerationWarning] for the possible warnings. Returns an error under
+    /// the same conditions as [Self::generate].
+    pub fn generate_with_warnings(
+        config: WorldConfig,
+    ) -> anyhow::Result<(Self, Vec<GenerationWarning>)> {
+        let world = Self::generate(config)?;
+        let warnings = world.detect_warnings();
+        for warning in &warnings {
+            warn!("{warning}");
+        }
+        Ok((world, warnings))
+    }
+
+    /// Check this world for signs of a degenerate config. See
+    /// [Self::generate_with_warnings].
+    fn detect_warnings(&self) -> Vec<GenerationWarning> {
+        let mut warnings = Vec::new();
+        let has_land = self
+            .tiles
+            .values()
+            .any(|tile| tile.biome().biome_type() == BiomeType::Land);
+
+        if !has_land {
+            warnings.push(GenerationWarning::NoLand);
+        }
+        if !self.config.rainfall.enabled {
+            warnings.push(GenerationWarning::RunoffDisabled);
+        } else if has_land && self.total_river_length() == 0 {
+            warnings.push(GenerationWarning::NoRivers);
+        }
+
+        warnings
+    }
+
+    /// Generate a new world, but only if it would contain at most `max_tiles`
+    /// tiles. The tile count is checked against the budget before anything is
+    /// allocated, so this is a cheap way to protect against unexpectedly large
+    /// worlds. Returns an error if the budget is exceeded, or for any of the
+    /// reasons listed on [Self::generate].
+    pub fn generate_within_budget(
+        config: WorldConfig,
+        max_tiles: usize,
+    ) -> anyhow::Result<Self> {
+        let tile_count = config.tile_count();
+        if tile_count > max_tiles {
+            bail!(
+                "world with radius {} would have {} tiles, \
+                which exceeds the budget of {} tiles",
+                config.radius,
+                tile_count,
+                max_tiles
+            );
+        }
+        Self::generate(config)
+    }
+
+    /// Generate only the elevation map for the given config, skipping all
+    /// other generation steps (water, rainfall, biomes, etc.). This is much
+    /// faster than [Self::generate], so it's useful for quickly previewing
+    /// terrain while iterating on config. The elevations will be identical to
+    /// the ones in a full world generated from the same config. Returns an
+    /// error if the given config is invalid.
+    pub fn generate_elevation_only(
+        config: WorldConfig,
+    ) -> anyhow::Result<TilePointMap<Meter>> {
+        config.validate().context("invalid config")?;
+        timed!(
+            "Elevation generation",
             log::Level::Info,
-            WorldBuilder::new(&config).generate_world()
-        );
+            WorldBuilder::new(&config).generate_elevation()
+        )
+    }
+
+    /// Serializes this world into JSON, with only the tile fields selected by
+    /// the given options. With the default options, this holds the same data
+    /// as [World::to_json]. Position, elevation, and biome are always
+    /// included. Unlike [World::to_json], the output can't be loaded back
+    /// into a [World] unless all fields are included.
+    #[cfg(feature = "json")]
+    pub fn to_json_with(&self, options: JsonExportOptions) -> String {
+        // Panic here indicates an internal bug in the data format
+        serde_json::to_string(&export::WorldExport::new(self, options))
+            .expect("error serializing world")
+    }
+
+    /// Serializes just the tiles within the given box into JSON, e.g. to export
+    /// one chunk of a huge world at a time. The output is in the same format
+    /// as [World::to_json], so it can be loaded back with [World::from_json]
+    /// to get a partial world. The box is recorded in the region's metadata
+    /// (see [WorldMeta::region]). The config is kept as-is, so it still
+    /// describes the whole world. Returns an error if the box doesn't contain
+    /// any tiles of this world, since an empty world isn't useful for
+    /// anything.
+    #[cfg(feature = "json")]
+    pub fn to_json_region(
+        &self,
+        bbox: TileBoundingBox,
+    ) -> anyhow::Result<String> {
+        Ok(self.region(bbox)?.to_json())
+    }
+
+    /// Serializes just the tiles within the given box into binary format. See
+    /// [World::to_json_region]. The output can be loaded back with
+    /// [World::from_bin]. Returns an error if the box doesn't contain any
+    /// tiles of this world.
+    #[cfg(feature = "bin")]
+    pub fn to_bin_region(
+        &self,
+        bbox: TileBoundingBox,
+    ) -> anyhow::Result<Vec<u8>> {
+        Ok(self.region(bbox)?.to_bin())
+    }
 
-        Ok(Self { config, tiles })
+    /// Copy the tiles within the given box into a new partial world, for
+    /// region exports. Returns an error if no tiles are in the box.
+    #[cfg(any(feature = "json", feature = "bin"))]
+    fn region(&self, bbox: TileBoundingBox) -> anyhow::Result<Self> {
+        let tiles: TilePointMap<Tile> = self
+            .tiles
+            .iter()
+            .filter(|(pos, _)| bbox.contains(**pos))
+            .map(|(pos, tile)| (*pos, tile.clone()))
+            .collect();
+        ensure!(!tiles.is_empty(), "region {:?} contains no tiles", bbox);
+        Ok(Self {
+            config: self.config.clone(),
+            tiles,
+            meta: WorldMeta {
+                region: Some(bbox),
+                ..self.meta.clone()
+            },
+            layers: HashMap::new(),
+            climate_rng: None,
+        })
     }
 
     /// Deserialize a world from JSON. A world can be serialized into JSON with
@@ -123,11 +1235,312 @@ impl World {
     pub fn from_bin(read: impl std::io::Read) -> anyhow::Result<Self> {
         serde_cbor::from_reader(read).context("error deserializing world")
     }
+
+    /// Deserialize a world's tiles from JSON, and combine them with the
+    /// given config. The tiles can be serialized with
+    /// [World::to_json_tiles_only]. The config should be the one that the
+    /// world was originally generated with. Will fail if the input is
+    /// malformed, the config is invalid, or the tiles don't fit the config
+    /// (no tiles, duplicate tiles, or tiles outside the radius). The tiles
+    /// don't record which version of Terra generated them, so the world's
+    /// [WorldMeta::terra_version] is left empty.
+    #[cfg(feature = "json")]
+    pub fn from_json_tiles_only(
+        json: &str,
+        config: WorldConfig,
+    ) -> anyhow::Result<Self> {
+        let tiles: Vec<Tile> =
+            serde_json::from_str(json).context("error deserializing tiles")?;
+        Self::from_tiles(tiles, config)
+    }
+
+    /// Deserialize a world's tiles from binary format, and combine them with
+    /// the given config. The tiles can be serialized with
+    /// [World::to_bin_tiles_only]. The config should be the one that the
+    /// world was originally generated with. Will fail if the input is
+    /// malformed, the config is invalid, or the tiles don't fit the config
+    /// (no tiles, duplicate tiles, or tiles outside the radius). The tiles
+    /// don't record which version of Terra generated them, so the world's
+    /// [WorldMeta::terra_version] is left empty.
+    #[cfg(feature = "bin")]
+    pub fn from_bin_tiles_only(
+        read: impl std::io::Read,
+        config: WorldConfig,
+    ) -> anyhow::Result<Self> {
+        let tiles: Vec<Tile> = serde_cbor::from_reader(read)
+            .context("error deserializing tiles")?;
+        Self::from_tiles(tiles, config)
+    }
+
+    /// Assemble a world from a list of deserialized tiles and a config.
+    /// Returns an error if the list is empty, has more than one tile at the
+    /// same position, or has a tile outside the config's radius.
+    #[cfg(any(feature = "json", feature = "bin"))]
+    fn from_tiles(
+        tiles: Vec<Tile>,
+        config: WorldConfig,
+    ) -> anyhow::Result<Self> {
+        config.validate().context("invalid config")?;
+        if tiles.is_empty() {
+            bail!("cannot build a world with no tiles");
+        }
+        let mut tile_map = TilePointMap::with_capacity_and_hasher(
+            tiles.len(),
+            Default::default(),
+        );
+        for tile in tiles {
+            let pos = tile.position();
+            if pos.distance_to(TilePoint::ORIGIN) > config.radius as usize {
+                bail!(
+                    "tile {pos} is outside the world radius of {}",
+                    config.radius
+                );
+            }
+            if tile_map.insert(pos, tile).is_some() {
+                bail!("duplicate tile at {}", pos);
+            }
+        }
+        // The tiles don't carry any metadata, so the best we can do is
+        // describe the given config. We don't know which version generated
+        // the tiles, so leave that out rather than claiming it was this one.
+        let meta = WorldMeta {
+            terra_version: String::new(),
+            ..WorldMeta::new(&config)
+        };
+        Ok(Self {
+            config,
+            tiles: tile_map,
+            meta,
+            layers: HashMap::new(),
+            climate_rng: None,
+        })
+    }
+
+    /// Create a copy of this world with every tile shifted by the given
+    /// offset. All tile data is preserved. Returns an error if any shifted
+    /// position isn't representable. Note that the config is copied as-is, so
+    /// anything derived from the config (e.g. [Self::latitude]) still assumes
+    /// the world is centered on the origin.
+    pub fn translate(&self, offset: TilePoint) -> anyhow::Result<Self> {
+        let shift = |pos: TilePoint| {
+            pos.checked_translate(offset).ok_or_else(|| {
+                anyhow!(
+                    "cannot translate tile at {} by {}, out of range",
+                    pos,
+                    offset
+                )
+            })
+        };
+
+        let mut tiles = TilePointMap::with_capacity_and_hasher(
+            self.tiles.len(),
+            Default::default(),
+        );
+        for tile in self.tiles.values() {
+            let mut tile = tile.clone();
+            tile.position = shift(tile.position)?;
+            if let Some(watershed) = &mut tile.watershed {
+                *watershed = WatershedId::new(shift(watershed.outlet())?);
+            }
+            if let DrainageDestination::Terminals { terminals, .. } =
+                &mut tile.drainage
+            {
+                for terminal in terminals.iter_mut() {
+                    *terminal = shift(*terminal)?;
+                }
+            }
+            tiles.insert(tile.position, tile);
+        }
+
+        Ok(Self {
+            config: self.config.clone(),
+            tiles,
+            meta: self.meta.clone(),
+            layers: HashMap::new(),
+            climate_rng: None,
+        })
+    }
+
+    /// Combine the tiles of two worlds into one. This world's config is kept,
+    /// and the other's is discarded. Typically you'll want to
+    /// [translate](Self::translate) one of the worlds first so they don't
+    /// overlap. Returns an error if both worlds have a tile at the same
+    /// position.
+    pub fn merge(mut self, other: World) -> anyhow::Result<Self> {
+        if let Some(pos) =
+            other.tiles.keys().find(|pos| self.tiles.contains_key(pos))
+        {
+            bail!("cannot merge worlds, both have a tile at {}", pos);
+        }
+        self.tiles.extend(other.tiles);
+        Ok(self)
+    }
+
+    /// Pull a single continent out into its own world. The continent is the
+    /// cluster of contiguous land tiles that includes `seed_tile`, plus the
+    /// ring of water tiles immediately surrounding it. Tile positions (and
+    /// the config) are preserved, so the new world is a subset of this one.
+    /// Returns an error if `seed_tile` isn't in the world, or isn't land.
+    pub fn extract_continent(
+        &self,
+        seed_tile: TilePoint,
+    ) -> anyhow::Result<Self> {
+        match self.tiles.get(&seed_tile) {
+            None => bail!("tile {} is not in the world", seed_tile),
+            Some(tile) if !tile.is_land_biome() => {
+                bail!("tile {} is not land", seed_tile)
+            }
+            Some(_) => {}
+        }
+
+        let wrap_radius = self.config.wrap_radius();
+        let continent = flood_fill(
+            seed_tile,
+            |pos| pos.adjacents_wrapping(wrap_radius),
+            |pos, _| self.tiles.get(pos).map_or(false, Tile::is_land_biome),
+        );
+        // Include the surrounding ring of water too
+        let shore: TilePointSet = continent
+            .iter()
+            .flat_map(|pos| pos.adjacents_wrapping(wrap_radius))
+            .filter(|pos| !continent.contains(pos))
+            .collect();
+
+        let tiles = continent
+            .iter()
+            .chain(&shore)
+            .filter_map(|pos| {
+                let tile = self.tiles.get(pos)?;
+                Some((*pos, tile.clone()))
+            })
+            .collect();
+
+        Ok(Self {
+            config: self.config.clone(),
+            tiles,
+            meta: self.meta.clone(),
+            layers: HashMap::new(),
+            climate_rng: None,
+        })
+    }
+
+    /// Create a reduced-resolution copy of this world, e.g. for thumbnails or
+    /// minimaps of large worlds. The new world has `radius / factor` radius,
+    /// and each of its tiles is an aggregate of the block of roughly
+    /// `factor²` original tiles around the corresponding position. Numeric
+    /// values (elevation, rainfall, runoff, temperature, humidity, fertility)
+    /// are averaged, and the biome is the most common one in the block.
+    ///
+    /// Anything that doesn't aggregate meaningfully is **dropped**:
+    /// geographic features (rivers, lakes, etc.), runoff traversal, drainage,
+    /// and watersheds. Terrain classes and coast distances are recalculated
+    /// for the new tiles. A factor of 1 gives an identical copy, and a factor
+    /// of 0 panics.
+    pub fn downsample(&self, factor: u16) -> World {
+        assert!(factor > 0, "downsample factor must be at least 1");
+        if factor == 1 {
+            return self.clone();
+        }
+
+        let config = WorldConfig {
+            radius: self.config.radius / factor,
+            ..self.config.clone()
+        };
+
+        // Group each original tile with the downsampled tile whose center is
+        // closest once the world is scaled down. Tiles are grouped in sorted
+        // order, so that biome ties are broken deterministically.
+        let scale = factor as f64;
+        let mut blocks: TilePointMap<Vec<&Tile>> = TilePointMap::default();
+        for tile in self.tiles_sorted() {
+            let pos = TilePoint::nearest(
+                tile.position.x() as f64 / scale,
+                tile.position.y() as f64 / scale,
+            );
+            // Partial blocks past the new edge get cut off
+            if pos.distance_to(TilePoint::ORIGIN) <= config.radius as usize {
+                blocks.entry(pos).or_default().push(tile);
+            }
+        }
+
+        let mut tiles: TilePointMap<Tile> = blocks
+            .into_iter()
+            .map(|(pos, block)| (pos, aggregate_tiles(pos, &block)))
+            .collect();
+        let wrap_radius = config.wrap_radius();
+        for (pos, terrain_class) in
+            terrain::terrain_classes(&tiles, wrap_radius)
+        {
+            tiles.get_mut(&pos).unwrap().terrain_class = terrain_class;
+        }
+        for (pos, distance) in coast::coast_distances(&tiles, wrap_radius) {
+            tiles.get_mut(&pos).unwrap().coast_distance = Some(distance);
+        }
+
+        // The radius changed, so the hash has to follow the new config
+        let meta = WorldMeta {
+            config_hash: config.config_hash(),
+            ..self.meta.clone()
+        };
+        Self {
+            config,
+            tiles,
+            meta,
+            layers: HashMap::new(),
+            climate_rng: None,
+        }
+    }
 }
 
 // Wasm-friendly API
 #[cfg_attr(feature = "js", wasm_bindgen)]
 impl World {
+    /// Distance from the center of the world to the edge, in tiles. This is
+    /// the same as the radius in the world config.
+    #[cfg_attr(feature = "js", wasm_bindgen(getter))]
+    pub fn radius(&self) -> u16 {
+        self.config.radius
+    }
+
+    /// The seed used to generate this world, as a string. Seeds are always
+    /// represented as strings in JS, since they can be too big to fit in a JS
+    /// number.
+    #[cfg_attr(feature = "js", wasm_bindgen(getter))]
+    pub fn seed_string(&self) -> String {
+        self.config.seed.to_string()
+    }
+
+    /// Get the average screen-space position of all land tiles in the world.
+    /// This is useful for framing a camera around the interesting part of the
+    /// world. Returns `None` if there is no land at all. See [crate::hex] for
+    /// a description of screen space.
+    pub fn land_centroid(&self) -> Option<Point2> {
+        let (sum, count) = self
+            .tiles
+            .values()
+            .filter(|tile| tile.is_land_biome())
+            .fold((Point2::default(), 0), |(sum, count), tile| {
+                (sum + hex_to_screen_space(tile.position()), count + 1)
+            });
+        if count > 0 {
+            Some(sum / count as f64)
+        } else {
+            None
+        }
+    }
+
+    /// Throw away the directional runoff data for every tile in this world.
+    /// This data is only needed for [Tile::runoff_ingress] and
+    /// [Tile::runoff_egress] (which will return zero afterwards), but it makes
+    /// up a large chunk of the serialized world. If you don't need it, call
+    /// this before serializing to get a much smaller output. Geographic
+    /// features (e.g. rivers) are unaffected.
+    pub fn clear_runoff_traversed(&mut self) {
+        for tile in self.tiles.values_mut() {
+            tile.runoff_traversed = Default::default();
+        }
+    }
+
     /// Serializes this world into JSON. This is a recoverable format, which can
     /// be loaded back into a [World] with [World::from_json].
     #[cfg(feature = "json")]
@@ -136,6 +1549,15 @@ impl World {
         serde_json::to_string(self).expect("error serializing world")
     }
 
+    /// Serializes this world into indented, multi-line JSON. This holds the
+    /// same data as [World::to_json] and can be loaded back the same way, but
+    /// is easier to read and diff, at the cost of being even bigger.
+    #[cfg(feature = "json")]
+    pub fn to_json_pretty(&self) -> String {
+        // Panic here indicates an internal bug in the data format
+        serde_json::to_string_pretty(self).expect("error serializing world")
+    }
+
     /// Serializes this world into a binary format. This is a recoverable
     /// format, which can be loaded back into a [World] with [World::from_bin].
     /// See the struct-level [World] documentation for a description of the
@@ -148,6 +1570,88 @@ impl World {
             .expect("error serializing world");
         buffer
     }
+
+    /// Serializes just the tiles of this world into JSON, without the config.
+    /// This is handy when many worlds share one known config, since the config
+    /// doesn't have to be repeated for every world. The tiles (sorted by
+    /// position) can be loaded back into a [World] with
+    /// [World::from_json_tiles_only].
+    #[cfg(feature = "json")]
+    pub fn to_json_tiles_only(&self) -> String {
+        // Panic here indicates an internal bug in the data format
+        serde_json::to_string(&self.tiles_sorted())
+            .expect("error serializing tiles")
+    }
+
+    /// Serializes just the tiles of this world into binary format, without the
+    /// config. See [World::to_json_tiles_only] for why you'd want this. The
+    /// tiles can be loaded back into a [World] with
+    /// [World::from_bin_tiles_only].
+    #[cfg(feature = "bin")]
+    pub fn to_bin_tiles_only(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        // Panic here indicates an internal bug in the data format
+        serde_cbor::to_writer(&mut buffer, &self.tiles_sorted())
+            .expect("error serializing tiles");
+        buffer
+    }
+}
+
+/// Combine a block of tiles into one tile at the given position, for
+/// [World::downsample]. The block must not be empty.
+fn aggregate_tiles(position: TilePoint, block: &[&Tile]) -> Tile {
+    let mean = |value: fn(&Tile) -> f64| -> f64 {
+        block.iter().map(|tile| value(tile)).sum::<f64>() / block.len() as f64
+    };
+
+    // Tally up each biome, keeping them in the order they first appear
+    let mut biome_counts: Vec<(Biome, usize)> = Vec::new();
+    for tile in block {
+        match biome_counts
+            .iter_mut()
+            .find(|(biome, _)| *biome == tile.biome)
+        {
+            Some((_, count)) => *count += 1,
+            None => biome_counts.push((tile.biome, 1)),
+        }
+    }
+    // max_by_key returns the _last_ max, so reverse to let the first biome
+    // win ties
+    let (biome, _) = biome_counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .expect("cannot aggregate empty block of tiles");
+
+    Tile {
+        position,
+        elevation: Meter(mean(|tile| tile.elevation.0)),
+        rainfall: Meter3(mean(|tile| tile.rainfall.0)),
+        temperature: Celsius(mean(|tile| tile.temperature.0)),
+        humidity: mean(|tile| tile.humidity),
+        fertility: mean(|tile| tile.fertility),
+        runoff: Meter3(mean(|tile| tile.runoff.0)),
+        runoff_traversed: Default::default(),
+        drainage: DrainageDestination::None,
+        watershed: None,
+        // Recalculated once all the tiles are known
+        terrain_class: TerrainClass::Flat,
+        coast_distance: None,
+        biome,
+        features: Vec::new(),
+    }
+}
+
+/// Calculate the latitude of a position in a world of the given radius. See
+/// [World::latitude]. This is separate so it can be used during generation,
+/// before the world exists.
+pub(crate) fn latitude(position: TilePoint, radius: u16) -> f64 {
+    if radius == 0 {
+        return 0.0;
+    }
+    // Rows of tiles that run horizontally across the screen all share the same
+    // z, and z increases going north
+    (position.z() as f64 / radius as f64).clamp(-1.0, 1.0)
 }
 
 /// High-level categories for biomes: land or water?
@@ -162,7 +1666,20 @@ pub enum BiomeType {
 ///
 /// https://en.wikipedia.org/wiki/Biome
 #[cfg_attr(feature = "js", wasm_bindgen)]
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumIter,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum Biome {
     // Water
@@ -205,6 +1722,12 @@ pub enum GeoFeature {
     /// more info.
     Lake,
 
+    /// A lake tile that's part of a particularly large body of water. Large
+    /// lakes are classified as inland seas, based on
+    /// [GeoFeatureConfig::inland_sea_size_threshold](crate::GeoFeatureConfig::inland_sea_size_threshold).
+    /// Any tile with this feature will **also** have [Self::Lake].
+    InlandSea,
+
     /// A river entering a tile from a specific direction. A tile can have
     /// multiple river entrances, but each one must have a unique direction and
     /// none of them can have the same direction as a river exit. These are
@@ -221,4 +1744,476 @@ pub enum GeoFeature {
         direction: TileDirection,
         volume: Meter3,
     },
+
+    /// Polar ice. Ice forms on cold tiles at high latitudes, based on
+    /// [BiomeConfig::ice_cap_latitude](crate::BiomeConfig::ice_cap_latitude).
+    /// Ice can appear on both land and water, and land tiles with ice always
+    /// have the [Biome::Snow] biome.
+    Ice,
+}
+
+impl GeoFeature {
+    /// Get the kind of this feature, which drops any associated data
+    pub fn kind(self) -> GeoFeatureKind {
+        match self {
+            Self::Lake => GeoFeatureKind::Lake,
+            Self::InlandSea => GeoFeatureKind::InlandSea,
+            Self::RiverEntrance { .. } => GeoFeatureKind::RiverEntrance,
+            Self::RiverExit { .. } => GeoFeatureKind::RiverExit,
+            Self::Ice => GeoFeatureKind::Ice,
+        }
+    }
+}
+
+/// The kind of a [GeoFeature], without any of the associated data (e.g. river
+/// direction and volume). Useful for grouping and counting features.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeoFeatureKind {
+    Lake,
+    InlandSea,
+    RiverEntrance,
+    RiverExit,
+    Ice,
+}
+
+/// A non-fatal problem detected in a generated world. These indicate that the
+/// config is valid, but probably isn't producing the world the user wanted.
+/// See [World::generate_with_warnings].
+#[derive(Copy, Clone, Debug, Display, PartialEq, Eq)]
+pub enum GenerationWarning {
+    /// Every tile in the world is water
+    #[display(fmt = "World has no land")]
+    NoLand,
+    /// The world has land, but no rivers formed. Typically this means there
+    /// isn't enough rainfall, or the river threshold is too high.
+    #[display(fmt = "World has land but no rivers")]
+    NoRivers,
+    /// Rainfall (and therefore runoff) simulation was disabled, so there are
+    /// no rivers or lakes.
+    #[display(fmt = "Rainfall and runoff simulation are disabled")]
+    RunoffDisabled,
+}
+
+/// A summary of where runoff from a tile ends up, based on how water flows
+/// downhill during runoff simulation. This only accounts for the initial
+/// downhill flow, **not** for basins that later overflow. So a tile that
+/// drains to a terminal could still end up contributing to the ocean, if that
+/// terminal's basin overflows.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrainageDestination {
+    /// The tile didn't participate in runoff simulation, either because it's
+    /// water or because rainfall simulation was disabled.
+    #[default]
+    None,
+    /// All runoff from this tile flows into the ocean (or off the edge of the
+    /// world).
+    Ocean,
+    /// Some or all runoff from this tile collects at one or more terminal
+    /// tiles, which are tiles with no lower neighbors. A terminal tile drains
+    /// to itself.
+    Terminals {
+        /// Positions of the terminal tiles, sorted
+        terminals: Vec<TilePoint>,
+        /// Does any of the runoff also reach the ocean?
+        ocean: bool,
+    },
+}
+
+impl DrainageDestination {
+    /// Does at least some runoff from this tile reach the ocean?
+    pub fn reaches_ocean(&self) -> bool {
+        match self {
+            Self::None => false,
+            Self::Ocean => true,
+            Self::Terminals { ocean, .. } => *ocean,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util;
+
+    /// Build a world with the given radius, where each tile's biome is
+    /// determined by the given function. All other tile data is zeroed out.
+    fn make_world(radius: u16, biome_fn: impl Fn(TilePoint) -> Biome) -> World {
+        let mut tiles = TilePointMap::default();
+        let r = radius as i16;
+        for x in -r..=r {
+            for y in (-r).max(-x - r)..=r.min(-x + r) {
+                let position = TilePoint::new_xy(x, y);
+                let tile = Tile {
+                    biome: biome_fn(position),
+                    ..Tile::new_test(position)
+                };
+                tiles.insert(position, tile);
+            }
+        }
+        assert_eq!(tiles.len(), util::world_len(radius));
+        let config = WorldConfig {
+            radius,
+            ..Default::default()
+        };
+        World {
+            meta: WorldMeta::new(&config),
+            config,
+            tiles,
+            layers: HashMap::new(),
+            climate_rng: None,
+        }
+    }
+
+    #[test]
+    fn test_suggested_spawn_no_land() {
+        let world = make_world(3, |_| Biome::Ocean);
+        assert_eq!(world.suggested_spawn(), None);
+
+        let world = make_world(3, |pos| {
+            if pos == TilePoint::new_xy(1, -2) {
+                Biome::Plains
+            } else {
+                Biome::Ocean
+            }
+        });
+        assert_eq!(world.suggested_spawn(), Some(TilePoint::new_xy(1, -2)));
+    }
+
+    #[test]
+    fn test_terrain_features() {
+        let peak = TilePoint::ORIGIN;
+        let pit = TilePoint::new_xy(2, 0);
+        let mut world = make_world(3, |_| Biome::Plains);
+        for tile in world.tiles.values_mut() {
+            tile.elevation = match tile.position {
+                pos if pos == peak => Meter(10.0),
+                pos if pos == pit => Meter(0.0),
+                _ => Meter(5.0),
+            };
+        }
+
+        let classes = world.terrain_features();
+        assert_eq!(classes.len(), world.tiles.len());
+        assert_eq!(classes[&peak], TerrainClass::Peak);
+        assert_eq!(classes[&pit], TerrainClass::Pit);
+        // Far from both, everything is level
+        assert_eq!(classes[&TilePoint::new_xy(-3, 0)], TerrainClass::Flat);
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let from = TilePoint::new_xy(-4, 0);
+        let to = TilePoint::new_xy(4, 0);
+        let mut world = make_world(5, |_| Biome::Plains);
+
+        // Flat plain, nothing in the way
+        assert!(world.has_line_of_sight(from, to, Meter(0.0)));
+        assert!(world.has_line_of_sight(from, from, Meter(0.0)));
+        // Off the edge of the world
+        assert!(!world.has_line_of_sight(
+            from,
+            TilePoint::new_xy(9, 0),
+            Meter(0.0)
+        ));
+
+        // A ridge between the two, running across the line
+        for tile in world.tiles.values_mut() {
+            if tile.position.x() == 0 {
+                tile.elevation = Meter(10.0);
+            }
+        }
+        assert!(!world.has_line_of_sight(from, to, Meter(0.0)));
+        assert!(!world.has_line_of_sight(to, from, Meter(9.0)));
+        // Standing tall enough to see over it
+        assert!(world.has_line_of_sight(from, to, Meter(10.0)));
+        assert!(world.has_line_of_sight(to, from, Meter(12.0)));
+
+        // Standing on the ridge, you can see down both sides
+        let crest = TilePoint::ORIGIN;
+        assert!(world.has_line_of_sight(crest, from, Meter(0.0)));
+        assert!(world.has_line_of_sight(to, crest, Meter(0.0)));
+    }
+
+    #[test]
+    fn test_interestingness() {
+        let ocean = make_world(5, |_| Biome::Ocean);
+        assert_eq!(ocean.interestingness(), 0.0);
+
+        // Half land with a few different biomes, plus a river
+        let biomes = [Biome::Forest, Biome::Plains, Biome::Desert];
+        let mut varied = make_world(5, |pos| {
+            if pos.x() > 0 {
+                Biome::Ocean
+            } else {
+                biomes[pos.y().rem_euclid(3) as usize]
+            }
+        });
+        let score = varied.interestingness();
+        assert!(score > 0.3 && score <= 1.0, "Unexpected score {score}");
+
+        // Adding water features should make things more interesting
+        for tile in varied.tiles.values_mut() {
+            if tile.position() == TilePoint::ORIGIN {
+                tile.features.push(GeoFeature::Lake);
+            }
+        }
+        assert!(varied.interestingness() > score);
+    }
+
+    #[test]
+    fn test_land_centroid() {
+        // No land at all
+        let world = make_world(3, |_| Biome::Ocean);
+        assert_eq!(world.land_centroid(), None);
+
+        // Land only in the +x/+y quadrant of screen space
+        let world = make_world(5, |pos| {
+            let screen_pos = hex_to_screen_space(pos);
+            if screen_pos.x > 0.0 && screen_pos.y > 0.0 {
+                Biome::Plains
+            } else {
+                Biome::Ocean
+            }
+        });
+        let centroid = world.land_centroid().unwrap();
+        assert!(
+            centroid.x > 0.0 && centroid.y > 0.0,
+            "Expected centroid {centroid} to be in the +x/+y quadrant"
+        );
+    }
+
+    #[test]
+    fn test_coastline() {
+        // Island of radius 2 in the middle, water around it
+        let world = make_world(4, |pos| {
+            if pos.distance_to(TilePoint::ORIGIN) <= 2 {
+                Biome::Plains
+            } else {
+                Biome::Ocean
+            }
+        });
+        let coastline = world.coastline();
+
+        // Only the outer ring of the island touches water
+        assert_eq!(coastline.len(), 12);
+        for tile in &coastline {
+            assert!(tile.is_land_biome());
+            assert!(world
+                .adjacent_tiles(tile.position())
+                .any(Tile::is_water_biome));
+        }
+        // Inland tiles are excluded
+        for pos in TilePoint::ORIGIN.adjacents().chain([TilePoint::ORIGIN]) {
+            assert!(!coastline.iter().any(|tile| tile.position() == pos));
+        }
+    }
+
+    #[test]
+    fn test_continent_coastline_length() {
+        // A hexagonal island of radius r has 6 * (2r + 1) sides facing water,
+        // so the length should grow along with the outer ring of the island
+        for (island_radius, coast_sides) in [(0, 6.0), (1, 18.0), (2, 30.0)] {
+            let world = make_world(4, |pos| {
+                if pos.distance_to(TilePoint::ORIGIN) <= island_radius {
+                    Biome::Plains
+                } else {
+                    Biome::Ocean
+                }
+            });
+            let continents = world.continents();
+            assert_eq!(continents.len(), 1);
+            assert_eq!(
+                continents[0].coastline_length(&world),
+                Tile::SIDE_LENGTH * coast_sides
+            );
+        }
+
+        // Sides along the edge of the world don't face any water
+        let world = make_world(2, |_| Biome::Plains);
+        assert_eq!(world.continents()[0].coastline_length(&world), Meter(0.0));
+    }
+
+    #[test]
+    fn test_land_bridges() {
+        // A dumbbell: two round islands joined by a neck one tile wide
+        let (left, right) = (TilePoint::new_xy(-7, 0), TilePoint::new_xy(7, 0));
+        let neck: Vec<TilePoint> =
+            (-3..=3).map(|x| TilePoint::new_xy(x, 0)).collect();
+        let world = make_world(12, |pos| {
+            if pos.distance_to(left) <= 3
+                || pos.distance_to(right) <= 3
+                || neck.contains(&pos)
+            {
+                Biome::Plains
+            } else {
+                Biome::Ocean
+            }
+        });
+        assert_eq!(world.continents().len(), 1);
+        assert_eq!(world.land_bridges(1), vec![neck.clone()]);
+        // A wider limit still finds the same neck
+        assert_eq!(world.land_bridges(3), vec![neck]);
+        // Nothing is narrower than 0 tiles
+        assert!(world.land_bridges(0).is_empty());
+
+        // A peninsula doesn't connect anything
+        let world = make_world(12, |pos| {
+            if pos.distance_to(left) <= 3
+                || (-3..=3).contains(&pos.x()) && pos.y() == 0
+            {
+                Biome::Plains
+            } else {
+                Biome::Ocean
+            }
+        });
+        assert!(world.land_bridges(1).is_empty());
+    }
+
+    #[test]
+    fn test_wind_at() {
+        let mut world = make_world(3, |_| Biome::Plains);
+        assert_eq!(world.wind_at(TilePoint::ORIGIN), None);
+
+        world.meta.wind_direction = Some(HexAxialDirection::Y_POS);
+        for pos in world.tiles.keys() {
+            assert_eq!(world.wind_at(*pos), Some(TileDirection::SSE));
+        }
+        assert_eq!(world.wind_at(TilePoint::new_xy(4, 0)), None);
+    }
+
+    #[test]
+    fn test_coastline_contours() {
+        // Two islands, plus one tile of land in a corner of the world
+        let corner = TilePoint::new_xy(6, -6);
+        let world = make_world(6, |pos| {
+            if pos.distance_to(TilePoint::ORIGIN) <= 1
+                || pos == TilePoint::new_xy(-4, 0)
+                || pos == corner
+            {
+                Biome::Plains
+            } else {
+                Biome::Ocean
+            }
+        });
+
+        let mut contours = world.coastline_contours();
+        contours.sort_by_key(Vec::len);
+        assert_eq!(
+            contours.iter().map(Vec::len).collect::<Vec<_>>(),
+            // Closed loops get their first point repeated at the end
+            vec![3, 6 + 1, 18 + 1]
+        );
+        // Each island produces a closed loop
+        for contour in &contours[1..] {
+            assert_eq!(contour.first(), contour.last());
+        }
+        // The corner tile only has water on 3 sides, and the polyline is cut
+        // off where it runs into the edge of the world
+        assert_ne!(contours[0].first(), contours[0].last());
+
+        // The single-tile island's loop visits the midpoint of each of its
+        // sides in order, walking clockwise
+        let island = TilePoint::new_xy(-4, 0);
+        let midpoints: Vec<Point2> = TileDirection::CLOCKWISE
+            .iter()
+            .map(|&dir| hex_to_screen_space(island.side_midpoint(dir)))
+            .collect();
+        let loop_points = &contours[1][..6];
+        let offset = midpoints
+            .iter()
+            .position(|point| *point == loop_points[0])
+            .expect("contour doesn't start on a side midpoint");
+        for (i, point) in loop_points.iter().enumerate() {
+            assert_eq!(*point, midpoints[(offset + i) % 6]);
+        }
+
+        // No coast, no contours
+        let world = make_world(2, |_| Biome::Plains);
+        assert!(world.coastline_contours().is_empty());
+    }
+
+    #[test]
+    fn test_validate_features() {
+        let entrance = GeoFeature::RiverEntrance {
+            direction: TileDirection::W,
+            volume: Meter3(100.0),
+        };
+        let exit = GeoFeature::RiverExit {
+            direction: TileDirection::E,
+            volume: Meter3(100.0),
+        };
+        let source = TilePoint::ORIGIN;
+        let dest = source.adjacent(TileDirection::E);
+
+        let mut world = make_world(3, |_| Biome::Plains);
+        world.validate_features().unwrap();
+        world.tiles.get_mut(&source).unwrap().features.push(exit);
+        world.tiles.get_mut(&dest).unwrap().features.push(entrance);
+        world.validate_features().unwrap();
+
+        // Exit with no matching entrance
+        let mut corrupted = world.clone();
+        corrupted.tiles.get_mut(&dest).unwrap().features.clear();
+        assert!(corrupted.validate_features().is_err());
+
+        // Entrance with no matching exit
+        let mut corrupted = world.clone();
+        corrupted.tiles.get_mut(&source).unwrap().features.clear();
+        assert!(corrupted.validate_features().is_err());
+
+        // Entrance and exit in the same direction
+        let mut corrupted = world.clone();
+        corrupted.tiles.get_mut(&source).unwrap().features.push(
+            GeoFeature::RiverEntrance {
+                direction: TileDirection::E,
+                volume: Meter3(100.0),
+            },
+        );
+        assert!(corrupted.validate_features().is_err());
+
+        // Rivers are allowed to flow out into water
+        let mut world = make_world(3, |pos| {
+            if pos == dest {
+                Biome::Ocean
+            } else {
+                Biome::Plains
+            }
+        });
+        world.tiles.get_mut(&source).unwrap().features.push(exit);
+        world.validate_features().unwrap();
+    }
+
+    #[test]
+    fn test_validate_complete() {
+        let world = World::generate(WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 10,
+            ..Default::default()
+        })
+        .unwrap();
+        world.validate_complete().unwrap();
+        // Zeroes are fine too
+        make_world(3, |_| Biome::Plains)
+            .validate_complete()
+            .unwrap();
+
+        let corruptions: [fn(&mut Tile); 4] = [
+            |tile| tile.elevation = Meter(f64::NAN),
+            |tile| tile.rainfall = Meter3(-1.0),
+            |tile| tile.runoff = Meter3(-1.0),
+            |tile| tile.runoff = Meter3(f64::INFINITY),
+        ];
+        for corrupt in corruptions {
+            let mut corrupted = world.clone();
+            corrupt(corrupted.tiles.get_mut(&TilePoint::ORIGIN).unwrap());
+            let error = corrupted.validate_complete().unwrap_err();
+            // The error should point at the offending tile
+            assert!(
+                error.to_string().contains(&TilePoint::ORIGIN.to_string()),
+                "{error}"
+            );
+        }
+    }
 }
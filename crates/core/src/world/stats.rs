@@ -0,0 +1,179 @@
+use crate::{
+    util::unit::{Meter, Meter3},
+    world::{tile::Tile, Biome, BiomeType, GeoFeature, GeoFeatureKind},
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Aggregate statistics about a generated world, useful for comparing the
+/// output of different configs. See [World::stats](crate::World::stats).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WorldStats {
+    /// Total number of tiles in the world
+    pub tile_count: usize,
+
+    /// Number of tiles of each biome. Biomes that don't appear in the world
+    /// are omitted.
+    pub biome_counts: HashMap<Biome, usize>,
+
+    /// The most common biome, and the fraction of all tiles that have it.
+    /// `None` if there are no tiles. See
+    /// [World::dominant_biome](crate::World::dominant_biome).
+    pub dominant_biome: Option<(Biome, f64)>,
+
+    /// Number of occurrences of each kind of geographic feature, across all
+    /// tiles. See [World::feature_counts](crate::World::feature_counts).
+    pub feature_counts: HashMap<GeoFeatureKind, usize>,
+
+    /// Number of tiles that rivers flow through. See
+    /// [World::total_river_length](crate::World::total_river_length).
+    pub total_river_length: usize,
+
+    /// River tiles per unit of land area. See
+    /// [World::drainage_density](crate::World::drainage_density).
+    pub drainage_density: f64,
+}
+
+impl WorldStats {
+    /// Compute stats over an arbitrary set of tiles
+    pub(crate) fn from_tiles<'a>(
+        tiles: impl Iterator<Item = &'a Tile> + Clone,
+    ) -> Self {
+        let mut tile_count = 0;
+        let mut biome_counts = HashMap::new();
+        for tile in tiles.clone() {
+            tile_count += 1;
+            *biome_counts.entry(tile.biome()).or_default() += 1;
+        }
+
+        Self {
+            tile_count,
+            dominant_biome: dominant_biome(&biome_counts, tile_count),
+            biome_counts,
+            feature_counts: feature_counts(tiles.clone()),
+            total_river_length: total_river_length(tiles.clone()),
+            drainage_density: drainage_density(tiles),
+        }
+    }
+
+    /// Fraction of tiles that have a water biome, in `[0, 1]`. Returns 0 if
+    /// there are no tiles.
+    pub fn water_fraction(&self) -> f64 {
+        if self.tile_count == 0 {
+            return 0.0;
+        }
+        let water_count: usize = self
+            .biome_counts
+            .iter()
+            .filter(|(biome, _)| biome.biome_type() == BiomeType::Water)
+            .map(|(_, count)| count)
+            .sum();
+        water_count as f64 / self.tile_count as f64
+    }
+}
+
+/// Average climate of all the tiles of one biome. See
+/// [World::biome_climate_summary](crate::World::biome_climate_summary).
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+pub struct BiomeClimate {
+    /// Number of tiles of this biome
+    pub tile_count: usize,
+    /// Mean elevation of tiles of this biome
+    pub mean_elevation: Meter,
+    /// Mean rainfall of tiles of this biome
+    pub mean_rainfall: Meter3,
+    /// Mean runoff of tiles of this biome
+    pub mean_runoff: Meter3,
+}
+
+/// See [World::biome_climate_summary](crate::World::biome_climate_summary)
+pub(super) fn biome_climate_summary<'a>(
+    tiles: impl Iterator<Item = &'a Tile>,
+) -> HashMap<Biome, BiomeClimate> {
+    // Sum everything up first, then divide once we know the counts
+    let mut sums: HashMap<Biome, (usize, Meter, Meter3, Meter3)> =
+        HashMap::new();
+    for tile in tiles {
+        let (count, elevation, rainfall, runoff) =
+            sums.entry(tile.biome()).or_default();
+        *count += 1;
+        *elevation += tile.elevation();
+        *rainfall += tile.rainfall();
+        *runoff += tile.runoff();
+    }
+    sums.into_iter()
+        .map(|(biome, (count, elevation, rainfall, runoff))| {
+            let climate = BiomeClimate {
+                tile_count: count,
+                mean_elevation: elevation / count as f64,
+                mean_rainfall: rainfall / count as f64,
+                mean_runoff: runoff / count as f64,
+            };
+            (biome, climate)
+        })
+        .collect()
+}
+
+/// Find the biome with the highest count, along with its fraction of the
+/// total. Ties go to the lowest biome (by ordering), so the result doesn't
+/// depend on hash order. See
+/// [World::dominant_biome](crate::World::dominant_biome).
+pub(super) fn dominant_biome(
+    biome_counts: &HashMap<Biome, usize>,
+    tile_count: usize,
+) -> Option<(Biome, f64)> {
+    if tile_count == 0 {
+        return None;
+    }
+    biome_counts
+        .iter()
+        .max_by(|(biome_a, count_a), (biome_b, count_b)| {
+            count_a.cmp(count_b).then_with(|| biome_b.cmp(biome_a))
+        })
+        .map(|(biome, count)| (*biome, *count as f64 / tile_count as f64))
+}
+
+/// See [World::feature_counts](crate::World::feature_counts)
+pub(super) fn feature_counts<'a>(
+    tiles: impl Iterator<Item = &'a Tile>,
+) -> HashMap<GeoFeatureKind, usize> {
+    let mut counts = HashMap::new();
+    for tile in tiles {
+        for feature in tile.features() {
+            *counts.entry(feature.kind()).or_default() += 1;
+        }
+    }
+    counts
+}
+
+/// See [World::total_river_length](crate::World::total_river_length)
+pub(super) fn total_river_length<'a>(
+    tiles: impl Iterator<Item = &'a Tile>,
+) -> usize {
+    tiles
+        .filter(|tile| {
+            tile.features().iter().any(|feature| {
+                matches!(
+                    feature,
+                    GeoFeature::RiverEntrance { .. }
+                        | GeoFeature::RiverExit { .. }
+                )
+            })
+        })
+        .count()
+}
+
+/// See [World::drainage_density](crate::World::drainage_density)
+pub(super) fn drainage_density<'a>(
+    tiles: impl Iterator<Item = &'a Tile> + Clone,
+) -> f64 {
+    let land_count = tiles
+        .clone()
+        .filter(|tile| tile.biome().biome_type() == BiomeType::Land)
+        .count();
+    if land_count == 0 {
+        return 0.0;
+    }
+    let land_area = land_count as f64 * Tile::AREA.0;
+    total_river_length(tiles) as f64 / land_area
+}
@@ -0,0 +1,237 @@
+use crate::{
+    util::{cmp_unwrap, unit::Meter},
+    world::{
+        hex::{
+            HasHexPosition, HexDirection, TileDirection, TilePoint,
+            TilePointMap,
+        },
+        tile::Tile,
+    },
+};
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use strum::EnumIter;
+#[cfg(feature = "js")]
+use wasm_bindgen::prelude::*;
+
+/// Neighbors within this much elevation of a tile are considered level with
+/// it, so that tiny bumps don't turn flat land into a field of peaks and pits
+const LEVEL_TOLERANCE: Meter = Meter(0.5);
+
+/// A morphological classification of a tile, based on its elevation compared
+/// to its neighbors. This is handy for labeling mountain ridges and valley
+/// floors. See [World::terrain_features](crate::World::terrain_features).
+#[cfg_attr(feature = "js", wasm_bindgen)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Display,
+    EnumIter,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum TerrainClass {
+    /// A local maximum: no neighbor is higher
+    Peak,
+    /// Almost all neighbors are lower, but at least one is higher
+    Ridge,
+    /// A mix of higher and lower neighbors
+    Slope,
+    /// Almost all neighbors are higher, but at least one is lower
+    Valley,
+    /// A local minimum: no neighbor is lower
+    Pit,
+    /// All neighbors are level with the tile
+    #[default]
+    Flat,
+}
+
+impl TerrainClass {
+    /// Classify a tile from the number of its neighbors that are higher and
+    /// lower than it, out of its total number of neighbors. Neighbors that
+    /// are level with the tile count toward the total, but neither of the
+    /// others. A tile is a ridge (or valley) if at least two thirds of its
+    /// neighbors are lower (or higher).
+    fn from_counts(higher: usize, lower: usize, total: usize) -> Self {
+        match (higher, lower) {
+            (0, 0) => Self::Flat,
+            (0, _) => Self::Peak,
+            (_, 0) => Self::Pit,
+            _ if lower * 3 >= total * 2 => Self::Ridge,
+            _ if higher * 3 >= total * 2 => Self::Valley,
+            _ => Self::Slope,
+        }
+    }
+}
+
+/// Classify every tile by comparing its elevation to its neighbors. See
+/// [TerrainClass]. Neighbors outside the world are ignored, unless
+/// `wrap_radius` is given, in which case neighbors wrap around the edges of
+/// the world.
+pub(crate) fn terrain_classes(
+    tiles: &TilePointMap<Tile>,
+    wrap_radius: Option<u16>,
+) -> TilePointMap<TerrainClass> {
+    tiles
+        .values()
+        .map(|tile| {
+            let mut higher = 0;
+            let mut lower = 0;
+            let mut total = 0;
+            for adj_tile in tile
+                .position()
+                .adjacents_wrapping(wrap_radius)
+                .filter_map(|adj_pos| tiles.get(&adj_pos))
+            {
+                let diff = adj_tile.elevation() - tile.elevation();
+                if diff > LEVEL_TOLERANCE {
+                    higher += 1;
+                } else if diff < -LEVEL_TOLERANCE {
+                    lower += 1;
+                }
+                total += 1;
+            }
+            (
+                tile.position(),
+                TerrainClass::from_counts(higher, lower, total),
+            )
+        })
+        .collect()
+}
+
+/// Trace ridge lines by following steepest ascent from every saddle up to a
+/// peak. A saddle is a tile whose neighbors, going around it, rise and fall
+/// at least twice, i.e. there are at least two separate arcs of higher
+/// neighbors. Unlike [terrain_classes], there's no [LEVEL_TOLERANCE] here,
+/// since the ground right around a saddle is nearly flat. One line is traced
+/// up each higher arc, starting from the highest neighbor in that arc, then
+/// always stepping to the highest neighbor until no neighbor is higher. Each
+/// line starts at its saddle, and ends at a tile that [terrain_classes] would
+/// call a [TerrainClass::Peak]. Lines that top out on a [TerrainClass::Flat]
+/// tile instead are dropped.
+///
+/// Tiles missing any neighbor (i.e. along the edge of an unwrapped world)
+/// can't be saddles. Lines are ordered by saddle position, then clockwise
+/// around the saddle. Lines from different saddles can share tiles, and
+/// often end at the same peak.
+pub(crate) fn ridge_lines(
+    tiles: &TilePointMap<Tile>,
+    wrap_radius: Option<u16>,
+) -> Vec<Vec<TilePoint>> {
+    let mut saddles: Vec<&Tile> = tiles.values().collect();
+    saddles.sort_by_key(|tile| tile.position());
+
+    let mut lines = Vec::new();
+    for saddle in saddles {
+        for start in rising_arc_summits(tiles, saddle, wrap_radius) {
+            let mut line = vec![saddle.position()];
+            let mut current = start;
+            loop {
+                line.push(current.position());
+                // Strictly uphill, so this can't loop forever
+                let next = current
+                    .position()
+                    .adjacents_wrapping(wrap_radius)
+                    .filter_map(|adj_pos| tiles.get(&adj_pos))
+                    .filter(|adj_tile| {
+                        adj_tile.elevation() > current.elevation()
+                    })
+                    .max_by(|a, b| cmp_unwrap(&a.elevation(), &b.elevation()));
+                match next {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+            // A flat hilltop isn't a peak, so the line doesn't count
+            let is_peak = current
+                .position()
+                .adjacents_wrapping(wrap_radius)
+                .filter_map(|adj_pos| tiles.get(&adj_pos))
+                .any(|adj_tile| {
+                    current.elevation() - adj_tile.elevation() > LEVEL_TOLERANCE
+                });
+            if is_peak {
+                lines.push(line);
+            }
+        }
+    }
+    lines
+}
+
+/// If a tile is a saddle (see [ridge_lines]), get the highest neighbor in each
+/// arc of higher neighbors, in clockwise order. If it isn't a saddle, get
+/// nothing.
+fn rising_arc_summits<'a>(
+    tiles: &'a TilePointMap<Tile>,
+    tile: &Tile,
+    wrap_radius: Option<u16>,
+) -> Vec<&'a Tile> {
+    // Each neighbor that's higher or lower, in clockwise order
+    let mut ring: Vec<(bool, &Tile)> = Vec::with_capacity(6);
+    for dir in TileDirection::CLOCKWISE {
+        let adj_pos = tile.position().adjacent_wrapping(*dir, wrap_radius);
+        let adj_tile = match tiles.get(&adj_pos) {
+            Some(adj_tile) => adj_tile,
+            None => return Vec::new(),
+        };
+        match cmp_unwrap(&adj_tile.elevation(), &tile.elevation()) {
+            Ordering::Greater => ring.push((true, adj_tile)),
+            Ordering::Less => ring.push((false, adj_tile)),
+            Ordering::Equal => {}
+        }
+    }
+
+    // Start the ring on a lower neighbor, so that every higher arc is
+    // followed by a lower one and none of them wrap around the end
+    let first_lower = match ring.iter().position(|(is_higher, _)| !is_higher) {
+        Some(i) => i,
+        None => return Vec::new(),
+    };
+    ring.rotate_left(first_lower);
+
+    let mut summits = Vec::new();
+    let mut summit: Option<&Tile> = None;
+    for (is_higher, adj_tile) in ring {
+        if is_higher {
+            summit = match summit {
+                Some(summit) if summit.elevation() >= adj_tile.elevation() => {
+                    Some(summit)
+                }
+                _ => Some(adj_tile),
+            };
+        } else if let Some(summit) = summit.take() {
+            summits.push(summit);
+        }
+    }
+    summits.extend(summit);
+
+    if summits.len() >= 2 {
+        summits
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_counts() {
+        assert_eq!(TerrainClass::from_counts(0, 0, 6), TerrainClass::Flat);
+        assert_eq!(TerrainClass::from_counts(0, 6, 6), TerrainClass::Peak);
+        assert_eq!(TerrainClass::from_counts(0, 2, 6), TerrainClass::Peak);
+        assert_eq!(TerrainClass::from_counts(6, 0, 6), TerrainClass::Pit);
+        assert_eq!(TerrainClass::from_counts(1, 4, 6), TerrainClass::Ridge);
+        assert_eq!(TerrainClass::from_counts(4, 1, 6), TerrainClass::Valley);
+        assert_eq!(TerrainClass::from_counts(3, 3, 6), TerrainClass::Slope);
+        assert_eq!(TerrainClass::from_counts(2, 2, 6), TerrainClass::Slope);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
-    world::hex::TileDirectionValues, Biome, BiomeType, GeoFeature,
-    HasHexPosition, Meter, Meter2, Meter3, TilePoint, World,
+    world::hex::TileDirectionValues, Biome, BiomeType, Celsius,
+    DrainageDestination, GeoFeature, HasHexPosition, Meter, Meter2, Meter3,
+    TerrainClass, TilePoint, WatershedId, World,
 };
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "js")]
@@ -32,6 +33,17 @@ pub struct Tile {
     /// Amount of rain that fell on this tile during rain simulation.
     pub(super) rainfall: Meter3,
 
+    /// Temperature of this tile, based on latitude and elevation.
+    pub(super) temperature: Celsius,
+
+    /// Normalized humidity of this tile, in `[0, 1]`. Currently derived from
+    /// rainfall during generation.
+    pub(super) humidity: f64,
+
+    /// How suitable this tile's land is for growing things, in `[0, 1]`.
+    /// Always 0 for water.
+    pub(super) fertility: f64,
+
     /// Amount of runoff water that remains on the tile after runoff
     /// simulation.
     pub(super) runoff: Meter3,
@@ -41,8 +53,34 @@ pub struct Tile {
     /// direction) and negative values indicate egress (i.e. runoff left in
     /// that direction). The value should be positive if the neighbor in that
     /// direction is a higher elevation, and negative if it is lower.
+    ///
+    /// This will be all zeroes if it's been stripped with
+    /// [World::clear_runoff_traversed], in which case we skip it during
+    /// serialization to save space.
+    #[serde(default, skip_serializing_if = "is_default")]
     pub(super) runoff_traversed: TileDirectionValues<Meter3>,
 
+    /// Where runoff from this tile ends up. Derived from the runoff pattern
+    /// that was used during runoff simulation.
+    pub(super) drainage: DrainageDestination,
+
+    /// The watershed that this tile belongs to. `None` for water tiles, and
+    /// for land that didn't participate in runoff simulation.
+    // Serde treats missing options as None by default, but a missing value
+    // means the save is from before this was generated, so require it
+    #[serde(deserialize_with = "Option::deserialize")]
+    pub(super) watershed: Option<WatershedId>,
+
+    /// The shape of the land around this tile, based on its elevation
+    /// compared to its neighbors.
+    pub(super) terrain_class: TerrainClass,
+
+    /// Distance from this tile to the nearest coastline, in tiles. `None` for
+    /// water, and for land that can't reach any coast.
+    // Required for the same reason as watershed
+    #[serde(deserialize_with = "Option::deserialize")]
+    pub(super) coast_distance: Option<u16>,
+
     /// The biome for this tile. Every tile exists in a single biome, which
     /// describes its climate characteristics. See [Biome] for more info.
     pub(super) biome: Biome,
@@ -66,6 +104,11 @@ impl Tile {
     /// runoff stuff harder?
     pub const AREA: Meter2 = Meter2(1.0);
 
+    /// Length of one side of a tile, in world space. This is the side length
+    /// of a regular hexagon with an area of [Self::AREA], i.e.
+    /// `sqrt(2 * AREA / (3 * sqrt(3)))`. Same caveats as [Self::AREA] apply.
+    pub const SIDE_LENGTH: Meter = Meter(0.6204032394013997);
+
     /// Get a list of geographic features that appear on this tile. See
     /// [GeoFeature] for more info.
     ///
@@ -89,20 +132,33 @@ impl Tile {
         self.rainfall
     }
 
-    /// A normalized (meaning [0,1]) proxy for rainfall. Since rainfall is an
-    /// unbounded range, we define an arbitrary soft maximum for it, and
-    /// anything at/above that max will map to 1.0 humidity. Anything between
-    /// the min (0) and the soft max will map proportionally to [0,1] to
-    /// determine humidity.
+    /// A normalized (meaning [0,1]) measure of the moisture on this tile.
+    /// Humidity is generated separately from rainfall, but currently it's
+    /// just a proxy for it. Since rainfall is an unbounded range, we define an
+    /// arbitrary soft maximum for it, and anything at/above that max will map
+    /// to 1.0 humidity. Anything between the min (0) and the soft max will map
+    /// proportionally to [0,1] to determine humidity.
     ///
     /// This function will **always** return a value in [0,1].
     pub fn humidity(&self) -> f64 {
-        World::RAINFALL_SOFT_RANGE
-            .value(self.rainfall)
-            .clamp()
-            .convert::<f64>()
-            .normalize()
-            .inner()
+        self.humidity
+    }
+
+    /// A normalized (meaning [0,1]) measure of how fertile this tile's land
+    /// is. Fertility is highest on wet, temperate, flat land, and low on
+    /// anything dry, frozen/scorching or steep. Water tiles always have 0
+    /// fertility.
+    ///
+    /// This function will **always** return a value in [0,1].
+    pub fn fertility(&self) -> f64 {
+        self.fertility
+    }
+
+    /// The temperature of this tile. Temperature is warmest at the equator and
+    /// coldest at the poles, and decreases with elevation above sea level.
+    /// See [TemperatureConfig](crate::TemperatureConfig).
+    pub fn temperature(&self) -> Celsius {
+        self.temperature
     }
 
     /// The amount of water runoff that collected on this tile. This is the
@@ -136,6 +192,38 @@ impl Tile {
             .sum::<Meter3>()
     }
 
+    /// Get a summary of where runoff from this tile ends up. See
+    /// [DrainageDestination] for more info.
+    ///
+    /// **Note**: NOT available to WebAssembly, for the same reason as
+    /// [Self::features].
+    pub fn drainage(&self) -> &DrainageDestination {
+        &self.drainage
+    }
+
+    /// Get the watershed that this tile drains to, i.e. the region of land
+    /// that shares its final outlet. `None` for water tiles, and for land
+    /// that didn't participate in runoff simulation. See [World::watersheds].
+    ///
+    /// **Note**: NOT available to WebAssembly, for the same reason as
+    /// [Self::features].
+    pub fn watershed(&self) -> Option<WatershedId> {
+        self.watershed
+    }
+
+    /// Get the shape of the land around this tile, e.g. whether it's a peak or
+    /// a valley floor. See [TerrainClass] and [World::terrain_features].
+    pub fn terrain_class(&self) -> TerrainClass {
+        self.terrain_class
+    }
+
+    /// Get the distance from this tile to the nearest coastline, in tiles.
+    /// Coastline tiles (land next to water) are 0. `None` for water tiles, and
+    /// for land that can't reach any coast. See [World::coast_distance_field].
+    pub fn coast_distance(&self) -> Option<u16> {
+        self.coast_distance
+    }
+
     /// Get the elevation of the top of the runoff on this tile. For tiles with
     /// no runoff, this will just be the tile's elevation. For lake tiles and
     /// others with runoff, it will be the elevation of the water's surface.
@@ -162,6 +250,23 @@ impl Tile {
     }
 }
 
+// Wasm-friendly API
+#[cfg_attr(feature = "js", wasm_bindgen)]
+impl Tile {
+    /// The tile's elevation, mapped from [World::ELEVATION_RANGE] to `[0, 1]`.
+    /// Useful for driving gradients/shaders, so that consumers don't have to
+    /// duplicate the elevation range.
+    #[cfg_attr(feature = "js", wasm_bindgen(getter))]
+    pub fn elevation_normalized(&self) -> f64 {
+        World::ELEVATION_RANGE.normalize(self.elevation).0
+    }
+}
+
+/// Used to skip serializing fields that have no meaningful data
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 impl HasHexPosition for Tile {
     type Point = TilePoint;
 
@@ -169,3 +274,51 @@ impl HasHexPosition for Tile {
         self.position
     }
 }
+
+#[cfg(test)]
+impl Tile {
+    /// Create a tile with all data zeroed out (and an ocean biome), for tests
+    /// that need to construct worlds by hand. Use struct update syntax to
+    /// override specific fields.
+    pub(crate) fn new_test(position: TilePoint) -> Self {
+        Self {
+            position,
+            elevation: Meter(0.0),
+            rainfall: Meter3(0.0),
+            temperature: Celsius(0.0),
+            humidity: 0.0,
+            fertility: 0.0,
+            runoff: Meter3(0.0),
+            runoff_traversed: Default::default(),
+            drainage: Default::default(),
+            watershed: None,
+            terrain_class: TerrainClass::Flat,
+            coast_distance: None,
+            biome: Biome::Ocean,
+            features: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+
+    #[test]
+    fn test_elevation_normalized() {
+        let tile = |elevation| Tile {
+            elevation,
+            ..Tile::new_test(TilePoint::ORIGIN)
+        };
+        assert_approx_eq!(
+            tile(World::ELEVATION_RANGE.min).elevation_normalized(),
+            0.0
+        );
+        assert_approx_eq!(tile(World::SEA_LEVEL).elevation_normalized(), 0.5);
+        assert_approx_eq!(
+            tile(World::ELEVATION_RANGE.max).elevation_normalized(),
+            1.0
+        );
+    }
+}
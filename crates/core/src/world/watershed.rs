@@ -0,0 +1,123 @@
+use crate::{
+    util,
+    world::{
+        hex::{HasHexPosition, TilePoint, TilePointMap},
+        tile::Tile,
+        DrainageDestination, World,
+    },
+};
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+/// Identifies a watershed, which is the region of land that drains to a
+/// single outlet. The ID is the position of that outlet tile:
+/// - For tiles that drain to one or more terminals, it's the first of those
+///   terminals (see [DrainageDestination::Terminals])
+/// - For tiles that drain entirely to the ocean, it's the last land tile along
+///   the steepest path downhill, i.e. the mouth where the runoff enters the
+///   ocean (or runs off the edge of the world)
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub struct WatershedId(TilePoint);
+
+impl WatershedId {
+    pub(crate) fn new(outlet: TilePoint) -> Self {
+        Self(outlet)
+    }
+
+    /// Position of the tile that all runoff in this watershed drains to
+    pub fn outlet(self) -> TilePoint {
+        self.0
+    }
+}
+
+/// Label every land tile with the watershed it drains to. Water tiles, and
+/// land tiles that have no drainage data (e.g. because rainfall simulation was
+/// disabled), are left out. If `wrap_radius` is given, downhill paths can
+/// cross the edges of a wrapping world.
+pub(crate) fn watersheds(
+    tiles: &TilePointMap<Tile>,
+    wrap_radius: Option<u16>,
+) -> TilePointMap<WatershedId> {
+    // Outlets for ocean-draining tiles, so that each downhill path only gets
+    // walked once
+    let mut ocean_outlets = TilePointMap::default();
+    tiles
+        .values()
+        .filter(|tile| tile.is_land_biome())
+        .filter_map(|tile| {
+            let outlet = match tile.drainage() {
+                DrainageDestination::None => return None,
+                DrainageDestination::Terminals { terminals, .. } => {
+                    *terminals.first()?
+                }
+                DrainageDestination::Ocean => ocean_outlet(
+                    tiles,
+                    tile.position(),
+                    wrap_radius,
+                    &mut ocean_outlets,
+                ),
+            };
+            Some((tile.position(), WatershedId(outlet)))
+        })
+        .collect()
+}
+
+/// Follow the steepest path downhill from a tile until the next step would
+/// leave land, and return the last land tile on that path. Water and
+/// positions outside the world are treated as bottomless sinks, the same as
+/// during runoff simulation.
+fn ocean_outlet(
+    tiles: &TilePointMap<Tile>,
+    start: TilePoint,
+    wrap_radius: Option<u16>,
+    cache: &mut TilePointMap<TilePoint>,
+) -> TilePoint {
+    let mut path = Vec::new();
+    let mut current = start;
+    let outlet = loop {
+        if let Some(outlet) = cache.get(&current) {
+            break *outlet;
+        }
+        path.push(current);
+
+        // Find the lowest neighbor, breaking ties by position so the path is
+        // deterministic. Sinks sort before land at the same elevation.
+        let (lowest_elev, is_land, lowest_pos) = current
+            .adjacents_wrapping(wrap_radius)
+            .map(|adj_pos| match tiles.get(&adj_pos) {
+                Some(adj_tile) if adj_tile.is_land_biome() => {
+                    (adj_tile.elevation(), true, adj_pos)
+                }
+                _ => (World::ELEVATION_RANGE.min, false, adj_pos),
+            })
+            .min_by(|a, b| {
+                util::cmp_unwrap(&a.0, &b.0)
+                    .then(a.1.cmp(&b.1))
+                    .then(a.2.cmp(&b.2))
+            })
+            .unwrap();
+
+        // Elevation strictly decreases along the path, so this always ends
+        if !is_land || lowest_elev >= tiles[&current].elevation() {
+            break current;
+        }
+        current = lowest_pos;
+    };
+
+    for pos in path {
+        cache.insert(pos, outlet);
+    }
+    outlet
+}
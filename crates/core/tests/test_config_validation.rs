@@ -1,39 +1,70 @@
 use terra::{
-    ElevationConfig, GeoFeatureConfig, Meter, Meter3, NoiseFnConfig,
-    NoiseFnType, RainfallConfig, RenderConfig, TileLens, World, WorldConfig,
-    WorldRenderer,
+    BiomeConfig, Celsius, Color3, ElevationConfig, GeoFeatureConfig, Meter,
+    Meter3, NoiseFnConfig, NoiseFnType, Palette, RainfallConfig, RenderConfig,
+    TemperatureConfig, TileLens, TilePoint, World, WorldConfig, WorldRenderer,
 };
-use validator::ValidationErrors;
+use validator::{Validate, ValidationErrors};
 
 #[test]
 fn test_world_config_validation() {
     let config = WorldConfig {
         seed: 0.into(),
         radius: 10001, // invalid (too big)
+        wrap: false,
+        center: TilePoint::new_xy(-500, 200),
+        capture_layers: false,
         elevation: ElevationConfig {
             noise_fn: NoiseFnConfig {
                 noise_type: NoiseFnType::Fbm,
                 octaves: 0,        // valid (but weird)
                 frequency: 0.0,    // invalid
-                lacunarity: -1.0,  // valid (but weird)
-                persistence: -1.0, // valid (but weird)
+                lacunarity: -1.0,  // invalid
+                persistence: -1.0, // invalid
                 exponent: -1.0,    // valid (but weird)
             },
             rounding_interval: Some(Meter(0.1)), // valid
+            smoothing_passes: 2,                 // valid
+            ridge_strength: -0.5,                // invalid
+            elevation_offset: Meter(-5.0),       // can't validate Meters :(
             edge_buffer_fraction: -0.1,          // invalid
             edge_buffer_exponent: -1.0,          // valid (but weird)
+            force_ocean_border: true,
+            guarantee_center_land: true,
+            ocean_floor: Some(Meter(1.0)), // invalid
+            coast_depth: Meter(-1.0),      // can't validate Meters :(
         },
         rainfall: RainfallConfig {
             enabled: true,
             evaporation_default: Meter3(-1.0), // can't validate Meter3s :(
             evaporation_land_scale: -1.0,      // invalid
+            coastal_evaporation_bonus: 0.5,    // valid
             evaporation_spread_distance: 0,    // valid
             evaporation_spread_exponent: -1.0, // valid (but weird)
             rainfall_fraction_limit: 5.0,      // invalid
+            min_rainfall: Meter3(-1.0),        // can't validate Meter3s :(
+            extra_evaporation: Default::default(), // valid
+            max_runoff_elevation: Some(Meter(-10.0)), // valid (but weird)
+            runoff_concentration: 2.0,         // valid
+            max_backflow_iterations: 0,        // invalid
+        },
+        temperature: TemperatureConfig {
+            equator_temperature: Celsius(30.0), // valid
+            pole_temperature: Celsius(40.0),    // valid (but weird)
+            elevation_lapse_rate: -1.0,         // invalid
+            hemisphere_offset: 0.25,            // valid
+            current_strength: 0.5,              // valid
         },
         geo_feature: GeoFeatureConfig {
             // Unfortunately we can't validate Meter3s right now
             river_runoff_traversed_threshold: Meter3(-1.0),
+            inland_sea_size_threshold: 100, // valid
+            meander_strength: 0.5,          // valid
+        },
+        biome: BiomeConfig {
+            ice_cap_latitude: 1.5,         // invalid
+            snow_line: Some(Meter(-50.0)), // valid (but weird)
+            smoothing: true,
+            desert_min_coast_distance: Some(0), // valid (but weird)
         },
     };
 
@@ -48,19 +79,119 @@ fn test_world_config_validation() {
     error_fields.sort_unstable();
     assert_eq!(
         error_fields,
-        vec!["elevation", "radius", "rainfall"],
+        vec!["biome", "elevation", "radius", "rainfall", "temperature"],
         "incorrect validation errors in {validation_errors:#?}",
     );
+
+    // The ocean floor has to be under water
+    let ocean_floor = |floor| ElevationConfig {
+        ocean_floor: Some(Meter(floor)),
+        ..Default::default()
+    };
+    assert!(ocean_floor(0.0).validate().is_ok());
+    assert!(ocean_floor(-20.0).validate().is_ok());
+    assert!(ocean_floor(1.0).validate().is_err());
+
+    // Extra evaporation can only add water
+    let extra_evaporation = |evaporation| RainfallConfig {
+        extra_evaporation: [(TilePoint::ORIGIN, Meter3(evaporation))]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    assert!(extra_evaporation(0.0).validate().is_ok());
+    assert!(extra_evaporation(10.0).validate().is_ok());
+    assert!(extra_evaporation(-1.0).validate().is_err());
+}
+
+/// Noise params that would blow up the amplitude of the noise function should
+/// be rejected
+#[test]
+fn test_noise_fn_config_validation() {
+    let noise_fn = ElevationConfig::default().noise_fn;
+    let invalid_fields = |config: NoiseFnConfig| match config.validate() {
+        Ok(()) => Vec::new(),
+        Err(errors) => {
+            let mut fields: Vec<&str> =
+                errors.errors().keys().copied().collect();
+            fields.sort_unstable();
+            fields
+        }
+    };
+
+    let valid = NoiseFnConfig {
+        persistence: 0.5,
+        lacunarity: 1.0,
+        ..noise_fn
+    };
+    assert_eq!(invalid_fields(valid), Vec::<&str>::new());
+    assert_eq!(
+        invalid_fields(NoiseFnConfig {
+            persistence: 1.0,
+            ..noise_fn
+        }),
+        Vec::<&str>::new()
+    );
+    assert_eq!(
+        invalid_fields(NoiseFnConfig {
+            persistence: 2.0,
+            ..noise_fn
+        }),
+        vec!["persistence"]
+    );
+    assert_eq!(
+        invalid_fields(NoiseFnConfig {
+            persistence: 0.0,
+            ..noise_fn
+        }),
+        vec!["persistence"]
+    );
+    assert_eq!(
+        invalid_fields(NoiseFnConfig {
+            lacunarity: 0.5,
+            ..noise_fn
+        }),
+        vec!["lacunarity"]
+    );
+
+    // Bad noise params should invalidate the whole world config
+    let config = WorldConfig {
+        elevation: ElevationConfig {
+            noise_fn: NoiseFnConfig {
+                persistence: 2.0,
+                ..noise_fn
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+    assert!(WorldConfig::default().validate().is_ok());
 }
 
 #[test]
 fn test_render_config_validation() {
     let render_config = RenderConfig {
-        vertical_scale: 0.0,           // invalid
-        tile_lens: TileLens::Surface,  // valid
-        show_features: false,          // valid
-        max_runoff: Meter3(5.0),       // valid (can't validate Meter3 atm)
-        max_runoff_flow: Meter3(50.0), // valid (can't validate Meter3 atm)
+        vertical_scale: 0.0,               // invalid
+        tile_lens: TileLens::Surface,      // valid
+        elevation_gradient: None,          // valid
+        palette: Palette::Grayscale,       // valid
+        hidden_biomes: Default::default(), // valid
+        show_features: false,              // valid
+        max_runoff: Meter3(5.0),           // valid (can't validate Meter3 atm)
+        max_runoff_flow: Meter3(50.0),     // valid (can't validate Meter3 atm)
+        min_lake_depth: Meter(0.1),        // valid (can't validate Meter atm)
+        svg_view_box: None,                // valid
+        position_jitter: 0.0,              // valid
+        svg_padding: -1.0,                 // invalid
+        minimap: false,                    // valid
+        draw_biome_borders: false,         // valid
+        show_elevation_labels: false,      // valid
+        stl_surface_only: false,           // valid
+        stl_bevel: -1.0,                   // invalid
+        png_supersample: 0,                // invalid
+        tile_scale: 0.0,                   // invalid
+        hex_orientation: Default::default(),
     };
 
     // This is a bit of a lazy check but it works well enough
@@ -74,7 +205,28 @@ fn test_render_config_validation() {
     error_fields.sort_unstable();
     assert_eq!(
         error_fields,
-        vec!["vertical_scale"],
+        vec![
+            "png_supersample",
+            "stl_bevel",
+            "svg_padding",
+            "tile_scale",
+            "vertical_scale"
+        ],
         "incorrect validation errors in {validation_errors:#?}",
     );
+
+    // Gradient stops have to be sorted and normalized
+    let gradient = |stops: &[f64]| RenderConfig {
+        elevation_gradient: Some(
+            stops
+                .iter()
+                .map(|&pos| (pos, Color3::new_int(0, 0, 0)))
+                .collect(),
+        ),
+        ..Default::default()
+    };
+    assert!(WorldRenderer::new(gradient(&[0.0, 0.5, 0.5, 1.0])).is_ok());
+    assert!(WorldRenderer::new(gradient(&[0.0, 1.0, 0.5])).is_err());
+    assert!(WorldRenderer::new(gradient(&[-0.1, 1.0])).is_err());
+    assert!(WorldRenderer::new(gradient(&[0.0, 1.5])).is_err());
 }
@@ -0,0 +1,1037 @@
+use assert_approx_eq::assert_approx_eq;
+#[cfg(feature = "png")]
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+use terra::{
+    rasterize_tiles, Biome, Color3, ColorTable, GeoFeature, HasHexPosition,
+    HexOrientation, Meter, Palette, Point2, RenderConfig, Tile, TileDirection,
+    TileLens, TilePoint, VertexDirection, World, WorldConfig, WorldRenderer,
+};
+
+/// Generate a small world with a fixed seed, so tests are reproducible
+fn small_world() -> World {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    };
+    World::generate(config).unwrap()
+}
+
+/// Decode an RGBA PNG, and return its width, height, and pixel buffer
+#[cfg(feature = "png")]
+fn decode_png(png: &[u8]) -> (u32, u32, Vec<u8>) {
+    let mut reader = png::Decoder::new(png).read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+    buffer.truncate(info.buffer_size());
+    (info.width, info.height, buffer)
+}
+
+/// The bulk color buffer should have one RGB triple per tile, and each triple
+/// should match what we get by coloring that tile individually
+#[test]
+fn test_tile_colors_for_lens() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+
+    for lens in [
+        TileLens::Surface,
+        TileLens::Biome,
+        TileLens::Elevation,
+        TileLens::Humidity,
+        TileLens::Fertility,
+        TileLens::Watershed,
+        TileLens::Runoff,
+        TileLens::Terrain,
+        TileLens::CoastDistance,
+    ] {
+        let colors = renderer.tile_colors_for_lens(&world, lens);
+        assert_eq!(colors.len(), world.tiles().len() * 3);
+
+        let lens_renderer = WorldRenderer::new(RenderConfig {
+            tile_lens: lens,
+            ..Default::default()
+        })
+        .unwrap();
+        for (tile, rgb) in
+            world.tiles_sorted().into_iter().zip(colors.chunks(3))
+        {
+            let (r, g, b) = lens_renderer.tile_color(tile).to_ints();
+            assert_eq!(rgb, [r, g, b], "Color mismatch for lens {lens}");
+        }
+    }
+}
+
+/// The grayscale palette should make every biome gray, and a custom palette
+/// should fall back to the standard colors for biomes it doesn't cover
+#[test]
+fn test_palette() {
+    let renderer = WorldRenderer::new(RenderConfig {
+        palette: Palette::Grayscale,
+        ..Default::default()
+    })
+    .unwrap();
+    for biome in Biome::iter() {
+        let color = renderer.biome_color(biome);
+        assert!(
+            color.red == color.green && color.green == color.blue,
+            "Expected {biome} to be gray, but got {color:?}"
+        );
+    }
+
+    let custom_color = Color3::new_int(255, 0, 255);
+    let standard_renderer =
+        WorldRenderer::new(RenderConfig::default()).unwrap();
+    let custom_renderer = WorldRenderer::new(RenderConfig {
+        palette: Palette::Custom(
+            [(Biome::Desert, custom_color)].into_iter().collect(),
+        ),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(custom_renderer.biome_color(Biome::Desert), custom_color);
+    assert_eq!(
+        custom_renderer.biome_color(Biome::Forest),
+        standard_renderer.biome_color(Biome::Forest)
+    );
+}
+
+/// RGBA colors should match the RGB colors, with transparency for hidden and
+/// water tiles
+#[test]
+fn test_tile_color_rgba() {
+    let world = small_world();
+    let hidden_biome = world.tiles()[&TilePoint::ORIGIN].biome();
+    let renderer = WorldRenderer::new(RenderConfig {
+        hidden_biomes: [hidden_biome].into_iter().collect(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    for tile in world.tiles().values() {
+        let color = renderer.tile_color_rgba(tile);
+        assert_eq!(color.rgb(), renderer.tile_color(tile));
+        let expected_alpha = if tile.biome() == hidden_biome {
+            0.0
+        } else if tile.is_water_biome() {
+            WorldRenderer::WATER_COLOR.alpha
+        } else {
+            1.0
+        };
+        assert_eq!(color.alpha, expected_alpha, "{}", tile.position());
+    }
+}
+
+/// Each vertex height should be the mean of the heights of the tiles that
+/// touch that vertex
+#[test]
+fn test_vertex_height() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let tiles = world.tiles();
+
+    let tile = &tiles[&TilePoint::ORIGIN];
+    for dir in VertexDirection::iter() {
+        let (left, right) = dir.adjacent_tile_directions();
+        let expected = (renderer.tile_height(tile)
+            + renderer.tile_height(&tiles[&tile.position().adjacent(left)])
+            + renderer.tile_height(&tiles[&tile.position().adjacent(right)]))
+            / 3.0;
+        assert_approx_eq!(renderer.vertex_height(&world, tile, dir), expected);
+    }
+
+    // The outermost vertex of an edge tile isn't shared with anything
+    let corner = &tiles[&TilePoint::new_xy(10, 0)];
+    let heights: Vec<f64> = VertexDirection::iter()
+        .map(|dir| renderer.vertex_height(&world, corner, dir))
+        .collect();
+    assert!(heights.contains(&renderer.tile_height(corner)));
+}
+
+/// ASCII STL should have one facet per triangle in the binary STL
+#[cfg(feature = "stl")]
+#[test]
+fn test_render_as_stl_ascii() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 1,
+        ..Default::default()
+    })
+    .unwrap();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+
+    let ascii = renderer.render_as_stl_ascii(&world);
+    assert!(ascii.starts_with("solid"));
+    assert!(ascii.trim_end().ends_with("endsolid terra"));
+
+    // Binary STL has an 80-byte header, followed by a little-endian u32
+    // triangle count
+    let binary = renderer.render_as_stl(&world);
+    let triangle_count =
+        u32::from_le_bytes(binary[80..84].try_into().unwrap()) as usize;
+    // Each tile has at least a top and bottom face, 4 triangles apiece
+    assert!(triangle_count >= world.tiles().len() * 8);
+    assert_eq!(ascii.matches("endfacet").count(), triangle_count);
+    assert_eq!(ascii.matches("facet normal").count(), triangle_count);
+}
+
+/// A surface-only STL should be a single height mesh, which is much lighter
+/// than a prism for every tile
+#[cfg(feature = "stl")]
+#[test]
+fn test_render_as_stl_surface_only() {
+    let world = small_world();
+    let triangle_count = |stl: &[u8]| {
+        u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize
+    };
+
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let prism_count = triangle_count(&renderer.render_as_stl(&world));
+    let renderer = WorldRenderer::new(RenderConfig {
+        stl_surface_only: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let surface_count = triangle_count(&renderer.render_as_stl(&world));
+
+    assert!(surface_count > 0);
+    assert!(surface_count < prism_count);
+    // Each tile owns at most 2 triangles of the mesh
+    assert!(surface_count <= world.tiles().len() * 2);
+}
+
+/// A bevel adds triangles to every tile, but shouldn't change the outline of
+/// the model
+#[cfg(feature = "stl")]
+#[test]
+fn test_render_as_stl_bevel() {
+    // Get the triangle count and (x, z) bounds of a model
+    let measure = |stl_bevel: f64| {
+        let renderer = WorldRenderer::new(RenderConfig {
+            stl_bevel,
+            ..Default::default()
+        })
+        .unwrap();
+        let ascii = renderer.render_as_stl_ascii(&small_world());
+        let mut bounds = [f32::INFINITY, f32::NEG_INFINITY].repeat(2);
+        for line in ascii.lines() {
+            if let Some(vertex) = line.trim().strip_prefix("vertex ") {
+                let coords: Vec<f32> =
+                    vertex.split(' ').map(|v| v.parse().unwrap()).collect();
+                for (i, coord) in [coords[0], coords[2]].into_iter().enumerate()
+                {
+                    bounds[i * 2] = bounds[i * 2].min(coord);
+                    bounds[i * 2 + 1] = bounds[i * 2 + 1].max(coord);
+                }
+            }
+        }
+        (ascii.matches("endfacet").count(), bounds)
+    };
+
+    let (sharp_count, sharp_bounds) = measure(0.0);
+    let (beveled_count, beveled_bounds) = measure(0.2);
+    // At least 2 extra triangles for each side of each tile. Tiles that are
+    // shorter than the bevel can end up with some extra walls too.
+    assert!(beveled_count >= sharp_count + small_world().tiles().len() * 12);
+    assert_eq!(beveled_bounds, sharp_bounds);
+}
+
+/// STL metadata ranges should cover every triangle exactly once, and describe
+/// the tile that each range came from
+#[cfg(all(feature = "stl", feature = "json"))]
+#[test]
+fn test_render_as_stl_with_metadata() {
+    use terra::StlMetadata;
+
+    let world = small_world();
+    for stl_surface_only in [false, true] {
+        let renderer = WorldRenderer::new(RenderConfig {
+            stl_surface_only,
+            ..Default::default()
+        })
+        .unwrap();
+        let (stl, metadata) = renderer.render_as_stl_with_metadata(&world);
+        let metadata: StlMetadata = serde_json::from_str(&metadata).unwrap();
+
+        // The model should be the same as we'd get without metadata
+        assert_eq!(stl.len(), renderer.render_as_stl(&world).len());
+        let triangle_count =
+            u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+        assert_eq!(metadata.triangle_count, triangle_count);
+
+        // Ranges should be non-empty and back-to-back, from the first
+        // triangle to the last
+        let mut next_start = 0;
+        for range in &metadata.ranges {
+            assert_eq!(range.start, next_start, "gap or overlap at {range:?}");
+            assert!(range.start < range.end, "empty range {range:?}");
+            next_start = range.end;
+
+            let tile = &world.tiles()[&range.position];
+            assert_eq!(range.biome, tile.biome());
+            assert_eq!(range.features.len(), tile.features().len());
+        }
+        assert_eq!(next_start, triangle_count);
+    }
+}
+
+/// Pull the value of the first viewBox attribute out of an SVG
+#[cfg(feature = "svg")]
+fn svg_view_box(svg: &str) -> &str {
+    let start = svg.find("viewBox=\"").unwrap() + "viewBox=\"".len();
+    let len = svg[start..].find('"').unwrap();
+    &svg[start..start + len]
+}
+
+/// A fixed view box should be used verbatim (plus padding), regardless of the
+/// size of the world
+#[cfg(feature = "svg")]
+#[test]
+fn test_svg_view_box() {
+    use terra::Point2;
+
+    let world_small = small_world();
+    let world_big = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Auto view box depends on world size
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    assert_ne!(
+        svg_view_box(&renderer.render_as_svg(&world_small)),
+        svg_view_box(&renderer.render_as_svg(&world_big))
+    );
+
+    let view_box = (Point2::new(-10.0, -5.0), Point2::new(10.0, 5.0));
+    let renderer = WorldRenderer::new(RenderConfig {
+        svg_view_box: Some(view_box),
+        ..Default::default()
+    })
+    .unwrap();
+    let svg_small = renderer.render_as_svg(&world_small);
+    let svg_big = renderer.render_as_svg(&world_big);
+    assert_eq!(svg_view_box(&svg_small), "-10 -5 20 10");
+    assert_eq!(svg_view_box(&svg_small), svg_view_box(&svg_big));
+
+    // Padding grows the box on every side
+    let renderer = WorldRenderer::new(RenderConfig {
+        svg_view_box: Some(view_box),
+        svg_padding: 1.5,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        svg_view_box(&renderer.render_as_svg(&world_small)),
+        "-11.5 -6.5 23 13"
+    );
+}
+
+/// There should be exactly one border for each pair of neighbors with
+/// different biomes
+#[cfg(feature = "svg")]
+#[test]
+fn test_svg_biome_borders() {
+    let world = small_world();
+    let border_count = |draw_biome_borders: bool| {
+        let renderer = WorldRenderer::new(RenderConfig {
+            draw_biome_borders,
+            ..Default::default()
+        })
+        .unwrap();
+        renderer
+            .render_as_svg(&world)
+            .matches("class=\"biome-border\"")
+            .count()
+    };
+
+    let differing_pairs: usize = world
+        .biome_adjacency_matrix()
+        .into_iter()
+        .filter(|((a, b), _)| a != b)
+        .map(|(_, count)| count)
+        .sum();
+    assert!(differing_pairs > 0);
+    assert_eq!(border_count(true), differing_pairs);
+    assert_eq!(border_count(false), 0);
+
+    // Hiding every biome but one leaves nothing to border
+    let renderer = WorldRenderer::new(RenderConfig {
+        draw_biome_borders: true,
+        hidden_biomes: Biome::iter().filter(|b| *b != Biome::Ocean).collect(),
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(!renderer
+        .render_as_svg(&world)
+        .contains("class=\"biome-border\""));
+}
+
+/// Elevation labels should give one label per tile, showing that tile's
+/// rounded elevation
+#[cfg(feature = "svg")]
+#[test]
+fn test_svg_elevation_labels() {
+    let world = small_world();
+    let render = |show_elevation_labels: bool| {
+        WorldRenderer::new(RenderConfig {
+            show_elevation_labels,
+            ..Default::default()
+        })
+        .unwrap()
+        .render_as_svg(&world)
+    };
+    assert!(!render(false).contains("class=\"elevation-label\""));
+
+    // Pull the text out of each label. Attribute order isn't stable, so find
+    // the end of the tag after the class attribute.
+    let svg = render(true);
+    let mut labels: Vec<i64> = svg
+        .split("class=\"elevation-label\"")
+        .skip(1)
+        .map(|rest| {
+            let content = &rest[rest.find('>').unwrap() + 1..];
+            let content = &content[..content.find("</text>").unwrap()];
+            content.trim().parse().unwrap()
+        })
+        .collect();
+    let mut expected: Vec<i64> = world
+        .tiles()
+        .values()
+        .map(|tile| tile.elevation().0.round() as i64)
+        .collect();
+    assert_eq!(labels.len(), world.tiles().len());
+    labels.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(labels, expected);
+}
+
+/// The minimap should only be drawn when enabled, and should outline the
+/// region covered by the view box
+#[cfg(feature = "svg")]
+#[test]
+fn test_svg_minimap() {
+    use terra::Point2;
+
+    // Big enough that the minimap gets downsampled
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 60,
+        ..Default::default()
+    })
+    .unwrap();
+    let view_box = (Point2::new(-10.0, -5.0), Point2::new(10.0, 5.0));
+
+    let renderer = WorldRenderer::new(RenderConfig {
+        svg_view_box: Some(view_box),
+        ..Default::default()
+    })
+    .unwrap();
+    let svg = renderer.render_as_svg(&world);
+    assert!(!svg.contains("minimap"));
+
+    let renderer = WorldRenderer::new(RenderConfig {
+        svg_view_box: Some(view_box),
+        minimap: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let svg = renderer.render_as_svg(&world);
+    assert!(svg.contains("class=\"minimap\""));
+    // The view box shouldn't be affected by the minimap
+    assert_eq!(svg_view_box(&svg), "-10 -5 20 10");
+
+    // Find the region indicator, which should cover the view box
+    let rect_start = svg.find("<rect").unwrap();
+    let rect =
+        &svg[rect_start..rect_start + svg[rect_start..].find('>').unwrap()];
+    assert!(rect.contains("class=\"minimap-region\""));
+    assert!(rect.contains("x=\"-10\""));
+    assert!(rect.contains("y=\"-5\""));
+    assert!(rect.contains("width=\"20\""));
+    assert!(rect.contains("height=\"10\""));
+}
+
+/// PPM output should have a P6 header, followed by 3 bytes per pixel
+#[test]
+fn test_render_as_ppm() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+
+    // Pull the dimensions out of the header
+    let parse = |ppm: &[u8]| -> (usize, usize, usize) {
+        let header: Vec<&[u8]> = ppm.splitn(4, |b| *b == b'\n').collect();
+        assert_eq!(header[0], b"P6");
+        assert_eq!(header[2], b"255");
+        let dimensions = std::str::from_utf8(header[1]).unwrap();
+        let (width, height) = dimensions.split_once(' ').unwrap();
+        (
+            width.parse().unwrap(),
+            height.parse().unwrap(),
+            header[3].len(),
+        )
+    };
+
+    let ppm = renderer.render_as_ppm(&world, 4).unwrap();
+    assert!(ppm.starts_with(b"P6\n"));
+    let (width, height, pixel_bytes) = parse(&ppm);
+    assert!(width > 0 && height > 0);
+    assert_eq!(pixel_bytes, width * height * 3);
+
+    // Doubling the resolution should (roughly) double each dimension
+    let (width_2x, height_2x, pixel_bytes_2x) =
+        parse(&renderer.render_as_ppm(&world, 8).unwrap());
+    assert_eq!(pixel_bytes_2x, width_2x * height_2x * 3);
+    assert!(width_2x.abs_diff(width * 2) <= 1);
+    assert!(height_2x.abs_diff(height * 2) <= 1);
+
+    // Zero-width tiles are a user error, not a panic
+    assert!(renderer.render_as_ppm(&world, 0).is_err());
+    assert!(renderer.render_to_rgba(&world, 0).is_err());
+    #[cfg(feature = "png")]
+    assert!(renderer.render_layers(&world, 0).is_err());
+}
+
+/// RGBA output should have 4 bytes per pixel, with transparent corners since
+/// the world is a hexagon
+#[test]
+fn test_render_to_rgba() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+
+    let (buffer, width, height) = renderer.render_to_rgba(&world, 4).unwrap();
+    assert!(width > 0 && height > 0);
+    assert_eq!(buffer.len(), (width * height * 4) as usize);
+
+    let pixel = |x: u32, y: u32| {
+        let i = ((y * width + x) * 4) as usize;
+        &buffer[i..i + 4]
+    };
+    for (x, y) in [
+        (0, 0),
+        (width - 1, 0),
+        (0, height - 1),
+        (width - 1, height - 1),
+    ] {
+        assert_eq!(pixel(x, y), [0, 0, 0, 0], "pixel ({x}, {y})");
+    }
+    // Center of the world should be opaque
+    assert_eq!(pixel(width / 2, height / 2)[3], u8::MAX);
+}
+
+/// Every layer should be present, and should be a PNG with the same
+/// dimensions as the other layers
+#[cfg(feature = "png")]
+#[test]
+fn test_render_layers() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let (_, width, height) = renderer.render_to_rgba(&world, 4).unwrap();
+
+    let layers = renderer.render_layers(&world, 4).unwrap();
+    assert_eq!(layers.len(), WorldRenderer::LAYER_NAMES.len());
+    let mut decoded = HashMap::new();
+    for name in ["biome", "elevation", "water", "rivers"] {
+        let png = layers
+            .get(name)
+            .unwrap_or_else(|| panic!("Missing layer {name}"));
+        let (layer_width, layer_height, pixels) = decode_png(png);
+        assert_eq!((layer_width, layer_height), (width, height), "{name}");
+        decoded.insert(name, pixels);
+    }
+
+    // The biome layer should match a plain render with the biome lens
+    let biome_renderer = WorldRenderer::new(RenderConfig {
+        tile_lens: TileLens::Biome,
+        ..Default::default()
+    })
+    .unwrap();
+    let (biome_rgba, _, _) = biome_renderer.render_to_rgba(&world, 4).unwrap();
+    assert_eq!(decoded["biome"], biome_rgba);
+
+    // The water layer only covers some of the world, so it should have
+    // both opaque and transparent pixels
+    let alphas: Vec<u8> =
+        decoded["water"].chunks(4).map(|pixel| pixel[3]).collect();
+    assert!(alphas.contains(&0));
+    assert!(alphas.contains(&u8::MAX));
+}
+
+/// Supersampling should keep the same output dimensions, but blend the colors
+/// along the boundaries between tiles
+#[test]
+fn test_png_supersample() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let supersample_renderer = WorldRenderer::new(RenderConfig {
+        png_supersample: 2,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let (buffer, width, height) = renderer.render_to_rgba(&world, 4).unwrap();
+    let (ss_buffer, ss_width, ss_height) =
+        supersample_renderer.render_to_rgba(&world, 4).unwrap();
+    assert_eq!((ss_width, ss_height), (width, height));
+    assert_eq!(ss_buffer.len(), buffer.len());
+    assert_ne!(ss_buffer, buffer);
+
+    // Blending creates new colors that aren't the color of any tile
+    let distinct_colors = |buffer: &[u8]| {
+        buffer
+            .chunks(4)
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+    };
+    assert!(distinct_colors(&ss_buffer) > distinct_colors(&buffer));
+
+    // Only pixels near the boundary between two colors should change. Deep
+    // inside a tile, every sub-pixel hits the same tile, so the average is
+    // the same as the plain sample.
+    let pixel = |buffer: &[u8], x: u32, y: u32| {
+        let i = ((y * width + x) * 4) as usize;
+        buffer[i..i + 4].to_vec()
+    };
+    let mut changed = 0;
+    for y in 2..height - 2 {
+        for x in 2..width - 2 {
+            if pixel(&ss_buffer, x, y) == pixel(&buffer, x, y) {
+                continue;
+            }
+            changed += 1;
+            let color = pixel(&buffer, x, y);
+            let on_boundary = (y - 2..=y + 2).any(|adj_y| {
+                (x - 2..=x + 2)
+                    .any(|adj_x| pixel(&buffer, adj_x, adj_y) != color)
+            });
+            assert!(on_boundary, "pixel ({x}, {y}) changed inside a tile");
+        }
+    }
+    assert!(changed > 0, "no pixels were smoothed");
+
+    // Supersampling can push the dimensions out of range
+    assert!(supersample_renderer
+        .render_to_rgba(&world, u32::MAX)
+        .is_err());
+}
+
+/// Scanline spans should cover the whole height of the image, and match the
+/// colors of the full raster
+#[test]
+fn test_rasterize_tiles() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 1,
+        ..Default::default()
+    })
+    .unwrap();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let (buffer, width, height) = renderer.render_to_rgba(&world, 8).unwrap();
+
+    let rows: Vec<_> = rasterize_tiles(&renderer, &world, 8).unwrap().collect();
+    assert_eq!(rows.len(), height as usize);
+    for (i, (y, spans)) in rows.iter().enumerate() {
+        assert_eq!(*y, i as u32);
+        // Spans are sorted and don't overlap
+        for pair in spans.windows(2) {
+            assert!(pair[0].0.end <= pair[1].0.start);
+        }
+
+        for x in 0..width {
+            let i = ((y * width + x) * 4) as usize;
+            let pixel = &buffer[i..i + 4];
+            match spans.iter().find(|(range, _)| range.contains(&x)) {
+                Some((_, color)) => {
+                    let (r, g, b) = color.to_ints();
+                    assert_eq!(pixel, [r, g, b, u8::MAX], "pixel ({x}, {y})");
+                }
+                None => assert_eq!(pixel[3], 0, "pixel ({x}, {y})"),
+            }
+        }
+    }
+
+    // The middle row goes through 3 tiles, all the way across the world
+    let (_, middle) = &rows[height as usize / 2];
+    assert_eq!(middle.len(), 3);
+    // The top and bottom rows are in the margin around the world
+    assert!(rows[0].1.is_empty());
+    assert!(rows[height as usize - 1].1.is_empty());
+}
+
+/// Jitter should move tiles, but the same way every time
+#[test]
+fn test_position_jitter() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let jitter_renderer = WorldRenderer::new(RenderConfig {
+        position_jitter: 0.25,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let mut any_moved = false;
+    for tile in world.tiles().values() {
+        let position = renderer.jittered_tile_position(tile);
+        assert_eq!(position, renderer.tile_position(tile));
+
+        let jittered = jitter_renderer.jittered_tile_position(tile);
+        let offset = jittered - position;
+        assert!((offset.x.powi(2) + offset.y.powi(2)).sqrt() <= 0.25);
+        any_moved |= jittered != position;
+        // Stable across calls
+        assert_eq!(jittered, jitter_renderer.jittered_tile_position(tile));
+    }
+    assert!(any_moved);
+}
+
+/// Jittered SVGs should differ from un-jittered ones, but be reproducible
+#[cfg(feature = "svg")]
+#[test]
+fn test_svg_position_jitter() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let jitter_renderer = WorldRenderer::new(RenderConfig {
+        position_jitter: 0.25,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let jittered = jitter_renderer.render_as_svg(&world);
+    assert_ne!(jittered, renderer.render_as_svg(&world));
+    assert_eq!(jittered, jitter_renderer.render_as_svg(&world));
+}
+
+/// Hiding a biome should drop all tiles of that biome from the SVG
+#[cfg(feature = "svg")]
+#[test]
+fn test_svg_hidden_biomes() {
+    // Needs to be big enough to have an ocean
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    })
+    .unwrap();
+    let ocean_count = world
+        .tiles()
+        .values()
+        .filter(|tile| tile.biome() == Biome::Ocean)
+        .count();
+    assert!(ocean_count > 0);
+
+    // Each tile is drawn as one translated group
+    let tile_groups = |svg: &str| svg.matches("<g transform").count();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    assert_eq!(
+        tile_groups(&renderer.render_as_svg(&world)),
+        world.tiles().len()
+    );
+
+    let renderer = WorldRenderer::new(RenderConfig {
+        hidden_biomes: [Biome::Ocean].into_iter().collect(),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        tile_groups(&renderer.render_as_svg(&world)),
+        world.tiles().len() - ocean_count
+    );
+}
+
+/// Tile scale should scale all screen space distances
+#[test]
+fn test_tile_scale() {
+    let distance = |renderer: &WorldRenderer| {
+        let offset = renderer.hex_to_screen_space(TilePoint::new_xy(1, 0))
+            - renderer.hex_to_screen_space(TilePoint::ORIGIN);
+        (offset.x.powi(2) + offset.y.powi(2)).sqrt()
+    };
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let scaled_renderer = WorldRenderer::new(RenderConfig {
+        tile_scale: 2.0,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_approx_eq!(distance(&scaled_renderer), distance(&renderer) * 2.0);
+    assert_approx_eq!(
+        scaled_renderer.tile_side_radius(),
+        renderer.tile_side_radius() * 2.0
+    );
+    assert_approx_eq!(
+        scaled_renderer.tile_vertex_radius(),
+        WorldRenderer::TILE_VERTEX_RADIUS * 2.0
+    );
+}
+
+/// A custom color function should override the lens for every tile
+#[test]
+fn test_render_with_color_fn() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let color = Color3::new_int(12, 34, 56);
+
+    let ppm = renderer.render_ppm_with(&world, 4, |_| color).unwrap();
+    // Skip the header, which is 3 lines
+    let header_len = ppm
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| **byte == b'\n')
+        .nth(2)
+        .unwrap()
+        .0
+        + 1;
+    let pixels: Vec<&[u8]> = ppm[header_len..].chunks(3).collect();
+    assert!(pixels.iter().any(|pixel| *pixel == [12, 34, 56]));
+    for pixel in pixels {
+        // Anything that isn't the custom color has to be background
+        assert!(
+            pixel == [12, 34, 56] || pixel == [0, 0, 0],
+            "Unexpected pixel {pixel:?}"
+        );
+    }
+
+    // The PNG should have the same pixels, except the background is
+    // transparent
+    #[cfg(feature = "png")]
+    {
+        let png = renderer.render_png_with(&world, 4, |_| color).unwrap();
+        let (width, height, rgba) = decode_png(&png);
+        let (expected, expected_width, expected_height) =
+            renderer.render_to_rgba(&world, 4).unwrap();
+        assert_eq!((width, height), (expected_width, expected_height));
+        assert_eq!(rgba.len(), expected.len());
+        assert!(rgba.chunks(4).any(|pixel| pixel == [12, 34, 56, u8::MAX]));
+        for (pixel, expected_pixel) in rgba.chunks(4).zip(expected.chunks(4)) {
+            // Same coverage as a normal render, but every tile is recolored
+            if expected_pixel[3] == 0 {
+                assert_eq!(pixel, [0, 0, 0, 0]);
+            } else {
+                assert_eq!(pixel, [12, 34, 56, u8::MAX]);
+            }
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    {
+        let svg = renderer.render_svg_with(&world, |_| color);
+        assert_eq!(svg.matches("fill=").count(), world.tiles().len());
+        assert_eq!(
+            svg.matches(&format!("fill=\"{}\"", color.to_html()))
+                .count(),
+            world.tiles().len()
+        );
+    }
+}
+
+/// Screen bounds should contain every vertex of every tile, including jitter
+#[test]
+fn test_screen_bounds() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig {
+        tile_scale: 1.5,
+        position_jitter: 0.3,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let (min, max) = renderer.screen_bounds(&world);
+    let mut touches_min = false;
+    let mut touches_max = false;
+    for tile in world.tiles().values() {
+        for vertex in renderer.tile_vertices(tile) {
+            assert!(
+                min.x <= vertex.x
+                    && min.y <= vertex.y
+                    && vertex.x <= max.x
+                    && vertex.y <= max.y,
+                "{vertex:?} is outside bounds {min:?}, {max:?}"
+            );
+            touches_min |= vertex.x == min.x || vertex.y == min.y;
+            touches_max |= vertex.x == max.x || vertex.y == max.y;
+        }
+    }
+    // The bounds should be tight
+    assert!(touches_min && touches_max);
+}
+
+/// A square covering one quadrant of the world should select exactly the tiles
+/// with centers in that quadrant
+#[test]
+fn test_tiles_in_polygon() {
+    let world = small_world();
+    let renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let (_, max) = renderer.screen_bounds(&world);
+    // Nudge the square off the axes, so no tile center sits right on an edge
+    let (near, far) = (0.01, max.x.max(max.y) + 1.0);
+    let square = [
+        Point2::new(near, near),
+        Point2::new(far, near),
+        Point2::new(far, far),
+        Point2::new(near, far),
+    ];
+
+    let selected = renderer.tiles_in_polygon(&world, &square);
+    let expected: Vec<&Tile> = world
+        .tiles_sorted()
+        .into_iter()
+        .filter(|tile| {
+            let center = renderer.hex_to_screen_space(tile.position());
+            center.x > near && center.y > near
+        })
+        .collect();
+    assert!(!selected.is_empty());
+    assert!(selected.len() < world.tiles().len() / 2);
+    assert_eq!(
+        selected
+            .iter()
+            .map(|tile| tile.position())
+            .collect::<Vec<_>>(),
+        expected
+            .iter()
+            .map(|tile| tile.position())
+            .collect::<Vec<_>>()
+    );
+
+    // Winding order doesn't matter
+    let mut reversed = square;
+    reversed.reverse();
+    assert_eq!(
+        renderer.tiles_in_polygon(&world, &reversed).len(),
+        selected.len()
+    );
+    // Degenerate polygons contain nothing
+    assert!(renderer.tiles_in_polygon(&world, &square[..2]).is_empty());
+}
+
+/// Flat-top tiles are a 90° rotation of pointy-top tiles, so the width and
+/// height of the world should swap
+#[test]
+fn test_hex_orientation() {
+    let world = small_world();
+    let size = |orientation: HexOrientation| {
+        let renderer = WorldRenderer::new(RenderConfig {
+            hex_orientation: orientation,
+            ..Default::default()
+        })
+        .unwrap();
+        let (min, max) = renderer.screen_bounds(&world);
+        (max.x - min.x, max.y - min.y)
+    };
+
+    let (pointy_width, pointy_height) = size(HexOrientation::PointyTop);
+    let (flat_width, flat_height) = size(HexOrientation::FlatTop);
+    assert_approx_eq!(pointy_width, flat_height);
+    assert_approx_eq!(pointy_height, flat_width);
+    // Make sure the rotation actually changed something
+    assert!((pointy_width - pointy_height).abs() > 0.1);
+
+    // Screen space north for pointy-top becomes east for flat-top
+    let renderer = WorldRenderer::new(RenderConfig {
+        hex_orientation: HexOrientation::FlatTop,
+        ..Default::default()
+    })
+    .unwrap();
+    let north = TilePoint::ORIGIN
+        .adjacent(TileDirection::NNE)
+        .adjacent(TileDirection::NNW);
+    assert_approx_eq!(renderer.bearing(TilePoint::ORIGIN, north), 90.0);
+
+    // Raster output should be rotated too
+    let (_, width, height) = WorldRenderer::new(RenderConfig::default())
+        .unwrap()
+        .render_to_rgba(&world, 8)
+        .unwrap();
+    let (_, flat_width, flat_height) =
+        renderer.render_to_rgba(&world, 8).unwrap();
+    assert!(flat_width.abs_diff(height) <= 1);
+    assert!(flat_height.abs_diff(width) <= 1);
+}
+
+#[test]
+fn test_color_table() {
+    let renderer = WorldRenderer::new(RenderConfig {
+        tile_lens: TileLens::Biome,
+        ..Default::default()
+    })
+    .unwrap();
+    match renderer.color_table() {
+        ColorTable::Discrete { entries } => {
+            assert_eq!(entries.len(), Biome::iter().count());
+            for (entry, biome) in entries.iter().zip(Biome::iter()) {
+                assert_eq!(entry.label, biome.to_string());
+                assert_eq!(entry.color, renderer.biome_color(biome));
+            }
+        }
+        table => panic!("Expected discrete table, got {table:?}"),
+    }
+
+    // Gradients should span the whole elevation range
+    let renderer = WorldRenderer::new(RenderConfig {
+        tile_lens: TileLens::Elevation,
+        ..Default::default()
+    })
+    .unwrap();
+    match renderer.color_table() {
+        ColorTable::Gradient { stops } => {
+            assert_eq!(stops.len(), 2);
+            assert_eq!(stops[0].value, World::ELEVATION_RANGE.min.0);
+            assert_eq!(stops[1].value, World::ELEVATION_RANGE.max.0);
+            assert_eq!(stops[0].color, Color3::new_int(255, 255, 255));
+        }
+        table => panic!("Expected gradient table, got {table:?}"),
+    }
+}
+
+/// Lakes shallower than the min depth should be drawn as their biome instead,
+/// while deeper lakes are still drawn as water
+#[test]
+fn test_min_lake_depth() {
+    // Needs to be big enough to have some lakes
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    })
+    .unwrap();
+    let depth = |tile: &Tile| tile.runoff() / Tile::AREA;
+    let mut lakes: Vec<&Tile> = world
+        .tiles()
+        .values()
+        .filter(|tile| {
+            let features = tile.features();
+            features.contains(&GeoFeature::Lake)
+                && !features.contains(&GeoFeature::InlandSea)
+                && !features.contains(&GeoFeature::Ice)
+        })
+        .collect();
+    lakes.sort_by(|a, b| depth(a).partial_cmp(&depth(b)).unwrap());
+    let shallow = lakes.first().expect("world has no lakes");
+    let deep = lakes.last().unwrap();
+    assert!(
+        depth(shallow) < depth(deep),
+        "all lakes have the same depth"
+    );
+
+    let default_renderer = WorldRenderer::new(RenderConfig::default()).unwrap();
+    let lake_color = default_renderer.tile_color(shallow);
+    assert_ne!(lake_color, default_renderer.biome_color(shallow.biome()));
+
+    let renderer = WorldRenderer::new(RenderConfig {
+        min_lake_depth: Meter((depth(shallow).0 + depth(deep).0) / 2.0),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        renderer.tile_color(shallow),
+        renderer.biome_color(shallow.biome())
+    );
+    assert_eq!(renderer.tile_color(deep), lake_color);
+    // The underlying data is untouched
+    assert!(shallow.features().contains(&GeoFeature::Lake));
+}
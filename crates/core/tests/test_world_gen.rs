@@ -1,4 +1,14 @@
-use terra::{ElevationConfig, World, WorldConfig};
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use std::{cmp::Reverse, collections::HashSet};
+#[cfg(feature = "json")]
+use terra::TileBoundingBox;
+use terra::{
+    flood_fill, Biome, BiomeConfig, BiomeType, DrainageDestination,
+    ElevationConfig, GenerationWarning, GeoFeature, GeoFeatureKind,
+    HasHexPosition, HexDirection, Meter, Meter3, NoiseFnConfig, RainfallConfig,
+    TerrainClass, Tile, TileDirection, TilePoint, World, WorldConfig,
+};
 
 /// Sanity check, make sure the default world config doesn't horrifically crash
 /// and burn.
@@ -70,3 +80,2497 @@ fn test_world_gen_mega_lake() {
     let world = World::generate(config).unwrap();
     assert_eq!(world.tiles().len(), 10981);
 }
+
+/// Every land tile on the edge of its continent slopes down toward the ocean
+/// (or off the edge of the world), so some of its runoff should drain there
+#[test]
+fn test_drainage_to_ocean() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    };
+    let world = World::generate(config).unwrap();
+
+    let mut edge_tiles = 0;
+    for tile in world.tiles().values().filter(|tile| tile.is_land_biome()) {
+        let on_edge = tile.position().adjacents().any(|adj| {
+            world
+                .drainage_destination(adj)
+                .map_or(true, |drainage| *drainage == DrainageDestination::None)
+        });
+        if on_edge && tile.elevation() > World::ELEVATION_RANGE.min {
+            edge_tiles += 1;
+            assert!(
+                tile.drainage().reaches_ocean(),
+                "Expected {tile:?} to drain to the ocean"
+            );
+        }
+    }
+    // The outer ring of the world is always on the edge, so this should never
+    // be empty
+    assert!(edge_tiles > 0);
+}
+
+/// A coastal evaporation bonus should put more moisture in the clouds near
+/// shore, so land tiles along the coast should get more rain
+#[test]
+fn test_coastal_evaporation_bonus() {
+    /// Average rainfall over all land tiles that border water
+    fn coastal_rainfall(coastal_evaporation_bonus: f64) -> f64 {
+        let world = World::generate(WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 100,
+            rainfall: RainfallConfig {
+                coastal_evaporation_bonus,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        let tiles = world.tiles();
+        let coastal: Vec<f64> = tiles
+            .values()
+            .filter(|tile| {
+                tile.is_land_biome()
+                    && tile.position().adjacents().any(|adj| {
+                        tiles
+                            .get(&adj)
+                            .map_or(false, |adj| adj.is_water_biome())
+                    })
+            })
+            .map(|tile| tile.rainfall().0)
+            .collect();
+        assert!(!coastal.is_empty(), "World has no coastline");
+        coastal.iter().sum::<f64>() / coastal.len() as f64
+    }
+
+    assert!(coastal_rainfall(1.0) > coastal_rainfall(0.0));
+}
+
+/// The metadata getters should just reflect the config
+#[test]
+fn test_world_metadata() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 3,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(world.radius(), 3);
+    assert_eq!(world.radius(), world.config().radius);
+    assert_eq!(world.seed_string(), "1021522790211909");
+
+    let world = World::generate(WorldConfig {
+        seed: "potato".into(),
+        radius: 1,
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(world.radius(), 1);
+    assert_eq!(world.seed_string(), "potato");
+}
+
+/// Stripping directional runoff data should shrink the serialized world, and
+/// the result should still load
+#[cfg(feature = "json")]
+#[test]
+fn test_clear_runoff_traversed() {
+    let mut world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+    let full_json = world.to_json();
+
+    world.clear_runoff_traversed();
+    let stripped_json = world.to_json();
+    assert!(
+        stripped_json.len() < full_json.len(),
+        "Expected stripped world ({} bytes) to be smaller than full world \
+        ({} bytes)",
+        stripped_json.len(),
+        full_json.len()
+    );
+    // Match the quoted key, since the config has a similarly named field
+    assert!(!stripped_json.contains("\"runoff_traversed\""));
+
+    let loaded = world.from_json(&stripped_json).unwrap();
+    assert_eq!(loaded.tiles().len(), world.tiles().len());
+    for tile in loaded.tiles().values() {
+        assert_eq!(tile.runoff_ingress(), Meter3(0.0));
+        assert_eq!(tile.runoff_egress(), Meter3(0.0));
+    }
+}
+
+/// Neighbor elevations should line up with the clockwise direction order, and
+/// be missing past the edge of the world
+#[test]
+fn test_neighbor_elevations() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+    let tiles = world.tiles();
+
+    // Center tile has all of its neighbors
+    let elevations = world.neighbor_elevations(TilePoint::ORIGIN);
+    for (elevation, dir) in elevations.iter().zip(TileDirection::CLOCKWISE) {
+        let neighbor = TilePoint::ORIGIN.adjacent(*dir);
+        assert_eq!(*elevation, Some(tiles[&neighbor].elevation()), "{dir:?}");
+    }
+
+    // Corner tile is missing its 3 outward neighbors
+    let corner = TilePoint::new_xy(10, 0);
+    let elevations = world.neighbor_elevations(corner);
+    for (elevation, dir) in elevations.iter().zip(TileDirection::CLOCKWISE) {
+        let expected = tiles.get(&corner.adjacent(*dir)).map(|t| t.elevation());
+        assert_eq!(*elevation, expected, "{dir:?}");
+    }
+    assert_eq!(elevations.iter().filter(|e| e.is_none()).count(), 3);
+    assert!(elevations[2].is_none()); // SSE
+    assert!(elevations[0].is_some()); // NNE
+}
+
+/// A cross section should follow the line between the two points, and drop
+/// the parts of the line that fall outside the world
+#[test]
+fn test_cross_section() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Start and end well outside the world
+    let from = TilePoint::new_xy(-15, 3);
+    let to = TilePoint::new_xy(20, -8);
+    let line = from.line_to(to);
+    let section = world.cross_section(from, to);
+    assert!(!section.is_empty());
+    assert!(section.len() < line.len());
+
+    for tile in &section {
+        assert!(line.contains(&tile.position()));
+    }
+    // The world is convex, so the in-world part of the line is contiguous
+    for pair in section.windows(2) {
+        assert_eq!(pair[0].position().distance_to(pair[1].position()), 1);
+    }
+    let expected: Vec<TilePoint> = line
+        .into_iter()
+        .filter(|pos| world.tiles().contains_key(pos))
+        .collect();
+    let actual: Vec<TilePoint> =
+        section.iter().map(|tile| tile.position()).collect();
+    assert_eq!(actual, expected);
+}
+
+/// Every band of a grid should cover the whole grid, and the cell in the middle
+/// of the grid should sample the origin tile
+#[test]
+fn test_to_grid() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(world.to_grid(0).is_err());
+    let grid = world.to_grid(8).unwrap();
+    let (width, height) = (grid.width() as usize, grid.height() as usize);
+    assert!(width > 0 && height > 0);
+    for (name, band) in grid.bands() {
+        assert_eq!(band.len(), width * height, "Wrong size for band {name}");
+    }
+
+    let origin = &world.tiles()[&TilePoint::ORIGIN];
+    let center = (height / 2) * width + width / 2;
+    assert_eq!(grid.elevation()[center], origin.elevation().0 as f32);
+    assert_eq!(grid.rainfall()[center], origin.rainfall().0 as f32);
+    // Corners are outside the hexagon
+    assert!(grid.elevation()[0].is_nan());
+
+    // Header is 52 bytes, then each band has a name and 4 bytes per cell
+    let bytes = grid.to_bytes();
+    assert!(bytes.starts_with(b"TRGD"));
+    let names_len: usize =
+        grid.bands().iter().map(|(name, _)| 1 + name.len()).sum();
+    assert_eq!(bytes.len(), 52 + names_len + 3 * width * height * 4);
+}
+
+/// The spawn point should be a land tile by the water, and the same every time
+#[test]
+fn test_suggested_spawn() {
+    // Needs to be big enough to have an ocean
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let spawn = world.suggested_spawn().unwrap();
+    assert_eq!(world.suggested_spawn(), Some(spawn));
+    let tile = &world.tiles()[&spawn];
+    assert!(tile.is_land_biome(), "{spawn} isn't land");
+    assert!(!tile.features().contains(&GeoFeature::Lake));
+
+    let has_river = tile.features().iter().any(|feature| {
+        matches!(
+            feature,
+            GeoFeature::RiverEntrance { .. } | GeoFeature::RiverExit { .. }
+        )
+    });
+    let next_to_water = spawn
+        .adjacents()
+        .filter_map(|pos| world.tiles().get(&pos))
+        .any(|neighbor| {
+            neighbor.is_water_biome()
+                || neighbor.features().contains(&GeoFeature::Lake)
+        });
+    assert!(has_river || next_to_water, "{spawn} isn't near water");
+}
+
+/// Coastline tiles should have a coast distance of 0, and each step inland
+/// should add exactly 1
+#[test]
+fn test_coast_distance_field() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+    let field = world.coast_distance_field();
+    assert!(!field.is_empty());
+
+    for tile in world.tiles().values() {
+        let pos = tile.position();
+        assert_eq!(tile.coast_distance(), field.get(&pos).copied());
+        let distance = match field.get(&pos) {
+            Some(distance) => *distance,
+            None => {
+                assert!(
+                    tile.is_water_biome(),
+                    "Land tile {pos} has no distance"
+                );
+                continue;
+            }
+        };
+        assert!(tile.is_land_biome(), "Water tile {pos} has a distance");
+
+        let is_coastline = world
+            .adjacent_tiles(pos)
+            .any(|adj_tile| adj_tile.is_water_biome());
+        assert_eq!(distance == 0, is_coastline, "Wrong distance for {pos}");
+        if distance > 0 {
+            // The nearest neighbor to the coast is exactly one step closer,
+            // and no neighbor can be more than one step away from this tile
+            let neighbor_distances: Vec<u16> = pos
+                .adjacents()
+                .filter_map(|adj_pos| field.get(&adj_pos).copied())
+                .collect();
+            assert_eq!(
+                neighbor_distances.iter().min(),
+                Some(&(distance - 1)),
+                "No downhill path to the coast from {pos}"
+            );
+            assert!(neighbor_distances.iter().all(|d| *d <= distance + 1));
+        }
+    }
+}
+
+/// World metadata should identify the generating config, and the config hash
+/// should be stable for a fixed config
+#[test]
+fn test_world_meta() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    let meta = world.meta();
+    assert_eq!(meta.terra_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(meta.created_seed, 1021522790211909);
+    assert_eq!(meta.config_hash, config.config_hash());
+    // An identical config built separately hashes the same
+    let same_config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    };
+    assert_eq!(meta.config_hash, same_config.config_hash());
+    assert_eq!(World::generate(config.clone()).unwrap().meta(), meta);
+
+    // Any change to the config should change the hash
+    let other_configs = [
+        WorldConfig {
+            radius: 11,
+            ..config.clone()
+        },
+        WorldConfig {
+            seed: 1.into(),
+            ..config.clone()
+        },
+        WorldConfig {
+            elevation: ElevationConfig {
+                smoothing_passes: 1,
+                ..config.elevation
+            },
+            ..config.clone()
+        },
+    ];
+    for other_config in other_configs {
+        assert_ne!(other_config.config_hash(), meta.config_hash);
+    }
+
+    // ...except for capturing layers, which doesn't affect the world
+    let capturing = WorldConfig {
+        capture_layers: true,
+        ..config
+    };
+    assert_eq!(capturing.config_hash(), meta.config_hash);
+}
+
+/// Two overlapping chunks of a bigger map should agree on the elevation of
+/// every tile they share, as long as nothing measured from the center of each
+/// chunk (e.g. the edge buffer) gets in the way
+#[test]
+fn test_center() {
+    let chunk = |center: TilePoint, elevation: ElevationConfig| {
+        World::generate(WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 10,
+            center,
+            elevation,
+            // Erosion changes elevation based on the rest of the chunk
+            rainfall: RainfallConfig {
+                enabled: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap()
+    };
+    let shared_elevations = |a: &World, b: &World| -> Vec<(Meter, Meter)> {
+        // Shift from a's coordinates into b's
+        let offset = TilePoint::new_xy(-15, 0);
+        let mut positions: Vec<TilePoint> = a.tiles().keys().copied().collect();
+        positions.sort();
+        positions
+            .into_iter()
+            .filter_map(|pos| {
+                let b_tile = b.tiles().get(&pos.checked_translate(offset)?)?;
+                Some((a.tiles()[&pos].elevation(), b_tile.elevation()))
+            })
+            .collect()
+    };
+
+    // The edge buffer, ocean border, and center land are all measured from
+    // the logical center, so they line up between chunks too
+    let elevation = ElevationConfig {
+        guarantee_center_land: true,
+        ..Default::default()
+    };
+    let origin_chunk = chunk(TilePoint::ORIGIN, elevation);
+    let neighbor_chunk = chunk(TilePoint::new_xy(15, 0), elevation);
+    let shared = shared_elevations(&origin_chunk, &neighbor_chunk);
+    assert!(!shared.is_empty(), "Chunks don't overlap");
+    for (a, b) in &shared {
+        assert_eq!(a, b);
+    }
+
+    // Sanity check: if both chunks are centered on the origin, the same
+    // tiles don't line up
+    let uncentered =
+        shared_elevations(&origin_chunk, &chunk(TilePoint::ORIGIN, elevation));
+    assert!(uncentered.iter().any(|(a, b)| a != b));
+
+    // Smoothing averages each tile with its neighbors, which get cut off at
+    // the edge of each chunk, so the seams no longer line up
+    let smoothed = ElevationConfig {
+        edge_buffer_fraction: 0.0,
+        force_ocean_border: false,
+        smoothing_passes: 1,
+        ..Default::default()
+    };
+    let smoothed = shared_elevations(
+        &chunk(TilePoint::ORIGIN, smoothed),
+        &chunk(TilePoint::new_xy(15, 0), smoothed),
+    );
+    assert!(smoothed.iter().any(|(a, b)| a != b));
+}
+
+/// Terrain classes stored on tiles during generation should match the ones
+/// computed from elevation after the fact
+#[test]
+fn test_terrain_class() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+    let classes = world.terrain_features();
+    assert_eq!(classes.len(), world.tiles().len());
+    for tile in world.tiles().values() {
+        assert_eq!(tile.terrain_class(), classes[&tile.position()]);
+    }
+}
+
+/// Every ridge line should start at a saddle, only ever climb, and end at a
+/// peak
+#[test]
+fn test_ridge_lines() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 50,
+        ..Default::default()
+    })
+    .unwrap();
+    let tiles = world.tiles();
+    let ridge_lines = world.ridge_lines();
+    assert!(!ridge_lines.is_empty(), "No ridge lines");
+
+    for line in &ridge_lines {
+        assert!(line.len() >= 2, "Ridge line is too short: {line:?}");
+        for pair in line.windows(2) {
+            let (from, to) = (&tiles[&pair[0]], &tiles[&pair[1]]);
+            assert!(
+                from.elevation() <= to.elevation(),
+                "Ridge line goes downhill from {} ({}) to {} ({})",
+                pair[0],
+                from.elevation(),
+                pair[1],
+                to.elevation()
+            );
+        }
+        let end = &tiles[line.last().unwrap()];
+        assert_eq!(
+            end.terrain_class(),
+            TerrainClass::Peak,
+            "Ridge line ends at {}",
+            end.position()
+        );
+    }
+}
+
+/// A filtered JSON export should leave out the unselected fields, but still be
+/// valid JSON
+#[cfg(feature = "json")]
+#[test]
+fn test_json_export_options() {
+    use terra::JsonExportOptions;
+
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let json = world.to_json_with(JsonExportOptions::minimal());
+    assert!(json.len() < world.to_json().len());
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let tiles = value["tiles"].as_array().unwrap();
+    assert_eq!(tiles.len(), world.tiles().len());
+    for tile in tiles {
+        let tile = tile.as_object().unwrap();
+        for field in ["position", "elevation", "biome"] {
+            assert!(tile.contains_key(field), "missing {field}");
+        }
+        for field in ["rainfall", "runoff", "runoff_traversed", "features"] {
+            assert!(!tile.contains_key(field), "unexpected {field}");
+        }
+    }
+
+    // Including everything should give the same tiles as a full export
+    let json = world.to_json_with(JsonExportOptions::default());
+    let loaded = world.from_json(&json).unwrap();
+    assert_eq!(loaded.to_json_tiles_only(), world.to_json_tiles_only());
+}
+
+/// Pretty JSON should be spread across indented lines, but hold exactly the
+/// same data as the compact form
+#[cfg(feature = "json")]
+#[test]
+fn test_to_json_pretty() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+
+    let compact = world.to_json();
+    let pretty = world.to_json_pretty();
+    assert!(!compact.contains('\n'));
+    assert!(pretty.contains("\n  \""), "pretty JSON isn't indented");
+    assert!(pretty.len() > compact.len());
+
+    let compact_value: serde_json::Value =
+        serde_json::from_str(&compact).unwrap();
+    let pretty_value: serde_json::Value =
+        serde_json::from_str(&pretty).unwrap();
+    assert_eq!(pretty_value, compact_value);
+    let loaded = world.from_json(&pretty).unwrap();
+    assert_eq!(loaded.to_json_tiles_only(), world.to_json_tiles_only());
+}
+
+/// Tiles serialized without a config should load back into the same world when
+/// paired with the original config
+#[cfg(feature = "json")]
+#[test]
+fn test_json_tiles_only() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    let json = world.to_json_tiles_only();
+    assert!(json.len() < world.to_json().len());
+
+    let loaded = World::from_json_tiles_only(&json, config.clone()).unwrap();
+    assert_eq!(loaded.config(), &config);
+    assert_eq!(loaded.meta().config_hash, config.config_hash());
+    // There's no way to know which version generated the tiles
+    assert_eq!(loaded.meta().terra_version, "");
+    assert_eq!(loaded.tiles().len(), world.tiles().len());
+    // Tiles are serialized in sorted order, so identical tiles give identical
+    // output
+    assert_eq!(loaded.to_json_tiles_only(), json);
+
+    assert!(World::from_json_tiles_only("{}", config.clone()).is_err());
+    assert!(World::from_json_tiles_only("[]", config.clone()).is_err());
+
+    // Every tile has to fit in the config, exactly once
+    let mut tiles: Vec<serde_json::Value> =
+        serde_json::from_str(&json).unwrap();
+    tiles.push(tiles[0].clone());
+    let duplicated = serde_json::to_string(&tiles).unwrap();
+    assert!(World::from_json_tiles_only(&duplicated, config.clone()).is_err());
+    let too_small = WorldConfig {
+        radius: 5,
+        ..config
+    };
+    assert!(World::from_json_tiles_only(&json, too_small).is_err());
+}
+
+/// Saves that are missing generated tile fields (e.g. from before those fields
+/// existed) should be rejected, rather than loaded with default values
+#[cfg(feature = "json")]
+#[test]
+fn test_json_missing_tile_fields() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+    let value: serde_json::Value =
+        serde_json::from_str(&world.to_json()).unwrap();
+
+    for field in ["humidity", "temperature", "watershed", "coast_distance"] {
+        let mut value = value.clone();
+        for tile in value["tiles"].as_array_mut().unwrap() {
+            tile.as_object_mut().unwrap().remove(field);
+        }
+        assert!(
+            world.from_json(&value.to_string()).is_err(),
+            "Expected world without {field} to be rejected"
+        );
+    }
+}
+
+/// Same as the JSON test, but for binary format
+#[cfg(feature = "bin")]
+#[test]
+fn test_bin_tiles_only() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    let bin = world.to_bin_tiles_only();
+    assert!(bin.len() < world.to_bin().len());
+
+    let loaded =
+        World::from_bin_tiles_only(bin.as_slice(), config.clone()).unwrap();
+    assert_eq!(loaded.config(), &config);
+    assert_eq!(loaded.tiles().len(), world.tiles().len());
+    assert_eq!(loaded.to_bin_tiles_only(), bin);
+}
+
+/// Elevation-only generation should spit out the exact same elevations as full
+/// generation
+#[test]
+fn test_generate_elevation_only() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    };
+    let elevations = World::generate_elevation_only(config.clone()).unwrap();
+    let world = World::generate(config).unwrap();
+
+    assert_eq!(elevations.len(), world.tiles().len());
+    for (pos, tile) in world.tiles() {
+        assert_eq!(elevations[pos], tile.elevation(), "Mismatch at {pos}");
+    }
+}
+
+/// Smoothing should make the terrain less jagged, meaning the elevation
+/// differences between adjacent tiles should shrink
+#[test]
+fn test_elevation_smoothing() {
+    /// Mean squared elevation difference between each pair of adjacent tiles
+    fn adjacent_variance(smoothing_passes: u16) -> f64 {
+        let elevations = World::generate_elevation_only(WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 30,
+            elevation: ElevationConfig {
+                smoothing_passes,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+
+        let diffs: Vec<f64> = elevations
+            .iter()
+            .flat_map(|(pos, elevation)| {
+                let elevations = &elevations;
+                pos.adjacents().filter_map(move |adj_pos| {
+                    Some((*elevation - *elevations.get(&adj_pos)?).0)
+                })
+            })
+            .collect();
+        diffs.iter().map(|diff| diff * diff).sum::<f64>() / diffs.len() as f64
+    }
+
+    let raw = adjacent_variance(0);
+    let smoothed = adjacent_variance(3);
+    assert!(
+        smoothed < raw,
+        "Expected smoothed variance {smoothed} to be less than raw {raw}"
+    );
+}
+
+/// Generation should refuse configs that exceed the tile budget
+#[test]
+fn test_generate_within_budget() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 5,
+        ..Default::default()
+    };
+    assert_eq!(config.tile_count(), 91);
+
+    // Right at the budget is fine
+    let world = World::generate_within_budget(config.clone(), 91).unwrap();
+    assert_eq!(world.tiles().len(), 91);
+
+    // Over budget
+    let error = World::generate_within_budget(config, 90).unwrap_err();
+    assert!(
+        error.to_string().contains("exceeds the budget of 90 tiles"),
+        "unexpected error: {error}"
+    );
+}
+
+/// With the default humidity generation, stored humidity should be exactly the
+/// rainfall mapped through the soft range
+#[test]
+fn test_humidity_from_rainfall() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(world.tiles().values().any(|tile| tile.humidity() > 0.0));
+    for tile in world.tiles().values() {
+        let expected = World::RAINFALL_SOFT_RANGE
+            .value(tile.rainfall())
+            .clamp()
+            .convert::<f64>()
+            .normalize()
+            .inner();
+        assert_eq!(tile.humidity(), expected, "{tile:?}");
+    }
+}
+
+/// The edge buffer pushes elevation down, so the average elevation should
+/// fall off toward the edge of the world
+#[test]
+fn test_radial_profile() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 50,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    let profile = world.radial_profile(|tile| tile.elevation().0);
+    assert_eq!(profile.len(), config.radius as usize + 1);
+    assert!(profile.iter().all(|elevation| elevation.is_finite()));
+
+    let buffer_start = (config.radius - config.edge_buffer_size()) as usize;
+    assert!(buffer_start < config.radius as usize);
+    let mean =
+        |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let interior = &profile[..=buffer_start];
+    let buffer = &profile[buffer_start + 1..];
+    assert!(mean(buffer) < mean(interior), "{profile:?}");
+    // The outermost ring gets pushed down the most
+    let edge = profile[config.radius as usize];
+    assert!(edge < buffer[0], "{profile:?}");
+    assert!(edge < mean(interior), "{profile:?}");
+}
+
+/// With a rainfall floor, no tile should end up drier than the floor
+#[test]
+fn test_min_rainfall() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    };
+    let min_rainfall = Meter3(0.5);
+
+    // The clouds start out empty, so some tiles get less than the floor
+    // without it
+    let world = World::generate(config.clone()).unwrap();
+    assert!(world
+        .tiles()
+        .values()
+        .any(|tile| tile.rainfall() < min_rainfall));
+
+    let world = World::generate(WorldConfig {
+        rainfall: RainfallConfig {
+            min_rainfall,
+            ..Default::default()
+        },
+        ..config
+    })
+    .unwrap();
+    for tile in world.tiles().values() {
+        assert!(tile.rainfall() >= min_rainfall, "{tile:?}");
+    }
+}
+
+/// Ice should only form beyond the ice cap latitude, and land ice should
+/// always be snow
+#[test]
+fn test_ice_caps() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 30,
+        biome: BiomeConfig {
+            ice_cap_latitude: 0.6,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+
+    let ice_tiles: Vec<_> = world
+        .tiles()
+        .values()
+        .filter(|tile| tile.features().contains(&GeoFeature::Ice))
+        .collect();
+    assert!(!ice_tiles.is_empty());
+    for tile in ice_tiles {
+        assert!(world.latitude(tile.position()).abs() >= 0.6, "{tile:?}");
+        assert!(tile.temperature() <= World::FREEZING_POINT, "{tile:?}");
+        if tile.biome().biome_type() == BiomeType::Land {
+            assert_eq!(tile.biome(), Biome::Snow, "{tile:?}");
+        }
+    }
+}
+
+/// Translating a world should move every tile without changing its data
+#[test]
+fn test_translate() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 3,
+        ..Default::default()
+    })
+    .unwrap();
+    let offset = TilePoint::new_xy(10, -4);
+    assert_eq!(
+        TilePoint::new_xy(1, 2).checked_translate(offset),
+        Some(TilePoint::new_xy(11, -2))
+    );
+    let translated = world.translate(offset).unwrap();
+
+    assert_eq!(translated.tiles().len(), world.tiles().len());
+    for tile in world.tiles().values() {
+        let new_pos = tile.position().checked_translate(offset).unwrap();
+        let moved = translated.tiles().get(&new_pos).unwrap();
+        assert_eq!(moved.position(), new_pos);
+        assert_eq!(moved.elevation(), tile.elevation());
+        assert_eq!(moved.rainfall(), tile.rainfall());
+        assert_eq!(moved.biome(), tile.biome());
+        assert_eq!(moved.features(), tile.features());
+    }
+
+    // Can't move past the bounds of the coordinate system
+    assert!(world.translate(TilePoint::new_xy(i16::MAX, 0)).is_err());
+}
+
+/// Merging should combine disjoint worlds, and reject overlapping ones
+#[test]
+fn test_merge() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 2,
+        ..Default::default()
+    };
+    let world = World::generate(config).unwrap();
+
+    // Far enough apart that they don't touch
+    let other = world.translate(TilePoint::new_xy(5, 0)).unwrap();
+    let merged = world.clone().merge(other).unwrap();
+    assert_eq!(merged.tiles().len(), 38);
+    assert_eq!(merged.config(), world.config());
+
+    // Shifted by one, so most tiles overlap
+    let other = world.translate(TilePoint::new_xy(1, 0)).unwrap();
+    let error = world.merge(other).unwrap_err();
+    assert!(
+        error.to_string().contains("both have a tile"),
+        "unexpected error: {error}"
+    );
+}
+
+/// A world that's entirely underwater should warn about having no land
+#[test]
+fn test_generation_warnings() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        // Big enough that the one giant sub-sea-level cluster always becomes
+        // an ocean
+        radius: 60,
+        elevation: ElevationConfig {
+            noise_fn: NoiseFnConfig {
+                // Crush all elevations down to near the minimum
+                exponent: 100.0,
+                ..ElevationConfig::default().noise_fn
+            },
+            edge_buffer_fraction: 1.0,
+            edge_buffer_exponent: 100.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (world, warnings) = World::generate_with_warnings(config).unwrap();
+    assert!(world
+        .tiles()
+        .values()
+        .all(|tile| tile.biome().biome_type() == BiomeType::Water));
+    assert_eq!(warnings, vec![GenerationWarning::NoLand]);
+
+    // Disabling rainfall gets its own warning
+    let (_, warnings) = World::generate_with_warnings(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        rainfall: RainfallConfig {
+            enabled: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(warnings.contains(&GenerationWarning::RunoffDisabled));
+}
+
+/// In a wrapping world, every tile has a full set of neighbors, and edge tiles
+/// find theirs on the far side of the world
+#[test]
+fn test_wrapping_neighbor() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        wrap: true,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+
+    let edge = TilePoint::new_xy(10, 0);
+    let neighbor = world.wrapping_neighbor(edge, TileDirection::W).unwrap();
+    // The neighbor is all the way across the world
+    assert_eq!(neighbor.position(), TilePoint::new_xy(-10, 9));
+    assert!(neighbor.position().distance_to(edge) > 10);
+    // And the seam works in both directions
+    assert_eq!(
+        world
+            .wrapping_neighbor(neighbor.position(), TileDirection::E)
+            .unwrap()
+            .position(),
+        edge
+    );
+    for tile in world.tiles().values() {
+        for direction in [
+            TileDirection::NNE,
+            TileDirection::E,
+            TileDirection::SSE,
+            TileDirection::SSW,
+            TileDirection::W,
+            TileDirection::NNW,
+        ] {
+            assert!(world
+                .wrapping_neighbor(tile.position(), direction)
+                .is_some());
+        }
+    }
+
+    // Without wrapping, there's nothing off the edge
+    let world = World::generate(WorldConfig {
+        wrap: false,
+        ..config
+    })
+    .unwrap();
+    assert!(world.wrapping_neighbor(edge, TileDirection::W).is_none());
+    assert!(world.wrapping_neighbor(edge, TileDirection::E).is_some());
+}
+
+/// Fertility should always be normalized, and water should never be fertile
+#[test]
+fn test_fertility() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(world.tiles().values().any(|tile| tile.fertility() > 0.0));
+    for tile in world.tiles().values() {
+        assert!((0.0..=1.0).contains(&tile.fertility()), "{tile:?}");
+        if tile.is_water_biome() {
+            assert_eq!(tile.fertility(), 0.0, "{tile:?}");
+        }
+    }
+}
+
+/// Tiles that drain to the same terminal should share a watershed, and tiles
+/// that drain to the ocean should share one with the river mouth they reach
+#[test]
+fn test_watersheds() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 50,
+        ..Default::default()
+    })
+    .unwrap();
+    let watersheds = world.watersheds();
+    assert!(!watersheds.is_empty());
+
+    for tile in world.tiles().values() {
+        let watershed = watersheds.get(&tile.position()).copied();
+        // The map should match what's stored on each tile
+        assert_eq!(watershed, tile.watershed(), "{tile:?}");
+
+        match tile.drainage() {
+            DrainageDestination::None => assert_eq!(watershed, None),
+            DrainageDestination::Terminals { terminals, .. } => {
+                // Everything on a slope down to a terminal shares the
+                // terminal's own watershed
+                let watershed = watershed.unwrap();
+                assert_eq!(watershed.outlet(), terminals[0]);
+                assert_eq!(watersheds[&terminals[0]], watershed);
+            }
+            DrainageDestination::Ocean => {
+                // The outlet is in its own watershed, and borders water or
+                // the edge of the world
+                let outlet = watershed.unwrap().outlet();
+                assert_eq!(watersheds[&outlet], watershed.unwrap());
+                assert!(
+                    world.adjacent_tiles(outlet).count() < 6
+                        || world
+                            .adjacent_tiles(outlet)
+                            .any(|adj_tile| adj_tile.is_water_biome()),
+                    "outlet {outlet} for {tile:?} is landlocked"
+                );
+            }
+        }
+    }
+}
+
+/// Generating with identically seeded external RNGs should give identical
+/// worlds, and the default generation should match an RNG seeded from the
+/// config
+#[test]
+fn test_generate_with_rng() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    };
+    let world_a =
+        World::generate_with_rng(config.clone(), Pcg64::seed_from_u64(1234))
+            .unwrap();
+    let world_b =
+        World::generate_with_rng(config.clone(), Pcg64::seed_from_u64(1234))
+            .unwrap();
+    let world_seeded = World::generate(config.clone()).unwrap();
+    let world_from_seed = World::generate_with_rng(
+        config.clone(),
+        Pcg64::seed_from_u64(config.seed.to_u64()),
+    )
+    .unwrap();
+
+    for (pos, tile) in world_a.tiles() {
+        let other = &world_b.tiles()[pos];
+        assert_eq!(tile.elevation(), other.elevation(), "Mismatch at {pos}");
+        assert_eq!(tile.biome(), other.biome(), "Mismatch at {pos}");
+
+        let seeded = &world_seeded.tiles()[pos];
+        let from_seed = &world_from_seed.tiles()[pos];
+        assert_eq!(seeded.elevation(), from_seed.elevation());
+        assert_eq!(seeded.biome(), from_seed.biome());
+    }
+
+    // Only the builder's own RNG can be checkpointed
+    assert!(world_seeded.rng_checkpoint().is_some());
+    assert!(world_a.rng_checkpoint().is_none());
+}
+
+/// Every river exit that flows onto land should line up with a river entrance
+/// on the neighbor in the matching direction, so the entrance count should
+/// match the number of exits that don't run into the sea
+#[test]
+fn test_feature_counts() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    };
+    let world = World::generate(config).unwrap();
+
+    let mut land_exits = 0;
+    for tile in world.tiles().values() {
+        for feature in tile.features() {
+            if let GeoFeature::RiverExit { direction, .. } = feature {
+                let neighbor = match world
+                    .wrapping_neighbor(tile.position(), *direction)
+                {
+                    Some(neighbor) if neighbor.is_land_biome() => neighbor,
+                    _ => continue,
+                };
+                land_exits += 1;
+                assert!(
+                    neighbor.features().iter().any(|feature| matches!(
+                        feature,
+                        GeoFeature::RiverEntrance { direction: entrance, .. }
+                            if *entrance == direction.opposite()
+                    )),
+                    "Exit from {} has no matching entrance on {}",
+                    tile.position(),
+                    neighbor.position()
+                );
+            }
+        }
+    }
+
+    let counts = world.feature_counts();
+    let count =
+        |kind: GeoFeatureKind| counts.get(&kind).copied().unwrap_or_default();
+    assert!(count(GeoFeatureKind::RiverExit) > 0);
+    assert_eq!(count(GeoFeatureKind::RiverEntrance), land_exits);
+    assert_eq!(world.stats().feature_counts, counts);
+    world.validate_features().unwrap();
+}
+
+/// Jungles need lots of rain and deserts need very little, so the climate
+/// summary should reflect that
+#[test]
+fn test_biome_climate_summary() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    })
+    .unwrap();
+    let summary = world.biome_climate_summary();
+
+    // Counts should line up with the regular stats
+    let stats = world.stats();
+    assert_eq!(summary.len(), stats.biome_counts.len());
+    for (biome, climate) in &summary {
+        assert_eq!(climate.tile_count, stats.biome_counts[biome]);
+    }
+
+    let desert = summary.get(&Biome::Desert).expect("No desert tiles");
+    let jungle = summary.get(&Biome::Jungle).expect("No jungle tiles");
+    assert!(
+        desert.mean_rainfall < jungle.mean_rainfall,
+        "Expected desert rainfall {} to be below jungle rainfall {}",
+        desert.mean_rainfall,
+        jungle.mean_rainfall
+    );
+    // Water tiles don't hold runoff
+    assert_eq!(summary[&Biome::Ocean].mean_runoff, Meter3(0.0));
+}
+
+/// The dominant biome should be the one with the highest count, and should
+/// match what's in the stats
+#[test]
+fn test_dominant_biome() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    })
+    .unwrap();
+    let (biome, fraction) = world.dominant_biome().unwrap();
+    let stats = world.stats();
+
+    let max_count = stats.biome_counts.values().copied().max().unwrap();
+    assert_eq!(stats.biome_counts[&biome], max_count);
+    assert!(
+        fraction > 0.0 && fraction <= 1.0,
+        "Expected fraction in (0, 1], got {fraction}"
+    );
+    assert_eq!(fraction, max_count as f64 / stats.tile_count as f64);
+    assert_eq!(stats.dominant_biome, Some((biome, fraction)));
+}
+
+/// Without runoff there are no rivers, so the drainage density should be 0
+#[test]
+fn test_drainage_density() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    assert!(world.total_river_length() > 0);
+    assert!(world.drainage_density() > 0.0);
+    let stats = world.stats();
+    assert_eq!(stats.total_river_length, world.total_river_length());
+    assert_eq!(stats.drainage_density, world.drainage_density());
+
+    let dry_world = World::generate(WorldConfig {
+        rainfall: RainfallConfig {
+            enabled: false,
+            ..Default::default()
+        },
+        ..config
+    })
+    .unwrap();
+    assert_eq!(dry_world.total_river_length(), 0);
+    assert_eq!(dry_world.drainage_density(), 0.0);
+}
+
+/// The edge buffer is all pushed down into the ocean, so leaving it out of the
+/// stats should give a drier picture of the world
+#[test]
+fn test_stats_excluding_buffer() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    let stats = world.stats();
+    let interior_stats = world.stats_excluding_buffer();
+
+    let interior_radius = config.radius - config.edge_buffer_size();
+    assert!(config.edge_buffer_size() > 0);
+    assert_eq!(
+        interior_stats.tile_count,
+        WorldConfig {
+            radius: interior_radius,
+            ..Default::default()
+        }
+        .tile_count()
+    );
+    assert!(interior_stats.water_fraction() < stats.water_fraction());
+
+    // For an off-center chunk, the buffer is measured from the logical
+    // center, so the interior gets cut off by the edge of the chunk
+    let centered_config = WorldConfig {
+        center: TilePoint::new_xy(50, 0),
+        ..config.clone()
+    };
+    let world = World::generate(centered_config.clone()).unwrap();
+    let expected_count = world
+        .tiles()
+        .keys()
+        .filter(|pos| {
+            centered_config.center_distance(**pos) <= interior_radius as usize
+        })
+        .count();
+    let interior_stats = world.stats_excluding_buffer();
+    assert_eq!(interior_stats.tile_count, expected_count);
+    assert!(interior_stats.tile_count < stats.tile_count);
+    assert!(
+        interior_stats.tile_count
+            < WorldConfig {
+                radius: interior_radius,
+                ..Default::default()
+            }
+            .tile_count()
+    );
+
+    // Wrapping worlds have no buffer to exclude
+    let world = World::generate(WorldConfig {
+        wrap: true,
+        ..config
+    })
+    .unwrap();
+    assert_eq!(world.stats_excluding_buffer(), world.stats());
+}
+
+/// A subsample should be the same every time, and about the right size
+#[test]
+fn test_sample_tiles() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+    let tile_count = world.tiles().len();
+
+    for every_n in [1, 2, 7, 100] {
+        let sample: Vec<TilePoint> =
+            world.sample_tiles(every_n).map(Tile::position).collect();
+        // Round up, since the first tile is always included
+        assert_eq!(sample.len(), (tile_count + every_n - 1) / every_n);
+        let again: Vec<TilePoint> =
+            world.sample_tiles(every_n).map(Tile::position).collect();
+        assert_eq!(sample, again);
+        // Sorted, with no repeats
+        assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+    assert_eq!(world.sample_tiles(1).count(), tile_count);
+}
+
+/// Resimulating water should pick up elevation edits, and leave an unedited
+/// world as it was
+#[test]
+fn test_resimulate_water() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Nothing edited, so nothing should change
+    let mut resimulated = world.clone();
+    resimulated.resimulate_water().unwrap();
+    for tile in world.tiles().values() {
+        let resimulated_tile = &resimulated.tiles()[&tile.position()];
+        assert_eq!(resimulated_tile.elevation(), tile.elevation());
+        assert_eq!(resimulated_tile.rainfall(), tile.rainfall());
+        assert_eq!(resimulated_tile.biome(), tile.biome());
+    }
+
+    // Build a mountain in the middle of some land
+    let is_land = |pos: TilePoint| {
+        world.tiles().get(&pos).map_or(false, Tile::is_land_biome)
+    };
+    let peak = world
+        .tiles_sorted()
+        .into_iter()
+        .map(|tile| tile.position())
+        .find(|pos| {
+            is_land(*pos)
+                && pos.adjacents().all(is_land)
+                && world.tiles()[pos].elevation() < Meter(100.0)
+        })
+        .unwrap();
+    let mut edited = world.clone();
+    edited.set_elevation(peak, Meter(100.0)).unwrap();
+    for adj_pos in peak.adjacents() {
+        edited.set_elevation(adj_pos, Meter(90.0)).unwrap();
+    }
+    assert!(edited.set_elevation(peak, Meter(1000.0)).is_err());
+    edited.resimulate_water().unwrap();
+
+    let old_peak = &world.tiles()[&peak];
+    let new_peak = &edited.tiles()[&peak];
+    assert_eq!(new_peak.elevation(), Meter(100.0));
+    // Water all runs off the top of the mountain, and it's too high for
+    // anything but snow
+    assert_eq!(new_peak.runoff(), Meter3(0.0));
+    assert_eq!(new_peak.biome(), Biome::Snow);
+    assert!(new_peak.temperature() < old_peak.temperature());
+
+    // Everything else should still hang together
+    assert_eq!(edited.tiles().len(), world.tiles().len());
+    for tile in world.tiles().values().filter(|tile| tile.is_water_biome()) {
+        assert_eq!(edited.tiles()[&tile.position()].biome(), tile.biome());
+    }
+    edited.validate_complete().unwrap();
+    edited.validate_features().unwrap();
+}
+
+/// With meandering enabled, resimulating an unedited world should replay the
+/// same random draws, and therefore give the same rivers
+#[test]
+fn test_resimulate_water_meander() {
+    let mut config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 50,
+        ..Default::default()
+    };
+    config.geo_feature.meander_strength = 1.0;
+    let world = World::generate(config.clone()).unwrap();
+    assert!(world.total_river_length() > 0);
+
+    let mut resimulated = world.clone();
+    resimulated.resimulate_water().unwrap();
+    for tile in world.tiles_sorted() {
+        let pos = tile.position();
+        let resimulated_tile = &resimulated.tiles()[&pos];
+        assert_eq!(resimulated_tile.features(), tile.features(), "At {pos}");
+        assert_eq!(resimulated_tile.biome(), tile.biome(), "At {pos}");
+    }
+
+    // Without a checkpoint, the rivers may bend differently, but they should
+    // still be consistent
+    let mut world = World::generate_with_rng(
+        config.clone(),
+        Pcg64::seed_from_u64(config.seed.to_u64()),
+    )
+    .unwrap();
+    assert!(world.rng_checkpoint().is_none());
+    world.resimulate_water().unwrap();
+    world.validate_features().unwrap();
+}
+
+/// Extracting a continent should give back a single contiguous landmass, plus
+/// the water around it
+#[test]
+fn test_extract_continent() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 50,
+        ..Default::default()
+    };
+    let world = World::generate(config).unwrap();
+    let seed_tile = world
+        .tiles_sorted()
+        .into_iter()
+        .find(|tile| tile.is_land_biome())
+        .unwrap()
+        .position();
+
+    let continent = world.extract_continent(seed_tile).unwrap();
+    assert!(continent.tiles().len() < world.tiles().len());
+
+    // Flood out from the seed over land, which should reach every land tile
+    let mut reached = HashSet::from([seed_tile]);
+    let mut queue = vec![seed_tile];
+    while let Some(pos) = queue.pop() {
+        for adj_pos in pos.adjacents() {
+            let is_land = continent
+                .tiles()
+                .get(&adj_pos)
+                .map_or(false, |tile| tile.is_land_biome());
+            if is_land && reached.insert(adj_pos) {
+                queue.push(adj_pos);
+            }
+        }
+    }
+    for (pos, tile) in continent.tiles() {
+        // Positions and tile data are untouched
+        assert_eq!(tile.elevation(), world.tiles()[pos].elevation());
+        if tile.is_land_biome() {
+            assert!(reached.contains(pos), "Land tile {pos} is unreachable");
+        } else {
+            // Water is only kept as a margin around the land
+            assert!(
+                pos.adjacents().any(|adj_pos| reached.contains(&adj_pos)),
+                "Water tile {pos} isn't on the margin"
+            );
+        }
+    }
+
+    // Can't start from water or outside the world
+    let water_tile = world
+        .tiles()
+        .values()
+        .find(|tile| tile.is_water_biome())
+        .unwrap();
+    assert!(world.extract_continent(water_tile.position()).is_err());
+    assert!(world.extract_continent(TilePoint::new_xy(100, 0)).is_err());
+}
+
+/// An ocean floor should flatten out deep water, without touching anything
+/// above sea level
+#[test]
+fn test_ocean_floor() {
+    let floor = Meter(-20.0);
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 50,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    let floored_world = World::generate(WorldConfig {
+        elevation: ElevationConfig {
+            ocean_floor: Some(floor),
+            ..config.elevation
+        },
+        ..config
+    })
+    .unwrap();
+
+    // Make sure the floor is actually doing something
+    assert!(world.tiles().values().any(|tile| tile.elevation() < floor));
+    for (pos, tile) in world.tiles() {
+        let floored_tile = &floored_world.tiles()[pos];
+        assert!(floored_tile.elevation() >= floor, "{pos} is too deep");
+        if tile.elevation() >= World::SEA_LEVEL {
+            assert_eq!(floored_tile.elevation(), tile.elevation());
+        }
+    }
+}
+
+/// Sorting by descending elevation should put the highest tile first
+#[test]
+fn test_tiles_sorted_by() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Floats aren't Ord, so scale them up to integers
+    let tiles = world.tiles_sorted_by(|tile| {
+        Reverse((tile.elevation().0 * 1_000_000.0) as i64)
+    });
+    assert_eq!(tiles.len(), world.tiles().len());
+    assert_eq!(
+        tiles[0].position(),
+        world.highest_tile().unwrap().position()
+    );
+    for pair in tiles.windows(2) {
+        assert!(pair[0].elevation() >= pair[1].elevation());
+    }
+}
+
+/// Downsampling should shrink the world, but keep its overall shape
+#[test]
+fn test_downsample() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 40,
+        ..Default::default()
+    })
+    .unwrap();
+    let mean_elevation = |world: &World| {
+        world
+            .tiles()
+            .values()
+            .map(|tile| tile.elevation().0)
+            .sum::<f64>()
+            / world.tiles().len() as f64
+    };
+
+    let small = world.downsample(4);
+    assert_eq!(small.config().radius, 10);
+    assert_eq!(small.meta().config_hash, small.config().config_hash());
+    assert_ne!(small.meta().config_hash, world.meta().config_hash);
+    assert_eq!(small.tiles().len(), small.config().tile_count());
+    assert!(small.tiles().len() < world.tiles().len());
+    let (mean, small_mean) = (mean_elevation(&world), mean_elevation(&small));
+    assert!(
+        (mean - small_mean).abs() < 5.0,
+        "Mean elevation changed from {mean} to {small_mean}"
+    );
+    // Features don't survive aggregation
+    assert!(small
+        .tiles()
+        .values()
+        .all(|tile| tile.features().is_empty()));
+
+    assert_eq!(world.downsample(1).tiles().len(), world.tiles().len());
+}
+
+/// A snow line should turn all land above it into snow, without touching
+/// anything below it
+#[test]
+fn test_snow_line() {
+    let snow_line = Meter(10.0);
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    };
+    let world = World::generate(WorldConfig {
+        biome: BiomeConfig {
+            snow_line: Some(snow_line),
+            ..config.biome
+        },
+        ..config.clone()
+    })
+    .unwrap();
+    let baseline = World::generate(config).unwrap();
+
+    let mut snow_count = 0;
+    for (pos, tile) in world.tiles() {
+        let baseline_tile = &baseline.tiles()[pos];
+        if tile.is_land_biome() && tile.elevation() > snow_line {
+            assert_eq!(tile.biome(), Biome::Snow, "{pos} is above snow line");
+            snow_count += 1;
+        } else {
+            assert_eq!(
+                tile.biome(),
+                baseline_tile.biome(),
+                "{pos} is below snow line"
+            );
+        }
+    }
+    assert!(snow_count > 0, "No tiles above snow line");
+}
+
+/// With the ocean border forced, every tile on the edge of the world should be
+/// water, even with no edge buffer
+#[test]
+fn test_force_ocean_border() {
+    let radius = 20;
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius,
+        elevation: ElevationConfig {
+            edge_buffer_fraction: 0.0,
+            force_ocean_border: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+
+    let edge_tiles: Vec<_> = world
+        .tiles()
+        .values()
+        .filter(|tile| {
+            tile.position().distance_to(TilePoint::ORIGIN) == radius as usize
+        })
+        .collect();
+    assert_eq!(edge_tiles.len(), 6 * radius as usize);
+    for tile in edge_tiles {
+        assert!(
+            tile.is_water_biome(),
+            "Edge tile {} is {:?}",
+            tile.position(),
+            tile.biome()
+        );
+        assert!(tile.elevation() < World::SEA_LEVEL);
+    }
+}
+
+/// Raising the whole world should make more land, and lowering it should make
+/// less
+#[test]
+fn test_elevation_offset() {
+    let land_count = |elevation_offset: Meter| {
+        let world = World::generate(WorldConfig {
+            seed: 1021522790211909.into(),
+            radius: 20,
+            elevation: ElevationConfig {
+                elevation_offset,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap();
+        world
+            .tiles()
+            .values()
+            .filter(|tile| tile.is_land_biome())
+            .count()
+    };
+
+    let baseline = land_count(Meter(0.0));
+    let raised = land_count(Meter(20.0));
+    let lowered = land_count(Meter(-20.0));
+    assert!(raised > baseline, "{raised} <= {baseline}");
+    assert!(lowered < baseline, "{lowered} >= {baseline}");
+}
+
+/// With center land guaranteed, the origin should always be on land, no matter
+/// the seed
+#[test]
+fn test_guarantee_center_land() {
+    for seed in [1021522790211909u64, 1234, 5678, 42, 3] {
+        let config = WorldConfig {
+            seed: seed.into(),
+            radius: 20,
+            ..Default::default()
+        };
+        let unraised = World::generate(config.clone()).unwrap();
+        let world = World::generate(WorldConfig {
+            elevation: ElevationConfig {
+                guarantee_center_land: true,
+                ..Default::default()
+            },
+            ..config
+        })
+        .unwrap();
+
+        let origin = &world.tiles()[&TilePoint::ORIGIN];
+        assert!(
+            origin.is_land_biome(),
+            "Seed {seed}: origin is {:?}",
+            origin.biome()
+        );
+        // 10% of the radius around the origin should be raised up too
+        for tile in world.tiles().values() {
+            if tile.position().distance_to(TilePoint::ORIGIN) <= 2 {
+                assert!(
+                    tile.elevation() > World::SEA_LEVEL,
+                    "Seed {seed}: {} is at {}",
+                    tile.position(),
+                    tile.elevation()
+                );
+            }
+            // Tiles are only raised a little, never into mountains
+            let unraised_elevation =
+                unraised.tiles()[&tile.position()].elevation();
+            assert!(
+                tile.elevation() == unraised_elevation
+                    || tile.elevation() <= Meter(10.0),
+                "Seed {seed}: {} was raised to {}",
+                tile.position(),
+                tile.elevation()
+            );
+        }
+    }
+}
+
+/// Ocean tiles should be split into coast and deep ocean purely by depth
+#[test]
+fn test_coast_depth() {
+    let coast_depth = Meter(10.0);
+    // Needs to be big enough to have an ocean
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        elevation: ElevationConfig {
+            coast_depth,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+    let min_coast_elev = World::SEA_LEVEL - coast_depth;
+
+    let mut coast_count = 0;
+    let mut ocean_count = 0;
+    for tile in world.tiles().values() {
+        let pos = tile.position();
+        match tile.biome() {
+            Biome::Coast => {
+                coast_count += 1;
+                assert!(
+                    tile.elevation() >= min_coast_elev
+                        && tile.elevation() <= World::SEA_LEVEL,
+                    "Coast tile {pos} is outside the shelf"
+                );
+            }
+            Biome::Ocean => {
+                ocean_count += 1;
+                assert!(
+                    tile.elevation() < min_coast_elev,
+                    "Ocean tile {pos} is too shallow"
+                );
+            }
+            _ => {}
+        }
+    }
+    assert!(coast_count > 0);
+    assert!(ocean_count > 0);
+}
+
+/// Shuffling should be reproducible for a given seed, and hit every tile once
+#[test]
+fn test_shuffled_tiles() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    };
+    let world = World::generate(config).unwrap();
+    let shuffled = |seed| -> Vec<TilePoint> {
+        world
+            .shuffled_tiles(seed)
+            .map(|tile| tile.position())
+            .collect()
+    };
+
+    let order = shuffled(1);
+    assert_eq!(order, shuffled(1));
+    assert_ne!(order, shuffled(2));
+    assert_ne!(
+        order,
+        world
+            .tiles_sorted()
+            .into_iter()
+            .map(|tile| tile.position())
+            .collect::<Vec<_>>()
+    );
+
+    let unique: HashSet<TilePoint> = order.iter().copied().collect();
+    assert_eq!(order.len(), world.tiles().len());
+    assert_eq!(unique.len(), order.len());
+}
+
+/// The biome map should cover the world's bounding box, with the biome of each
+/// tile at its index and the empty sentinel everywhere else
+#[test]
+fn test_biome_map() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    };
+    let world = World::generate(config).unwrap();
+    let (buffer, bbox) = world.biome_map();
+
+    // A hexagon of radius r spans 2r+1 in each direction
+    assert_eq!((bbox.width(), bbox.height()), (41, 41));
+    assert_eq!(buffer.len(), bbox.area());
+    for tile in world.tiles().values().step_by(7) {
+        let index = bbox.index(tile.position()).unwrap();
+        assert_eq!(buffer[index], tile.biome() as u8);
+    }
+    // The corners of the box are outside the hexagon
+    assert_eq!(buffer[0], World::BIOME_MAP_EMPTY);
+    assert_eq!(
+        buffer
+            .iter()
+            .filter(|b| **b != World::BIOME_MAP_EMPTY)
+            .count(),
+        world.tiles().len()
+    );
+}
+
+/// An extra evaporation source should put more water in the clouds as they
+/// pass over it, so tiles downwind of it get more rain (and no tile gets less)
+#[test]
+fn test_extra_evaporation() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 50,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    // Stick the source on the land tile closest to the middle
+    let source = world
+        .tiles_sorted()
+        .into_iter()
+        .filter(|tile| tile.is_land_biome())
+        .min_by_key(|tile| tile.position().distance_to(TilePoint::ORIGIN))
+        .unwrap()
+        .position();
+
+    let wet_world = World::generate(WorldConfig {
+        rainfall: RainfallConfig {
+            extra_evaporation: [(source, Meter3(1000.0))].into_iter().collect(),
+            ..config.rainfall
+        },
+        ..config
+    })
+    .unwrap();
+
+    let mut wetter_nearby = 0;
+    for (pos, tile) in world.tiles() {
+        let rainfall = tile.rainfall().0;
+        let wet_rainfall = wet_world.tiles()[pos].rainfall().0;
+        assert!(wet_rainfall >= rainfall - 1e-9, "{pos} got less rain");
+        if wet_rainfall > rainfall && pos.distance_to(source) <= 5 {
+            wetter_nearby += 1;
+        }
+    }
+    assert!(wetter_nearby > 0);
+}
+
+/// With a runoff elevation cap, no pool of runoff should rise above the cap
+#[test]
+fn test_max_runoff_elevation() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        // Smaller worlds with this seed drain entirely to the ocean
+        radius: 50,
+        ..Default::default()
+    };
+    let baseline = World::generate(config.clone()).unwrap();
+
+    // Put the cap halfway up the deepest pool of runoff, so that we know at
+    // least one basin gets capped
+    let deepest = baseline
+        .tiles()
+        .values()
+        .max_by(|a, b| a.runoff().partial_cmp(&b.runoff()).unwrap())
+        .unwrap();
+    assert!(
+        deepest.runoff() > Meter3(0.0),
+        "No runoff in baseline world"
+    );
+    let cap = (deepest.elevation() + deepest.runoff_elevation()) / 2.0;
+
+    let world = World::generate(WorldConfig {
+        rainfall: RainfallConfig {
+            max_runoff_elevation: Some(cap),
+            ..config.rainfall
+        },
+        ..config
+    })
+    .unwrap();
+
+    for (pos, tile) in world.tiles() {
+        if tile.runoff() > Meter3(0.0) {
+            assert!(
+                tile.runoff_elevation() <= cap + Meter(1.0e-6),
+                "{pos} has runoff elevation {} above cap {cap}",
+                tile.runoff_elevation()
+            );
+        }
+    }
+    let total_runoff = |world: &World| -> Meter3 {
+        world.tiles().values().map(|tile| tile.runoff()).sum()
+    };
+    assert!(total_runoff(&world) < total_runoff(&baseline));
+}
+
+/// Concentrating runoff into the steepest neighbor should make rivers fork
+/// less, so the same water is carried by fewer, bigger river segments. Note
+/// that the total number of river tiles can actually go *up*, because flows
+/// that used to be split below the river threshold now get pushed over it.
+#[test]
+fn test_runoff_concentration() {
+    /// Number of tiles where a river forks into multiple exits, and the
+    /// average volume leaving through each river exit
+    fn river_stats(world: &World) -> (usize, Meter3) {
+        let exits: Vec<Vec<Meter3>> = world
+            .tiles()
+            .values()
+            .map(|tile| {
+                tile.features()
+                    .iter()
+                    .filter_map(|feature| match feature {
+                        GeoFeature::RiverExit { volume, .. } => Some(*volume),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .collect();
+        let volumes: Vec<Meter3> = exits.iter().flatten().copied().collect();
+        assert!(!volumes.is_empty(), "World has no rivers");
+        let forks = exits.iter().filter(|exits| exits.len() > 1).count();
+        let average =
+            volumes.iter().copied().sum::<Meter3>() / volumes.len() as f64;
+        (forks, average)
+    }
+
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    };
+    let (spread_forks, spread_volume) =
+        river_stats(&World::generate(config.clone()).unwrap());
+    let concentrated = World::generate(WorldConfig {
+        rainfall: RainfallConfig {
+            runoff_concentration: 5.0,
+            ..config.rainfall
+        },
+        ..config
+    })
+    .unwrap();
+    concentrated.validate_features().unwrap();
+    let (concentrated_forks, concentrated_volume) = river_stats(&concentrated);
+
+    assert!(
+        concentrated_forks < spread_forks,
+        "Expected fewer river forks, but got {concentrated_forks} \
+        (vs {spread_forks})"
+    );
+    assert!(
+        concentrated_volume > spread_volume,
+        "Expected bigger rivers, but got {concentrated_volume} \
+        (vs {spread_volume})"
+    );
+}
+
+/// The adjacency graph should have one edge for each pair of neighboring tiles
+#[test]
+fn test_adjacency() {
+    let radius: usize = 5;
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: radius as u16,
+        ..Default::default()
+    })
+    .unwrap();
+    let graph = world.adjacency();
+
+    assert_eq!(graph.nodes().len(), world.tiles().len());
+    // Every tile has 6 neighbors, except the outer ring. The 6 corners have
+    // 3 and the rest of the ring has 4. Do the math and it comes out to this.
+    assert_eq!(graph.edges().len(), 9 * radius * radius + 3 * radius);
+
+    let mut pairs = HashSet::new();
+    for edge in graph.edges() {
+        let from = graph.nodes()[edge.from].position();
+        let to = graph.nodes()[edge.to].position();
+        assert_eq!(from.adjacent(edge.direction), to);
+        assert_eq!(graph.node_index(from), Some(edge.from));
+        assert!(
+            pairs.insert((from.min(to), from.max(to))),
+            "Duplicate edge between {from} and {to}"
+        );
+    }
+}
+
+/// Ridges should raise up connected chains of high tiles
+#[test]
+fn test_ridge_strength() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    };
+    let baseline = World::generate(config.clone()).unwrap();
+    let world = World::generate(WorldConfig {
+        elevation: ElevationConfig {
+            ridge_strength: 0.5,
+            ..config.elevation
+        },
+        ..config
+    })
+    .unwrap();
+
+    // Count the high tiles that are part of a connected group of at least a
+    // few high tiles, i.e. part of a range rather than an isolated peak
+    let clustered_high_tiles = |world: &World| -> usize {
+        let high: HashSet<TilePoint> = world
+            .tiles()
+            .values()
+            .filter(|tile| tile.elevation() > Meter(40.0))
+            .map(|tile| tile.position())
+            .collect();
+        let mut visited = HashSet::new();
+        let mut count = 0;
+        for &start in &high {
+            if !visited.insert(start) {
+                continue;
+            }
+            let mut cluster_size = 0;
+            let mut stack = vec![start];
+            while let Some(pos) = stack.pop() {
+                cluster_size += 1;
+                for adj_pos in pos.adjacents() {
+                    if high.contains(&adj_pos) && visited.insert(adj_pos) {
+                        stack.push(adj_pos);
+                    }
+                }
+            }
+            if cluster_size >= 3 {
+                count += cluster_size;
+            }
+        }
+        count
+    };
+
+    let ridged_count = clustered_high_tiles(&world);
+    let baseline_count = clustered_high_tiles(&baseline);
+    assert!(
+        ridged_count > baseline_count,
+        "Expected more clustered high tiles with ridges, \
+        but got {ridged_count} vs {baseline_count}"
+    );
+}
+
+/// Search outward for the nearest tile that matches a predicate
+#[test]
+fn test_nearest() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+    let is_ocean = |tile: &Tile| tile.biome() == Biome::Ocean;
+
+    // Pick the land tile that's furthest from the coast, so the search has to
+    // go several rings out
+    let inland = world
+        .tiles_sorted_by(|tile| Reverse(tile.coast_distance()))
+        .into_iter()
+        .next()
+        .unwrap();
+    let from = inland.position();
+    assert!(inland.is_land_biome());
+
+    let nearest = world.nearest(from, is_ocean).unwrap();
+    assert!(is_ocean(nearest));
+    // Nothing closer should be ocean
+    let distance = from.distance_to(nearest.position());
+    assert!(distance > 0);
+    for tile in world.tiles().values() {
+        if from.distance_to(tile.position()) < distance {
+            assert!(!is_ocean(tile), "{} is closer", tile.position());
+        }
+    }
+
+    // A tile should find itself
+    assert_eq!(
+        world
+            .nearest(from, |tile| tile.position() == from)
+            .map(|tile| tile.position()),
+        Some(from)
+    );
+    assert!(world.nearest(from, |_| false).is_none());
+}
+
+#[test]
+fn test_nearest_n() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+    let is_land = |tile: &Tile| tile.is_land_biome();
+    let from = TilePoint::new_xy(3, -5);
+
+    let nearest = world.nearest_n(from, 25, is_land);
+    assert_eq!(nearest.len(), 25);
+    assert!(nearest.iter().all(|tile| is_land(tile)));
+    // Sorted by distance, then position
+    for pair in nearest.windows(2) {
+        let key =
+            |tile: &Tile| (from.distance_to(tile.position()), tile.position());
+        assert!(key(pair[0]) < key(pair[1]));
+    }
+    // Nothing left out should be closer than the furthest match
+    let furthest = from.distance_to(nearest[24].position());
+    let closer_count = world
+        .tiles()
+        .values()
+        .filter(|tile| {
+            is_land(tile) && from.distance_to(tile.position()) < furthest
+        })
+        .count();
+    assert!(closer_count <= 25);
+
+    // The first match should agree with the single nearest search
+    assert_eq!(
+        nearest[0].position(),
+        world.nearest(from, is_land).unwrap().position()
+    );
+
+    // If there aren't enough matches, return all of them
+    let land_count =
+        world.tiles().values().filter(|tile| is_land(tile)).count();
+    assert_eq!(world.nearest_n(from, usize::MAX, is_land).len(), land_count);
+    assert!(world.nearest_n(from, 0, is_land).is_empty());
+}
+
+#[test]
+fn test_change_fraction() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+    assert_eq!(world.change_fraction(&world).unwrap(), 0.0);
+
+    let other = World::generate(WorldConfig {
+        seed: 1234.into(),
+        ..config.clone()
+    })
+    .unwrap();
+    let fraction = other.change_fraction(&world).unwrap();
+    assert!(
+        fraction > 0.0 && fraction <= 1.0,
+        "Unexpected fraction {fraction}"
+    );
+    assert_eq!(fraction, world.change_fraction(&other).unwrap());
+
+    // Worlds of different sizes can't be compared
+    let smaller = World::generate(WorldConfig {
+        radius: 5,
+        ..config
+    })
+    .unwrap();
+    assert!(world.change_fraction(&smaller).is_err());
+}
+
+/// Meandering should make rivers take a longer path to reach their mouths,
+/// without breaking up any of them
+#[test]
+fn test_meander_strength() {
+    /// Average ratio of each river's length to the straight-line distance
+    /// between its headwater and its mouth. Rivers that split are followed
+    /// along their first exit.
+    fn sinuosity(world: &World) -> f64 {
+        let exit = |tile: &Tile| {
+            tile.features().iter().find_map(|feature| match feature {
+                GeoFeature::RiverExit { direction, .. } => Some(*direction),
+                _ => None,
+            })
+        };
+        let has_entrance = |tile: &Tile| {
+            tile.features().iter().any(|feature| {
+                matches!(feature, GeoFeature::RiverEntrance { .. })
+            })
+        };
+
+        let mut ratios = Vec::new();
+        for headwater in world.tiles_sorted() {
+            if exit(headwater).is_none() || has_entrance(headwater) {
+                continue;
+            }
+            let mut current = headwater;
+            let mut length = 0;
+            while let Some(next) = exit(current).and_then(|direction| {
+                world.wrapping_neighbor(current.position(), direction)
+            }) {
+                current = next;
+                length += 1;
+                assert!(length <= world.tiles().len(), "River has a cycle");
+            }
+            let distance = headwater.position().distance_to(current.position());
+            if distance > 0 {
+                ratios.push(length as f64 / distance as f64);
+            }
+        }
+        assert!(!ratios.is_empty(), "World has no rivers");
+        ratios.iter().sum::<f64>() / ratios.len() as f64
+    }
+
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 100,
+        ..Default::default()
+    };
+    let straight = World::generate(config.clone()).unwrap();
+    let mut meandering_config = config;
+    meandering_config.geo_feature.meander_strength = 1.0;
+    let meandering = World::generate(meandering_config).unwrap();
+
+    meandering.validate_features().unwrap();
+    assert!(meandering.total_river_length() > straight.total_river_length());
+
+    // Runoff flow should follow the rerouted rivers, so every river carries
+    // no more water than actually flows through its side of the tile
+    for tile in meandering.tiles_sorted() {
+        let (mut entering, mut exiting) = (Meter3(0.0), Meter3(0.0));
+        for feature in tile.features() {
+            match feature {
+                GeoFeature::RiverEntrance { volume, .. } => entering += *volume,
+                GeoFeature::RiverExit { volume, .. } => exiting += *volume,
+                _ => {}
+            }
+        }
+        let pos = tile.position();
+        assert!(entering.0 <= tile.runoff_ingress().0 + 1e-6, "At {pos}");
+        assert!(exiting.0 <= tile.runoff_egress().0 + 1e-6, "At {pos}");
+    }
+    let (straight, meandering) = (sinuosity(&straight), sinuosity(&meandering));
+    assert!(
+        meandering > straight,
+        "Expected meandering sinuosity {meandering} to be above {straight}"
+    );
+}
+
+/// The standalone biome classifier should give the same biome as generation
+#[test]
+fn test_biome_classify() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        biome: BiomeConfig {
+            snow_line: Some(Meter(60.0)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let world = World::generate(config.clone()).unwrap();
+
+    let mut land_count = 0;
+    for tile in world.tiles_sorted() {
+        if !tile.is_land_biome() {
+            continue;
+        }
+        land_count += 1;
+        // Temperature only matters for tiles within the ice caps
+        let temperature = if tile.features().contains(&GeoFeature::Ice) {
+            Some(tile.temperature())
+        } else {
+            None
+        };
+        assert_eq!(
+            config.biome.classify(
+                tile.elevation(),
+                tile.humidity(),
+                temperature
+            ),
+            tile.biome(),
+            "Incorrect classification for {}",
+            tile.position()
+        );
+    }
+    assert!(land_count > 0);
+}
+
+/// Exporting a region should only include the tiles in the box, and load back
+/// into a partial world
+#[cfg(feature = "json")]
+#[test]
+fn test_json_region() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 10,
+        ..Default::default()
+    })
+    .unwrap();
+    let bbox = TileBoundingBox::new(
+        TilePoint::new_xy(-2, -3),
+        TilePoint::new_xy(4, 1),
+    );
+    let json = world.to_json_region(bbox).unwrap();
+    let region = world.from_json(&json).unwrap();
+
+    assert_eq!(region.meta().region, Some(bbox));
+    assert_eq!(world.meta().region, None);
+    assert_eq!(region.config(), world.config());
+    let expected: Vec<&Tile> = world
+        .tiles_sorted()
+        .into_iter()
+        .filter(|tile| bbox.contains(tile.position()))
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(region.tiles().len(), expected.len());
+    for tile in expected {
+        assert_eq!(
+            format!("{:?}", region.tiles()[&tile.position()]),
+            format!("{tile:?}")
+        );
+    }
+
+    // A box that misses the world entirely is an error, not an empty world
+    let outside = TileBoundingBox::new(
+        TilePoint::new_xy(50, 50),
+        TilePoint::new_xy(60, 60),
+    );
+    assert!(world.to_json_region(outside).is_err());
+}
+
+/// Captured layers should hold the data from before it was overwritten
+#[test]
+fn test_capture_layers() {
+    let config = WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        elevation: ElevationConfig {
+            edge_buffer_fraction: 0.5,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Disabled by default
+    let world = World::generate(config.clone()).unwrap();
+    assert!(world.layer("raw_elevation").is_none());
+
+    let world = World::generate(WorldConfig {
+        capture_layers: true,
+        ..config
+    })
+    .unwrap();
+    let raw_elevation = world.layer("raw_elevation").unwrap();
+    assert_eq!(raw_elevation.len(), world.tiles().len());
+    assert!(world.layer("fake_layer").is_none());
+
+    // The edge buffer pushes tiles down, so some final elevations should be
+    // lower than their raw values, but never higher
+    let mut num_lowered = 0;
+    for (pos, tile) in world.tiles() {
+        let raw = raw_elevation[pos];
+        assert!(tile.elevation().0 <= raw + 1.0e-9, "{pos:?} was raised");
+        if tile.elevation().0 < raw {
+            num_lowered += 1;
+        }
+    }
+    assert!(num_lowered > 0);
+
+    // The rainfall floor can only raise rainfall above its raw value
+    let raw_rainfall = world.layer("raw_rainfall").unwrap();
+    assert_eq!(raw_rainfall.len(), world.tiles().len());
+    for (pos, tile) in world.tiles() {
+        let raw = raw_rainfall[pos];
+        assert!(tile.rainfall().0 >= raw, "{pos:?} was lowered");
+        if raw >= world.config().rainfall.min_rainfall.0 {
+            assert_eq!(tile.rainfall().0, raw, "{pos:?} was changed");
+        }
+    }
+}
+
+/// A fixed seed should make the default config reproducible
+#[test]
+fn test_default_with_seed() {
+    let seed: u64 = 1021522790211909;
+    let config = || WorldConfig {
+        radius: 10,
+        ..WorldConfig::default_with_seed(seed.into())
+    };
+    assert_eq!(config(), config());
+    assert_eq!(config().seed, seed.into());
+
+    let world1 = World::generate(config()).unwrap();
+    let world2 = World::generate(config()).unwrap();
+    assert_eq!(
+        format!("{:?}", world1.tiles_sorted()),
+        format!("{:?}", world2.tiles_sorted())
+    );
+}
+
+/// Every land tile should be in exactly one continent, and continent IDs
+/// should be the same every time
+#[test]
+fn test_continents() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+    let continents = world.continents();
+    assert!(!continents.is_empty());
+
+    let land_count = world
+        .tiles()
+        .values()
+        .filter(|tile| tile.is_land_biome())
+        .count();
+    let total: usize = continents.iter().map(|c| c.tiles().len()).sum();
+    assert_eq!(total, land_count);
+
+    let mut seen = HashSet::new();
+    for continent in &continents {
+        let min_pos = continent
+            .tiles()
+            .iter()
+            .map(|tile| tile.position())
+            .min()
+            .unwrap();
+        assert_eq!(continent.id(), min_pos);
+        assert_eq!(continent.area().0, continent.tiles().len() as f64);
+        for tile in continent.tiles() {
+            assert!(tile.is_land_biome());
+            assert!(continent.contains(tile.position()));
+            assert!(seen.insert(tile.position()), "{tile:?} in two continents");
+        }
+    }
+
+    // IDs are in order, and don't change between calls
+    let ids: Vec<TilePoint> = continents.iter().map(|c| c.id()).collect();
+    let mut sorted_ids = ids.clone();
+    sorted_ids.sort();
+    assert_eq!(ids, sorted_ids);
+    let ids_again: Vec<TilePoint> =
+        world.continents().iter().map(|c| c.id()).collect();
+    assert_eq!(ids, ids_again);
+}
+
+/// Flood-filling land from a land tile should cover exactly that tile's
+/// continent, and stop at the water around it
+#[test]
+fn test_flood_fill() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+    let tiles = world.tiles();
+    let is_land =
+        |pos: &TilePoint| tiles.get(pos).map_or(false, Tile::is_land_biome);
+    let continent = world
+        .continents()
+        .into_iter()
+        .max_by_key(|continent| continent.tiles().len())
+        .unwrap();
+
+    let filled =
+        flood_fill(continent.id(), TilePoint::adjacents, |pos, _| is_land(pos));
+    assert_eq!(filled.len(), continent.tiles().len());
+    for pos in &filled {
+        assert!(continent.contains(*pos));
+        // Anything next to the fill that wasn't included must be water (or
+        // off the edge of the world)
+        for adj_pos in pos.adjacents().filter(|adj| !filled.contains(adj)) {
+            assert!(!is_land(&adj_pos), "fill should include {adj_pos}");
+        }
+    }
+
+    // A cost limit keeps the fill close to the start
+    let start = continent.id();
+    let limited = flood_fill(start, TilePoint::adjacents, |pos, cost| {
+        cost <= 2 && is_land(pos)
+    });
+    assert!(!limited.is_empty());
+    assert!(limited.len() < filled.len());
+    for pos in &limited {
+        assert!(pos.distance_to(start) <= 2);
+        assert!(filled.contains(pos));
+    }
+
+    // Starting on water gives nothing
+    let water = *tiles.keys().find(|pos| !is_land(pos)).unwrap();
+    assert!(
+        flood_fill(water, TilePoint::adjacents, |pos, _| is_land(pos))
+            .is_empty()
+    );
+}
+
+/// Each adjacent pair should be counted once under a single ordering, and a
+/// normal world should mostly have biomes bordering themselves
+#[test]
+fn test_biome_adjacency_matrix() {
+    let world = World::generate(WorldConfig {
+        seed: 1021522790211909.into(),
+        radius: 20,
+        ..Default::default()
+    })
+    .unwrap();
+    let matrix = world.biome_adjacency_matrix();
+
+    for (a, b) in matrix.keys() {
+        assert!(a <= b, "({a}, {b}) is out of order");
+    }
+    let total: usize = matrix.values().sum();
+    assert_eq!(total, world.adjacency().edges().len());
+
+    let same: usize = matrix
+        .iter()
+        .filter(|((a, b), _)| a == b)
+        .map(|(_, count)| count)
+        .sum();
+    assert!(
+        same * 2 > total,
+        "only {same} of {total} pairs are the same biome"
+    );
+}
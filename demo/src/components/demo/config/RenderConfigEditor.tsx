@@ -48,6 +48,24 @@ const RenderConfigEditor: React.FC = () => {
             <MenuItem value="elevation">Elevation</MenuItem>
             <MenuItem value="humidity">Humidity</MenuItem>
             <MenuItem value="runoff">Runoff</MenuItem>
+            <MenuItem value="terrain">Terrain</MenuItem>
+            <MenuItem value="coast_distance">Coast Distance</MenuItem>
+          </SelectConfigInput>
+        </ConfigInput>
+
+        {/* Custom palettes need a color picker, so they're skipped here */}
+        <ConfigInput<RenderConfigObject>
+          configHandler={renderConfigHandler}
+          field={["palette"]}
+          label="Palette"
+          description={renderDescriptions.palette}
+        >
+          <SelectConfigInput>
+            <MenuItem value="standard">Standard</MenuItem>
+            <MenuItem value="realistic">Realistic</MenuItem>
+            <MenuItem value="pastel">Pastel</MenuItem>
+            <MenuItem value="retro">Retro</MenuItem>
+            <MenuItem value="grayscale">Grayscale</MenuItem>
           </SelectConfigInput>
         </ConfigInput>
 
@@ -104,6 +104,14 @@ const WorldConfigEditor: React.FC<{ standalone?: boolean }> = ({
         >
           <RangeConfigInput {...EXPONENT_RANGE} />
         </ConfigInput>
+        <ConfigInput<WorldConfigObject>
+          configHandler={worldConfigHandler}
+          field={["elevation", "force_ocean_border"]}
+          label="Force Ocean Border?"
+          description={worldDescriptions.edge_buffer.force_ocean_border}
+        >
+          <CheckboxConfigInput />
+        </ConfigInput>
       </ConfigSection>
 
       <ConfigSection
@@ -146,7 +154,7 @@ const WorldConfigEditor: React.FC<{ standalone?: boolean }> = ({
           label="Lacunarity"
           description={worldDescriptions.elevation.lacunarity}
         >
-          <RangeConfigInput min={0.5} max={10.0} step={0.5} />
+          <RangeConfigInput min={1.0} max={10.0} step={0.5} />
         </ConfigInput>
         <ConfigInput<WorldConfigObject>
           configHandler={worldConfigHandler}
@@ -154,7 +162,7 @@ const WorldConfigEditor: React.FC<{ standalone?: boolean }> = ({
           label="Persistence"
           description={worldDescriptions.elevation.persistence}
         >
-          <RangeConfigInput min={0.0} max={2.0} step={0.1} />
+          <RangeConfigInput min={0.1} max={1.0} step={0.1} />
         </ConfigInput>
         <ConfigInput<WorldConfigObject>
           configHandler={worldConfigHandler}
@@ -63,6 +63,14 @@ export const worldDescriptions = {
         </p>
       </>
     ),
+    force_ocean_border: (
+      <>
+        Guarantee that the map is surrounded by ocean. The outermost ring of
+        tiles is sunk below sea level, and any water touching the edge of the
+        map becomes ocean, no matter how small. Has no effect on wrapping
+        worlds.
+      </>
+    ),
   },
   elevation: {
     root: (
@@ -142,9 +150,9 @@ export const worldDescriptions = {
     ),
     lacunarity: (
       <>
-        The difference in frequency between one octave and the next. E.g. with{" "}
+        The ratio of frequency between one octave and the next. E.g. with{" "}
         <code>l=2.0</code> if the lowest octave has <code>f=1.0</code>, then the
-        next two octaves will be <code>f=3.0</code> and <code>f=5.0</code>.
+        next two octaves will be <code>f=2.0</code> and <code>f=4.0</code>.
       </>
     ),
     persistence: (
@@ -152,9 +160,11 @@ export const worldDescriptions = {
         The ratio of amplitude between one octave and the next. With{" "}
         <code>p=1.0</code>, all octaves will have the same amplitude. With{" "}
         <code>p&lt;1.0</code>, the amplitude will diminish from one octave to
-        the next, which is typically what you want. With <code>p&gt;1.0</code>,
-        the amplitude will increase from one octave to the next, which is rather
-        weird but I won't stop you.
+        the next, which is typically what you want. Values above{" "}
+        <code>1.0</code> aren't allowed, because the amplitude would blow up
+        with each octave and flatten the terrain out to the extremes. It also
+        has to be above <code>0.0</code>, otherwise only the first octave
+        would be heard.
       </>
     ),
     exponent: (
@@ -306,4 +316,10 @@ export const renderDescriptions = {
       biome.
     </>
   ),
+  palette: (
+    <>
+      The set of colors used to draw each biome. This only changes how tiles
+      look, not the world itself.
+    </>
+  ),
 };